@@ -0,0 +1,495 @@
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample};
+use serde::Serialize;
+use tracing::error;
+
+//
+// ====== Audio backends ======
+//
+
+/// Receives interleaved 16-bit input as it is captured
+pub type InputCallback = Box<dyn FnMut(&[i16]) + Send>;
+/// Fills an interleaved output buffer; whatever it leaves untouched plays as silence
+pub type OutputCallback = Box<dyn FnMut(&mut [f32]) + Send>;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceInfo {
+    pub name: String,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub formats: Vec<String>,
+}
+
+/// Channels and sample rate asked of an input; zero leaves either at the device default
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreamFormat {
+    pub channels: u16,
+    pub sample_rate: u32,
+}
+
+impl StreamFormat {
+    // The requested format, with the device's own filling in whatever wasn't asked for
+    fn or(self, channels: u16, sample_rate: u32) -> (u16, u32) {
+        (
+            if self.channels == 0 { channels } else { self.channels },
+            if self.sample_rate == 0 { sample_rate } else { self.sample_rate },
+        )
+    }
+}
+
+/// A stream a backend opened; audio flows once it is played and stops when it is dropped
+pub trait ActiveStream {
+    fn play(&self) -> Result<(), String>;
+}
+
+pub struct AudioStream {
+    /// The device the stream was opened on and the format it runs at
+    pub device: DeviceInfo,
+    stream: Box<dyn ActiveStream>,
+}
+
+// cpal streams aren't Send on every platform, but we only ever play and drop them
+unsafe impl Send for AudioStream {}
+unsafe impl Sync for AudioStream {}
+
+impl AudioStream {
+    pub fn new(device: DeviceInfo, stream: Box<dyn ActiveStream>) -> Self {
+        Self { device, stream }
+    }
+
+    pub fn play(&self) -> Result<(), String> {
+        self.stream.play()
+    }
+}
+
+/// Everything the engine needs from the platform's audio system. `device` picks a
+/// device by name; `None` means the system default. An input opens in `format` or
+/// fails, rather than quietly capturing something else.
+pub trait AudioBackend: Send + Sync {
+    fn input_devices(&self) -> Result<Vec<DeviceInfo>, String>;
+    fn output_devices(&self) -> Result<Vec<DeviceInfo>, String>;
+    fn default_input(&self) -> Result<DeviceInfo, String>;
+    fn open_input(
+        &self,
+        device: Option<&str>,
+        format: StreamFormat,
+        on_samples: InputCallback,
+    ) -> Result<AudioStream, String>;
+    fn open_output(&self, device: Option<&str>, fill: OutputCallback) -> Result<AudioStream, String>;
+}
+
+//
+// ====== cpal ======
+//
+
+/// The real thing: the default cpal host
+pub struct CpalBackend;
+
+impl ActiveStream for cpal::Stream {
+    fn play(&self) -> Result<(), String> {
+        StreamTrait::play(self).map_err(|e| format!("Failed to start stream: {}", e))
+    }
+}
+
+fn describe(device: &cpal::Device, config: &cpal::SupportedStreamConfig, formats: Vec<String>) -> DeviceInfo {
+    DeviceInfo {
+        name: device.name().unwrap_or_else(|_| "Unknown Device".to_string()),
+        channels: config.channels(),
+        sample_rate: config.sample_rate().0,
+        formats,
+    }
+}
+
+fn describe_input(device: &cpal::Device) -> Option<DeviceInfo> {
+    let config = device.default_input_config().ok()?;
+    let formats = device
+        .supported_input_configs()
+        .ok()?
+        .map(|c| format!("{:?}", c.sample_format()))
+        .collect();
+    Some(describe(device, &config, formats))
+}
+
+fn describe_output(device: &cpal::Device) -> Option<DeviceInfo> {
+    let config = device.default_output_config().ok()?;
+    let formats = device
+        .supported_output_configs()
+        .ok()?
+        .map(|c| format!("{:?}", c.sample_format()))
+        .collect();
+    Some(describe(device, &config, formats))
+}
+
+// The device's default input config if it is in `format`, otherwise a supported one
+// that is, preferring the default's sample format
+fn input_config(device: &cpal::Device, format: StreamFormat) -> Result<cpal::SupportedStreamConfig, String> {
+    let default = device
+        .default_input_config()
+        .map_err(|e| format!("Error getting default input config: {}", e))?;
+    let (channels, sample_rate) = format.or(default.channels(), default.sample_rate().0);
+    if (channels, sample_rate) == (default.channels(), default.sample_rate().0) {
+        return Ok(default);
+    }
+
+    let mut candidates = device
+        .supported_input_configs()
+        .map_err(|e| format!("Failed to get supported input configs: {}", e))?
+        .filter(|c| c.channels() == channels)
+        .filter(|c| (c.min_sample_rate().0..=c.max_sample_rate().0).contains(&sample_rate))
+        .filter(|c| matches!(c.sample_format(), SampleFormat::I16 | SampleFormat::U16 | SampleFormat::F32))
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|c| c.sample_format() != default.sample_format());
+    candidates
+        .into_iter()
+        .next()
+        .map(|c| c.with_sample_rate(cpal::SampleRate(sample_rate)))
+        .ok_or_else(|| {
+            let name = device.name().unwrap_or_else(|_| "The input device".to_string());
+            format!("{} can't record {} channel(s) at {} Hz", name, channels, sample_rate)
+        })
+}
+
+// The named device, or the default one when `name` is `None`
+fn find_device<I>(devices: I, default: Option<cpal::Device>, name: Option<&str>) -> Result<cpal::Device, String>
+where
+    I: Iterator<Item = cpal::Device>,
+{
+    match name {
+        Some(name) => {
+            let mut devices = devices;
+            devices
+                .find(|d| d.name().is_ok_and(|n| n == name))
+                .ok_or_else(|| format!("No audio device named '{}'", name))
+        }
+        None => default.ok_or_else(|| "No default device available.".to_string()),
+    }
+}
+
+fn build_input<T>(device: &cpal::Device, config: &cpal::StreamConfig, mut on_samples: InputCallback) -> Result<cpal::Stream, String>
+where
+    T: SizedSample,
+    i16: FromSample<T>,
+{
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                let converted = data.iter().map(|&s| s.to_sample::<i16>()).collect::<Vec<_>>();
+                on_samples(&converted);
+            },
+            |err| error!("An error occurred on the input stream: {}", err),
+            None,
+        )
+        .map_err(|e| format!("Failed to build input stream: {}", e))
+}
+
+fn build_output<T>(device: &cpal::Device, config: &cpal::StreamConfig, mut fill: OutputCallback) -> Result<cpal::Stream, String>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let mut buffer = Vec::new();
+    device
+        .build_output_stream(
+            config,
+            move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+                buffer.clear();
+                buffer.resize(data.len(), 0.0f32);
+                fill(&mut buffer);
+                for (out, &sample) in data.iter_mut().zip(&buffer) {
+                    *out = T::from_sample(sample);
+                }
+            },
+            |err| error!("An error occurred on the output stream: {}", err),
+            None,
+        )
+        .map_err(|e| format!("Failed to build output stream: {}", e))
+}
+
+impl AudioBackend for CpalBackend {
+    fn input_devices(&self) -> Result<Vec<DeviceInfo>, String> {
+        let devices = cpal::default_host()
+            .input_devices()
+            .map_err(|e| format!("Failed to get input devices: {}", e))?;
+        Ok(devices.filter_map(|d| describe_input(&d)).collect())
+    }
+
+    fn output_devices(&self) -> Result<Vec<DeviceInfo>, String> {
+        let devices = cpal::default_host()
+            .output_devices()
+            .map_err(|e| format!("Failed to get output devices: {}", e))?;
+        Ok(devices.filter_map(|d| describe_output(&d)).collect())
+    }
+
+    fn default_input(&self) -> Result<DeviceInfo, String> {
+        let device = cpal::default_host()
+            .default_input_device()
+            .ok_or_else(|| "No default input device available.".to_string())?;
+        describe_input(&device).ok_or_else(|| "Failed to get default config".to_string())
+    }
+
+    fn open_input(
+        &self,
+        device: Option<&str>,
+        format: StreamFormat,
+        on_samples: InputCallback,
+    ) -> Result<AudioStream, String> {
+        let host = cpal::default_host();
+        let devices = host
+            .input_devices()
+            .map_err(|e| format!("Failed to get input devices: {}", e))?;
+        let device = find_device(devices, host.default_input_device(), device)?;
+        let config = input_config(&device, format)?;
+        let info = describe(&device, &config, vec![format!("{:?}", config.sample_format())]);
+
+        let stream_config = config.config();
+        let stream = match config.sample_format() {
+            SampleFormat::I16 => build_input::<i16>(&device, &stream_config, on_samples)?,
+            SampleFormat::U16 => build_input::<u16>(&device, &stream_config, on_samples)?,
+            SampleFormat::F32 => build_input::<f32>(&device, &stream_config, on_samples)?,
+            other => return Err(format!("Unsupported sample format {:?}", other)),
+        };
+        Ok(AudioStream::new(info, Box::new(stream)))
+    }
+
+    fn open_output(&self, device: Option<&str>, fill: OutputCallback) -> Result<AudioStream, String> {
+        let host = cpal::default_host();
+        let devices = host
+            .output_devices()
+            .map_err(|e| format!("Failed to get output devices: {}", e))?;
+        let device = find_device(devices, host.default_output_device(), device)?;
+        let config = device
+            .default_output_config()
+            .map_err(|e| format!("Error getting default output config: {}", e))?;
+        let info = describe(&device, &config, vec![format!("{:?}", config.sample_format())]);
+
+        let stream_config = config.config();
+        let stream = match config.sample_format() {
+            SampleFormat::I16 => build_output::<i16>(&device, &stream_config, fill)?,
+            SampleFormat::U16 => build_output::<u16>(&device, &stream_config, fill)?,
+            SampleFormat::F32 => build_output::<f32>(&device, &stream_config, fill)?,
+            other => return Err(format!("Unsupported sample format {:?}", other)),
+        };
+        Ok(AudioStream::new(info, Box::new(stream)))
+    }
+}
+
+//
+// ====== Mock ======
+//
+
+// Frames handed over per callback, like a typical hardware buffer
+const MOCK_CALLBACK_FRAMES: usize = 512;
+
+/// What the mock input "hears"
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Signal {
+    Silence,
+    Sine { frequency: f32, amplitude: f32 },
+    /// Uniform white noise from a fixed seed, so every run is identical
+    Noise { amplitude: f32 },
+}
+
+struct MockStream {
+    playing: Arc<AtomicBool>,
+    // Cleared on drop so the mock stops delivering to a closed stream
+    callback: Arc<Mutex<Option<MockCallback>>>,
+}
+
+enum MockCallback {
+    Input(InputCallback),
+    Output(OutputCallback),
+}
+
+impl ActiveStream for MockStream {
+    fn play(&self) -> Result<(), String> {
+        self.playing.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+impl Drop for MockStream {
+    fn drop(&mut self) {
+        self.playing.store(false, Ordering::SeqCst);
+        *self.callback.lock().unwrap() = None;
+    }
+}
+
+struct MockPort {
+    playing: Arc<AtomicBool>,
+    callback: Arc<Mutex<Option<MockCallback>>>,
+}
+
+impl MockPort {
+    fn new() -> Self {
+        Self {
+            playing: Arc::new(AtomicBool::new(false)),
+            callback: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+/// Deterministic stand-in for a sound card. Nothing happens on its own: tests push
+/// input with [`MockBackend::feed`] and pull output with [`MockBackend::drain`], so a
+/// recording contains exactly the audio the test asked for.
+pub struct MockBackend {
+    channels: u16,
+    sample_rate: u32,
+    input: MockPort,
+    output: MockPort,
+    // Frames generated so far, which keeps sines continuous across feeds
+    clock: Mutex<u64>,
+    noise_state: Mutex<u32>,
+}
+
+impl MockBackend {
+    pub const INPUT_NAME: &'static str = "Mock Input";
+    pub const OUTPUT_NAME: &'static str = "Mock Output";
+
+    pub fn new(channels: u16, sample_rate: u32) -> Self {
+        Self {
+            channels,
+            sample_rate,
+            input: MockPort::new(),
+            output: MockPort::new(),
+            clock: Mutex::new(0),
+            noise_state: Mutex::new(0x9E37_79B9),
+        }
+    }
+
+    fn info(&self, name: &str) -> DeviceInfo {
+        DeviceInfo {
+            name: name.to_string(),
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            formats: vec!["I16".to_string()],
+        }
+    }
+
+    fn open(&self, port: &MockPort, name: &str, device: Option<&str>, callback: MockCallback) -> Result<AudioStream, String> {
+        if device.is_some_and(|d| d != name) {
+            return Err(format!("No audio device named '{}'", device.unwrap_or_default()));
+        }
+        let mut slot = port.callback.lock().unwrap();
+        if slot.is_some() {
+            return Err(format!("{} is already open", name));
+        }
+        *slot = Some(callback);
+        port.playing.store(false, Ordering::SeqCst);
+        let stream = MockStream {
+            playing: Arc::clone(&port.playing),
+            callback: Arc::clone(&port.callback),
+        };
+        Ok(AudioStream::new(self.info(name), Box::new(stream)))
+    }
+
+    /// Capture `duration` of `signal` on every channel; returns the frames delivered
+    pub fn feed(&self, signal: Signal, duration: Duration) -> Result<usize, String> {
+        self.feed_channels(&vec![signal; self.channels as usize], duration)
+    }
+
+    /// Capture `duration` with a separate signal per channel
+    pub fn feed_channels(&self, signals: &[Signal], duration: Duration) -> Result<usize, String> {
+        if signals.len() != self.channels as usize {
+            return Err(format!("Expected {} signals, one per channel", self.channels));
+        }
+        if !self.input.playing.load(Ordering::SeqCst) {
+            return Err("Mock input is not playing".to_string());
+        }
+
+        let frames = (duration.as_secs_f64() * self.sample_rate as f64).round() as usize;
+        let mut delivered = 0;
+        while delivered < frames {
+            let chunk = MOCK_CALLBACK_FRAMES.min(frames - delivered);
+            let samples = self.generate(signals, chunk);
+            match self.input.callback.lock().unwrap().as_mut() {
+                Some(MockCallback::Input(on_samples)) => on_samples(&samples),
+                _ => return Err("Mock input is closed".to_string()),
+            }
+            delivered += chunk;
+        }
+        Ok(delivered)
+    }
+
+    /// Run the output callback for `frames` frames and return what it played
+    pub fn drain(&self, frames: usize) -> Result<Vec<f32>, String> {
+        if !self.output.playing.load(Ordering::SeqCst) {
+            return Err("Mock output is not playing".to_string());
+        }
+        let mut played = Vec::with_capacity(frames * self.channels as usize);
+        let mut remaining = frames;
+        while remaining > 0 {
+            let chunk = MOCK_CALLBACK_FRAMES.min(remaining);
+            let mut buffer = vec![0.0; chunk * self.channels as usize];
+            match self.output.callback.lock().unwrap().as_mut() {
+                Some(MockCallback::Output(fill)) => fill(&mut buffer),
+                _ => return Err("Mock output is closed".to_string()),
+            }
+            played.extend_from_slice(&buffer);
+            remaining -= chunk;
+        }
+        Ok(played)
+    }
+
+    // Interleaved 16-bit frames continuing from where the last feed stopped
+    fn generate(&self, signals: &[Signal], frames: usize) -> Vec<i16> {
+        let mut clock = self.clock.lock().unwrap();
+        let mut noise_state = self.noise_state.lock().unwrap();
+        let mut samples = Vec::with_capacity(frames * signals.len());
+        for frame in 0..frames as u64 {
+            let t = (*clock + frame) as f64 / self.sample_rate as f64;
+            for signal in signals {
+                let value = match *signal {
+                    Signal::Silence => 0.0,
+                    Signal::Sine { frequency, amplitude } => amplitude * (2.0 * PI * (frequency as f64 * t).fract() as f32).sin(),
+                    Signal::Noise { amplitude } => {
+                        // xorshift32
+                        *noise_state ^= *noise_state << 13;
+                        *noise_state ^= *noise_state >> 17;
+                        *noise_state ^= *noise_state << 5;
+                        amplitude * (*noise_state as f32 / u32::MAX as f32 * 2.0 - 1.0)
+                    }
+                };
+                samples.push((value.clamp(-1.0, 1.0) * i16::MAX as f32) as i16);
+            }
+        }
+        *clock += frames as u64;
+        samples
+    }
+}
+
+impl AudioBackend for MockBackend {
+    fn input_devices(&self) -> Result<Vec<DeviceInfo>, String> {
+        Ok(vec![self.info(Self::INPUT_NAME)])
+    }
+
+    fn output_devices(&self) -> Result<Vec<DeviceInfo>, String> {
+        Ok(vec![self.info(Self::OUTPUT_NAME)])
+    }
+
+    fn default_input(&self) -> Result<DeviceInfo, String> {
+        Ok(self.info(Self::INPUT_NAME))
+    }
+
+    fn open_input(
+        &self,
+        device: Option<&str>,
+        format: StreamFormat,
+        on_samples: InputCallback,
+    ) -> Result<AudioStream, String> {
+        // The mock only ever runs at the format it was made with
+        if format.or(self.channels, self.sample_rate) != (self.channels, self.sample_rate) {
+            let (channels, sample_rate) = format.or(self.channels, self.sample_rate);
+            return Err(format!("{} can't record {} channel(s) at {} Hz", Self::INPUT_NAME, channels, sample_rate));
+        }
+        self.open(&self.input, Self::INPUT_NAME, device, MockCallback::Input(on_samples))
+    }
+
+    fn open_output(&self, device: Option<&str>, fill: OutputCallback) -> Result<AudioStream, String> {
+        self.open(&self.output, Self::OUTPUT_NAME, device, MockCallback::Output(fill))
+    }
+}
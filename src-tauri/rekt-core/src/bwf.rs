@@ -0,0 +1,142 @@
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+//
+// ====== Broadcast WAV (bext) metadata ======
+//
+
+const DESCRIPTION_LEN: usize = 256;
+const ORIGINATOR_LEN: usize = 32;
+const REFERENCE_LEN: usize = 32;
+const UMID_LEN: usize = 64;
+// Loudness fields and reserved space after the UMID, all zero in version 1
+const RESERVED_LEN: usize = 190;
+const VERSION: u16 = 1;
+
+/// The `bext` chunk of a Broadcast WAV file. Editors and sync tools line recordings up
+/// on their `time_reference`, the sample count since midnight at which they started.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bext {
+    pub description: String,
+    pub originator: String,
+    pub originator_reference: String,
+    /// Local date and time the recording started, to the second
+    pub origination: NaiveDateTime,
+    /// First sample's position, in samples since midnight on the origination date
+    pub time_reference: u64,
+    pub coding_history: String,
+}
+
+impl Bext {
+    /// Metadata for 16-bit PCM captured from `started` on, at the given format
+    pub fn new(started: DateTime<Local>, channels: u16, sample_rate: u32) -> Self {
+        let since_midnight = started.num_seconds_from_midnight() as f64 + started.nanosecond() as f64 / 1e9;
+        let mode = match channels {
+            1 => "mono",
+            2 => "stereo",
+            _ => "multichannel",
+        };
+        Self {
+            description: String::new(),
+            originator: "rekt".to_string(),
+            originator_reference: started.format("rekt%Y%m%d%H%M%S").to_string(),
+            origination: started.naive_local().with_nanosecond(0).unwrap_or(started.naive_local()),
+            time_reference: (since_midnight * sample_rate as f64).round() as u64,
+            coding_history: format!("A=PCM,F={},W=16,M={},T=rekt\r\n", sample_rate, mode),
+        }
+    }
+
+    /// The whole chunk, ID and size included, ready to go between `fmt ` and `data`
+    pub fn chunk(&self) -> Vec<u8> {
+        let mut body = Vec::new();
+        put_text(&mut body, &self.description, DESCRIPTION_LEN);
+        put_text(&mut body, &self.originator, ORIGINATOR_LEN);
+        put_text(&mut body, &self.originator_reference, REFERENCE_LEN);
+        body.extend_from_slice(self.origination.format("%Y-%m-%d").to_string().as_bytes());
+        body.extend_from_slice(self.origination.format("%H:%M:%S").to_string().as_bytes());
+        body.extend_from_slice(&(self.time_reference as u32).to_le_bytes());
+        body.extend_from_slice(&((self.time_reference >> 32) as u32).to_le_bytes());
+        body.extend_from_slice(&VERSION.to_le_bytes());
+        body.resize(body.len() + UMID_LEN + RESERVED_LEN, 0);
+        body.extend(self.coding_history.bytes().filter(u8::is_ascii));
+        // Chunks are word-aligned
+        if body.len() % 2 == 1 {
+            body.push(0);
+        }
+
+        let mut chunk = Vec::with_capacity(body.len() + 8);
+        chunk.extend_from_slice(b"bext");
+        chunk.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        chunk.extend_from_slice(&body);
+        chunk
+    }
+
+    fn parse(body: &[u8]) -> Option<Self> {
+        let mut rest = body;
+        let mut take = |len: usize| {
+            let (field, tail) = rest.split_at_checked(len)?;
+            rest = tail;
+            Some(field)
+        };
+        let description = get_text(take(DESCRIPTION_LEN)?);
+        let originator = get_text(take(ORIGINATOR_LEN)?);
+        let originator_reference = get_text(take(REFERENCE_LEN)?);
+        let date = NaiveDate::parse_from_str(&get_text(take(10)?), "%Y-%m-%d").ok()?;
+        let time = NaiveTime::parse_from_str(&get_text(take(8)?), "%H:%M:%S").ok()?;
+        let low = u32::from_le_bytes(take(4)?.try_into().ok()?) as u64;
+        let high = u32::from_le_bytes(take(4)?.try_into().ok()?) as u64;
+        take(2 + UMID_LEN + RESERVED_LEN)?;
+        Some(Self {
+            description,
+            originator,
+            originator_reference,
+            origination: date.and_time(time),
+            time_reference: high << 32 | low,
+            coding_history: get_text(rest),
+        })
+    }
+}
+
+// ASCII, cut or zero-padded to exactly `len` bytes
+fn put_text(out: &mut Vec<u8>, text: &str, len: usize) {
+    let start = out.len();
+    out.extend(text.bytes().filter(u8::is_ascii).take(len));
+    out.resize(start + len, 0);
+}
+
+fn get_text(field: &[u8]) -> String {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).to_string()
+}
+
+/// The `bext` chunk of a WAV file, if it has one
+pub fn read_bext(path: &Path) -> Result<Option<Bext>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open WAV file: {}", e))?;
+    let mut reader = BufReader::new(file);
+    let mut header = [0u8; 12];
+    reader
+        .read_exact(&mut header)
+        .map_err(|e| format!("Failed to read WAV header: {}", e))?;
+    if &header[..4] != b"RIFF" || &header[8..] != b"WAVE" {
+        return Err("Not a WAV file".to_string());
+    }
+
+    let mut chunk = [0u8; 8];
+    while reader.read_exact(&mut chunk).is_ok() {
+        let len = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]) as u64;
+        if &chunk[..4] == b"bext" {
+            let mut body = vec![0u8; len as usize];
+            reader
+                .read_exact(&mut body)
+                .map_err(|e| format!("Failed to read bext chunk: {}", e))?;
+            return Ok(Bext::parse(&body));
+        }
+        reader
+            .seek(SeekFrom::Current((len + len % 2) as i64))
+            .map_err(|e| format!("Failed to read WAV file: {}", e))?;
+    }
+    Ok(None)
+}
@@ -0,0 +1,353 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+
+//
+// ====== iCalendar events ======
+//
+
+// Occurrences of a recurring event are generated from its start; stop eventually even
+// for rules that never end
+const MAX_OCCURRENCES: usize = 10_000;
+
+/// One occurrence of a meeting
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub title: String,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+// Times written with a trailing `Z` are UTC; everything else, including times with a
+// `TZID`, is taken as the local zone
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Zone {
+    Utc,
+    Local,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Time {
+    naive: NaiveDateTime,
+    zone: Zone,
+}
+
+impl Time {
+    fn parse(value: &str) -> Option<Self> {
+        let (value, zone) = match value.strip_suffix('Z') {
+            Some(utc) => (utc, Zone::Utc),
+            None => (value, Zone::Local),
+        };
+        let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+            .ok()
+            .or_else(|| NaiveDate::parse_from_str(value, "%Y%m%d").ok()?.and_hms_opt(0, 0, 0))?;
+        Some(Self { naive, zone })
+    }
+
+    fn local(self) -> Option<DateTime<Local>> {
+        match self.zone {
+            Zone::Utc => Some(Utc.from_utc_datetime(&self.naive).with_timezone(&Local)),
+            // The earlier of the two during a DST fall-back
+            Zone::Local => Local.from_local_datetime(&self.naive).earliest(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Frequency {
+    Daily,
+    Weekly,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<usize>,
+    until: Option<Time>,
+    /// Weekdays of a weekly rule; empty means the start's weekday
+    weekdays: Vec<Weekday>,
+}
+
+impl Rule {
+    fn parse(value: &str) -> Option<Self> {
+        let mut rule = Self {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            weekdays: Vec::new(),
+        };
+        let mut frequency = None;
+        for part in value.split(';') {
+            let (key, value) = part.split_once('=')?;
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Some(Frequency::Daily),
+                        "WEEKLY" => Some(Frequency::Weekly),
+                        _ => None,
+                    }
+                }
+                "INTERVAL" => rule.interval = value.parse().ok().filter(|&i| i > 0)?,
+                "COUNT" => rule.count = Some(value.parse().ok()?),
+                "UNTIL" => rule.until = Some(Time::parse(value)?),
+                // Position prefixes like `2MO` only mean something for monthly rules
+                "BYDAY" => {
+                    let position = |c: char| c == '+' || c == '-' || c.is_ascii_digit();
+                    rule.weekdays = value
+                        .split(',')
+                        .filter_map(|day| weekday(day.trim_start_matches(position)))
+                        .collect()
+                }
+                _ => {}
+            }
+        }
+        rule.frequency = frequency?;
+        Some(rule)
+    }
+
+    // Start times from `first` on, in order, until `past` returns true for one
+    fn starts(&self, first: Time, mut past: impl FnMut(Time) -> bool) -> Vec<Time> {
+        let at = |naive: NaiveDateTime| Time { naive, zone: first.zone };
+        let mut starts = Vec::new();
+        let mut push = |time: Time| -> bool {
+            if self.count.is_some_and(|count| starts.len() >= count)
+                || self.until.is_some_and(|until| time.naive > until.naive)
+                || starts.len() >= MAX_OCCURRENCES
+                || past(time)
+            {
+                return false;
+            }
+            starts.push(time);
+            true
+        };
+
+        match self.frequency {
+            Frequency::Daily => {
+                let mut naive = first.naive;
+                while push(at(naive)) {
+                    naive += Duration::days(self.interval as i64);
+                }
+            }
+            Frequency::Weekly => {
+                let mut weekdays = self.weekdays.clone();
+                if weekdays.is_empty() {
+                    weekdays.push(first.naive.weekday());
+                }
+                weekdays.sort_by_key(|day| day.num_days_from_monday());
+                let monday = first.naive - Duration::days(first.naive.weekday().num_days_from_monday() as i64);
+                'weeks: for week in 0.. {
+                    let week_start = monday + Duration::weeks(week * self.interval as i64);
+                    for day in &weekdays {
+                        let naive = week_start + Duration::days(day.num_days_from_monday() as i64);
+                        if naive >= first.naive && !push(at(naive)) {
+                            break 'weeks;
+                        }
+                    }
+                }
+            }
+        }
+        starts
+    }
+}
+
+fn weekday(code: &str) -> Option<Weekday> {
+    Some(match code.to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+// `P1D`, `PT1H30M`, `P2W` and the like
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.strip_prefix('+').unwrap_or(value).strip_prefix('P')?;
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in value.chars() {
+        match c {
+            'T' => {}
+            '0'..='9' => number.push(c),
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match unit {
+                    'W' => Duration::weeks(n),
+                    'D' => Duration::days(n),
+                    'H' => Duration::hours(n),
+                    'M' => Duration::minutes(n),
+                    'S' => Duration::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+    Some(total)
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push(' '),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out.trim().to_string()
+}
+
+#[derive(Debug, Default)]
+struct Vevent {
+    uid: String,
+    title: String,
+    start: Option<Time>,
+    end: Option<Time>,
+    duration: Option<Duration>,
+    all_day: bool,
+    cancelled: bool,
+    rule: Option<Rule>,
+    excluded: Vec<Time>,
+    /// Set on an edited occurrence of a recurring event, which replaces that occurrence
+    recurrence_id: Option<Time>,
+}
+
+impl Vevent {
+    fn set(&mut self, name: &str, value: &str) {
+        match name {
+            "UID" => self.uid = value.to_string(),
+            "SUMMARY" => self.title = unescape(value),
+            "DTSTART" => {
+                self.start = Time::parse(value);
+                // A date without a time
+                self.all_day = !value.contains('T');
+            }
+            "DTEND" => self.end = Time::parse(value),
+            "DURATION" => self.duration = parse_duration(value),
+            "STATUS" => self.cancelled = value.eq_ignore_ascii_case("CANCELLED"),
+            "RRULE" => self.rule = Rule::parse(value),
+            "EXDATE" => self.excluded.extend(value.split(',').filter_map(Time::parse)),
+            "RECURRENCE-ID" => self.recurrence_id = Time::parse(value),
+            _ => {}
+        }
+    }
+}
+
+/// The timed events of an iCalendar (`.ics`) document. All-day events and cancelled
+/// ones are left out, since there is nothing to record for them.
+#[derive(Debug, Default)]
+pub struct Calendar {
+    events: Vec<Vevent>,
+}
+
+impl Calendar {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        // Long lines are folded onto continuation lines that start with whitespace
+        let mut lines: Vec<String> = Vec::new();
+        for line in text.lines() {
+            match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+                (Some(rest), Some(last)) => last.push_str(rest),
+                _ => lines.push(line.to_string()),
+            }
+        }
+        if !lines.iter().any(|line| line.eq_ignore_ascii_case("BEGIN:VCALENDAR")) {
+            return Err("Not an iCalendar file".to_string());
+        }
+
+        let mut events = Vec::new();
+        // Components nested inside the event, like alarms, have their own properties
+        let mut current: Option<(Vevent, usize)> = None;
+        for line in &lines {
+            let Some((head, value)) = split_property(line) else {
+                continue;
+            };
+            // Parameters like `TZID` are ignored; see `Zone`
+            let name = head.split(';').next().unwrap_or(head).to_ascii_uppercase();
+            match (name.as_str(), current.as_mut()) {
+                ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => current = Some((Vevent::default(), 0)),
+                ("BEGIN", Some((_, depth))) => *depth += 1,
+                ("END", Some((_, depth))) if *depth > 0 => *depth -= 1,
+                ("END", Some(_)) => events.extend(current.take().map(|(event, _)| event)),
+                (_, Some((event, 0))) => event.set(&name, value),
+                _ => {}
+            }
+        }
+        Ok(Self { events })
+    }
+
+    /// Occurrences overlapping `from..to`, earliest first
+    pub fn events_between(&self, from: DateTime<Local>, to: DateTime<Local>) -> Vec<CalendarEvent> {
+        let mut found = Vec::new();
+        for event in &self.events {
+            let Some(start) = event.start.filter(|_| !event.all_day && !event.cancelled) else {
+                continue;
+            };
+            let length = match (event.end, event.duration) {
+                (Some(end), _) => end.naive - start.naive,
+                (None, Some(duration)) => duration,
+                (None, None) => Duration::zero(),
+            };
+            // Occurrences edited separately are listed as events of their own
+            let overridden = self
+                .events
+                .iter()
+                .filter(|other| other.uid == event.uid)
+                .filter_map(|other| other.recurrence_id)
+                .collect::<Vec<_>>();
+
+            let starts = match (&event.rule, event.recurrence_id) {
+                (Some(rule), None) => rule.starts(start, |time| time.local().is_some_and(|t| t >= to)),
+                _ => vec![start],
+            };
+            for occurrence in starts {
+                let skipped = event.excluded.iter().chain(&overridden).any(|t| t.local() == occurrence.local());
+                if event.recurrence_id.is_none() && skipped {
+                    continue;
+                }
+                let (Some(begin), Some(end)) = (
+                    occurrence.local(),
+                    Time {
+                        naive: occurrence.naive + length,
+                        ..occurrence
+                    }
+                    .local(),
+                ) else {
+                    continue;
+                };
+                if begin < to && end > from {
+                    found.push(CalendarEvent {
+                        uid: event.uid.clone(),
+                        title: event.title.clone(),
+                        start: begin,
+                        end,
+                    });
+                }
+            }
+        }
+        found.sort_by_key(|event| event.start);
+        found
+    }
+}
+
+// `NAME;PARAM="a:b":value` splits at the first colon outside quotes
+fn split_property(line: &str) -> Option<(&str, &str)> {
+    let mut quoted = false;
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ':' if !quoted => return Some((&line[..index], &line[index + 1..])),
+            _ => {}
+        }
+    }
+    None
+}
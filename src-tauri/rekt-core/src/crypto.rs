@@ -0,0 +1,46 @@
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+
+//
+// ====== Sealed files ======
+//
+
+const MAGIC: &[u8; 8] = b"REKTENC1";
+const NONCE_LEN: usize = 24;
+
+/// Stretch a passphrase into a 256-bit key with Argon2
+pub fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Failed to derive key: {}", e))?;
+    Ok(key)
+}
+
+/// Encrypt `plaintext` with a fresh random nonce.
+/// Layout: MAGIC | nonce | ciphertext+tag
+pub fn seal(key: &[u8; 32], plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(key));
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| "Encryption failed".to_string())?;
+
+    let mut sealed = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(MAGIC);
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+/// Decrypt data produced by [`seal`], failing on a wrong key or any tampering
+pub fn open(key: &[u8; 32], sealed: &[u8]) -> Result<Vec<u8>, String> {
+    if sealed.len() < MAGIC.len() + NONCE_LEN || &sealed[..MAGIC.len()] != MAGIC {
+        return Err("Not an encrypted recording".to_string());
+    }
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(key));
+    let (nonce, ciphertext) = sealed[MAGIC.len()..].split_at(NONCE_LEN);
+    cipher
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Decryption failed: wrong passphrase or corrupted file".to_string())
+}
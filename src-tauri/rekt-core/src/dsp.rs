@@ -0,0 +1,196 @@
+use std::f32::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+//
+// ====== Real-time filters ======
+//
+
+// Pole of the DC blocker; closer to 1 means a lower cutoff (about 4 Hz at 48 kHz)
+const DC_BLOCKER_POLE: f32 = 0.9995;
+const BUTTERWORTH_Q: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+fn default_high_pass_hz() -> Option<f32> {
+    Some(80.0)
+}
+
+/// Cleanup applied to the input as it is captured, set per profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputFilterConfig {
+    /// Remove a constant offset from the signal
+    #[serde(default)]
+    pub remove_dc: bool,
+    /// Cut rumble below this frequency; `None` disables the high-pass
+    #[serde(default = "default_high_pass_hz")]
+    pub high_pass_hz: Option<f32>,
+}
+
+impl InputFilterConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(hz) = self.high_pass_hz {
+            if !(20.0..=500.0).contains(&hz) {
+                return Err("High-pass frequency must be between 20 and 500 Hz".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Second-order IIR section (RBJ cookbook), processed in direct form I
+#[derive(Debug, Clone)]
+pub struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl Biquad {
+    fn new(b0: f32, b1: f32, b2: f32, a0: f32, a1: f32, a2: f32) -> Self {
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+            x1: 0.0,
+            x2: 0.0,
+            y1: 0.0,
+            y2: 0.0,
+        }
+    }
+
+    pub fn high_pass(cutoff_hz: f32, q: f32, sample_rate: u32) -> Self {
+        let w0 = 2.0 * PI * cutoff_hz / sample_rate as f32;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * q);
+        Self::new(
+            (1.0 + cos) / 2.0,
+            -(1.0 + cos),
+            (1.0 + cos) / 2.0,
+            1.0 + alpha,
+            -2.0 * cos,
+            1.0 - alpha,
+        )
+    }
+
+    pub fn peaking(center_hz: f32, gain_db: f32, q: f32, sample_rate: u32) -> Self {
+        let a = 10f32.powf(gain_db / 40.0);
+        let w0 = 2.0 * PI * center_hz / sample_rate as f32;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * q);
+        Self::new(
+            1.0 + alpha * a,
+            -2.0 * cos,
+            1.0 - alpha * a,
+            1.0 + alpha / a,
+            -2.0 * cos,
+            1.0 - alpha / a,
+        )
+    }
+
+    pub fn low_shelf(corner_hz: f32, gain_db: f32, q: f32, sample_rate: u32) -> Self {
+        let a = 10f32.powf(gain_db / 40.0);
+        let w0 = 2.0 * PI * corner_hz / sample_rate as f32;
+        let (sin, cos) = w0.sin_cos();
+        let beta = 2.0 * a.sqrt() * sin / (2.0 * q);
+        Self::new(
+            a * ((a + 1.0) - (a - 1.0) * cos + beta),
+            2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+            a * ((a + 1.0) - (a - 1.0) * cos - beta),
+            (a + 1.0) + (a - 1.0) * cos + beta,
+            -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+            (a + 1.0) + (a - 1.0) * cos - beta,
+        )
+    }
+
+    pub fn high_shelf(corner_hz: f32, gain_db: f32, q: f32, sample_rate: u32) -> Self {
+        let a = 10f32.powf(gain_db / 40.0);
+        let w0 = 2.0 * PI * corner_hz / sample_rate as f32;
+        let (sin, cos) = w0.sin_cos();
+        let beta = 2.0 * a.sqrt() * sin / (2.0 * q);
+        Self::new(
+            a * ((a + 1.0) + (a - 1.0) * cos + beta),
+            -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+            a * ((a + 1.0) + (a - 1.0) * cos - beta),
+            (a + 1.0) - (a - 1.0) * cos + beta,
+            2.0 * ((a - 1.0) - (a + 1.0) * cos),
+            (a + 1.0) - (a - 1.0) * cos - beta,
+        )
+    }
+
+    pub fn process(&mut self, x: f32) -> f32 {
+        let y = self.b0 * x + self.b1 * self.x1 + self.b2 * self.x2 - self.a1 * self.y1 - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
+
+// One-pole high-pass that only removes the constant component
+#[derive(Debug, Clone, Default)]
+struct DcBlocker {
+    x1: f32,
+    y1: f32,
+}
+
+impl DcBlocker {
+    fn process(&mut self, x: f32) -> f32 {
+        let y = x - self.x1 + DC_BLOCKER_POLE * self.y1;
+        self.x1 = x;
+        self.y1 = y;
+        y
+    }
+}
+
+/// Per-channel filter state for one recording
+pub struct InputFilter {
+    channels: usize,
+    dc_blockers: Option<Vec<DcBlocker>>,
+    high_pass: Option<Vec<Biquad>>,
+}
+
+impl InputFilter {
+    pub fn new(config: &InputFilterConfig, channels: u16, sample_rate: u32) -> Self {
+        let channels = channels.max(1) as usize;
+        Self {
+            channels,
+            dc_blockers: config.remove_dc.then(|| vec![DcBlocker::default(); channels]),
+            high_pass: config
+                .high_pass_hz
+                .map(|hz| vec![Biquad::high_pass(hz, BUTTERWORTH_Q, sample_rate); channels]),
+        }
+    }
+
+    /// Filter interleaved samples in place
+    pub fn process(&mut self, samples: &mut [i16]) {
+        for (i, sample) in samples.iter_mut().enumerate() {
+            let channel = i % self.channels;
+            let mut x = *sample as f32;
+            if let Some(dc_blockers) = self.dc_blockers.as_mut() {
+                x = dc_blockers[channel].process(x);
+            }
+            if let Some(high_pass) = self.high_pass.as_mut() {
+                x = high_pass[channel].process(x);
+            }
+            *sample = x.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+        }
+    }
+}
+
+//
+// ====== Windows ======
+//
+
+/// Symmetric Hann window of `len` points, zero at both ends
+pub fn hann_window(len: usize) -> Vec<f32> {
+    let last = len.saturating_sub(1).max(1) as f32;
+    (0..len).map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / last).cos()).collect()
+}
@@ -0,0 +1,176 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::backend::{DeviceInfo, InputCallback};
+
+//
+// ====== Stereo from two mono inputs ======
+//
+
+// Right-channel audio kept waiting, enough to ride out the two devices' callbacks
+// arriving at different times
+const TARGET_BACKLOG_MS: f64 = 40.0;
+// More than this and the right device has stalled and caught up in a burst; skip ahead
+const MAX_BACKLOG_MS: f64 = 500.0;
+// Ratio change per millisecond of backlog error, and the most the ratio may be bent.
+// Crystal clocks disagree by tens of ppm, so a fraction of a percent is plenty.
+const CORRECTION_PER_MS: f64 = 0.000_02;
+const MAX_CORRECTION: f64 = 0.002;
+// The backlog jumps by a whole buffer with every callback; steer by its average
+const BACKLOG_SMOOTHING: f64 = 0.01;
+
+/// Two input devices recorded as the left and right channel of one stereo recording
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DevicePair {
+    pub left: String,
+    pub right: String,
+}
+
+#[derive(Clone, Copy)]
+struct Format {
+    channels: usize,
+    sample_rate: u32,
+}
+
+#[derive(Default)]
+struct Joiner {
+    left: Option<Format>,
+    right: Option<Format>,
+    // Right-device audio, mixed to mono, waiting for the left device to catch up
+    queue: VecDeque<f32>,
+    // Fractional read position into `queue`
+    position: f64,
+    backlog_ms: f64,
+    correction: f64,
+    // Whether enough right audio has built up to start reading it
+    primed: bool,
+}
+
+impl Joiner {
+    fn target_backlog(&self, right: Format) -> usize {
+        (TARGET_BACKLOG_MS * right.sample_rate as f64 / 1000.0) as usize
+    }
+
+    fn join(&mut self, left_samples: &[i16]) -> Vec<i16> {
+        let (Some(left), Some(right)) = (self.left, self.right) else {
+            return Vec::new();
+        };
+        let target = self.target_backlog(right);
+        if !self.primed && self.queue.len() >= target {
+            self.queue.drain(..self.queue.len() - target);
+            self.position = 0.0;
+            self.primed = true;
+        }
+
+        let step = right.sample_rate as f64 / left.sample_rate as f64 * (1.0 + self.correction);
+        let mut stereo = Vec::with_capacity(left_samples.len() / left.channels * 2);
+        for frame in left_samples.chunks_exact(left.channels) {
+            stereo.push(mono(frame));
+            let index = self.position as usize;
+            let sample = match (self.queue.get(index), self.queue.get(index + 1)) {
+                (Some(&a), Some(&b)) if self.primed => {
+                    let t = self.position.fract() as f32;
+                    self.position += step;
+                    a + (b - a) * t
+                }
+                _ => {
+                    // Ran dry: the right device fell behind, so rebuild the backlog
+                    if self.primed {
+                        debug!("Right input ran dry; waiting for it to catch up");
+                    }
+                    self.primed = false;
+                    0.0
+                }
+            };
+            stereo.push(sample.clamp(i16::MIN as f32, i16::MAX as f32) as i16);
+        }
+        let consumed = (self.position as usize).min(self.queue.len());
+        self.queue.drain(..consumed);
+        self.position -= consumed as f64;
+
+        let backlog_ms = self.queue.len() as f64 * 1000.0 / right.sample_rate as f64;
+        if backlog_ms > MAX_BACKLOG_MS {
+            debug!("Right input is {:.0} ms ahead; skipping ahead", backlog_ms);
+            self.queue.drain(..self.queue.len() - target);
+            self.position = 0.0;
+            self.backlog_ms = TARGET_BACKLOG_MS;
+        } else {
+            self.backlog_ms += (backlog_ms - self.backlog_ms) * BACKLOG_SMOOTHING;
+        }
+        // A growing backlog means the right clock runs fast, so read it faster
+        self.correction =
+            ((self.backlog_ms - TARGET_BACKLOG_MS) * CORRECTION_PER_MS).clamp(-MAX_CORRECTION, MAX_CORRECTION);
+        stereo
+    }
+}
+
+// One frame averaged down to a single sample
+fn mono(frame: &[i16]) -> i16 {
+    (frame.iter().map(|&s| s as i32).sum::<i32>() / frame.len() as i32) as i16
+}
+
+/// Joins two inputs into one stereo signal. The left device's clock paces the recording;
+/// the right device is resampled to follow it, slightly faster or slower as its backlog
+/// grows or shrinks, so the channels stay in sync even though the hardware clocks drift.
+#[derive(Default)]
+pub struct DualMono {
+    joiner: Mutex<Joiner>,
+}
+
+impl DualMono {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Set the two streams' formats once both are open; input before that is dropped
+    pub fn configure(&self, left: &DeviceInfo, right: &DeviceInfo) {
+        let mut joiner = self.joiner.lock().unwrap();
+        *joiner = Joiner {
+            left: Some(Format {
+                channels: left.channels.max(1) as usize,
+                sample_rate: left.sample_rate.max(1),
+            }),
+            right: Some(Format {
+                channels: right.channels.max(1) as usize,
+                sample_rate: right.sample_rate.max(1),
+            }),
+            backlog_ms: TARGET_BACKLOG_MS,
+            ..Joiner::default()
+        };
+    }
+
+    /// Callback for the right device, which only queues its audio
+    pub fn right_input(self: &Arc<Self>) -> InputCallback {
+        let pair = Arc::clone(self);
+        Box::new(move |samples| {
+            let mut joiner = pair.joiner.lock().unwrap();
+            if let Some(right) = joiner.right {
+                joiner.queue.extend(samples.chunks_exact(right.channels).map(|frame| mono(frame) as f32));
+            }
+        })
+    }
+
+    /// Callback for the left device: each block it delivers is joined with the right
+    /// channel and handed on as interleaved stereo
+    pub fn left_input<F>(self: &Arc<Self>, mut on_stereo: F) -> InputCallback
+    where
+        F: FnMut(&[i16]) + Send + 'static,
+    {
+        let pair = Arc::clone(self);
+        Box::new(move |samples| {
+            let stereo = pair.joiner.lock().unwrap().join(samples);
+            if !stereo.is_empty() {
+                on_stereo(&stereo);
+            }
+        })
+    }
+
+    /// How much the right device's audio is currently sped up (positive) or slowed down
+    /// to keep up with the left, in parts per million
+    pub fn drift_ppm(&self) -> f64 {
+        self.joiner.lock().unwrap().correction * 1_000_000.0
+    }
+}
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+use crate::processing::AudioBuffer;
+
+//
+// ====== Non-destructive edit lists ======
+//
+
+// Fade at each side of a join between clips that weren't next to each other, so the
+// cut doesn't click
+const JOIN_FADE_MS: u64 = 5;
+
+/// A span of the original recording
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Clip {
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+/// The edited recording as spans of the original, played in order. Trims, cuts and
+/// reorderings are all just a different list of clips; the file itself never changes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EditList {
+    pub clips: Vec<Clip>,
+}
+
+impl EditList {
+    pub fn validate(&self, duration_ms: u64) -> Result<(), String> {
+        if self.clips.is_empty() {
+            return Err("An edit list needs at least one clip".to_string());
+        }
+        for clip in &self.clips {
+            if clip.end_ms <= clip.start_ms {
+                return Err("Clips must end after they start".to_string());
+            }
+            if clip.end_ms > duration_ms {
+                return Err(format!("Clip ends at {} ms, past the end of the recording", clip.end_ms));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn duration_ms(&self) -> u64 {
+        self.clips.iter().map(|clip| clip.end_ms - clip.start_ms).sum()
+    }
+
+    /// Render the edited recording from the original audio
+    pub fn apply(&self, buffer: &AudioBuffer) -> AudioBuffer {
+        let channels = buffer.channels.max(1) as usize;
+        let frames = buffer.samples.len() / channels;
+        let to_frame = |ms: u64| ((ms * buffer.sample_rate as u64 / 1000) as usize).min(frames);
+        let fade = (JOIN_FADE_MS * buffer.sample_rate as u64 / 1000) as usize;
+
+        let mut samples = Vec::with_capacity(to_frame(self.duration_ms()) * channels);
+        for (index, clip) in self.clips.iter().enumerate() {
+            let (start, end) = (to_frame(clip.start_ms), to_frame(clip.end_ms));
+            let joined_before = index > 0 && self.clips[index - 1].end_ms != clip.start_ms;
+            let joined_after = self.clips.get(index + 1).is_some_and(|next| next.start_ms != clip.end_ms);
+            let fade = fade.min((end - start) / 2);
+
+            for frame in start..end {
+                let from_start = frame - start;
+                let from_end = end - 1 - frame;
+                let gain = match (joined_before && from_start < fade, joined_after && from_end < fade) {
+                    (true, _) => from_start as f32 / fade as f32,
+                    (_, true) => from_end as f32 / fade as f32,
+                    _ => 1.0,
+                };
+                samples.extend(buffer.samples[frame * channels..(frame + 1) * channels].iter().map(|s| s * gain));
+            }
+        }
+
+        AudioBuffer {
+            channels: buffer.channels,
+            sample_rate: buffer.sample_rate,
+            samples,
+        }
+    }
+}
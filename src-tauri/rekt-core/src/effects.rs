@@ -0,0 +1,179 @@
+use std::f32::consts::PI;
+
+use rustfft::num_complex::Complex;
+use rustfft::FftPlanner;
+use serde::{Deserialize, Serialize};
+
+use crate::dsp;
+use crate::processing::{self, AudioBuffer, StereoConfig};
+
+//
+// ====== Offline effects ======
+//
+
+const STFT_SIZE: usize = 2048;
+const SYNTHESIS_HOP: usize = STFT_SIZE / 4;
+
+fn default_ratio() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessOptions {
+    /// Playback speed; 0.5 is half speed, 2.0 double, without changing pitch
+    #[serde(default = "default_ratio")]
+    pub tempo_ratio: f32,
+    /// Transpose by this many semitones without changing speed
+    #[serde(default)]
+    pub pitch_semitones: f32,
+    /// Mid/side decoding or stereo width change, applied before tempo and pitch
+    #[serde(default)]
+    pub stereo: Option<StereoConfig>,
+}
+
+impl ProcessOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.25..=4.0).contains(&self.tempo_ratio) {
+            return Err("Tempo ratio must be between 0.25 and 4".to_string());
+        }
+        if !(-24.0..=24.0).contains(&self.pitch_semitones) {
+            return Err("Pitch shift must be between -24 and +24 semitones".to_string());
+        }
+        if let Some(stereo) = &self.stereo {
+            stereo.validate()?;
+        } else if !self.changes_tempo_or_pitch() {
+            return Err("Nothing to do: tempo ratio is 1 and pitch shift is 0".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn changes_tempo_or_pitch(&self) -> bool {
+        self.tempo_ratio != 1.0 || self.pitch_semitones != 0.0
+    }
+}
+
+fn wrap_phase(phase: f32) -> f32 {
+    (phase + PI).rem_euclid(2.0 * PI) - PI
+}
+
+// Phase vocoder: re-space STFT frames by `stretch` and advance each bin's phase by its
+// measured frequency so partials stay continuous across the new spacing
+fn stretch_channel<P>(input: &[f32], stretch: f32, progress: &mut P, span: (f32, f32)) -> Result<Vec<f32>, String>
+where
+    P: FnMut(f32) -> Result<(), String>,
+{
+    let analysis_hop = SYNTHESIS_HOP as f32 / stretch;
+    let bins = STFT_SIZE / 2 + 1;
+    // Periodic rather than symmetric, so overlapping frames add back up evenly
+    let mut window = dsp::hann_window(STFT_SIZE + 1);
+    window.pop();
+
+    let mut planner = FftPlanner::new();
+    let forward = planner.plan_fft_forward(STFT_SIZE);
+    let inverse = planner.plan_fft_inverse(STFT_SIZE);
+
+    let frames = (input.len() as f32 / analysis_hop).ceil() as usize + 1;
+    let output_len = (frames - 1) * SYNTHESIS_HOP + STFT_SIZE;
+    let mut output = vec![0f32; output_len];
+    let mut weights = vec![0f32; output_len];
+    let mut last_phase = vec![0f32; bins];
+    let mut synth_phase = vec![0f32; bins];
+    let mut spectrum = vec![Complex::default(); STFT_SIZE];
+    let mut last_start = 0;
+
+    for frame in 0..frames {
+        if frame % 256 == 0 {
+            progress(span.0 + span.1 * frame as f32 / frames as f32)?;
+        }
+
+        // Frames start on whole samples, so measure the hop actually taken
+        let start = (frame as f32 * analysis_hop) as usize;
+        let hop = (start - last_start).max(1) as f32;
+        last_start = start;
+        for (i, slot) in spectrum.iter_mut().enumerate() {
+            *slot = Complex::new(input.get(start + i).copied().unwrap_or(0.0) * window[i], 0.0);
+        }
+        forward.process(&mut spectrum);
+
+        for bin in 0..bins {
+            let (magnitude, phase) = spectrum[bin].to_polar();
+            let expected = 2.0 * PI * bin as f32 * hop / STFT_SIZE as f32;
+            let deviation = wrap_phase(phase - last_phase[bin] - expected);
+            last_phase[bin] = phase;
+            let frequency = 2.0 * PI * bin as f32 / STFT_SIZE as f32 + deviation / hop;
+            synth_phase[bin] = if frame == 0 { phase } else { synth_phase[bin] + frequency * SYNTHESIS_HOP as f32 };
+            spectrum[bin] = Complex::from_polar(magnitude, synth_phase[bin]);
+        }
+        // Real signal: the upper half mirrors the lower
+        for bin in bins..STFT_SIZE {
+            spectrum[bin] = spectrum[STFT_SIZE - bin].conj();
+        }
+        inverse.process(&mut spectrum);
+
+        let offset = frame * SYNTHESIS_HOP;
+        for i in 0..STFT_SIZE {
+            output[offset + i] += spectrum[i].re / STFT_SIZE as f32 * window[i];
+            weights[offset + i] += window[i] * window[i];
+        }
+    }
+
+    for (sample, weight) in output.iter_mut().zip(&weights) {
+        if *weight > 1e-3 {
+            *sample /= weight;
+        }
+    }
+    output.truncate((input.len() as f32 * stretch) as usize);
+    Ok(output)
+}
+
+/// Change tempo and pitch independently: stretch by the combined factor, then resample
+/// to undo the length change the pitch shift needs. `progress` gets the fraction done
+/// and may abort with an error.
+pub fn change_tempo_and_pitch<P>(buffer: &AudioBuffer, options: &ProcessOptions, mut progress: P) -> Result<AudioBuffer, String>
+where
+    P: FnMut(f32) -> Result<(), String>,
+{
+    let pitch = 2f32.powf(options.pitch_semitones / 12.0);
+    let stretch = pitch / options.tempo_ratio;
+    let channels = buffer.channels.max(1) as usize;
+
+    let mut stretched_channels = Vec::with_capacity(channels);
+    for channel in 0..channels {
+        let input = buffer.samples.iter().skip(channel).step_by(channels).copied().collect::<Vec<_>>();
+        let share = 1.0 / channels as f32;
+        stretched_channels.push(stretch_channel(&input, stretch, &mut progress, (channel as f32 * share, share))?);
+    }
+
+    let frames = stretched_channels.iter().map(Vec::len).min().unwrap_or(0);
+    let mut samples = Vec::with_capacity(frames * channels);
+    for frame in 0..frames {
+        samples.extend(stretched_channels.iter().map(|channel| channel[frame]));
+    }
+
+    // Played back `pitch` times faster, the stretched audio is back to the requested length
+    let stretched = AudioBuffer {
+        channels: buffer.channels,
+        sample_rate: (buffer.sample_rate as f32 * pitch).round() as u32,
+        samples,
+    };
+    let mut result = processing::resample(&stretched, buffer.sample_rate);
+    // Overlapping partials can sum past full scale
+    processing::limit(&mut result, -1.0);
+    Ok(result)
+}
+
+/// Apply every step `options` asks for: stereo treatment, then tempo and pitch
+pub fn process<P>(mut buffer: AudioBuffer, options: &ProcessOptions, progress: P) -> Result<AudioBuffer, String>
+where
+    P: FnMut(f32) -> Result<(), String>,
+{
+    if let Some(stereo) = &options.stereo {
+        processing::apply_stereo(&mut buffer, stereo);
+    }
+    if options.changes_tempo_or_pitch() {
+        change_tempo_and_pitch(&buffer, options, progress)
+    } else {
+        processing::limit(&mut buffer, processing::STEREO_CEILING_DB);
+        Ok(buffer)
+    }
+}
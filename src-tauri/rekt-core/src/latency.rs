@@ -0,0 +1,184 @@
+use std::f32::consts::PI;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::backend::{AudioBackend, StreamFormat};
+
+//
+// ====== Round-trip latency ======
+//
+
+const CHIRP_MS: u32 = 60;
+const CHIRP_START_HZ: f32 = 500.0;
+const CHIRP_END_HZ: f32 = 8_000.0;
+const CHIRP_LEVEL: f32 = 0.5;
+// Silence before the chirp, so both streams are running steadily when it plays
+const LEAD_IN: Duration = Duration::from_millis(300);
+// Longer round trips than this aren't measured
+const MAX_LATENCY: Duration = Duration::from_secs(1);
+// How far the correlation peak has to stand out from the average for the chirp to
+// count as heard rather than a coincidence in noise
+const MIN_CLARITY: f32 = 8.0;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RoundTrip {
+    /// From handing the chirp to the output device to getting it back from the input
+    pub latency_ms: f64,
+    pub input_device: String,
+    pub output_device: String,
+    /// How far the chirp stood out from the background; higher is more trustworthy
+    pub clarity: f32,
+}
+
+/// A linear sine sweep, faded in and out so it doesn't click
+pub fn chirp(sample_rate: u32) -> Vec<f32> {
+    let len = (sample_rate * CHIRP_MS / 1000) as usize;
+    let duration = len as f32 / sample_rate as f32;
+    let fade = (len / 10).max(1);
+    (0..len)
+        .map(|i| {
+            let t = i as f32 / sample_rate as f32;
+            let sweep = CHIRP_START_HZ * t + (CHIRP_END_HZ - CHIRP_START_HZ) * t * t / (2.0 * duration);
+            let envelope = (i.min(len - 1 - i) as f32 / fade as f32).min(1.0);
+            (2.0 * PI * sweep).sin() * envelope
+        })
+        .collect()
+}
+
+/// Where `chirp` starts in `mono`, by cross-correlation, and how far the match stands
+/// out from the average correlation
+pub fn find_chirp(mono: &[f32], chirp: &[f32]) -> Option<(usize, f32)> {
+    if chirp.is_empty() || mono.len() < chirp.len() {
+        return None;
+    }
+    let scores = mono
+        .windows(chirp.len())
+        .map(|window| window.iter().zip(chirp).map(|(a, b)| a * b).sum::<f32>().abs())
+        .collect::<Vec<_>>();
+    let (start, peak) = scores.iter().copied().enumerate().max_by(|a, b| a.1.total_cmp(&b.1))?;
+    let mean = scores.iter().sum::<f32>() / scores.len() as f32;
+    (mean > 0.0).then_some((start, peak / mean))
+}
+
+// What the output callback has played, and when the chirp went out
+#[derive(Default)]
+struct Player {
+    channels: usize,
+    sample_rate: u32,
+    chirp: Vec<f32>,
+    frames: usize,
+    sent_at: Option<Instant>,
+}
+
+// Everything the input delivered, with when each callback's last sample arrived
+#[derive(Default)]
+struct Capture {
+    samples: Vec<i16>,
+    arrivals: Vec<(usize, Instant)>,
+}
+
+/// Play a chirp on `output` and listen for it on `input` (the defaults when `None`).
+/// Needs the sound to get from one to the other: a loopback cable, or a microphone
+/// within earshot of the speaker. Blocks for a bit over a second.
+pub fn measure_roundtrip(
+    backend: &dyn AudioBackend,
+    input: Option<&str>,
+    output: Option<&str>,
+) -> Result<RoundTrip, String> {
+    let capture = Arc::new(Mutex::new(Capture::default()));
+    let receiver = Arc::clone(&capture);
+    let input_stream = backend.open_input(
+        input,
+        StreamFormat::default(),
+        Box::new(move |samples| {
+            let mut capture = receiver.lock().unwrap();
+            capture.samples.extend_from_slice(samples);
+            let received = capture.samples.len();
+            capture.arrivals.push((received, Instant::now()));
+        }),
+    )?;
+
+    let player = Arc::new(Mutex::new(Player::default()));
+    let filler = Arc::clone(&player);
+    let output_stream = backend.open_output(
+        output,
+        Box::new(move |buffer| {
+            let mut player = filler.lock().unwrap();
+            // Not set up yet; leave it silent
+            if player.channels == 0 {
+                return;
+            }
+            let channels = player.channels;
+            let start = (LEAD_IN.as_secs_f64() * player.sample_rate as f64) as usize;
+            let first = player.frames;
+            if player.sent_at.is_none() && (first..first + buffer.len() / channels).contains(&start) {
+                let offset = Duration::from_secs_f64((start - first) as f64 / player.sample_rate as f64);
+                player.sent_at = Some(Instant::now() + offset);
+            }
+            for (index, frame) in buffer.chunks_mut(channels).enumerate() {
+                if let Some(sample) = (first + index).checked_sub(start).and_then(|i| player.chirp.get(i)) {
+                    frame.fill(sample * CHIRP_LEVEL);
+                }
+            }
+            player.frames += buffer.len() / channels;
+        }),
+    )?;
+    {
+        let mut player = player.lock().unwrap();
+        player.channels = output_stream.device.channels.max(1) as usize;
+        player.sample_rate = output_stream.device.sample_rate;
+        player.chirp = chirp(output_stream.device.sample_rate);
+    }
+
+    let input_device = input_stream.device.clone();
+    let output_device = output_stream.device.clone();
+    input_stream.play()?;
+    output_stream.play()?;
+    thread::sleep(LEAD_IN + Duration::from_millis(CHIRP_MS as u64) + MAX_LATENCY);
+    drop(output_stream);
+    drop(input_stream);
+
+    let sent_at = player
+        .lock()
+        .unwrap()
+        .sent_at
+        .ok_or_else(|| "The output device didn't play anything".to_string())?;
+    let capture = std::mem::take(&mut *capture.lock().unwrap());
+    let channels = input_device.channels.max(1) as usize;
+    let rate = input_device.sample_rate;
+    let mono = capture
+        .samples
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().map(|&s| s as f32 / i16::MAX as f32).sum::<f32>() / channels as f32)
+        .collect::<Vec<_>>();
+
+    let (start, clarity) =
+        find_chirp(&mono, &chirp(rate)).ok_or_else(|| "The input didn't deliver any audio".to_string())?;
+    if clarity < MIN_CLARITY {
+        return Err("The chirp wasn't heard on the input; connect the output to the input, \
+                    or turn the speaker up near the microphone"
+            .to_string());
+    }
+
+    // The last sample of each delivery arrived when the callback ran
+    let sample = start * channels;
+    let &(end, arrived) = capture
+        .arrivals
+        .iter()
+        .find(|(end, _)| *end > sample)
+        .ok_or_else(|| "Lost track of when the input arrived".to_string())?;
+    let heard_at = arrived - Duration::from_secs_f64((end - sample) as f64 / channels as f64 / rate as f64);
+    let latency = heard_at
+        .checked_duration_since(sent_at)
+        .ok_or_else(|| "The chirp was heard before it was played; is something else making that sound?".to_string())?;
+
+    Ok(RoundTrip {
+        latency_ms: latency.as_secs_f64() * 1000.0,
+        input_device: input_device.name,
+        output_device: output_device.name,
+        clarity,
+    })
+}
@@ -0,0 +1,27 @@
+//! Recording, playback, processing and library logic with no UI attached, shared by
+//! the desktop app and anything else that wants to drive the engine headless.
+
+pub mod backend;
+pub mod bwf;
+pub mod calendar;
+pub mod crypto;
+pub mod dsp;
+pub mod dual_mono;
+pub mod edits;
+pub mod effects;
+pub mod latency;
+pub mod library;
+pub mod pitch;
+pub mod playback;
+pub mod processing;
+pub mod recording;
+pub mod search;
+pub mod self_test;
+pub mod settings;
+pub mod share;
+pub mod shell;
+pub mod speech;
+pub mod spectrum;
+pub mod telemetry;
+pub mod tempo;
+pub mod transcript;
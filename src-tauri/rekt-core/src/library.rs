@@ -0,0 +1,628 @@
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::edits::EditList;
+use crate::search::{self, TranscriptHit, TranscriptIndex};
+use crate::speech::SpeechSegment;
+use crate::tempo::TempoInfo;
+use crate::transcript::Transcript;
+
+//
+// ====== Recording library index ======
+//
+
+const INDEX_FILE: &str = "library.json";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordingSource {
+    #[default]
+    Recorded,
+    Imported,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecordingEntry {
+    pub path: String,
+    pub file_name: String,
+    pub created_at: String,
+    pub duration_ms: u64,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub size_bytes: u64,
+    #[serde(default)]
+    pub source: RecordingSource,
+    #[serde(default)]
+    pub original_path: Option<String>,
+    /// Recording an edit or effect rendered this one from
+    #[serde(default)]
+    pub derived_from: Option<String>,
+    /// When a backup brought the recording back; retention counts its age from here
+    #[serde(default)]
+    pub restored_at: Option<String>,
+    #[serde(default)]
+    pub sync_status: Option<SyncStatus>,
+    #[serde(default)]
+    pub encrypted: bool,
+    #[serde(default)]
+    pub markers: Vec<Marker>,
+    /// Most recently rendered spectrogram image
+    #[serde(default)]
+    pub spectrogram: Option<String>,
+    /// Free-form description of what the recording contains
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Pinned by the user; never removed by the retention policy
+    #[serde(default)]
+    pub favorite: bool,
+    /// SHA-256 of the unencrypted WAV, used to spot duplicates
+    #[serde(default)]
+    pub content_hash: Option<String>,
+    /// Modification time of the file `content_hash` was taken from, in ms since the
+    /// epoch; with `size_bytes` it tells whether the hash is still good
+    #[serde(default)]
+    pub hashed_mtime_ms: Option<u64>,
+    /// Collection the recording is filed under; `None` is the library root
+    #[serde(default)]
+    pub folder: Option<String>,
+    /// Set by `detect_tempo`
+    #[serde(default)]
+    pub tempo: Option<TempoInfo>,
+    /// Where playback was last stopped, for `play_audio` with `resume`
+    #[serde(default)]
+    pub playback_position_ms: Option<u64>,
+    /// Detected on the first speech navigation
+    #[serde(default)]
+    pub speech_segments: Option<Vec<SpeechSegment>>,
+    #[serde(default)]
+    pub transcript: Option<Transcript>,
+    /// `transcript` in other languages, one per language, with the same segment timings
+    #[serde(default)]
+    pub translations: Vec<Transcript>,
+    /// A few sentences on what `transcript` covers, from the summarize pipeline stage
+    #[serde(default)]
+    pub summary: Option<String>,
+    /// Code of the language spoken, e.g. `en`, from `detect_language` or transcription
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Cuts and reorderings applied on export; the file itself is left as recorded
+    #[serde(default)]
+    pub edits: Option<EditList>,
+    /// Session the recording was made in, with its take number there
+    #[serde(default)]
+    pub session: Option<String>,
+    #[serde(default)]
+    pub take: Option<u32>,
+    /// How far the input clock drifted from the system clock while recording, in ppm.
+    /// Positive means the file runs long: an hour of it covers `1 - ppm / 10^6` hours of
+    /// real time, which matters when lining it up with video shot alongside.
+    #[serde(default)]
+    pub clock_drift_ppm: Option<f64>,
+    /// Interval of the beep played while recording, for jurisdictions that require one
+    #[serde(default)]
+    pub compliance_tone_secs: Option<u32>,
+}
+
+/// Criteria for narrowing the library view; every set field must match
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LibraryFilter {
+    /// Substring of the file name or note
+    pub text: Option<String>,
+    /// Recordings must carry all of these tags
+    pub tags: Vec<String>,
+    pub min_duration_ms: Option<u64>,
+    pub max_duration_ms: Option<u64>,
+    /// Inclusive local dates, `YYYY-MM-DD`
+    pub from_date: Option<String>,
+    pub to_date: Option<String>,
+    /// Only recordings from the last N days, for saved filters like "this week"
+    pub within_days: Option<u32>,
+    /// Only recordings filed in this folder
+    pub folder: Option<String>,
+    /// Only recordings in this language, by code
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedFilter {
+    pub name: String,
+    pub filter: LibraryFilter,
+}
+
+/// A point of interest dropped while recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Marker {
+    pub position_ms: u64,
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncState {
+    Pending,
+    Uploading,
+    Synced,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncStatus {
+    pub state: SyncState,
+    pub remote_url: Option<String>,
+    pub error: Option<String>,
+    pub attempts: u32,
+    pub updated_at: String,
+}
+
+/// A group of takes recorded one after another, e.g. the chunks of a podcast episode
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub name: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LibraryIndex {
+    recordings: Vec<RecordingEntry>,
+    /// Kept separately so empty folders survive
+    #[serde(default)]
+    folders: Vec<String>,
+    #[serde(default)]
+    sessions: Vec<Session>,
+    /// New recordings become takes of this session
+    #[serde(default)]
+    active_session: Option<String>,
+}
+
+/// A recording whose transcript matches a search
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptMatch {
+    pub recording: RecordingEntry,
+    pub hits: Vec<TranscriptHit>,
+}
+
+/// Index of every recording in the app data directory, persisted as `library.json`
+pub struct Library {
+    dir: PathBuf,
+    index: Mutex<LibraryIndex>,
+    /// Rebuilt from the transcripts on open rather than persisted
+    transcripts: Mutex<TranscriptIndex>,
+}
+
+impl Library {
+    /// Load the index from `dir`, picking up WAV files that aren't indexed yet and
+    /// dropping entries whose files have disappeared
+    pub fn open(dir: PathBuf) -> Result<Self, String> {
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create library directory: {}", e))?;
+
+        let index_path = dir.join(INDEX_FILE);
+        let mut index: LibraryIndex = if index_path.exists() {
+            let raw = fs::read_to_string(&index_path)
+                .map_err(|e| format!("Failed to read library index: {}", e))?;
+            serde_json::from_str(&raw).unwrap_or_else(|e| {
+                warn!("Library index is corrupt, rebuilding: {}", e);
+                LibraryIndex::default()
+            })
+        } else {
+            LibraryIndex::default()
+        };
+
+        // Keep entries on a drive or share that is merely offline right now
+        index.recordings.retain(|entry| {
+            let path = Path::new(&entry.path);
+            path.exists() || path.parent().is_some_and(|dir| !dir.exists())
+        });
+
+        let entries = fs::read_dir(&dir)
+            .map_err(|e| format!("Failed to scan library directory: {}", e))?;
+        for dir_entry in entries.flatten() {
+            let path = dir_entry.path();
+            let is_wav = path
+                .extension()
+                .map(|ext| ext.eq_ignore_ascii_case("wav"))
+                .unwrap_or(false);
+            let path_str = path.to_string_lossy().to_string();
+            if !is_wav || index.recordings.iter().any(|e| e.path == path_str) {
+                continue;
+            }
+            match probe_wav(&path) {
+                Ok(entry) => {
+                    if let Some(original) = find_duplicate(&index.recordings, &entry) {
+                        warn!("{} has the same content as {}", path.display(), original.path);
+                    }
+                    index.recordings.push(entry)
+                }
+                Err(e) => warn!("Skipping unreadable file {}: {}", path.display(), e),
+            }
+        }
+
+        let mut transcripts = TranscriptIndex::default();
+        for entry in &index.recordings {
+            transcripts.insert(&entry.path, entry.transcript.as_ref());
+        }
+
+        let library = Self {
+            dir,
+            index: Mutex::new(index),
+            transcripts: Mutex::new(transcripts),
+        };
+        library.persist(&library.index.lock().unwrap())?;
+        Ok(library)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Add an entry; if the path is already indexed only the audio properties are refreshed
+    /// so user metadata survives
+    pub fn add(&self, entry: RecordingEntry) -> Result<(), String> {
+        let mut index = self.index.lock().unwrap();
+        match index.recordings.iter_mut().find(|e| e.path == entry.path) {
+            Some(existing) => {
+                existing.duration_ms = entry.duration_ms;
+                existing.channels = entry.channels;
+                existing.sample_rate = entry.sample_rate;
+                existing.size_bytes = entry.size_bytes;
+                existing.content_hash = entry.content_hash;
+                existing.hashed_mtime_ms = entry.hashed_mtime_ms;
+            }
+            None => {
+                self.transcripts.lock().unwrap().insert(&entry.path, entry.transcript.as_ref());
+                index.recordings.push(entry)
+            }
+        }
+        self.persist(&index)
+    }
+
+    /// Modify the entry for `path` in place and persist the index
+    pub fn update<F>(&self, path: &str, f: F) -> Result<RecordingEntry, String>
+    where
+        F: FnOnce(&mut RecordingEntry),
+    {
+        let mut index = self.index.lock().unwrap();
+        let entry = index
+            .recordings
+            .iter_mut()
+            .find(|e| e.path == path)
+            .ok_or_else(|| format!("Recording not found in library: {}", path))?;
+        f(entry);
+        let updated = entry.clone();
+        if updated.path != path {
+            for other in index.recordings.iter_mut() {
+                if other.derived_from.as_deref() == Some(path) {
+                    other.derived_from = Some(updated.path.clone());
+                }
+            }
+        }
+        {
+            // The update may have moved the file as well as changed its transcript
+            let mut transcripts = self.transcripts.lock().unwrap();
+            transcripts.remove(path);
+            transcripts.insert(&updated.path, updated.transcript.as_ref());
+        }
+        self.persist(&index)?;
+        Ok(updated)
+    }
+
+    /// Drop an entry from the index; the file itself is left alone
+    pub fn remove(&self, path: &str) -> Result<Option<RecordingEntry>, String> {
+        let mut index = self.index.lock().unwrap();
+        let position = match index.recordings.iter().position(|e| e.path == path) {
+            Some(position) => position,
+            None => return Ok(None),
+        };
+        let removed = index.recordings.remove(position);
+        self.transcripts.lock().unwrap().remove(path);
+        self.persist(&index)?;
+        Ok(Some(removed))
+    }
+
+    /// Like `probe_wav`, but reusing the indexed hash of `path` when the file's size and
+    /// modification time haven't changed since it was taken
+    pub fn probe(&self, path: &Path) -> Result<RecordingEntry, String> {
+        probe(path, self.get(&path.to_string_lossy()).as_ref())
+    }
+
+    pub fn get(&self, path: &str) -> Option<RecordingEntry> {
+        self.index
+            .lock()
+            .unwrap()
+            .recordings
+            .iter()
+            .find(|e| e.path == path)
+            .cloned()
+    }
+
+    pub fn entries(&self) -> Vec<RecordingEntry> {
+        self.index.lock().unwrap().recordings.clone()
+    }
+
+    pub fn folders(&self) -> Vec<String> {
+        self.index.lock().unwrap().folders.clone()
+    }
+
+    pub fn create_folder(&self, name: &str) -> Result<(), String> {
+        let mut index = self.index.lock().unwrap();
+        if index.folders.iter().any(|f| f.eq_ignore_ascii_case(name)) {
+            return Err(format!("Folder '{}' already exists", name));
+        }
+        index.folders.push(name.to_string());
+        self.persist(&index)
+    }
+
+    /// Delete a folder; its recordings go back to the library root
+    pub fn delete_folder(&self, name: &str) -> Result<(), String> {
+        let mut index = self.index.lock().unwrap();
+        let before = index.folders.len();
+        index.folders.retain(|f| f != name);
+        if index.folders.len() == before {
+            return Err(format!("No folder named '{}'", name));
+        }
+        for entry in index.recordings.iter_mut() {
+            if entry.folder.as_deref() == Some(name) {
+                entry.folder = None;
+            }
+        }
+        self.persist(&index)
+    }
+
+    pub fn sessions(&self) -> Vec<Session> {
+        self.index.lock().unwrap().sessions.clone()
+    }
+
+    pub fn active_session(&self) -> Option<String> {
+        self.index.lock().unwrap().active_session.clone()
+    }
+
+    /// Start a session; recordings saved from now on are numbered as its takes
+    pub fn create_session(&self, name: &str) -> Result<Session, String> {
+        let mut index = self.index.lock().unwrap();
+        if index.sessions.iter().any(|s| s.name.eq_ignore_ascii_case(name)) {
+            return Err(format!("Session '{}' already exists", name));
+        }
+        let session = Session {
+            name: name.to_string(),
+            created_at: chrono::Local::now().to_rfc3339(),
+        };
+        index.sessions.push(session.clone());
+        index.active_session = Some(session.name.clone());
+        self.persist(&index)?;
+        Ok(session)
+    }
+
+    /// Go back to recordings that belong to no session
+    pub fn end_session(&self) -> Result<(), String> {
+        let mut index = self.index.lock().unwrap();
+        index.active_session = None;
+        self.persist(&index)
+    }
+
+    /// Make a newly saved recording the next take of the active session, if there is one
+    pub fn assign_take(&self, entry: &mut RecordingEntry) {
+        let index = self.index.lock().unwrap();
+        let Some(session) = index.active_session.clone() else {
+            return;
+        };
+        let last = index
+            .recordings
+            .iter()
+            .filter(|e| e.session.as_ref() == Some(&session))
+            .filter_map(|e| e.take)
+            .max()
+            .unwrap_or(0);
+        entry.session = Some(session);
+        entry.take = Some(last + 1);
+    }
+
+    /// The recordings of `session` in take order
+    pub fn takes(&self, session: &str) -> Vec<RecordingEntry> {
+        let mut takes = self
+            .index
+            .lock()
+            .unwrap()
+            .recordings
+            .iter()
+            .filter(|e| e.session.as_deref() == Some(session))
+            .cloned()
+            .collect::<Vec<_>>();
+        takes.sort_by_key(|e| e.take);
+        takes
+    }
+
+    /// Recordings whose transcripts contain every word of `query`, most hits first, with
+    /// a snippet and playback position for each matching segment
+    pub fn search_transcripts(&self, query: &str) -> Vec<TranscriptMatch> {
+        let index = self.index.lock().unwrap();
+        let found = self.transcripts.lock().unwrap().search(query);
+        let mut matches = found
+            .into_iter()
+            .filter_map(|(path, segments)| {
+                let entry = index.recordings.iter().find(|e| e.path == path)?;
+                let transcript = entry.transcript.as_ref()?;
+                let hits = segments
+                    .into_iter()
+                    .filter_map(|i| Some(search::hit(i, transcript.segments.get(i)?, query)))
+                    .collect();
+                Some(TranscriptMatch {
+                    recording: entry.clone(),
+                    hits,
+                })
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by(|a, b| {
+            b.hits
+                .len()
+                .cmp(&a.hits.len())
+                .then_with(|| b.recording.created_at.cmp(&a.recording.created_at))
+        });
+        matches
+    }
+
+    /// An already indexed recording with the same content as `entry`, other than itself
+    pub fn find_duplicate(&self, entry: &RecordingEntry) -> Option<RecordingEntry> {
+        find_duplicate(&self.index.lock().unwrap().recordings, entry).cloned()
+    }
+
+    fn persist(&self, index: &LibraryIndex) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(index)
+            .map_err(|e| format!("Failed to serialize library index: {}", e))?;
+
+        // Write to a temp file and rename so a crash never leaves a half-written index
+        let tmp_path = self.dir.join(format!("{}.tmp", INDEX_FILE));
+        fs::write(&tmp_path, json)
+            .map_err(|e| format!("Failed to write library index: {}", e))?;
+        fs::rename(&tmp_path, self.dir.join(INDEX_FILE))
+            .map_err(|e| format!("Failed to replace library index: {}", e))
+    }
+}
+
+/// Read a WAV header and build a library entry for it
+pub fn probe_wav(path: &Path) -> Result<RecordingEntry, String> {
+    probe(path, None)
+}
+
+// Hashing reads the whole file, so `known`'s hash is kept if the file looks the same
+fn probe(path: &Path, known: Option<&RecordingEntry>) -> Result<RecordingEntry, String> {
+    let reader = hound::WavReader::open(path)
+        .map_err(|e| format!("Failed to read WAV header: {}", e))?;
+    let spec = reader.spec();
+    let frames = reader.duration() as u64;
+
+    let metadata = fs::metadata(path)
+        .map_err(|e| format!("Failed to read file metadata: {}", e))?;
+    let created_at = metadata
+        .created()
+        .or_else(|_| metadata.modified())
+        .map(chrono::DateTime::<chrono::Local>::from)
+        .unwrap_or_else(|_| chrono::Local::now());
+
+    let mtime_ms = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|since| since.as_millis() as u64);
+    let content_hash = match known {
+        Some(known)
+            if known.content_hash.is_some()
+                && mtime_ms.is_some()
+                && known.hashed_mtime_ms == mtime_ms
+                && known.size_bytes == metadata.len() =>
+        {
+            known.content_hash.clone()
+        }
+        _ => Some(hash_file(path)?),
+    };
+
+    Ok(RecordingEntry {
+        path: path.to_string_lossy().to_string(),
+        file_name: path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        created_at: created_at.to_rfc3339(),
+        duration_ms: frames * 1000 / spec.sample_rate.max(1) as u64,
+        channels: spec.channels,
+        sample_rate: spec.sample_rate,
+        size_bytes: metadata.len(),
+        content_hash,
+        hashed_mtime_ms: mtime_ms,
+        ..Default::default()
+    })
+}
+
+/// Hex-encoded SHA-256 of a file's contents
+pub fn hash_file(path: &Path) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+fn find_duplicate<'a>(recordings: &'a [RecordingEntry], entry: &RecordingEntry) -> Option<&'a RecordingEntry> {
+    let hash = entry.content_hash.as_ref()?;
+    recordings
+        .iter()
+        .find(|e| e.path != entry.path && e.content_hash.as_ref() == Some(hash))
+}
+
+// Case-insensitive match against the file name and note
+fn matches_text(entry: &RecordingEntry, query: &str) -> bool {
+    let query = query.to_lowercase();
+    entry.file_name.to_lowercase().contains(&query)
+        || entry.note.as_ref().is_some_and(|n| n.to_lowercase().contains(&query))
+}
+
+fn parse_date(date: &str) -> Result<chrono::NaiveDate, String> {
+    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|e| format!("Invalid date '{}', expected YYYY-MM-DD: {}", date, e))
+}
+
+impl LibraryFilter {
+    pub fn validate(&self) -> Result<(), String> {
+        for date in [&self.from_date, &self.to_date].into_iter().flatten() {
+            parse_date(date)?;
+        }
+        Ok(())
+    }
+
+    pub fn matches(&self, entry: &RecordingEntry) -> bool {
+        if let Some(text) = self.text.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+            if !matches_text(entry, text) {
+                return false;
+            }
+        }
+        if !self.tags.iter().all(|tag| entry.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))) {
+            return false;
+        }
+        if self.folder.is_some() && entry.folder != self.folder {
+            return false;
+        }
+        if let Some(language) = &self.language {
+            if !entry.language.as_ref().is_some_and(|l| l.eq_ignore_ascii_case(language)) {
+                return false;
+            }
+        }
+        if self.min_duration_ms.is_some_and(|min| entry.duration_ms < min)
+            || self.max_duration_ms.is_some_and(|max| entry.duration_ms > max)
+        {
+            return false;
+        }
+
+        if self.from_date.is_none() && self.to_date.is_none() && self.within_days.is_none() {
+            return true;
+        }
+        let created = match chrono::DateTime::parse_from_rfc3339(&entry.created_at) {
+            Ok(created) => created.with_timezone(&chrono::Local),
+            Err(_) => return false,
+        };
+        let date = created.date_naive();
+        let after_from = self.from_date.as_deref().and_then(|d| parse_date(d).ok()).is_none_or(|from| date >= from);
+        let before_to = self.to_date.as_deref().and_then(|d| parse_date(d).ok()).is_none_or(|to| date <= to);
+        let recent = self
+            .within_days
+            .is_none_or(|days| chrono::Local::now() - created <= chrono::Duration::days(days as i64));
+        after_from && before_to && recent
+    }
+}
+
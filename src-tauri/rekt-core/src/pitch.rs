@@ -0,0 +1,83 @@
+use serde::Serialize;
+
+//
+// ====== Pitch detection ======
+//
+
+const MIN_FREQUENCY: f32 = 30.0;
+const MAX_FREQUENCY: f32 = 2_000.0;
+// YIN dip threshold; lower is stricter about what counts as pitched
+const YIN_THRESHOLD: f32 = 0.15;
+
+const NOTE_NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PitchReading {
+    pub frequency_hz: f32,
+    /// Nearest equal-tempered note, e.g. "A"
+    pub note: String,
+    pub octave: i32,
+    /// How far off the nearest note, -50..=50
+    pub cents: f32,
+    /// 0..=1, how clearly periodic the input was
+    pub clarity: f32,
+}
+
+/// YIN: the lag where the signal best matches a shifted copy of itself is the period.
+/// Returns the frequency and how clearly periodic the input was.
+pub fn detect_pitch(mono: &[f32], sample_rate: u32) -> Option<(f32, f32)> {
+    let min_lag = (sample_rate as f32 / MAX_FREQUENCY) as usize;
+    let max_lag = ((sample_rate as f32 / MIN_FREQUENCY) as usize).min(mono.len() / 2);
+    if min_lag < 2 || min_lag >= max_lag {
+        return None;
+    }
+    let window = mono.len() - max_lag;
+
+    let difference = (0..=max_lag)
+        .map(|lag| {
+            (0..window)
+                .map(|i| {
+                    let delta = mono[i] - mono[i + lag];
+                    delta * delta
+                })
+                .sum::<f32>()
+        })
+        .collect::<Vec<_>>();
+
+    // Cumulative mean normalized difference
+    let mut normalized = vec![1.0; max_lag + 1];
+    let mut running = 0.0;
+    for lag in 1..=max_lag {
+        running += difference[lag];
+        normalized[lag] = if running > 0.0 { difference[lag] * lag as f32 / running } else { 1.0 };
+    }
+
+    // First dip under the threshold, followed down to its minimum
+    let mut lag = (min_lag..max_lag).find(|&lag| normalized[lag] < YIN_THRESHOLD)?;
+    while lag + 1 < max_lag && normalized[lag + 1] < normalized[lag] {
+        lag += 1;
+    }
+
+    // Parabolic interpolation between neighbouring lags for sub-sample accuracy
+    let (a, b, c) = (normalized[lag - 1], normalized[lag], normalized[lag + 1]);
+    let denominator = a - 2.0 * b + c;
+    let shift = if denominator.abs() > f32::EPSILON { 0.5 * (a - c) / denominator } else { 0.0 };
+    let period = lag as f32 + shift.clamp(-1.0, 1.0);
+
+    Some((sample_rate as f32 / period, (1.0 - b).clamp(0.0, 1.0)))
+}
+
+/// Name the note nearest `frequency_hz`, tuned so A4 is `reference_hz`
+pub fn describe(frequency_hz: f32, clarity: f32, reference_hz: f32) -> PitchReading {
+    // MIDI numbering: A4 is 69, C4 is 60
+    let midi = 69.0 + 12.0 * (frequency_hz / reference_hz).log2();
+    let nearest = midi.round();
+    let index = nearest as i32;
+    PitchReading {
+        frequency_hz,
+        note: NOTE_NAMES[index.rem_euclid(12) as usize].to_string(),
+        octave: index.div_euclid(12) - 1,
+        cents: (midi - nearest) * 100.0,
+        clarity,
+    }
+}
@@ -0,0 +1,299 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rodio::Source;
+use serde::Serialize;
+use symphonia::core::audio::{SampleBuffer, SignalSpec};
+use symphonia::core::codecs::{Decoder, DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
+use symphonia::core::io::{MediaSource, MediaSourceStream};
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+use symphonia::core::units::{Time, TimeBase};
+use tracing::warn;
+
+//
+// ====== Playback decoding ======
+//
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaybackErrorKind {
+    /// The file could not be opened or read
+    Open,
+    /// Not a container format we can demux
+    UnsupportedFormat,
+    /// The container is fine but its codec isn't supported
+    UnsupportedCodec,
+    /// Malformed audio data, at the start or part-way through
+    Decode,
+    /// No output device or sink
+    Output,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlaybackError {
+    pub kind: PlaybackErrorKind,
+    pub message: String,
+}
+
+impl PlaybackError {
+    pub fn new(kind: PlaybackErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for PlaybackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}: {}", self.kind, self.message)
+    }
+}
+
+/// Where playback is coming from, so the probe knows what to try first
+pub enum FormatHint<'a> {
+    Extension(&'a str),
+    MimeType(&'a str),
+}
+
+/// rodio source decoding through symphonia, which covers AAC/M4A, ALAC, MP3, FLAC, Ogg
+/// and the WAV/AIFF/CAF variants rodio's own decoder rejects
+pub struct SymphoniaSource {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    spec: SignalSpec,
+    buffer: Option<SampleBuffer<f32>>,
+    position: usize,
+    total_duration: Option<Duration>,
+    time_base: Option<TimeBase>,
+    // Start of the most recently decoded packet
+    position_ms: Arc<AtomicU64>,
+    // Set by `PlaybackControl::seek`, honoured at the next packet boundary
+    seek_request: Arc<Mutex<Option<Duration>>>,
+    // Set when decoding stops early; the player reports it once the sink drains
+    failure: Arc<Mutex<Option<PlaybackError>>>,
+}
+
+impl SymphoniaSource {
+    pub fn open(media: Box<dyn MediaSource>, hint: Option<FormatHint>) -> Result<Self, PlaybackError> {
+        let stream = MediaSourceStream::new(media, Default::default());
+        let mut probe_hint = Hint::new();
+        match hint {
+            Some(FormatHint::Extension(extension)) => probe_hint.with_extension(extension),
+            Some(FormatHint::MimeType(mime_type)) => probe_hint.mime_type(mime_type),
+            None => &mut probe_hint,
+        };
+
+        let format_options = FormatOptions {
+            enable_gapless: true,
+            ..Default::default()
+        };
+        let probed = symphonia::default::get_probe()
+            .format(&probe_hint, stream, &format_options, &MetadataOptions::default())
+            .map_err(|e| PlaybackError::new(PlaybackErrorKind::UnsupportedFormat, format!("Unrecognized audio format: {}", e)))?;
+        let format = probed.format;
+
+        let track = format
+            .tracks()
+            .iter()
+            .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+            .ok_or_else(|| PlaybackError::new(PlaybackErrorKind::UnsupportedFormat, "File has no audio track"))?;
+        let decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())
+            .map_err(|e| PlaybackError::new(PlaybackErrorKind::UnsupportedCodec, format!("Unsupported codec: {}", e)))?;
+
+        let params = &track.codec_params;
+        let total_duration = match (params.n_frames, params.sample_rate) {
+            (Some(frames), Some(rate)) if rate > 0 => Some(Duration::from_secs_f64(frames as f64 / rate as f64)),
+            _ => None,
+        };
+        let sample_rate = params.sample_rate.unwrap_or(44_100);
+        let time_base = params.time_base.or_else(|| params.sample_rate.map(|rate| TimeBase::new(1, rate)));
+        let track_id = track.id;
+
+        let mut source = Self {
+            format,
+            decoder,
+            track_id,
+            spec: SignalSpec::new(sample_rate, Default::default()),
+            buffer: None,
+            position: 0,
+            total_duration,
+            time_base,
+            position_ms: Arc::new(AtomicU64::new(0)),
+            seek_request: Arc::new(Mutex::new(None)),
+            failure: Arc::new(Mutex::new(None)),
+        };
+        // Decode ahead so channels and sample rate are real before rodio asks for them
+        if !source.decode_next() {
+            let failure = source.failure.lock().unwrap().take();
+            return Err(failure.unwrap_or_else(|| PlaybackError::new(PlaybackErrorKind::Decode, "File contains no audio")));
+        }
+        Ok(source)
+    }
+
+    /// Jump to `position` from the start; playback continues from the nearest packet
+    pub fn seek(&mut self, position: Duration) -> Result<(), PlaybackError> {
+        self.format
+            .seek(
+                SeekMode::Coarse,
+                SeekTo::Time {
+                    time: Time::from(position.as_secs_f64()),
+                    track_id: Some(self.track_id),
+                },
+            )
+            .map_err(|e| PlaybackError::new(PlaybackErrorKind::Decode, format!("Failed to seek: {}", e)))?;
+        self.decoder.reset();
+        self.buffer = None;
+        self.position = 0;
+        if !self.decode_next() {
+            let failure = self.failure.lock().unwrap().take();
+            return Err(failure.unwrap_or_else(|| PlaybackError::new(PlaybackErrorKind::Decode, "Nothing to play after that position")));
+        }
+        Ok(())
+    }
+
+    /// Handle for seeking and reading the position once the source belongs to a sink
+    pub fn control(&self, path: Option<String>) -> PlaybackControl {
+        PlaybackControl {
+            path,
+            position_ms: Arc::clone(&self.position_ms),
+            seek_request: Arc::clone(&self.seek_request),
+        }
+    }
+
+    /// Filled in if decoding gives up before the end of the file
+    pub fn failure(&self) -> Arc<Mutex<Option<PlaybackError>>> {
+        Arc::clone(&self.failure)
+    }
+
+    fn fail(&mut self, kind: PlaybackErrorKind, message: String) -> bool {
+        *self.failure.lock().unwrap() = Some(PlaybackError::new(kind, message));
+        false
+    }
+
+    // Decode packets until one yields samples; false at the end of the stream or on a fatal error
+    fn decode_next(&mut self) -> bool {
+        loop {
+            let packet = match self.format.next_packet() {
+                Ok(packet) => packet,
+                Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => return false,
+                Err(e) => return self.fail(PlaybackErrorKind::Decode, format!("Failed to read audio: {}", e)),
+            };
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+            if let Some(time_base) = self.time_base {
+                let time = time_base.calc_time(packet.ts());
+                let ms = time.seconds * 1000 + (time.frac * 1000.0) as u64;
+                self.position_ms.store(ms, Ordering::Relaxed);
+            }
+
+            match self.decoder.decode(&packet) {
+                Ok(decoded) => {
+                    let spec = *decoded.spec();
+                    let needed = decoded.capacity() * spec.channels.count();
+                    let reusable = self.spec == spec && self.buffer.as_ref().is_some_and(|b| b.capacity() >= needed);
+                    if !reusable {
+                        self.buffer = Some(SampleBuffer::new(decoded.capacity() as u64, spec));
+                        self.spec = spec;
+                    }
+                    let buffer = self.buffer.as_mut().expect("buffer allocated above");
+                    buffer.copy_interleaved_ref(decoded);
+                    self.position = 0;
+                    if !buffer.samples().is_empty() {
+                        return true;
+                    }
+                }
+                // A corrupt packet costs a few milliseconds of audio, not the whole file
+                Err(SymphoniaError::DecodeError(e)) => warn!("Skipping undecodable packet: {}", e),
+                Err(e) => return self.fail(PlaybackErrorKind::Decode, format!("Failed to decode audio: {}", e)),
+            }
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        self.buffer.as_ref().map_or(0, |b| b.samples().len().saturating_sub(self.position))
+    }
+}
+
+impl Iterator for SymphoniaSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.remaining() == 0 {
+            return None;
+        }
+        let sample = self.buffer.as_ref()?.samples()[self.position];
+        self.position += 1;
+        // Refill straight away so `current_frame_len` is only zero once the stream has ended
+        if self.remaining() == 0 {
+            let seek = self.seek_request.lock().unwrap().take();
+            match seek {
+                Some(position) => {
+                    if let Err(e) = self.seek(position) {
+                        warn!("Playback seek failed: {}", e);
+                    }
+                }
+                None => {
+                    self.decode_next();
+                }
+            }
+        }
+        Some(sample)
+    }
+}
+
+impl Source for SymphoniaSource {
+    // One decoded packet per frame, so a mid-stream format change lands on a frame boundary
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.remaining())
+    }
+
+    fn channels(&self) -> u16 {
+        self.spec.channels.count() as u16
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.spec.rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.total_duration
+    }
+}
+
+/// The file currently playing, as seen from outside the playback thread
+#[derive(Clone)]
+pub struct PlaybackControl {
+    /// Library path, when playing a recording rather than raw data
+    pub path: Option<String>,
+    position_ms: Arc<AtomicU64>,
+    seek_request: Arc<Mutex<Option<Duration>>>,
+}
+
+impl PlaybackControl {
+    pub fn position(&self) -> Duration {
+        Duration::from_millis(self.position_ms.load(Ordering::Relaxed))
+    }
+
+    /// Jump to `position`; takes effect within one packet
+    pub fn seek(&self, position: Duration) {
+        *self.seek_request.lock().unwrap() = Some(position);
+        // Report the target straight away so repeated seeks build on each other
+        self.position_ms.store(position.as_millis() as u64, Ordering::Relaxed);
+    }
+}
+
+/// How a `play` call finished
+pub enum PlaybackEnd {
+    Finished,
+    /// Stopped or replaced by another playback part-way through
+    Stopped { position: Duration },
+}
@@ -0,0 +1,629 @@
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+//
+// ====== Offline audio processing ======
+//
+
+const FFMPEG_POLL: Duration = Duration::from_millis(100);
+
+/// Interleaved samples in the -1.0..=1.0 range plus the format they came from
+pub struct AudioBuffer {
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub samples: Vec<f32>,
+}
+
+impl AudioBuffer {
+    pub fn peak(&self) -> f32 {
+        self.samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()))
+    }
+
+    pub fn rms(&self) -> f32 {
+        rms(&self.samples)
+    }
+
+    /// RMS of consecutive windows of `window_ms`, across all channels
+    pub fn window_rms(&self, window_ms: u32) -> Vec<f32> {
+        let frames = (self.sample_rate as usize * window_ms as usize / 1000).max(1);
+        self.samples
+            .chunks(frames * self.channels.max(1) as usize)
+            .map(rms)
+            .collect()
+    }
+}
+
+pub fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+/// Amplitude to dBFS, floored at -120 so silence stays finite
+pub fn to_db(amplitude: f32) -> f32 {
+    (20.0 * amplitude.max(1e-6).log10()).max(-120.0)
+}
+
+/// Decode any PCM or float WAV that hound understands
+pub fn read_wav(path: &Path) -> Result<AudioBuffer, String> {
+    let reader = hound::WavReader::open(path)
+        .map_err(|e| format!("Failed to open WAV file: {}", e))?;
+    decode_wav(reader)
+}
+
+/// Like `read_wav`, for a WAV already in memory (e.g. a decrypted recording)
+pub fn read_wav_bytes(bytes: &[u8]) -> Result<AudioBuffer, String> {
+    let reader = hound::WavReader::new(bytes)
+        .map_err(|e| format!("Failed to open WAV file: {}", e))?;
+    decode_wav(reader)
+}
+
+fn decode_wav<R: Read>(mut reader: hound::WavReader<R>) -> Result<AudioBuffer, String> {
+    let spec = reader.spec();
+    let samples = read_samples(&mut reader, usize::MAX)?;
+
+    Ok(AudioBuffer {
+        channels: spec.channels,
+        sample_rate: spec.sample_rate,
+        samples,
+    })
+}
+
+// Up to `limit` samples from the reader's position, scaled to -1.0..=1.0
+fn read_samples<R: Read>(reader: &mut hound::WavReader<R>, limit: usize) -> Result<Vec<f32>, String> {
+    let spec = reader.spec();
+
+    match spec.sample_format {
+        hound::SampleFormat::Float => reader
+            .samples::<f32>()
+            .take(limit)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read samples: {}", e)),
+        hound::SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .take(limit)
+                .map(|s| s.map(|v| v as f32 / scale))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("Failed to read samples: {}", e))
+        }
+    }
+}
+
+/// Write the buffer as 16-bit PCM, clamping anything out of range
+pub fn write_wav(path: &Path, buffer: &AudioBuffer) -> Result<(), String> {
+    let mut sink = WavSink::create(path, buffer.channels, buffer.sample_rate)?;
+    sink.write(&buffer.samples)?;
+    sink.finalize()
+}
+
+/// The buffer as an in-memory 16-bit PCM WAV file
+pub fn wav_bytes(buffer: &AudioBuffer) -> Result<Vec<u8>, String> {
+    let spec = hound::WavSpec {
+        channels: buffer.channels,
+        sample_rate: buffer.sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut bytes = Cursor::new(Vec::new());
+    let mut writer = hound::WavWriter::new(&mut bytes, spec).map_err(|e| format!("Failed to create WAV: {}", e))?;
+    for &sample in &buffer.samples {
+        writer
+            .write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+            .map_err(|e| format!("Failed to write sample: {}", e))?;
+    }
+    writer.finalize().map_err(|e| format!("Failed to finalize WAV: {}", e))?;
+    Ok(bytes.into_inner())
+}
+
+//
+// ====== Streaming WAV access ======
+//
+
+/// Frames per block for effects that stream rather than load the whole recording
+pub const BLOCK_FRAMES: usize = 65_536;
+
+/// A WAV file read a block at a time
+pub struct WavSource {
+    reader: hound::WavReader<BufReader<File>>,
+}
+
+impl WavSource {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let reader = hound::WavReader::open(path)
+            .map_err(|e| format!("Failed to open WAV file: {}", e))?;
+        Ok(Self { reader })
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.reader.spec().channels
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.reader.spec().sample_rate
+    }
+
+    /// Length in frames
+    pub fn frames(&self) -> u32 {
+        self.reader.duration()
+    }
+
+    pub fn seek(&mut self, frame: u32) -> Result<(), String> {
+        self.reader
+            .seek(frame)
+            .map_err(|e| format!("Failed to seek in WAV file: {}", e))
+    }
+
+    /// The next `frames` frames, interleaved; shorter at the end of the file, empty past it
+    pub fn read(&mut self, frames: usize) -> Result<Vec<f32>, String> {
+        let channels = self.channels().max(1) as usize;
+        read_samples(&mut self.reader, frames * channels)
+    }
+}
+
+/// A 16-bit PCM WAV file written a block at a time
+pub struct WavSink {
+    writer: hound::WavWriter<BufWriter<File>>,
+}
+
+impl WavSink {
+    pub fn create(path: &Path, channels: u16, sample_rate: u32) -> Result<Self, String> {
+        let spec = hound::WavSpec {
+            channels,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let writer = hound::WavWriter::create(path, spec)
+            .map_err(|e| format!("Failed to create WAV file: {}", e))?;
+        Ok(Self { writer })
+    }
+
+    /// Append interleaved samples, clamping anything out of range
+    pub fn write(&mut self, samples: &[f32]) -> Result<(), String> {
+        for &sample in samples {
+            let converted = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            self.writer
+                .write_sample(converted)
+                .map_err(|e| format!("Failed to write sample: {}", e))?;
+        }
+        Ok(())
+    }
+
+    pub fn finalize(self) -> Result<(), String> {
+        self.writer
+            .finalize()
+            .map_err(|e| format!("Failed to finalize WAV: {}", e))
+    }
+}
+
+/// Write `input` to `output` with its frames in reverse order, reading from the end a
+/// block at a time so memory use doesn't grow with the recording
+pub fn reverse<P>(input: &Path, output: &Path, progress: P) -> Result<(), String>
+where
+    P: Fn(f32) -> Result<(), String>,
+{
+    let mut source = WavSource::open(input)?;
+    let channels = source.channels().max(1) as usize;
+    let total = source.frames();
+    let mut sink = WavSink::create(output, source.channels(), source.sample_rate())?;
+
+    let mut end = total;
+    while end > 0 {
+        let start = end.saturating_sub(BLOCK_FRAMES as u32);
+        source.seek(start)?;
+        let block = source.read((end - start) as usize)?;
+        let reversed = block.chunks_exact(channels).rev().flatten().copied().collect::<Vec<_>>();
+        sink.write(&reversed)?;
+        end = start;
+        progress(1.0 - end as f32 / total as f32)?;
+    }
+    sink.finalize()
+}
+
+/// Write each channel of a stereo file to its own mono file, block by block
+pub fn split_channels<P>(input: &Path, left: &Path, right: &Path, progress: P) -> Result<(), String>
+where
+    P: Fn(f32) -> Result<(), String>,
+{
+    let mut source = WavSource::open(input)?;
+    if source.channels() != 2 {
+        return Err(format!("Expected a stereo recording, found {} channel(s)", source.channels()));
+    }
+    let mut left_sink = WavSink::create(left, 1, source.sample_rate())?;
+    let mut right_sink = WavSink::create(right, 1, source.sample_rate())?;
+
+    let total = source.frames() as usize;
+    let mut done = 0;
+    loop {
+        let block = source.read(BLOCK_FRAMES)?;
+        if block.is_empty() {
+            break;
+        }
+        let (l, r): (Vec<f32>, Vec<f32>) = block.chunks_exact(2).map(|frame| (frame[0], frame[1])).unzip();
+        left_sink.write(&l)?;
+        right_sink.write(&r)?;
+        done += block.len() / 2;
+        progress(done as f32 / total.max(1) as f32)?;
+    }
+    left_sink.finalize()?;
+    right_sink.finalize()
+}
+
+/// Scale the buffer so its peak lands on `target_peak_db` dBFS
+pub fn normalize(buffer: &mut AudioBuffer, target_peak_db: f32) {
+    let peak = buffer.peak();
+    if peak <= f32::EPSILON {
+        return;
+    }
+
+    let gain = 10f32.powf(target_peak_db / 20.0) / peak;
+    for sample in buffer.samples.iter_mut() {
+        *sample *= gain;
+    }
+}
+
+fn default_threshold_db() -> f32 {
+    -18.0
+}
+
+fn default_ratio() -> f32 {
+    3.0
+}
+
+fn default_attack_ms() -> f32 {
+    10.0
+}
+
+fn default_release_ms() -> f32 {
+    150.0
+}
+
+fn default_ceiling_db() -> f32 {
+    -1.0
+}
+
+/// Compressor followed by a brickwall limiter, e.g. for podcast exports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DynamicsConfig {
+    #[serde(default = "default_threshold_db")]
+    pub threshold_db: f32,
+    #[serde(default = "default_ratio")]
+    pub ratio: f32,
+    #[serde(default = "default_attack_ms")]
+    pub attack_ms: f32,
+    #[serde(default = "default_release_ms")]
+    pub release_ms: f32,
+    #[serde(default)]
+    pub makeup_gain_db: f32,
+    /// Limiter ceiling in dBTP
+    #[serde(default = "default_ceiling_db")]
+    pub ceiling_db: f32,
+}
+
+impl DynamicsConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(-60.0..=0.0).contains(&self.threshold_db) {
+            return Err("Compressor threshold must be between -60 and 0 dB".to_string());
+        }
+        if !(1.0..=20.0).contains(&self.ratio) {
+            return Err("Compressor ratio must be between 1 and 20".to_string());
+        }
+        if !(0.1..=500.0).contains(&self.attack_ms) || !(1.0..=5000.0).contains(&self.release_ms) {
+            return Err("Attack must be 0.1-500 ms and release 1-5000 ms".to_string());
+        }
+        if !(0.0..=24.0).contains(&self.makeup_gain_db) {
+            return Err("Makeup gain must be between 0 and 24 dB".to_string());
+        }
+        if !(-12.0..=0.0).contains(&self.ceiling_db) {
+            return Err("Limiter ceiling must be between -12 and 0 dBTP".to_string());
+        }
+        Ok(())
+    }
+}
+
+// Smoothing coefficient for a one-pole follower reaching ~63% in `ms`
+fn time_constant(ms: f32, sample_rate: u32) -> f32 {
+    (-1.0 / (ms.max(0.01) / 1000.0 * sample_rate as f32)).exp()
+}
+
+/// Feed-forward compressor with channels linked, so the stereo image doesn't shift
+pub fn compress(buffer: &mut AudioBuffer, config: &DynamicsConfig) {
+    let channels = buffer.channels.max(1) as usize;
+    let attack = time_constant(config.attack_ms, buffer.sample_rate);
+    let release = time_constant(config.release_ms, buffer.sample_rate);
+    let makeup = 10f32.powf(config.makeup_gain_db / 20.0);
+    let mut envelope_db = -120.0f32;
+
+    for frame in buffer.samples.chunks_mut(channels) {
+        let level_db = to_db(frame.iter().fold(0.0f32, |peak, s| peak.max(s.abs())));
+        let coefficient = if level_db > envelope_db { attack } else { release };
+        envelope_db = level_db + coefficient * (envelope_db - level_db);
+
+        let over = envelope_db - config.threshold_db;
+        let reduction_db = if over > 0.0 { over - over / config.ratio } else { 0.0 };
+        let gain = 10f32.powf(-reduction_db / 20.0) * makeup;
+        for sample in frame.iter_mut() {
+            *sample *= gain;
+        }
+    }
+}
+
+// Peak of a frame including the overshoot between samples, estimated by
+// interpolating each channel at quarter-sample offsets (4x oversampling)
+fn true_peak(samples: &[f32], channels: usize, frame: usize) -> f32 {
+    let frames = samples.len() / channels;
+    let at = |f: isize, c: usize| samples[(f.clamp(0, frames as isize - 1) as usize) * channels + c];
+    let f = frame as isize;
+
+    let mut peak = 0.0f32;
+    for c in 0..channels {
+        let (p0, p1, p2, p3) = (at(f - 1, c), at(f, c), at(f + 1, c), at(f + 2, c));
+        peak = peak.max(p1.abs());
+        for t in [0.25f32, 0.5, 0.75] {
+            // Catmull-Rom spline through the neighbouring samples
+            let value = 0.5
+                * (2.0 * p1
+                    + (p2 - p0) * t
+                    + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t * t
+                    + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t * t * t);
+            peak = peak.max(value.abs());
+        }
+    }
+    peak
+}
+
+/// Brickwall limiter: gain drops ahead of each peak so nothing exceeds `ceiling_db` dBTP
+pub fn limit(buffer: &mut AudioBuffer, ceiling_db: f32) {
+    const LOOKAHEAD_MS: f32 = 1.5;
+
+    let channels = buffer.channels.max(1) as usize;
+    let frames = buffer.samples.len() / channels;
+    if frames == 0 {
+        return;
+    }
+    let ceiling = 10f32.powf(ceiling_db / 20.0);
+    let lookahead = ((LOOKAHEAD_MS / 1000.0 * buffer.sample_rate as f32) as usize).max(1);
+    let release = time_constant(50.0, buffer.sample_rate);
+
+    let required = (0..frames)
+        .map(|frame| {
+            let peak = true_peak(&buffer.samples, channels, frame);
+            if peak > ceiling { ceiling / peak } else { 1.0 }
+        })
+        .collect::<Vec<_>>();
+
+    let mut gain = 1.0f32;
+    for frame in 0..frames {
+        let end = (frame + lookahead).min(frames);
+        let target = required[frame..end].iter().fold(1.0f32, |min, &g| min.min(g));
+        gain = if target < gain { target } else { target + release * (gain - target) };
+        for sample in &mut buffer.samples[frame * channels..(frame + 1) * channels] {
+            *sample *= gain;
+        }
+    }
+}
+
+/// Limiter ceiling after stereo processing when nothing else sets one
+pub const STEREO_CEILING_DB: f32 = -1.0;
+
+fn default_width() -> f32 {
+    1.0
+}
+
+/// Mid/side treatment of stereo material, e.g. field recordings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StereoConfig {
+    /// The file holds mid on the left channel and side on the right, as an M/S mic
+    /// pair records it; decode it to left/right
+    #[serde(default)]
+    pub decode_mid_side: bool,
+    /// Side level relative to mid: 0 folds to mono, 1 leaves the image as is, 2 doubles the side
+    #[serde(default = "default_width")]
+    pub width: f32,
+}
+
+impl StereoConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=2.0).contains(&self.width) {
+            return Err("Stereo width must be between 0 and 2".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Decode and/or rescale the side signal of a stereo buffer; other channel counts pass
+/// through. Decoding and widening can exceed full scale, so follow with `limit`.
+pub fn apply_stereo(buffer: &mut AudioBuffer, config: &StereoConfig) {
+    if buffer.channels != 2 {
+        return;
+    }
+    for frame in buffer.samples.chunks_exact_mut(2) {
+        let (mid, side) = if config.decode_mid_side {
+            (frame[0], frame[1])
+        } else {
+            ((frame[0] + frame[1]) / 2.0, (frame[0] - frame[1]) / 2.0)
+        };
+        let side = side * config.width;
+        frame[0] = mid + side;
+        frame[1] = mid - side;
+    }
+}
+
+/// Convert to `channels` by averaging each frame down to mono and copying it out,
+/// unless the channel count already matches
+pub fn remix(buffer: &AudioBuffer, channels: u16) -> AudioBuffer {
+    let from = buffer.channels.max(1) as usize;
+    let to = channels.max(1) as usize;
+    let samples = if from == to {
+        buffer.samples.clone()
+    } else {
+        buffer
+            .samples
+            .chunks_exact(from)
+            .flat_map(|frame| std::iter::repeat_n(frame.iter().sum::<f32>() / from as f32, to))
+            .collect()
+    };
+    AudioBuffer {
+        channels: to as u16,
+        sample_rate: buffer.sample_rate,
+        samples,
+    }
+}
+
+/// Linear-interpolation resample; fine for aligning takes, not for mastering
+pub fn resample(buffer: &AudioBuffer, sample_rate: u32) -> AudioBuffer {
+    let channels = buffer.channels.max(1) as usize;
+    let frames = buffer.samples.len() / channels;
+    if buffer.sample_rate == sample_rate || frames == 0 {
+        return AudioBuffer {
+            channels: buffer.channels,
+            sample_rate,
+            samples: buffer.samples.clone(),
+        };
+    }
+
+    let step = buffer.sample_rate as f64 / sample_rate as f64;
+    let out_frames = (frames as f64 / step) as usize;
+    let mut samples = Vec::with_capacity(out_frames * channels);
+    for frame in 0..out_frames {
+        let position = frame as f64 * step;
+        let index = position as usize;
+        let t = (position - index as f64) as f32;
+        let next = (index + 1).min(frames - 1);
+        for channel in 0..channels {
+            let a = buffer.samples[index * channels + channel];
+            let b = buffer.samples[next * channels + channel];
+            samples.push(a + (b - a) * t);
+        }
+    }
+    AudioBuffer {
+        channels: buffer.channels,
+        sample_rate,
+        samples,
+    }
+}
+
+/// Replace frames `start..end` of `base` with `take`, converted to the base's format.
+/// Each edge is an equal-power crossfade of up to `crossfade_ms` between the take and
+/// the audio it replaces, so the old region is only heard while it fades out.
+pub fn splice(base: &AudioBuffer, take: &AudioBuffer, start: usize, end: usize, crossfade_ms: u32) -> AudioBuffer {
+    let channels = base.channels.max(1) as usize;
+    let take = resample(&remix(take, base.channels), base.sample_rate);
+    let base_frames = base.samples.len() / channels;
+    let take_frames = take.samples.len() / channels;
+    let end = end.min(base_frames);
+    let start = start.min(end);
+    let fade = (crossfade_ms as usize * base.sample_rate as usize / 1000)
+        .min(take_frames / 2)
+        .min((end - start) / 2);
+
+    let mut samples = Vec::with_capacity((base_frames - (end - start) + take_frames) * channels);
+    samples.extend_from_slice(&base.samples[..start * channels]);
+    for frame in 0..take_frames {
+        // Where in the fade this frame is, and which old frame it overlaps
+        let edge = if frame < fade {
+            Some(((frame as f32 + 0.5) / fade as f32, start + frame))
+        } else if frame >= take_frames - fade {
+            let i = frame - (take_frames - fade);
+            Some((1.0 - (i as f32 + 0.5) / fade as f32, end - fade + i))
+        } else {
+            None
+        };
+        for channel in 0..channels {
+            let new = take.samples[frame * channels + channel];
+            samples.push(match edge {
+                Some((t, old)) => {
+                    let angle = t * std::f32::consts::FRAC_PI_2;
+                    new * angle.sin() + base.samples[old * channels + channel] * angle.cos()
+                }
+                None => new,
+            });
+        }
+    }
+    samples.extend_from_slice(&base.samples[end * channels..]);
+
+    AudioBuffer {
+        channels: base.channels,
+        sample_rate: base.sample_rate,
+        samples,
+    }
+}
+
+/// `<dir>/<stem>_<suffix>.<extension>` next to the source, numbered if it already exists
+pub fn derived_path(source: &Path, suffix: &str, extension: &str) -> PathBuf {
+    let dir = source.parent().unwrap_or_else(|| Path::new("."));
+    let stem = source
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "recording".to_string());
+
+    let mut candidate = dir.join(format!("{}_{}.{}", stem, suffix, extension));
+    let mut counter = 1;
+    while candidate.exists() {
+        candidate = dir.join(format!("{}_{}_{}.{}", stem, suffix, counter, extension));
+        counter += 1;
+    }
+    candidate
+}
+
+/// Transcode with an ffmpeg binary on PATH, used for formats we have no encoder for
+pub fn encode_with_ffmpeg(input: &Path, output: &Path, codec_args: &[&str]) -> Result<(), String> {
+    encode_with_ffmpeg_until(input, output, codec_args, || Ok(()))
+}
+
+/// Like `encode_with_ffmpeg`, but polls `keep_going` while ffmpeg runs and kills it
+/// (removing the partial output) as soon as that returns an error
+pub fn encode_with_ffmpeg_until<F>(input: &Path, output: &Path, codec_args: &[&str], keep_going: F) -> Result<(), String>
+where
+    F: Fn() -> Result<(), String>,
+{
+    let mut child = Command::new("ffmpeg")
+        .args(["-hide_banner", "-loglevel", "error", "-y", "-i"])
+        .arg(input)
+        .args(codec_args)
+        .arg(output)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run ffmpeg (is it installed and on PATH?): {}", e))?;
+
+    // Drain stderr on the side so a chatty ffmpeg can't block on a full pipe
+    let mut stderr = child.stderr.take();
+    let stderr_reader = thread::spawn(move || {
+        let mut message = String::new();
+        if let Some(stderr) = stderr.as_mut() {
+            let _ = stderr.read_to_string(&mut message);
+        }
+        message
+    });
+
+    let status = loop {
+        if let Err(e) = keep_going() {
+            let _ = child.kill();
+            let _ = child.wait();
+            let _ = fs::remove_file(output);
+            return Err(e);
+        }
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) => thread::sleep(FFMPEG_POLL),
+            Err(e) => return Err(format!("Failed to wait for ffmpeg: {}", e)),
+        }
+    };
+
+    if !status.success() {
+        let message = stderr_reader.join().unwrap_or_default();
+        return Err(format!("ffmpeg failed: {}", message.trim()));
+    }
+    Ok(())
+}
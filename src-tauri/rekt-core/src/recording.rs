@@ -0,0 +1,523 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use chrono::{DateTime, Local, SecondsFormat};
+use serde::Serialize;
+use tracing::debug;
+
+use crate::backend::{AudioStream, InputCallback};
+use crate::bwf::Bext;
+use crate::dsp;
+use crate::dual_mono::{DevicePair, DualMono};
+use crate::library::Marker;
+use crate::telemetry::CallbackTimer;
+
+//
+// ====== Audio capture ======
+//
+
+/// Where the recorder is in its life cycle. Only the moves `can_become` allows happen;
+/// everything else is refused, so two callers can't both start or both stop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecorderState {
+    Idle,
+    /// Opening the input device
+    Starting,
+    Recording,
+    /// Input is open but discarded until resumed
+    Paused,
+    /// Closing the input device
+    Stopping,
+    /// Writing the captured audio to disk
+    Saving,
+}
+
+impl RecorderState {
+    const ALL: [Self; 6] = [
+        Self::Idle,
+        Self::Starting,
+        Self::Recording,
+        Self::Paused,
+        Self::Stopping,
+        Self::Saving,
+    ];
+
+    pub fn can_become(self, next: Self) -> bool {
+        use RecorderState::*;
+        matches!(
+            (self, next),
+            (Idle, Starting)
+                | (Starting, Recording)
+                // The device couldn't be opened
+                | (Starting, Idle)
+                | (Recording, Paused)
+                | (Paused, Recording)
+                | (Recording, Stopping)
+                | (Paused, Stopping)
+                | (Stopping, Saving)
+                // Nothing was captured, so nothing to save
+                | (Stopping, Idle)
+                | (Saving, Idle)
+        )
+    }
+
+    fn from_u8(value: u8) -> Self {
+        Self::ALL[value as usize]
+    }
+}
+
+/// The format the input stream actually delivers, which the device may have picked
+/// differently from the one asked for. Tagged with the session it was negotiated for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CaptureFormat {
+    pub session: u64,
+    pub channels: u16,
+    pub sample_rate: u32,
+}
+
+// Seconds of audio between wall-clock timestamps
+const TIMESTAMP_INTERVAL_SECS: u64 = 60;
+
+/// The wall-clock time a frame of the recording was captured at
+#[derive(Debug, Clone, Serialize)]
+pub struct Timestamp {
+    pub frame: u64,
+    /// RFC 3339, to the microsecond
+    pub wall_clock: String,
+}
+
+/// Ring buffer of the most recent monitored audio, so a recording can begin a few
+/// seconds before it was started and those seconds can be replayed while idle
+#[derive(Debug, Default)]
+pub struct PreRoll {
+    samples: VecDeque<i16>,
+    capacity: usize,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl PreRoll {
+    /// Keep up to `secs` of audio in this format from now on, dropping anything held
+    pub fn configure(&mut self, secs: u32, channels: u16, sample_rate: u32) {
+        let capacity = secs as usize * sample_rate as usize * channels.max(1) as usize;
+        *self = Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+            channels,
+            sample_rate,
+        };
+    }
+
+    fn push(&mut self, samples: &[i16]) {
+        if self.capacity == 0 {
+            return;
+        }
+        // Whole frames only, so the channels stay in step when the front is dropped
+        let channels = self.channels.max(1) as usize;
+        let excess = (self.samples.len() + samples.len()).saturating_sub(self.capacity);
+        self.samples.drain(..excess.div_ceil(channels).saturating_mul(channels).min(self.samples.len()));
+        let skip = samples.len().saturating_sub(self.capacity) / channels * channels;
+        self.samples.extend(&samples[skip..]);
+    }
+
+    /// Up to the last `secs` held, with their channel count and sample rate
+    pub fn last(&self, secs: f32) -> (u16, u32, Vec<i16>) {
+        let channels = self.channels.max(1) as usize;
+        let wanted = (secs * self.sample_rate as f32) as usize * channels;
+        let start = self.samples.len().saturating_sub(wanted) / channels * channels;
+        (self.channels, self.sample_rate, self.samples.range(start..).copied().collect())
+    }
+
+    /// Everything held if it is in the given format, leaving the buffer empty either way
+    pub fn take(&mut self, channels: u16, sample_rate: u32) -> Option<Vec<i16>> {
+        let samples = std::mem::take(&mut self.samples);
+        (self.channels == channels && self.sample_rate == sample_rate && !samples.is_empty())
+            .then(|| samples.into())
+    }
+}
+
+#[derive(Default)]
+pub struct RecordingState {
+    // A `RecorderState`, atomic so the audio callback can check it without locking
+    state: AtomicU8,
+    /// Bumped on every start so timers can tell their recording from a later one
+    pub session: AtomicU64,
+    pub audio_data: Mutex<Vec<i16>>,
+    /// Format requested with the audio config; zero means the device default
+    pub channels: Mutex<u16>,
+    pub sample_rate: Mutex<u32>,
+    // Format of the stream feeding `audio_data`, set once when it starts
+    capture_format: Mutex<Option<CaptureFormat>>,
+    // Frames captured so far paired with when the last of them arrived, at the start,
+    // every `TIMESTAMP_INTERVAL_SECS` and after each resume
+    timeline: Mutex<Vec<(u64, SystemTime)>>,
+    // Set when recording (re)starts, so the next block gets a timestamp
+    restamp: AtomicBool,
+    // Set while something plays that the recording shouldn't pick up
+    held: AtomicBool,
+    pub input_stream: Mutex<Option<AudioStream>>,
+    /// Record from these two devices as left and right instead of the default input
+    pub device_pair: Mutex<Option<DevicePair>>,
+    /// The right-hand device while recording from a pair; `input_stream` is the left
+    pub paired_stream: Mutex<Option<AudioStream>>,
+    /// Input held open while idle so live consumers can listen without a recording
+    pub monitor_stream: Mutex<Option<AudioStream>>,
+    /// The last few seconds `monitor_stream` heard
+    pub pre_roll: Mutex<PreRoll>,
+    /// Pre-roll the current recording opens with, kept out of `audio_data` so checks on
+    /// what was captured see only audio from after the start; joined up when written
+    pub lead_in: Mutex<Vec<i16>>,
+    pub markers: Mutex<Vec<Marker>>,
+    /// Live consumers of captured audio, e.g. the streaming sink
+    pub taps: Mutex<Vec<SyncSender<Vec<i16>>>>,
+    /// Cleanup from the active profile, applied before samples are stored
+    pub input_filter: Mutex<Option<dsp::InputFilter>>,
+    /// Callback timing for the current or most recent recording
+    pub telemetry: Mutex<CallbackTimer>,
+}
+
+impl RecordingState {
+    pub fn recorder_state(&self) -> RecorderState {
+        RecorderState::from_u8(self.state.load(Ordering::SeqCst))
+    }
+
+    /// Move to `next` if that's allowed from where the recorder is now; returns the
+    /// state it left
+    pub fn transition(&self, next: RecorderState) -> Result<RecorderState, String> {
+        let mut current = self.recorder_state();
+        loop {
+            if !current.can_become(next) {
+                return Err(format!("Recorder can't go from {:?} to {:?}", current, next));
+            }
+            match self
+                .state
+                .compare_exchange(current as u8, next as u8, Ordering::SeqCst, Ordering::SeqCst)
+            {
+                Ok(_) => {
+                    if next == RecorderState::Recording {
+                        self.restamp.store(true, Ordering::SeqCst);
+                    }
+                    return Ok(current);
+                }
+                Err(actual) => current = RecorderState::from_u8(actual),
+            }
+        }
+    }
+
+    /// A recording is under way, paused or not
+    pub fn is_recording(&self) -> bool {
+        matches!(self.recorder_state(), RecorderState::Recording | RecorderState::Paused)
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.recorder_state() == RecorderState::Idle
+    }
+
+    /// Record the format the stream for `session` came up with; all of that session's
+    /// samples are interpreted with it
+    pub fn begin_capture(&self, session: u64, channels: u16, sample_rate: u32) {
+        *self.capture_format.lock().unwrap() = Some(CaptureFormat {
+            session,
+            channels,
+            sample_rate,
+        });
+        self.timeline.lock().unwrap().clear();
+    }
+
+    /// Format of the audio captured by the current session, if its stream has started
+    pub fn capture_format(&self) -> Option<CaptureFormat> {
+        let session = self.session.load(Ordering::SeqCst);
+        self.capture_format
+            .lock()
+            .unwrap()
+            .filter(|format| format.session == session && format.channels > 0 && format.sample_rate > 0)
+    }
+
+    // Append captured samples and hand a copy to every live consumer
+    pub fn push_samples(&self, samples: &[i16]) {
+        let mut filtered;
+        let samples = match self.input_filter.lock().unwrap().as_mut() {
+            Some(filter) => {
+                filtered = samples.to_vec();
+                filter.process(&mut filtered);
+                &filtered[..]
+            }
+            None => samples,
+        };
+
+        if let Ok(mut audio_data) = self.audio_data.lock() {
+            audio_data.extend_from_slice(samples);
+        }
+        self.feed_taps(samples);
+    }
+
+    fn feed_taps(&self, samples: &[i16]) {
+        let mut taps = self.taps.lock().unwrap();
+        // A slow consumer misses chunks rather than stalling the audio callback
+        taps.retain(|tap| !matches!(tap.try_send(samples.to_vec()), Err(TrySendError::Disconnected(_))));
+    }
+
+    /// Receive a copy of captured audio until the recording stops, or of monitored
+    /// audio while idle
+    pub fn add_tap(&self) -> Receiver<Vec<i16>> {
+        let (sender, receiver) = mpsc::sync_channel(64);
+        self.taps.lock().unwrap().push(sender);
+        receiver
+    }
+
+    /// Frames of pre-roll ahead of the current session's captured audio
+    pub fn lead_in_frames(&self) -> u64 {
+        let channels = self.capture_format().map_or(1, |format| format.channels) as u64;
+        self.lead_in.lock().unwrap().len() as u64 / channels
+    }
+
+    /// The lead-in followed by everything captured, as the recording is written
+    pub fn recorded_samples(&self) -> Vec<i16> {
+        [&self.lead_in.lock().unwrap()[..], &self.audio_data.lock().unwrap()[..]].concat()
+    }
+
+    /// Take the current session's markers, moved past the lead-in to their place in the
+    /// written recording
+    pub fn take_markers(&self) -> Vec<Marker> {
+        let mut markers = std::mem::take(&mut *self.markers.lock().unwrap());
+        if let Some(format) = self.capture_format() {
+            let lead_in_ms = self.lead_in_frames() * 1000 / format.sample_rate as u64;
+            markers.iter_mut().for_each(|marker| marker.position_ms += lead_in_ms);
+        }
+        markers
+    }
+
+    /// When the current session's recording started by the wall clock, lead-in included
+    pub fn started_at(&self) -> Option<DateTime<Local>> {
+        let format = self.capture_format()?;
+        let &(frame, arrived) = self.timeline.lock().unwrap().first()?;
+        let frame = frame + self.lead_in_frames();
+        let before = Duration::from_secs_f64(frame as f64 / format.sample_rate as f64);
+        Some(DateTime::from(arrived.checked_sub(before)?))
+    }
+
+    /// Wall-clock times of frames of the written recording, for lining it up with other
+    /// devices even where it was paused
+    pub fn timestamps(&self) -> Vec<Timestamp> {
+        let lead_in = self.lead_in_frames();
+        self.timeline
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|&(frame, arrived)| Timestamp {
+                frame: frame + lead_in,
+                wall_clock: DateTime::<Local>::from(arrived).to_rfc3339_opts(SecondsFormat::Micros, false),
+            })
+            .collect()
+    }
+
+    /// Broadcast WAV metadata for the current session's recording
+    pub fn bext(&self) -> Option<Bext> {
+        let format = self.capture_format()?;
+        Some(Bext::new(self.started_at()?, format.channels, format.sample_rate))
+    }
+
+    // A block that arrived at `arrived` has just been stored; note the time if one is due
+    fn stamp(&self, arrived: SystemTime) {
+        let Some(format) = self.capture_format() else {
+            return;
+        };
+        let frame = (self.audio_data.lock().unwrap().len() / format.channels as usize) as u64;
+        let mut timeline = self.timeline.lock().unwrap();
+        let interval = TIMESTAMP_INTERVAL_SECS * format.sample_rate as u64;
+        let due = timeline.last().is_none_or(|&(last, _)| frame >= last + interval);
+        if self.restamp.swap(false, Ordering::SeqCst) || due {
+            timeline.push((frame, arrived));
+        }
+    }
+
+    /// Discard input as if paused while `held`, e.g. while a spoken confirmation plays
+    /// through a speaker the microphone can hear
+    pub fn hold_input(&self, held: bool) {
+        match (self.held.swap(held, Ordering::SeqCst), held) {
+            (false, true) => self.telemetry.lock().unwrap().pause(),
+            // Kept audio picks up after a gap, like after a resume
+            (true, false) => self.restamp.store(true, Ordering::SeqCst),
+            _ => {}
+        }
+    }
+
+    // Store whatever arrives while recording and not paused or held
+    fn receive(&self, samples: &[i16]) {
+        if self.recorder_state() == RecorderState::Recording && !self.held.load(Ordering::SeqCst) {
+            let arrived = Instant::now();
+            let wall_clock = SystemTime::now();
+            self.push_samples(samples);
+            self.stamp(wall_clock);
+            self.telemetry.lock().unwrap().record(arrived, samples.len(), arrived.elapsed());
+        }
+    }
+
+    /// Input callback that stores whatever arrives while recording and not paused
+    pub fn capture(state: &Arc<Self>) -> InputCallback {
+        let state = Arc::clone(state);
+        Box::new(move |samples| state.receive(samples))
+    }
+
+    /// Input callback for `monitor_stream`: while idle, hands audio to the taps and keeps
+    /// the latest of it in `pre_roll`
+    pub fn monitor(state: &Arc<Self>) -> InputCallback {
+        let state = Arc::clone(state);
+        Box::new(move |samples| {
+            if state.is_idle() {
+                state.pre_roll.lock().unwrap().push(samples);
+                state.feed_taps(samples);
+            }
+        })
+    }
+
+    /// Left and right input callbacks for recording `pair` as one stereo signal
+    pub fn capture_pair(state: &Arc<Self>, pair: &Arc<DualMono>) -> (InputCallback, InputCallback) {
+        let state = Arc::clone(state);
+        (pair.left_input(move |samples| state.receive(samples)), pair.right_input())
+    }
+
+    /// Mark the current position of the recording in progress
+    pub fn add_marker(&self, label: Option<String>) -> Result<Marker, String> {
+        if !self.is_recording() {
+            return Err("Not recording".to_string());
+        }
+
+        let format = self
+            .capture_format()
+            .ok_or_else(|| "Capture format not known yet".to_string())?;
+        let position_ms = self.position_ms().ok_or_else(|| "Capture format not known yet".to_string())?;
+        self.add_marker_at(format.session, position_ms, label)
+    }
+
+    /// How much audio the current recording holds, pauses left out
+    pub fn position_ms(&self) -> Option<u64> {
+        let format = self.capture_format()?;
+        let samples = self.audio_data.lock().unwrap().len() as u64;
+        Some(samples / format.channels as u64 * 1000 / format.sample_rate as u64)
+    }
+
+    /// Mark an earlier position of `session`'s recording, e.g. where a spoken command began
+    pub fn add_marker_at(&self, session: u64, position_ms: u64, label: Option<String>) -> Result<Marker, String> {
+        if !self.is_recording() || self.session.load(Ordering::SeqCst) != session {
+            return Err("Not recording".to_string());
+        }
+
+        let marker = Marker {
+            position_ms,
+            label: label.filter(|l| !l.trim().is_empty()),
+        };
+
+        debug!("Marker at {} ms", marker.position_ms);
+        // Keep them in order even when a late one lands before the newest
+        let mut markers = self.markers.lock().unwrap();
+        let index = markers.partition_point(|m| m.position_ms <= position_ms);
+        markers.insert(index, marker.clone());
+        Ok(marker)
+    }
+}
+
+#[derive(Serialize)]
+struct TimestampSidecar<'a> {
+    sample_rate: u32,
+    timestamps: &'a [Timestamp],
+}
+
+/// Where the timestamps of `recording` are kept, next to it
+pub fn timestamps_path(recording: &Path) -> PathBuf {
+    let mut path = recording.as_os_str().to_owned();
+    path.push(".timestamps.json");
+    PathBuf::from(path)
+}
+
+/// Write `timestamps` to the sidecar file of `recording`
+pub fn write_timestamps(recording: &Path, sample_rate: u32, timestamps: &[Timestamp]) -> Result<PathBuf, String> {
+    let json = serde_json::to_string_pretty(&TimestampSidecar { sample_rate, timestamps })
+        .map_err(|e| format!("Failed to serialize timestamps: {}", e))?;
+    let path = timestamps_path(recording);
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write timestamps: {}", e))?;
+    Ok(path)
+}
+
+// Samples written between progress reports
+const PROGRESS_BLOCK: usize = 1 << 20;
+const WAV_HEADER_LEN: u32 = 44;
+
+/// Write captured 16-bit audio to `path`, with `bext` metadata if given, reporting the
+/// fraction written as it goes
+pub fn write_capture<P>(
+    path: &Path,
+    channels: u16,
+    sample_rate: u32,
+    samples: &[i16],
+    bext: Option<&Bext>,
+    mut progress: P,
+) -> Result<(), String>
+where
+    P: FnMut(f32),
+{
+    if samples.is_empty() {
+        return Err("No audio to write".to_string());
+    }
+    debug!("Writing {} samples...", samples.len());
+
+    let extra = bext.map(Bext::chunk).unwrap_or_default();
+    let data_len = u32::try_from(samples.len() * 2)
+        .ok()
+        .filter(|len| len.checked_add(WAV_HEADER_LEN + extra.len() as u32).is_some())
+        .ok_or_else(|| "Recording is too long for a WAV file".to_string())?;
+    let file = File::create(path).map_err(|e| format!("Failed to create WAV file: {}", e))?;
+    let mut writer = BufWriter::with_capacity(PROGRESS_BLOCK * 2, file);
+    writer
+        .write_all(&wav_header(channels, sample_rate, &extra, data_len))
+        .map_err(|e| format!("Failed to write WAV header: {}", e))?;
+
+    // WAV is little-endian, so on little-endian machines the samples go out as they are
+    let mut swapped = Vec::new();
+    for (index, block) in samples.chunks(PROGRESS_BLOCK).enumerate() {
+        let block = if cfg!(target_endian = "little") {
+            block
+        } else {
+            swapped.clear();
+            swapped.extend(block.iter().map(|s| s.to_le()));
+            &swapped[..]
+        };
+        writer
+            .write_all(bytemuck::cast_slice(block))
+            .map_err(|e| format!("Failed to write samples: {}", e))?;
+        progress(((index + 1) * PROGRESS_BLOCK).min(samples.len()) as f32 / samples.len() as f32);
+    }
+
+    writer
+        .into_inner()
+        .map_err(|e| format!("Failed to finalize WAV: {}", e.error()))?
+        .sync_all()
+        .map_err(|e| format!("Failed to finalize WAV: {}", e))
+}
+
+// Canonical 44-byte header for 16-bit PCM, with `extra` chunks between format and data
+fn wav_header(channels: u16, sample_rate: u32, extra: &[u8], data_len: u32) -> Vec<u8> {
+    let block_align = channels * 2;
+    let mut header = Vec::with_capacity(WAV_HEADER_LEN as usize + extra.len());
+    header.extend_from_slice(b"RIFF");
+    header.extend_from_slice(&(WAV_HEADER_LEN - 8 + extra.len() as u32 + data_len).to_le_bytes());
+    header.extend_from_slice(b"WAVEfmt ");
+    header.extend_from_slice(&16u32.to_le_bytes());
+    header.extend_from_slice(&1u16.to_le_bytes());
+    header.extend_from_slice(&channels.to_le_bytes());
+    header.extend_from_slice(&sample_rate.to_le_bytes());
+    header.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+    header.extend_from_slice(&block_align.to_le_bytes());
+    header.extend_from_slice(&16u16.to_le_bytes());
+    header.extend_from_slice(extra);
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&data_len.to_le_bytes());
+    header
+}
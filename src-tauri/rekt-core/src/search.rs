@@ -0,0 +1,185 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use serde::Serialize;
+
+use crate::transcript::{Transcript, TranscriptSegment};
+
+//
+// ====== Transcript full-text index ======
+//
+
+// Words of context kept either side of the first match in a snippet
+const SNIPPET_CONTEXT_WORDS: usize = 8;
+
+/// A transcript segment matching a search, with where playback should jump to hear it
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptHit {
+    pub segment_index: usize,
+    /// Start of the first matching word when the transcript has word timings,
+    /// otherwise the start of the segment
+    pub start_ms: u64,
+    pub snippet: String,
+    /// Character ranges of `snippet` that matched, as `[start, end)`
+    pub highlights: Vec<(usize, usize)>,
+}
+
+/// Lowercased words of `text`, with the character range each came from
+fn tokenize(text: &str) -> Vec<(String, usize, usize)> {
+    let mut tokens = Vec::new();
+    let mut current: Option<(String, usize)> = None;
+    for (position, c) in text.chars().enumerate() {
+        if c.is_alphanumeric() || (c == '\'' && current.is_some()) {
+            current.get_or_insert_with(|| (String::new(), position)).0.extend(c.to_lowercase());
+        } else if let Some((word, start)) = current.take() {
+            tokens.push((word, start, position));
+        }
+    }
+    if let Some((word, start)) = current {
+        let end = start + text.chars().skip(start).count();
+        tokens.push((word, start, end));
+    }
+    // Possessives and closing quotes shouldn't stop "paul" matching "paul's"; the
+    // character range still covers the whole word for highlighting
+    for token in tokens.iter_mut() {
+        let trimmed = token.0.trim_end_matches('\'');
+        let trimmed = trimmed.strip_suffix("'s").unwrap_or(trimmed);
+        token.0.truncate(trimmed.len());
+    }
+    tokens.retain(|t| !t.0.is_empty());
+    tokens
+}
+
+/// Inverted index from words to the transcript segments that contain them. Every term
+/// of a query has to occur in the same segment; the last one also matches as a prefix,
+/// so results come up while the word is still being typed.
+#[derive(Debug, Default)]
+pub struct TranscriptIndex {
+    // word -> recording path -> segment indexes
+    terms: BTreeMap<String, HashMap<String, BTreeSet<usize>>>,
+}
+
+impl TranscriptIndex {
+    /// Index the transcript of the recording at `path`, replacing what was there
+    pub fn insert(&mut self, path: &str, transcript: Option<&Transcript>) {
+        self.remove(path);
+        let Some(transcript) = transcript else {
+            return;
+        };
+        for (index, segment) in transcript.segments.iter().enumerate() {
+            for (word, _, _) in tokenize(&segment.text) {
+                self.terms.entry(word).or_default().entry(path.to_string()).or_default().insert(index);
+            }
+        }
+    }
+
+    pub fn remove(&mut self, path: &str) {
+        self.terms.retain(|_, recordings| {
+            recordings.remove(path);
+            !recordings.is_empty()
+        });
+    }
+
+    // Segments holding a word equal to `term`, or starting with it for a prefix
+    fn postings(&self, term: &str, prefix: bool) -> HashMap<&str, BTreeSet<usize>> {
+        let mut found: HashMap<&str, BTreeSet<usize>> = HashMap::new();
+        let words = self
+            .terms
+            .range(term.to_string()..)
+            .take_while(|(word, _)| if prefix { word.starts_with(term) } else { word.as_str() == term });
+        for (_, recordings) in words {
+            for (path, segments) in recordings {
+                found.entry(path.as_str()).or_default().extend(segments);
+            }
+        }
+        found
+    }
+
+    /// Recordings whose transcripts match `query`, each with its matching segment
+    /// indexes in order
+    pub fn search(&self, query: &str) -> Vec<(String, Vec<usize>)> {
+        let terms = query_terms(query);
+        let Some((last, rest)) = terms.split_last() else {
+            return Vec::new();
+        };
+        let mut matches = self.postings(last, query_ends_in_word(query));
+        for term in rest {
+            let postings = self.postings(term, false);
+            matches.retain(|path, segments| match postings.get(path) {
+                Some(other) => {
+                    segments.retain(|s| other.contains(s));
+                    !segments.is_empty()
+                }
+                None => false,
+            });
+        }
+        matches
+            .into_iter()
+            .map(|(path, segments)| (path.to_string(), segments.into_iter().collect()))
+            .collect()
+    }
+}
+
+fn query_terms(query: &str) -> Vec<String> {
+    tokenize(query).into_iter().map(|(word, _, _)| word).collect()
+}
+
+// Whether the query is still mid-word, so its last term should match as a prefix
+fn query_ends_in_word(query: &str) -> bool {
+    query.chars().last().is_some_and(char::is_alphanumeric)
+}
+
+/// Build the hit for a matching segment: a snippet around the first match, with every
+/// matching word in it highlighted
+pub fn hit(segment_index: usize, segment: &TranscriptSegment, query: &str) -> TranscriptHit {
+    let terms = query_terms(query);
+    let prefix = query_ends_in_word(query);
+    let is_match = |word: &str| {
+        terms.iter().enumerate().any(|(i, term)| {
+            if prefix && i + 1 == terms.len() {
+                word.starts_with(term.as_str())
+            } else {
+                word == term
+            }
+        })
+    };
+
+    let tokens = tokenize(&segment.text);
+    let matched = tokens.iter().enumerate().filter(|(_, t)| is_match(&t.0)).map(|(i, _)| i).collect::<Vec<_>>();
+    let first = matched.first().copied().unwrap_or(0);
+    let from = first.saturating_sub(SNIPPET_CONTEXT_WORDS);
+    let to = (first + SNIPPET_CONTEXT_WORDS).min(tokens.len().saturating_sub(1));
+
+    let chars = segment.text.chars().collect::<Vec<_>>();
+    // Cut at word boundaries, unless the snippet reaches the ends of the segment anyway
+    let snippet_start = tokens.get(from).filter(|_| from > 0).map_or(0, |t| t.1);
+    let snippet_end = tokens.get(to).filter(|_| to + 1 < tokens.len()).map_or(chars.len(), |t| t.2);
+    let leading = if snippet_start > 0 { "…" } else { "" };
+    let trailing = if snippet_end < chars.len() { "…" } else { "" };
+    let offset = leading.chars().count();
+    let snippet = format!(
+        "{}{}{}",
+        leading,
+        chars[snippet_start..snippet_end].iter().collect::<String>(),
+        trailing
+    );
+    let highlights = matched
+        .iter()
+        .filter(|&&i| (from..=to).contains(&i))
+        .map(|&i| (tokens[i].1 - snippet_start + offset, tokens[i].2 - snippet_start + offset))
+        .collect();
+
+    // Word timings are separate from the text, so find the first matching timed word
+    let start_ms = segment
+        .words
+        .iter()
+        .find(|w| tokenize(&w.text).iter().any(|t| is_match(&t.0)))
+        .map(|w| w.start_ms)
+        .unwrap_or(segment.start_ms);
+
+    TranscriptHit {
+        segment_index,
+        start_ms,
+        snippet,
+        highlights,
+    }
+}
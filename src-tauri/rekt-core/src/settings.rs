@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+
+//
+// ====== Settings with limits checked on save and on load ======
+//
+
+/// Click track played to the monitor while recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetronomeConfig {
+    pub enabled: bool,
+    pub bpm: f32,
+    /// Upper number of the time signature; the first beat of each bar is accented
+    pub beats_per_bar: u32,
+    /// 0.0..=1.0
+    pub volume: f32,
+}
+
+impl Default for MetronomeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bpm: 120.0,
+            beats_per_bar: 4,
+            volume: 0.5,
+        }
+    }
+}
+
+impl MetronomeConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(20.0..=400.0).contains(&self.bpm) {
+            return Err("Tempo must be between 20 and 400 BPM".to_string());
+        }
+        if !(1..=16).contains(&self.beats_per_bar) {
+            return Err("Beats per bar must be between 1 and 16".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.volume) {
+            return Err("Volume must be between 0 and 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Warning when the input stays silent during a recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SilenceWarningConfig {
+    pub enabled: bool,
+    /// Input below this level counts as silence
+    pub threshold_db: f32,
+    /// How long the input must stay silent before warning
+    pub after_secs: u64,
+    /// Also show a system notification, for when the window is hidden
+    pub notify: bool,
+}
+
+impl Default for SilenceWarningConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold_db: -60.0,
+            after_secs: 30,
+            notify: false,
+        }
+    }
+}
+
+impl SilenceWarningConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.after_secs == 0 {
+            return Err("Silence period must be at least 1 second".to_string());
+        }
+        if !(-120.0..=0.0).contains(&self.threshold_db) {
+            return Err("Threshold must be between -120 and 0 dBFS".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Lowering the system output volume while recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuckConfig {
+    pub enabled: bool,
+    /// Output level while recording, 0.0 (mute) to 1.0; never raises the volume
+    pub level: f32,
+}
+
+impl Default for DuckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            level: 0.2,
+        }
+    }
+}
+
+impl DuckConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.level) {
+            return Err("Duck level must be between 0.0 and 1.0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Automatic deletion of old recordings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Recordings older than this are deleted, except favorites
+    pub max_age_days: u32,
+}
+
+impl RetentionPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_age_days == 0 {
+            return Err("Retention must keep recordings for at least 1 day".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Spoken commands that drop markers while recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VoiceCommandConfig {
+    pub enabled: bool,
+    /// Words that drop a marker when they open an utterance; what follows becomes its label
+    pub keywords: Vec<String>,
+    /// From 0, exact matches only, to 1, forgiving a few misheard letters
+    pub sensitivity: f32,
+    /// Shell command that prints the text spoken in a WAV file; `{path}` is replaced
+    /// with the quoted path, e.g. a whisper.cpp or Vosk command line
+    pub recognizer: String,
+    pub timeout_secs: u64,
+}
+
+impl Default for VoiceCommandConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            keywords: vec!["marker".to_string(), "note".to_string()],
+            sensitivity: 0.5,
+            recognizer: String::new(),
+            timeout_secs: 10,
+        }
+    }
+}
+
+impl VoiceCommandConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.keywords.iter().all(|k| k.trim().is_empty()) {
+            return Err("At least one keyword is needed".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.sensitivity) {
+            return Err("Sensitivity must be between 0 and 1".to_string());
+        }
+        if self.enabled && self.recognizer.trim().is_empty() {
+            return Err("Voice commands need a recognizer command".to_string());
+        }
+        if self.timeout_secs == 0 {
+            return Err("Recognizer timeout must be at least 1 second".to_string());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+use std::path::Path;
+
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+//
+// ====== Share link URLs and headers ======
+//
+
+// Characters left as-is in the file name part of a share URL
+const NAME_SAFE: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'_').remove(b'.').remove(b'~');
+
+/// Path part of the share URL for `file_name`, escaped for use in a link
+pub fn url_path(token: &str, file_name: &str) -> String {
+    format!("/{}/{}", token, utf8_percent_encode(file_name, NAME_SAFE))
+}
+
+/// Whether a request URL asks for this share. Clients may escape the name differently,
+/// so it is compared decoded.
+pub fn is_share_request(url: &str, token: &str, file_name: &str) -> bool {
+    percent_decode_str(url).decode_utf8_lossy() == format!("/{}/{}", token, file_name)
+}
+
+pub fn content_type(file_name: &str) -> &'static str {
+    let ext = Path::new(file_name)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "wav" => "audio/wav",
+        "mp3" => "audio/mpeg",
+        "flac" => "audio/flac",
+        "ogg" | "opus" => "audio/ogg",
+        "m4a" | "aac" => "audio/mp4",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Content-Disposition for downloading `file_name`: a plain ASCII fallback for old
+/// clients plus the exact name in RFC 5987 form
+pub fn content_disposition(file_name: &str) -> String {
+    format!(
+        "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+        file_name.replace(|c: char| c == '"' || !c.is_ascii(), "_"),
+        utf8_percent_encode(file_name, NAME_SAFE)
+    )
+}
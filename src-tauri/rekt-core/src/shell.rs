@@ -0,0 +1,131 @@
+use std::io::Read;
+use std::path::Path;
+use std::process::{Child, Command};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+//
+// ====== External commands ======
+//
+
+// How long pipes may stay open after their process exits before reading gives up
+const PIPE_GRACE: Duration = Duration::from_secs(2);
+
+// Ok(None) means the timeout elapsed and the process was killed
+pub fn wait_with_timeout(child: &mut Child, timeout: Duration) -> Result<Option<Option<i32>>, String> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return Ok(Some(status.code())),
+            Ok(None) if Instant::now() >= deadline => {
+                kill(child);
+                return Ok(None);
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(50)),
+            Err(e) => return Err(format!("Failed to wait for command: {}", e)),
+        }
+    }
+}
+
+/// Kill a command started with `shell_command` along with every process it started.
+/// Killing only the shell would leave its children running and holding its pipes open.
+pub fn kill(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        // SAFETY: only sends a signal; `shell_command` made the child its own group leader,
+        // and the group id can't be reused before the child is waited on below
+        unsafe {
+            libc::killpg(child.id() as libc::pid_t, libc::SIGKILL);
+        }
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        use std::process::Stdio;
+
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        let _ = Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &child.id().to_string()])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .creation_flags(CREATE_NO_WINDOW)
+            .status();
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Output read from a child's pipe on its own thread, up to a size limit
+pub struct Capture {
+    buffer: Arc<Mutex<Vec<u8>>>,
+    reader: thread::JoinHandle<()>,
+}
+
+impl Capture {
+    /// What was read, once the pipe closes. Call after the process has exited: a
+    /// background process it left behind may hold the pipe open indefinitely, so after
+    /// a short grace period this returns what has arrived so far.
+    pub fn finish(self) -> String {
+        let deadline = Instant::now() + PIPE_GRACE;
+        while !self.reader.is_finished() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(20));
+        }
+        let buffer = self.buffer.lock().unwrap();
+        String::from_utf8_lossy(&buffer).to_string()
+    }
+}
+
+/// Drain `pipe` in the background, keeping at most `limit` bytes. The rest is read and
+/// dropped so the writer never blocks on a full pipe.
+pub fn capture<R: Read + Send + 'static>(pipe: Option<R>, limit: usize) -> Capture {
+    let buffer = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&buffer);
+    let reader = thread::spawn(move || {
+        let Some(mut pipe) = pipe else {
+            return;
+        };
+        let mut chunk = [0u8; 8192];
+        loop {
+            match pipe.read(&mut chunk) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => {
+                    let mut buffer = sink.lock().unwrap();
+                    let room = limit.saturating_sub(buffer.len());
+                    buffer.extend_from_slice(&chunk[..read.min(room)]);
+                }
+            }
+        }
+    });
+    Capture { buffer, reader }
+}
+
+#[cfg(target_os = "windows")]
+pub fn shell_command(command_line: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+
+    let mut command = Command::new("cmd");
+    // `kill` takes down the whole process tree, so no process group is needed here
+    command.arg("/C").raw_arg(command_line);
+    command
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn shell_command(command_line: &str) -> Command {
+    use std::os::unix::process::CommandExt;
+
+    let mut command = Command::new("sh");
+    // Its own process group, so `kill` can take down whatever the command line starts
+    command.arg("-c").arg(command_line).process_group(0);
+    command
+}
+
+#[cfg(target_os = "windows")]
+pub fn shell_quote(path: &Path) -> String {
+    format!("\"{}\"", path.display())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn shell_quote(path: &Path) -> String {
+    format!("'{}'", path.display().to_string().replace('\'', "'\\''"))
+}
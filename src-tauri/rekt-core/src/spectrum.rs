@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+use serde::Serialize;
+
+use crate::dsp;
+
+//
+// ====== Live spectrum analyzer ======
+//
+
+pub const FFT_SIZE: usize = 2048;
+pub const BAND_COUNT: usize = 32;
+const MIN_FREQUENCY: f32 = 20.0;
+const MAX_FREQUENCY: f32 = 20_000.0;
+const FLOOR_DB: f32 = -100.0;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SpectrumEvent {
+    /// Level of each log-spaced band in dBFS, lowest frequency first
+    pub bands: Vec<f32>,
+    pub min_frequency: f32,
+    pub max_frequency: f32,
+}
+
+/// Reusable FFT plan and window for turning recent audio into band levels
+pub struct SpectrumAnalyzer {
+    fft: Arc<dyn Fft<f32>>,
+    window: Vec<f32>,
+    buffer: Vec<Complex<f32>>,
+}
+
+impl Default for SpectrumAnalyzer {
+    fn default() -> Self {
+        Self {
+            fft: FftPlanner::new().plan_fft_forward(FFT_SIZE),
+            window: dsp::hann_window(FFT_SIZE),
+            buffer: vec![Complex::default(); FFT_SIZE],
+        }
+    }
+}
+
+impl SpectrumAnalyzer {
+    /// Analyze the last `FFT_SIZE` frames of interleaved `samples`, mixed down to mono
+    pub fn analyze(&mut self, samples: &[i16], channels: u16, sample_rate: u32) -> SpectrumEvent {
+        let channels = channels.max(1) as usize;
+        let frames = samples.chunks_exact(channels).rev().take(FFT_SIZE).collect::<Vec<_>>();
+
+        // Oldest frame first, zero-padded at the front when there is not enough audio yet
+        let padding = FFT_SIZE - frames.len();
+        for (i, slot) in self.buffer.iter_mut().enumerate() {
+            let value = if i < padding {
+                0.0
+            } else {
+                let frame = frames[FFT_SIZE - 1 - i];
+                frame.iter().map(|&s| s as f32 / i16::MAX as f32).sum::<f32>() / channels as f32
+            };
+            *slot = Complex::new(value * self.window[i], 0.0);
+        }
+        self.fft.process(&mut self.buffer);
+
+        // Scale so a full-scale sine reads about 0 dBFS
+        let window_gain: f32 = self.window.iter().sum();
+        let magnitudes = self.buffer[..FFT_SIZE / 2]
+            .iter()
+            .map(|c| c.norm() * 2.0 / window_gain)
+            .collect::<Vec<_>>();
+
+        let max_frequency = MAX_FREQUENCY.min(sample_rate as f32 / 2.0);
+        let bin_width = sample_rate as f32 / FFT_SIZE as f32;
+        let ratio = max_frequency / MIN_FREQUENCY;
+
+        let bands = (0..BAND_COUNT)
+            .map(|band| {
+                let low = MIN_FREQUENCY * ratio.powf(band as f32 / BAND_COUNT as f32);
+                let high = MIN_FREQUENCY * ratio.powf((band + 1) as f32 / BAND_COUNT as f32);
+                let first = ((low / bin_width) as usize).min(magnitudes.len() - 1);
+                // Narrow low bands can fall between bins; always use at least one
+                let last = ((high / bin_width) as usize).clamp(first + 1, magnitudes.len());
+                let peak = magnitudes[first..last].iter().fold(0.0f32, |a, &m| a.max(m));
+                (20.0 * peak.max(1e-9).log10()).max(FLOOR_DB)
+            })
+            .collect();
+
+        SpectrumEvent {
+            bands,
+            min_frequency: MIN_FREQUENCY,
+            max_frequency,
+        }
+    }
+}
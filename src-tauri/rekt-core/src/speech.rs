@@ -0,0 +1,248 @@
+use serde::{Deserialize, Serialize};
+
+use crate::processing::{self, AudioBuffer};
+
+//
+// ====== Speech detection ======
+//
+
+const FRAME_MS: u64 = 30;
+// Speech has to rise this far above the recording's noise floor
+const SPEECH_OVER_FLOOR_DB: f32 = 10.0;
+const MIN_SPEECH_DB: f32 = -50.0;
+// Pauses shorter than this stay inside one utterance
+const MAX_PAUSE_MS: u64 = 500;
+const MIN_SPEECH_MS: u64 = 200;
+
+/// A stretch of speech found by `detect_speech`, cached on the library entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeechSegment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+/// Energy-based voice activity: frames well above the quietest tenth of the recording
+pub fn detect_speech(buffer: &AudioBuffer) -> Vec<SpeechSegment> {
+    let channels = buffer.channels.max(1) as usize;
+    let frame_len = (buffer.sample_rate as u64 * FRAME_MS / 1000).max(1) as usize * channels;
+    let levels = buffer
+        .samples
+        .chunks(frame_len)
+        .map(|frame| processing::to_db(processing::rms(frame)))
+        .collect::<Vec<_>>();
+    if levels.is_empty() {
+        return Vec::new();
+    }
+
+    let mut sorted = levels.clone();
+    sorted.sort_by(f32::total_cmp);
+    let floor = sorted[sorted.len() / 10];
+    let threshold = (floor + SPEECH_OVER_FLOOR_DB).max(MIN_SPEECH_DB);
+
+    let mut segments: Vec<SpeechSegment> = Vec::new();
+    for (index, level) in levels.iter().enumerate() {
+        if *level < threshold {
+            continue;
+        }
+        let start_ms = index as u64 * FRAME_MS;
+        let end_ms = start_ms + FRAME_MS;
+        match segments.last_mut() {
+            Some(last) if start_ms - last.end_ms <= MAX_PAUSE_MS => last.end_ms = end_ms,
+            _ => segments.push(SpeechSegment { start_ms, end_ms }),
+        }
+    }
+    segments.retain(|s| s.end_ms - s.start_ms >= MIN_SPEECH_MS);
+    segments
+}
+
+// Utterances are ended by a pause this long; shorter than `MAX_PAUSE_MS` so a spoken
+// command is handed on quickly
+const UTTERANCE_PAUSE_MS: u64 = 300;
+// How fast the live noise floor creeps up after the room gets louder
+const FLOOR_RISE_DB_PER_FRAME: f32 = 0.05;
+
+/// A short stretch of speech cut out of live input by `UtteranceDetector`
+pub struct Utterance {
+    /// Position in the input, counted from the first samples pushed
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub audio: AudioBuffer,
+}
+
+enum Voiced {
+    Quiet,
+    // Frames since the utterance began, and the index of the last voiced one
+    Speaking { start: u64, samples: Vec<f32>, last_voiced: u64 },
+    // Too long for a command; wait for the speaker to pause
+    Skipping { last_voiced: u64 },
+}
+
+/// Live counterpart of `detect_speech`: feed it input as it arrives and it returns each
+/// utterance once the speaker pauses. Only utterances up to `max_ms` long are kept, so
+/// ordinary talking isn't mistaken for a command.
+pub struct UtteranceDetector {
+    channels: u16,
+    sample_rate: u32,
+    frame_len: usize,
+    max_frames: u64,
+    pending: Vec<f32>,
+    frames: u64,
+    floor_db: Option<f32>,
+    voiced: Voiced,
+}
+
+impl UtteranceDetector {
+    pub fn new(channels: u16, sample_rate: u32, max_ms: u64) -> Self {
+        let channels = channels.max(1);
+        Self {
+            channels,
+            sample_rate,
+            frame_len: (sample_rate as u64 * FRAME_MS / 1000).max(1) as usize * channels as usize,
+            max_frames: max_ms / FRAME_MS,
+            pending: Vec::new(),
+            frames: 0,
+            floor_db: None,
+            voiced: Voiced::Quiet,
+        }
+    }
+
+    pub fn push(&mut self, samples: &[i16]) -> Vec<Utterance> {
+        self.pending.extend(samples.iter().map(|&s| s as f32 / i16::MAX as f32));
+        let mut found = Vec::new();
+        let mut offset = 0;
+        while self.pending.len() - offset >= self.frame_len {
+            let frame = self.pending[offset..offset + self.frame_len].to_vec();
+            offset += self.frame_len;
+            found.extend(self.push_frame(frame));
+        }
+        self.pending.drain(..offset);
+        found
+    }
+
+    fn push_frame(&mut self, frame: Vec<f32>) -> Option<Utterance> {
+        let index = self.frames;
+        self.frames += 1;
+
+        let level = processing::to_db(processing::rms(&frame));
+        let floor = *self.floor_db.get_or_insert(level);
+        let is_speech = level >= (floor + SPEECH_OVER_FLOOR_DB).max(MIN_SPEECH_DB);
+        if !is_speech {
+            self.floor_db = Some(level.min(floor + FLOOR_RISE_DB_PER_FRAME));
+        }
+
+        let pause_frames = UTTERANCE_PAUSE_MS / FRAME_MS;
+        match &mut self.voiced {
+            Voiced::Quiet if is_speech => {
+                self.voiced = Voiced::Speaking {
+                    start: index,
+                    samples: frame,
+                    last_voiced: index,
+                };
+                None
+            }
+            Voiced::Quiet => None,
+            Voiced::Speaking { start, samples, last_voiced } => {
+                if is_speech {
+                    *last_voiced = index;
+                }
+                if *last_voiced + 1 - *start > self.max_frames {
+                    self.voiced = Voiced::Skipping { last_voiced: *last_voiced };
+                    return None;
+                }
+                samples.extend_from_slice(&frame);
+                if index - *last_voiced <= pause_frames {
+                    return None;
+                }
+
+                let (start, last_voiced) = (*start, *last_voiced);
+                let mut samples = std::mem::take(samples);
+                self.voiced = Voiced::Quiet;
+                let frames = last_voiced + 1 - start;
+                if frames * FRAME_MS < MIN_SPEECH_MS {
+                    return None;
+                }
+                samples.truncate(frames as usize * self.frame_len);
+                Some(Utterance {
+                    start_ms: start * FRAME_MS,
+                    end_ms: (last_voiced + 1) * FRAME_MS,
+                    audio: AudioBuffer {
+                        channels: self.channels,
+                        sample_rate: self.sample_rate,
+                        samples,
+                    },
+                })
+            }
+            Voiced::Skipping { last_voiced } => {
+                if is_speech {
+                    *last_voiced = index;
+                } else if index - *last_voiced > pause_frames {
+                    self.voiced = Voiced::Quiet;
+                }
+                None
+            }
+        }
+    }
+}
+
+//
+// ====== Keyword spotting ======
+//
+
+// At full sensitivity, this share of a keyword's letters may be misheard
+const MAX_EDIT_SHARE: f32 = 0.4;
+
+/// A spoken command found by `match_keyword`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeywordMatch {
+    pub keyword: String,
+    /// Whatever was said after the keyword, e.g. a marker label
+    pub rest: String,
+}
+
+/// Check whether recognized `text` opens with one of `keywords`. `sensitivity` runs from
+/// 0, where only an exact match counts, to 1, which forgives a few misheard letters.
+pub fn match_keyword(text: &str, keywords: &[String], sensitivity: f32) -> Option<KeywordMatch> {
+    let words = text.split_whitespace().collect::<Vec<_>>();
+    let normalized = words.iter().map(|w| normalize(w)).collect::<Vec<_>>();
+
+    keywords.iter().find_map(|keyword| {
+        let wanted = keyword.split_whitespace().map(normalize).collect::<Vec<_>>();
+        let count = wanted.len();
+        if count == 0 || count > words.len() {
+            return None;
+        }
+        let wanted = wanted.join(" ");
+        let heard = normalized[..count].join(" ");
+        let max_edits = (wanted.chars().count() as f32 * sensitivity.clamp(0.0, 1.0) * MAX_EDIT_SHARE) as usize;
+        if edit_distance(&wanted, &heard) > max_edits {
+            return None;
+        }
+
+        let rest = words[count..].join(" ");
+        Some(KeywordMatch {
+            keyword: keyword.clone(),
+            rest: rest.trim_matches(|c: char| !c.is_alphanumeric()).to_string(),
+        })
+    })
+}
+
+// Lowercase without the punctuation recognizers like to add
+fn normalize(word: &str) -> String {
+    word.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+// Levenshtein distance in characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = (above + 1).min(row[j] + 1).min(diagonal + usize::from(ca != *cb));
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
@@ -0,0 +1,189 @@
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+//
+// ====== Capture telemetry ======
+//
+
+// Spending more than this share of a buffer's duration inside the callback leaves
+// too little headroom for the OS to schedule us in time
+const HIGH_LOAD: f32 = 0.8;
+// A callback this late means at least one buffer sat in the driver far too long
+const LONG_GAP_MS: f32 = 100.0;
+// The hardware clock drifts a little from the system one; don't call that dropouts
+const DRIFT_TOLERANCE: f64 = 0.001;
+// Callback jitter swamps the drift over short spans; a minute of input pins it to a
+// few ppm
+const MIN_DRIFT_SPAN_SECS: f64 = 60.0;
+
+/// Timing statistics for the input callbacks of one recording
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SessionTelemetry {
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub callbacks: u64,
+    pub frames: u64,
+    /// Longest time between two callbacks
+    pub max_gap_ms: f32,
+    pub mean_gap_ms: f32,
+    /// Largest buffer the driver delivered, in frames
+    pub max_buffer_frames: usize,
+    /// Highest share of a buffer's duration spent handling it; near 1 the callback
+    /// is about to fall behind the hardware
+    pub peak_load: f32,
+    /// Frames the wall clock says should have arrived but never did
+    pub dropped_frames: u64,
+    /// How much faster the input clock ran than the system clock, in parts per million.
+    /// A positive drift means the recording plays back longer than it took to make.
+    /// `None` until enough input has arrived to tell.
+    pub clock_drift_ppm: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TelemetryWarningKind {
+    DroppedFrames,
+    LongGap,
+    HighLoad,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TelemetryWarning {
+    pub kind: TelemetryWarningKind,
+    pub message: String,
+    pub telemetry: SessionTelemetry,
+}
+
+/// Collects `SessionTelemetry` from inside the capture callback
+#[derive(Default)]
+pub struct CallbackTimer {
+    stats: SessionTelemetry,
+    // First callback since the start or the last pause, and the frames since then
+    anchor: Option<(Instant, usize)>,
+    anchored_frames: u64,
+    // Dropouts from before the last pause
+    earlier_dropped: u64,
+    // Wall-clock seconds and frames received across finished stretches of input, for
+    // the drift; each stretch counts from its first callback on
+    earlier_span: (f64, u64),
+    last: Option<Instant>,
+    gaps: u32,
+    total_gap: Duration,
+    warned: Vec<TelemetryWarningKind>,
+}
+
+impl CallbackTimer {
+    /// Start a new session at the given stream format
+    pub fn begin(&mut self, channels: u16, sample_rate: u32) {
+        *self = Self::default();
+        self.stats.channels = channels;
+        self.stats.sample_rate = sample_rate;
+    }
+
+    /// Stop expecting input until callbacks come in again, e.g. while paused
+    pub fn pause(&mut self) {
+        self.earlier_dropped = self.snapshot().dropped_frames;
+        self.earlier_span = self.span();
+        self.anchor = None;
+        self.anchored_frames = 0;
+        self.last = None;
+    }
+
+    /// Note a callback that arrived at `arrived` with `samples` interleaved samples and
+    /// took `busy` to handle
+    pub fn record(&mut self, arrived: Instant, samples: usize, busy: Duration) {
+        let stats = &mut self.stats;
+        if stats.sample_rate == 0 {
+            return;
+        }
+        let frames = samples / stats.channels.max(1) as usize;
+        if let Some(last) = self.last {
+            let gap = arrived.saturating_duration_since(last);
+            self.gaps += 1;
+            self.total_gap += gap;
+            stats.max_gap_ms = stats.max_gap_ms.max(gap.as_secs_f32() * 1000.0);
+        }
+        self.anchor.get_or_insert((arrived, frames));
+        self.anchored_frames += frames as u64;
+        self.last = Some(arrived);
+
+        stats.callbacks += 1;
+        stats.frames += frames as u64;
+        stats.max_buffer_frames = stats.max_buffer_frames.max(frames);
+        if frames > 0 {
+            let period = frames as f32 / stats.sample_rate as f32;
+            stats.peak_load = stats.peak_load.max(busy.as_secs_f32() / period);
+        }
+    }
+
+    // Wall-clock seconds and frames of input so far, pauses left out
+    fn span(&self) -> (f64, u64) {
+        let (mut seconds, mut frames) = self.earlier_span;
+        if let (Some((first, first_frames)), Some(last)) = (self.anchor, self.last) {
+            seconds += last.saturating_duration_since(first).as_secs_f64();
+            frames += self.anchored_frames - first_frames as u64;
+        }
+        (seconds, frames)
+    }
+
+    pub fn snapshot(&self) -> SessionTelemetry {
+        let mut stats = self.stats.clone();
+        if self.gaps > 0 {
+            stats.mean_gap_ms = self.total_gap.as_secs_f32() * 1000.0 / self.gaps as f32;
+        }
+        stats.dropped_frames = self.earlier_dropped;
+        if let (Some((first, first_frames)), Some(last)) = (self.anchor, self.last) {
+            // Everything up to the latest callback should have arrived by now, give or
+            // take one buffer of scheduling jitter and the clock drift
+            let elapsed = last.saturating_duration_since(first).as_secs_f64();
+            let expected = elapsed * stats.sample_rate as f64 + first_frames as f64;
+            let tolerance = (stats.max_buffer_frames * 2) as f64 + expected * DRIFT_TOLERANCE;
+            stats.dropped_frames += (expected - tolerance - self.anchored_frames as f64).max(0.0) as u64;
+        }
+        let (seconds, frames) = self.span();
+        if seconds >= MIN_DRIFT_SPAN_SECS {
+            let expected = seconds * stats.sample_rate as f64;
+            stats.clock_drift_ppm = Some((frames as f64 / expected - 1.0) * 1_000_000.0);
+        }
+        stats
+    }
+
+    /// Problems seen since the last call, each kind reported once per session
+    pub fn new_warnings(&mut self) -> Vec<TelemetryWarning> {
+        let stats = self.snapshot();
+        let mut found = Vec::new();
+        if stats.dropped_frames > 0 {
+            let lost_ms = stats.dropped_frames * 1000 / stats.sample_rate.max(1) as u64;
+            found.push((
+                TelemetryWarningKind::DroppedFrames,
+                format!("About {} ms of audio was lost; the system couldn't keep up with the input", lost_ms),
+            ));
+        }
+        if stats.max_gap_ms > LONG_GAP_MS {
+            found.push((
+                TelemetryWarningKind::LongGap,
+                format!("The audio input stalled for {:.0} ms", stats.max_gap_ms),
+            ));
+        }
+        if stats.peak_load > HIGH_LOAD {
+            found.push((
+                TelemetryWarningKind::HighLoad,
+                format!("Handling input took {:.0}% of the available time", stats.peak_load * 100.0),
+            ));
+        }
+
+        found.retain(|(kind, _)| !self.warned.contains(kind));
+        found
+            .into_iter()
+            .map(|(kind, message)| {
+                self.warned.push(kind);
+                TelemetryWarning {
+                    kind,
+                    message,
+                    telemetry: stats.clone(),
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,109 @@
+use rustfft::num_complex::Complex;
+use rustfft::FftPlanner;
+use serde::{Deserialize, Serialize};
+
+use crate::dsp;
+use crate::processing::AudioBuffer;
+
+//
+// ====== Tempo and beat detection ======
+//
+
+const FFT_SIZE: usize = 1024;
+const HOP: usize = 512;
+const MIN_BPM: f32 = 60.0;
+const MAX_BPM: f32 = 200.0;
+// Below this many onset frames there is too little material to find a pulse
+const MIN_FRAMES: usize = 64;
+
+/// Estimated tempo of a recording, kept in its library entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TempoInfo {
+    pub bpm: f32,
+    /// Beat positions in milliseconds from the start
+    pub beats_ms: Vec<u64>,
+}
+
+// Spectral flux: how much energy appeared in each hop compared to the one before
+fn onset_envelope(buffer: &AudioBuffer) -> Vec<f32> {
+    let channels = buffer.channels.max(1) as usize;
+    let mono = buffer
+        .samples
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect::<Vec<_>>();
+    if mono.len() < FFT_SIZE {
+        return Vec::new();
+    }
+
+    let fft = FftPlanner::new().plan_fft_forward(FFT_SIZE);
+    let window = dsp::hann_window(FFT_SIZE);
+
+    let mut previous = vec![0f32; FFT_SIZE / 2];
+    let mut spectrum = vec![Complex::default(); FFT_SIZE];
+    let mut envelope = Vec::with_capacity(mono.len() / HOP);
+    for start in (0..=mono.len() - FFT_SIZE).step_by(HOP) {
+        for (i, slot) in spectrum.iter_mut().enumerate() {
+            *slot = Complex::new(mono[start + i] * window[i], 0.0);
+        }
+        fft.process(&mut spectrum);
+
+        let mut flux = 0.0;
+        for (bin, last) in previous.iter_mut().enumerate() {
+            // Log magnitude so quiet passages count as much as loud ones
+            let magnitude = (1.0 + spectrum[bin].norm()).ln();
+            flux += (magnitude - *last).max(0.0);
+            *last = magnitude;
+        }
+        envelope.push(flux);
+    }
+
+    // Subtract the average so steady energy doesn't look like a beat
+    let mean = envelope.iter().sum::<f32>() / envelope.len().max(1) as f32;
+    envelope.iter().map(|flux| (flux - mean).max(0.0)).collect()
+}
+
+/// Estimate the tempo and beat grid of a recording
+pub fn detect(buffer: &AudioBuffer) -> Result<TempoInfo, String> {
+    let envelope = onset_envelope(buffer);
+    if envelope.len() < MIN_FRAMES {
+        return Err("Recording is too short to detect a tempo".to_string());
+    }
+    let frames_per_second = buffer.sample_rate as f32 / HOP as f32;
+
+    // The lag at which the onset envelope best matches itself is the beat period
+    let min_lag = (frames_per_second * 60.0 / MAX_BPM).floor().max(1.0) as usize;
+    let max_lag = ((frames_per_second * 60.0 / MIN_BPM).ceil() as usize).min(envelope.len() / 2);
+    if min_lag >= max_lag {
+        return Err("Recording is too short to detect a tempo".to_string());
+    }
+    let (period, strength) = (min_lag..=max_lag)
+        .map(|lag| {
+            let score = envelope.iter().zip(&envelope[lag..]).map(|(a, b)| a * b).sum::<f32>()
+                / (envelope.len() - lag) as f32;
+            (lag, score)
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or((min_lag, 0.0));
+    if strength <= f32::EPSILON {
+        return Err("No rhythmic pulse found".to_string());
+    }
+
+    // Slide a comb of that period to find where the beats land
+    let phase = (0..period)
+        .max_by(|&a, &b| {
+            let comb = |offset: usize| envelope.iter().skip(offset).step_by(period).sum::<f32>();
+            comb(a).total_cmp(&comb(b))
+        })
+        .unwrap_or(0);
+
+    let frame_ms = 1000.0 / frames_per_second;
+    let beats_ms = (phase..envelope.len())
+        .step_by(period)
+        .map(|frame| (frame as f32 * frame_ms) as u64)
+        .collect();
+    Ok(TempoInfo {
+        bpm: (60.0 * frames_per_second / period as f32 * 10.0).round() / 10.0,
+        beats_ms,
+    })
+}
@@ -0,0 +1,180 @@
+use serde::{Deserialize, Serialize};
+
+//
+// ====== Transcripts ======
+//
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptWord {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptSegment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+    /// Word timings, when the transcriber provides them
+    #[serde(default)]
+    pub words: Vec<TranscriptWord>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptFormat {
+    Srt,
+    Vtt,
+    Txt,
+    Json,
+}
+
+// `HH:MM:SS` plus milliseconds after `separator`: a comma for SRT, a dot for WebVTT
+fn cue_time(ms: u64, separator: char) -> String {
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        separator,
+        ms % 1000
+    )
+}
+
+// A blank line ends a cue, so subtitle text has to stay on one line, and an arrow in
+// it would read as another timing line
+fn cue_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").replace("-->", "->")
+}
+
+// `HH:MM:SS,mmm`, `HH:MM:SS.mmm` or `MM:SS.mmm` as milliseconds
+fn parse_cue_time(time: &str) -> Option<u64> {
+    let (clock, millis) = time.trim().rsplit_once([',', '.'])?;
+    let millis = format!("{:0<3}", millis).get(..3)?.parse::<u64>().ok()?;
+    let seconds = clock
+        .split(':')
+        .try_fold(0u64, |total, part| Some(total * 60 + part.parse::<u64>().ok()?))?;
+    Some(seconds * 1000 + millis)
+}
+
+/// Timed transcript of a recording, kept in its library entry
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Transcript {
+    #[serde(default)]
+    pub language: Option<String>,
+    pub segments: Vec<TranscriptSegment>,
+}
+
+impl Transcript {
+    pub fn validate(&self) -> Result<(), String> {
+        let mut previous_start = 0;
+        for segment in &self.segments {
+            if segment.end_ms < segment.start_ms || segment.start_ms < previous_start {
+                return Err("Transcript segments must be in order and end after they start".to_string());
+            }
+            if segment.words.iter().any(|w| w.end_ms < w.start_ms) {
+                return Err("Transcript words must end after they start".to_string());
+            }
+            previous_start = segment.start_ms;
+        }
+        Ok(())
+    }
+
+    /// Segment and word under `position_ms`, if any
+    pub fn locate(&self, position_ms: u64) -> Option<(usize, Option<usize>)> {
+        let segment = self
+            .segments
+            .iter()
+            .position(|s| (s.start_ms..s.end_ms).contains(&position_ms))?;
+        let word = self.segments[segment]
+            .words
+            .iter()
+            .position(|w| (w.start_ms..w.end_ms).contains(&position_ms));
+        Some((segment, word))
+    }
+
+    /// The transcript as a subtitle file, plain text or JSON
+    pub fn render(&self, format: TranscriptFormat) -> Result<String, String> {
+        // Segments with nothing in them would show as empty captions
+        let segments = self.segments.iter().filter(|s| !s.text.trim().is_empty());
+        let mut out = String::new();
+        match format {
+            TranscriptFormat::Srt => {
+                for (index, segment) in segments.enumerate() {
+                    out.push_str(&format!(
+                        "{}\n{} --> {}\n{}\n\n",
+                        index + 1,
+                        cue_time(segment.start_ms, ','),
+                        cue_time(segment.end_ms, ','),
+                        cue_text(&segment.text)
+                    ));
+                }
+            }
+            TranscriptFormat::Vtt => {
+                out.push_str("WEBVTT\n\n");
+                for segment in segments {
+                    out.push_str(&format!(
+                        "{} --> {}\n{}\n\n",
+                        cue_time(segment.start_ms, '.'),
+                        cue_time(segment.end_ms, '.'),
+                        cue_text(&segment.text)
+                    ));
+                }
+            }
+            TranscriptFormat::Txt => {
+                for segment in segments {
+                    out.push_str(segment.text.trim());
+                    out.push('\n');
+                }
+            }
+            TranscriptFormat::Json => {
+                out = serde_json::to_string_pretty(self)
+                    .map_err(|e| format!("Failed to serialize transcript: {}", e))?;
+            }
+        }
+        Ok(out)
+    }
+
+    /// Read SRT or WebVTT cues, or whisper.cpp's console output where each line is
+    /// `[00:00:00.000 --> 00:00:02.500]  text`. Anything that isn't a cue is skipped, so
+    /// text without timings gives no segments.
+    pub fn parse_cues(text: &str) -> Transcript {
+        let mut segments: Vec<TranscriptSegment> = Vec::new();
+        let mut in_cue = false;
+        for line in text.lines().map(str::trim) {
+            let timing = line.strip_prefix('[').and_then(|l| l.split_once(']')).unwrap_or((line, ""));
+            let cue = timing.0.split_once("-->").and_then(|(start, end)| {
+                // WebVTT puts cue settings after the end time
+                let end = end.split_whitespace().next()?;
+                Some((parse_cue_time(start)?, parse_cue_time(end)?))
+            });
+            match cue {
+                Some((start_ms, end_ms)) => {
+                    segments.push(TranscriptSegment {
+                        start_ms,
+                        end_ms: end_ms.max(start_ms),
+                        text: timing.1.trim().to_string(),
+                        words: Vec::new(),
+                    });
+                    in_cue = true;
+                }
+                None if line.is_empty() => in_cue = false,
+                None if in_cue => {
+                    let segment = segments.last_mut().expect("in a cue");
+                    if !segment.text.is_empty() {
+                        segment.text.push(' ');
+                    }
+                    segment.text.push_str(line);
+                }
+                None => {}
+            }
+        }
+        segments.retain(|s| !s.text.is_empty());
+        segments.sort_by_key(|s| s.start_ms);
+        Transcript {
+            language: None,
+            segments,
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+
+use rekt_core::calendar::Calendar;
+
+fn local(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Local> {
+    let naive = NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, 0).unwrap();
+    Local.from_local_datetime(&naive).earliest().unwrap()
+}
+
+fn ics(events: &str) -> String {
+    format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{}END:VCALENDAR\r\n", events)
+}
+
+// Titles and start times of the occurrences in March 2025
+fn march(calendar: &Calendar) -> Vec<(String, DateTime<Local>)> {
+    calendar
+        .events_between(local(2025, 3, 1, 0, 0), local(2025, 4, 1, 0, 0))
+        .into_iter()
+        .map(|event| (event.title, event.start))
+        .collect()
+}
+
+#[test]
+fn rejects_what_isnt_a_calendar() {
+    assert!(Calendar::parse("BEGIN:VEVENT\nEND:VEVENT\n").is_err());
+}
+
+#[test]
+fn unfolds_lines_and_unescapes_titles() {
+    let calendar = Calendar::parse(&ics(
+        "BEGIN:VEVENT\r\nUID:a\r\nSUMMARY:Design review\\, part\r\n  two\r\n\
+         DTSTART;TZID=\"Europe/Berlin\":20250310T090000\r\nDTEND:20250310T100000\r\n\
+         BEGIN:VALARM\r\nSUMMARY:Reminder\r\nEND:VALARM\r\nEND:VEVENT\r\n",
+    ))
+    .unwrap();
+    let events = calendar.events_between(local(2025, 3, 10, 0, 0), local(2025, 3, 11, 0, 0));
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].title, "Design review, part two");
+    assert_eq!((events[0].start, events[0].end), (local(2025, 3, 10, 9, 0), local(2025, 3, 10, 10, 0)));
+}
+
+#[test]
+fn leaves_out_all_day_and_cancelled_events() {
+    let calendar = Calendar::parse(&ics(
+        "BEGIN:VEVENT\r\nUID:a\r\nSUMMARY:Holiday\r\nDTSTART:20250310\r\nEND:VEVENT\r\n\
+         BEGIN:VEVENT\r\nUID:b\r\nSUMMARY:Off\r\nSTATUS:CANCELLED\r\nDTSTART:20250311T090000\r\n\
+         DURATION:PT1H\r\nEND:VEVENT\r\n",
+    ))
+    .unwrap();
+    assert!(march(&calendar).is_empty());
+}
+
+#[test]
+fn expands_weekly_rules_with_exceptions_and_overrides() {
+    let calendar = Calendar::parse(&ics(
+        "BEGIN:VEVENT\r\nUID:standup\r\nSUMMARY:Standup\r\nDTSTART:20250303T093000\r\nDURATION:PT15M\r\n\
+         RRULE:FREQ=WEEKLY;BYDAY=MO,WE;COUNT=6\r\nEXDATE:20250305T093000\r\nEND:VEVENT\r\n\
+         BEGIN:VEVENT\r\nUID:standup\r\nSUMMARY:Standup (moved)\r\nRECURRENCE-ID:20250310T093000\r\n\
+         DTSTART:20250310T110000\r\nDURATION:PT15M\r\nEND:VEVENT\r\n",
+    ))
+    .unwrap();
+    let found = march(&calendar);
+    let expected = [
+        ("Standup", local(2025, 3, 3, 9, 30)),
+        ("Standup (moved)", local(2025, 3, 10, 11, 0)),
+        ("Standup", local(2025, 3, 12, 9, 30)),
+        ("Standup", local(2025, 3, 17, 9, 30)),
+        ("Standup", local(2025, 3, 19, 9, 30)),
+    ];
+    assert_eq!(found, expected.map(|(title, start)| (title.to_string(), start)));
+}
+
+#[test]
+fn daily_rules_stop_at_until() {
+    let calendar = Calendar::parse(&ics(
+        "BEGIN:VEVENT\r\nUID:d\r\nSUMMARY:Sync\r\nDTSTART:20250330T080000\r\nDTEND:20250330T083000\r\n\
+         RRULE:FREQ=DAILY;INTERVAL=2;UNTIL=20250405T000000\r\nEND:VEVENT\r\n",
+    ))
+    .unwrap();
+    let all = calendar.events_between(local(2025, 3, 1, 0, 0), local(2025, 5, 1, 0, 0));
+    let days = all.iter().map(|event| event.start).collect::<Vec<_>>();
+    assert_eq!(days, [local(2025, 3, 30, 8, 0), local(2025, 4, 1, 8, 0), local(2025, 4, 3, 8, 0)]);
+}
@@ -0,0 +1,37 @@
+use rekt_core::crypto::{derive_key, open, seal};
+
+#[test]
+fn sealed_data_opens_with_the_same_key() {
+    let key = derive_key("correct horse battery", b"0123456789abcdef").unwrap();
+    let sealed = seal(&key, b"RIFF....WAVE").unwrap();
+    assert_ne!(&sealed[8..], b"RIFF....WAVE");
+    assert_eq!(open(&key, &sealed).unwrap(), b"RIFF....WAVE");
+    // A fresh nonce every time
+    assert_ne!(seal(&key, b"RIFF....WAVE").unwrap(), sealed);
+}
+
+#[test]
+fn key_depends_on_passphrase_and_salt() {
+    let key = derive_key("correct horse battery", b"0123456789abcdef").unwrap();
+    assert_eq!(derive_key("correct horse battery", b"0123456789abcdef").unwrap(), key);
+    assert_ne!(derive_key("correct horse battery", b"fedcba9876543210").unwrap(), key);
+    assert_ne!(derive_key("wrong horse battery", b"0123456789abcdef").unwrap(), key);
+}
+
+#[test]
+fn wrong_key_or_tampering_fails() {
+    let key = derive_key("correct horse battery", b"0123456789abcdef").unwrap();
+    let other = derive_key("wrong horse battery", b"0123456789abcdef").unwrap();
+    let mut sealed = seal(&key, b"audio").unwrap();
+    assert!(open(&other, &sealed).is_err());
+    let last = sealed.len() - 1;
+    sealed[last] ^= 1;
+    assert!(open(&key, &sealed).is_err());
+}
+
+#[test]
+fn plain_files_are_not_mistaken_for_sealed_ones() {
+    let key = [7u8; 32];
+    assert_eq!(open(&key, b"RIFF....WAVE").unwrap_err(), "Not an encrypted recording");
+    assert!(open(&key, b"REKTENC1").is_err());
+}
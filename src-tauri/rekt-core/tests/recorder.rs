@@ -0,0 +1,376 @@
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rekt_core::backend::{AudioBackend, MockBackend, Signal, StreamFormat};
+use rekt_core::bwf;
+use rekt_core::processing::{self, AudioBuffer};
+use rekt_core::recording::{self, RecorderState, RecordingState};
+use rekt_core::self_test;
+use rekt_core::speech;
+
+const RATE: u32 = 16_000;
+const TONE: Signal = Signal::Sine {
+    frequency: 440.0,
+    amplitude: 0.5,
+};
+// Quiet room tone rather than digital silence, like a real microphone
+const ROOM: Signal = Signal::Noise { amplitude: 0.001 };
+
+fn secs(s: f32) -> Duration {
+    Duration::from_secs_f32(s)
+}
+
+// A recording in progress on the mock input, the way the app's recorder thread sets one up
+fn start(backend: &MockBackend) -> Arc<RecordingState> {
+    let state = Arc::new(RecordingState::default());
+    let stream = backend.open_input(None, StreamFormat::default(), RecordingState::capture(&state)).unwrap();
+    state.begin_capture(0, stream.device.channels, stream.device.sample_rate);
+    stream.play().unwrap();
+    *state.input_stream.lock().unwrap() = Some(stream);
+    state.transition(RecorderState::Starting).unwrap();
+    state.transition(RecorderState::Recording).unwrap();
+    state
+}
+
+fn captured(state: &RecordingState) -> AudioBuffer {
+    let format = state.capture_format().unwrap();
+    AudioBuffer {
+        channels: format.channels,
+        sample_rate: format.sample_rate,
+        samples: state
+            .audio_data
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|&s| s as f32 / i16::MAX as f32)
+            .collect(),
+    }
+}
+
+#[test]
+fn records_exactly_what_the_device_delivers() {
+    let backend = MockBackend::new(1, RATE);
+    let state = start(&backend);
+
+    assert_eq!(backend.feed(TONE, secs(1.5)).unwrap(), 24_000);
+    assert_eq!(state.audio_data.lock().unwrap().len(), 24_000);
+}
+
+#[test]
+fn ignores_input_while_paused() {
+    let backend = MockBackend::new(1, RATE);
+    let state = start(&backend);
+
+    state.transition(RecorderState::Paused).unwrap();
+    backend.feed(TONE, secs(0.5)).unwrap();
+    assert!(state.audio_data.lock().unwrap().is_empty());
+
+    state.transition(RecorderState::Recording).unwrap();
+    backend.feed(TONE, secs(0.5)).unwrap();
+    assert_eq!(state.audio_data.lock().unwrap().len(), 8_000);
+}
+
+#[test]
+fn ignores_input_while_held() {
+    let backend = MockBackend::new(1, RATE);
+    let state = start(&backend);
+
+    state.hold_input(true);
+    backend.feed(TONE, secs(0.5)).unwrap();
+    assert!(state.audio_data.lock().unwrap().is_empty());
+
+    state.hold_input(false);
+    backend.feed(TONE, secs(0.5)).unwrap();
+    assert_eq!(state.audio_data.lock().unwrap().len(), 8_000);
+}
+
+#[test]
+fn monitoring_reaches_taps_without_recording() {
+    let backend = MockBackend::new(1, RATE);
+    let state = Arc::new(RecordingState::default());
+    let stream = backend.open_input(None, StreamFormat::default(), RecordingState::monitor(&state)).unwrap();
+    stream.play().unwrap();
+    let tap = state.add_tap();
+
+    backend.feed(TONE, secs(0.5)).unwrap();
+    assert_eq!(tap.try_iter().map(|chunk| chunk.len()).sum::<usize>(), 8_000);
+    assert!(state.audio_data.lock().unwrap().is_empty());
+
+    // Once recording starts the monitor falls silent rather than doubling the audio
+    state.transition(RecorderState::Starting).unwrap();
+    backend.feed(TONE, secs(0.5)).unwrap();
+    assert_eq!(tap.try_iter().count(), 0);
+}
+
+#[test]
+fn pre_roll_keeps_only_the_latest_monitored_audio() {
+    let backend = MockBackend::new(1, RATE);
+    let state = Arc::new(RecordingState::default());
+    state.pre_roll.lock().unwrap().configure(1, 1, RATE);
+    let stream = backend.open_input(None, StreamFormat::default(), RecordingState::monitor(&state)).unwrap();
+    stream.play().unwrap();
+
+    backend.feed(TONE, secs(2.5)).unwrap();
+    let (channels, sample_rate, last) = state.pre_roll.lock().unwrap().last(10.0);
+    assert_eq!((channels, sample_rate, last.len()), (1, RATE, RATE as usize));
+
+    // Audio in another format than the recording's is dropped rather than spliced in
+    assert!(state.pre_roll.lock().unwrap().take(2, RATE).is_none());
+    assert!(state.pre_roll.lock().unwrap().last(10.0).2.is_empty());
+
+    backend.feed(TONE, secs(0.5)).unwrap();
+    let taken = state.pre_roll.lock().unwrap().take(1, RATE).unwrap();
+    assert_eq!(taken.len(), RATE as usize / 2);
+}
+
+#[test]
+fn lead_in_stays_apart_from_the_captured_audio() {
+    let backend = MockBackend::new(1, RATE);
+    let state = start(&backend);
+    backend.feed(TONE, secs(0.5)).unwrap();
+    state.add_marker(None).unwrap();
+    let first_stamp = state.timestamps()[0].frame;
+    *state.lead_in.lock().unwrap() = vec![0; RATE as usize];
+
+    // Checks on the capture see only what arrived after the start
+    assert_eq!(state.audio_data.lock().unwrap().len(), RATE as usize / 2);
+    assert_eq!(state.recorded_samples().len(), RATE as usize * 3 / 2);
+    assert_eq!(state.take_markers()[0].position_ms, 1_500);
+    assert_eq!(state.timestamps()[0].frame, first_stamp + RATE as u64);
+}
+
+#[test]
+fn levels_match_the_signal() {
+    let backend = MockBackend::new(2, RATE);
+    let state = start(&backend);
+    backend.feed(TONE, secs(1.0)).unwrap();
+
+    let buffer = captured(&state);
+    // A sine's RMS is its amplitude over √2
+    assert!((processing::to_db(buffer.peak()) - processing::to_db(0.5)).abs() < 0.1);
+    assert!((buffer.rms() - 0.5 / 2f32.sqrt()).abs() < 0.005);
+}
+
+#[test]
+fn markers_land_at_the_captured_position() {
+    let backend = MockBackend::new(2, RATE);
+    let state = start(&backend);
+
+    backend.feed(ROOM, secs(2.5)).unwrap();
+    let marker = state.add_marker(Some("chorus".to_string())).unwrap();
+    assert_eq!(marker.position_ms, 2_500);
+    assert_eq!(marker.label.as_deref(), Some("chorus"));
+
+    state.transition(RecorderState::Stopping).unwrap();
+    assert!(state.add_marker(None).is_err());
+}
+
+#[test]
+fn capture_format_ignores_later_config_changes() {
+    let backend = MockBackend::new(2, RATE);
+    let state = start(&backend);
+
+    // Asking for another format mid-recording must not change how the take is read
+    *state.channels.lock().unwrap() = 1;
+    *state.sample_rate.lock().unwrap() = 48_000;
+    let format = state.capture_format().unwrap();
+    assert_eq!((format.channels, format.sample_rate), (2, RATE));
+
+    // Nor does a format left over from an earlier session count
+    state.session.fetch_add(1, Ordering::SeqCst);
+    assert!(state.capture_format().is_none());
+}
+
+#[test]
+fn speech_detection_finds_the_tone_bursts() {
+    let backend = MockBackend::new(1, RATE);
+    let state = start(&backend);
+    backend.feed(ROOM, secs(1.0)).unwrap();
+    backend.feed(TONE, secs(1.0)).unwrap();
+    backend.feed(ROOM, secs(2.0)).unwrap();
+    backend.feed(TONE, secs(0.5)).unwrap();
+    backend.feed(ROOM, secs(1.0)).unwrap();
+
+    let segments = speech::detect_speech(&captured(&state));
+    assert_eq!(segments.len(), 2);
+    // Detection works in 30 ms frames, so edges are only that precise
+    for (segment, (start, end)) in segments.iter().zip([(1_000, 2_000), (4_000, 4_500)]) {
+        assert!(segment.start_ms.abs_diff(start) <= 30, "{:?}", segment);
+        assert!(segment.end_ms.abs_diff(end) <= 30, "{:?}", segment);
+    }
+}
+
+#[test]
+fn written_capture_splits_into_its_channels() {
+    let backend = MockBackend::new(2, RATE);
+    let state = start(&backend);
+    backend.feed_channels(&[TONE, Signal::Silence], secs(1.0)).unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("capture.wav");
+    recording::write_capture(&path, 2, RATE, &state.audio_data.lock().unwrap(), None, |_| {}).unwrap();
+
+    let (left, right) = (dir.path().join("left.wav"), dir.path().join("right.wav"));
+    processing::split_channels(&path, &left, &right, |_| Ok(())).unwrap();
+    let left = processing::read_wav(&left).unwrap();
+    let right = processing::read_wav(&right).unwrap();
+    assert_eq!((left.channels, left.samples.len()), (1, RATE as usize));
+    assert_eq!((right.channels, right.samples.len()), (1, RATE as usize));
+    assert!(left.peak() > 0.49);
+    assert_eq!(right.peak(), 0.0);
+}
+
+#[test]
+fn capture_carries_its_start_time_in_bext() {
+    let backend = MockBackend::new(1, RATE);
+    let state = start(&backend);
+    backend.feed(TONE, secs(0.5)).unwrap();
+    state.transition(RecorderState::Paused).unwrap();
+    backend.feed(TONE, secs(0.5)).unwrap();
+    state.transition(RecorderState::Recording).unwrap();
+    backend.feed(TONE, secs(0.5)).unwrap();
+
+    // One timestamp as recording starts and another as it resumes
+    let frames = state.timestamps().iter().map(|t| t.frame).collect::<Vec<_>>();
+    assert_eq!(frames.len(), 2);
+    assert!(frames[0] > 0 && frames[0] <= frames[1] && frames[1] > 8_000, "{:?}", frames);
+
+    let bext = state.bext().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("capture.wav");
+    recording::write_capture(&path, 1, RATE, &state.audio_data.lock().unwrap(), Some(&bext), |_| {}).unwrap();
+
+    assert_eq!(bwf::read_bext(&path).unwrap(), Some(bext));
+    let written = processing::read_wav(&path).unwrap();
+    assert_eq!(written.samples.len(), 16_000);
+}
+
+#[test]
+fn empty_capture_writes_nothing() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("empty.wav");
+    assert!(recording::write_capture(&path, 2, RATE, &[], None, |_| {}).is_err());
+    assert!(!path.exists());
+}
+
+#[test]
+fn dropping_the_stream_stops_delivery() {
+    let backend = MockBackend::new(1, RATE);
+    let state = start(&backend);
+
+    state.input_stream.lock().unwrap().take();
+    assert!(backend.feed(TONE, secs(0.1)).is_err());
+    // The device can be opened again once it has been released
+    assert!(backend.open_input(None, StreamFormat::default(), RecordingState::capture(&state)).is_ok());
+}
+
+#[test]
+fn input_opens_in_the_requested_format_or_not_at_all() {
+    let backend = MockBackend::new(2, RATE);
+    let state = Arc::new(RecordingState::default());
+    let mono = StreamFormat {
+        channels: 1,
+        sample_rate: 0,
+    };
+    assert!(backend.open_input(None, mono, RecordingState::capture(&state)).is_err());
+
+    let stereo = StreamFormat {
+        channels: 2,
+        sample_rate: RATE,
+    };
+    let stream = backend.open_input(None, stereo, RecordingState::capture(&state)).unwrap();
+    assert_eq!((stream.device.channels, stream.device.sample_rate), (2, RATE));
+}
+
+#[test]
+fn output_plays_what_the_callback_fills() {
+    let backend = MockBackend::new(2, RATE);
+    let calls = Arc::new(Mutex::new(0));
+    let counter = Arc::clone(&calls);
+    let stream = backend
+        .open_output(
+            Some(MockBackend::OUTPUT_NAME),
+            Box::new(move |buffer| {
+                *counter.lock().unwrap() += 1;
+                buffer.fill(0.25);
+            }),
+        )
+        .unwrap();
+
+    assert!(backend.drain(100).is_err(), "output isn't playing yet");
+    stream.play().unwrap();
+    let played = backend.drain(1_000).unwrap();
+    assert_eq!(played.len(), 2_000);
+    assert!(played.iter().all(|&s| s == 0.25));
+    assert_eq!(*calls.lock().unwrap(), 2);
+}
+
+#[test]
+fn mock_signals_are_deterministic() {
+    let record = || {
+        let backend = MockBackend::new(1, RATE);
+        let state = start(&backend);
+        backend.feed(Signal::Noise { amplitude: 0.3 }, secs(0.25)).unwrap();
+        let samples = state.audio_data.lock().unwrap().clone();
+        samples
+    };
+    assert_eq!(record(), record());
+}
+
+#[test]
+fn unknown_devices_are_rejected() {
+    let backend = MockBackend::new(1, RATE);
+    let state = Arc::new(RecordingState::default());
+    assert!(backend.open_input(Some("USB Mic"), StreamFormat::default(), RecordingState::capture(&state)).is_err());
+    assert_eq!(backend.input_devices().unwrap()[0].name, MockBackend::INPUT_NAME);
+}
+
+#[test]
+fn written_capture_round_trips_exactly() {
+    let backend = MockBackend::new(2, RATE);
+    let state = start(&backend);
+    backend.feed(Signal::Noise { amplitude: 1.0 }, secs(3.0)).unwrap();
+    let samples = state.audio_data.lock().unwrap().clone();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("capture.wav");
+    let mut reported = Vec::new();
+    recording::write_capture(&path, 2, RATE, &samples, None, |p| reported.push(p)).unwrap();
+    assert_eq!(reported.last(), Some(&1.0));
+
+    let mut reader = hound::WavReader::open(&path).unwrap();
+    let spec = reader.spec();
+    assert_eq!((spec.channels, spec.sample_rate, spec.bits_per_sample), (2, RATE, 16));
+    let read = reader.samples::<i16>().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(read, samples);
+}
+
+#[test]
+fn recorder_only_makes_valid_transitions() {
+    let state = RecordingState::default();
+    assert_eq!(state.recorder_state(), RecorderState::Idle);
+    assert!(state.transition(RecorderState::Recording).is_err());
+    assert!(state.transition(RecorderState::Stopping).is_err());
+
+    assert_eq!(state.transition(RecorderState::Starting), Ok(RecorderState::Idle));
+    assert!(state.transition(RecorderState::Starting).is_err(), "a second start is refused");
+    state.transition(RecorderState::Recording).unwrap();
+    assert!(state.is_recording());
+    state.transition(RecorderState::Paused).unwrap();
+    assert!(state.is_recording());
+    assert!(state.transition(RecorderState::Saving).is_err());
+    state.transition(RecorderState::Stopping).unwrap();
+    assert!(state.transition(RecorderState::Stopping).is_err(), "a second stop is refused");
+    state.transition(RecorderState::Saving).unwrap();
+    assert!(!state.is_recording());
+    state.transition(RecorderState::Idle).unwrap();
+    assert!(state.is_idle());
+}
+
+#[test]
+fn self_test_passes() {
+    let report = self_test::run();
+    assert!(report.passed, "{:?}", report.steps);
+    assert_eq!(report.steps.len(), 5);
+}
@@ -0,0 +1,96 @@
+use rekt_core::dsp::InputFilterConfig;
+use rekt_core::library::LibraryFilter;
+use rekt_core::processing::DynamicsConfig;
+use rekt_core::settings::{DuckConfig, MetronomeConfig, RetentionPolicy, SilenceWarningConfig, VoiceCommandConfig};
+
+#[test]
+fn defaults_pass_their_own_checks() {
+    MetronomeConfig::default().validate().unwrap();
+    SilenceWarningConfig::default().validate().unwrap();
+    DuckConfig::default().validate().unwrap();
+    VoiceCommandConfig::default().validate().unwrap();
+    LibraryFilter::default().validate().unwrap();
+    // What an empty section in the config file loads as
+    serde_json::from_str::<MetronomeConfig>("{}").unwrap().validate().unwrap();
+    serde_json::from_str::<InputFilterConfig>("{}").unwrap().validate().unwrap();
+    serde_json::from_str::<DynamicsConfig>("{}").unwrap().validate().unwrap();
+}
+
+#[test]
+fn metronome_limits() {
+    let at = |bpm, beats_per_bar, volume| MetronomeConfig {
+        enabled: true,
+        bpm,
+        beats_per_bar,
+        volume,
+    };
+    assert!(at(20.0, 1, 0.0).validate().is_ok());
+    assert!(at(400.0, 16, 1.0).validate().is_ok());
+    assert!(at(19.9, 4, 0.5).validate().is_err());
+    assert!(at(120.0, 0, 0.5).validate().is_err());
+    assert!(at(120.0, 17, 0.5).validate().is_err());
+    assert!(at(120.0, 4, 1.1).validate().is_err());
+    assert!(at(f32::NAN, 4, 0.5).validate().is_err());
+}
+
+#[test]
+fn silence_ducking_and_retention_limits() {
+    let silence = |threshold_db, after_secs| SilenceWarningConfig {
+        threshold_db,
+        after_secs,
+        ..Default::default()
+    };
+    assert!(silence(-120.0, 1).validate().is_ok());
+    assert!(silence(-60.0, 0).validate().is_err());
+    assert!(silence(3.0, 30).validate().is_err());
+
+    assert!(DuckConfig { enabled: true, level: 0.0 }.validate().is_ok());
+    assert!(DuckConfig { enabled: true, level: 1.5 }.validate().is_err());
+    assert!(DuckConfig { enabled: true, level: -0.1 }.validate().is_err());
+
+    assert!(RetentionPolicy { max_age_days: 1 }.validate().is_ok());
+    assert!(RetentionPolicy { max_age_days: 0 }.validate().is_err());
+}
+
+#[test]
+fn voice_commands_need_keywords_and_a_recognizer_when_enabled() {
+    let enabled = VoiceCommandConfig {
+        enabled: true,
+        recognizer: "whisper-cli -f {path}".to_string(),
+        ..Default::default()
+    };
+    assert!(enabled.validate().is_ok());
+    assert!(VoiceCommandConfig { recognizer: " ".to_string(), ..enabled.clone() }.validate().is_err());
+    assert!(VoiceCommandConfig { keywords: vec![" ".to_string()], ..enabled.clone() }.validate().is_err());
+    assert!(VoiceCommandConfig { sensitivity: 1.5, ..enabled.clone() }.validate().is_err());
+    assert!(VoiceCommandConfig { timeout_secs: 0, ..enabled }.validate().is_err());
+}
+
+#[test]
+fn processing_settings_limits() {
+    let high_pass = |high_pass_hz| InputFilterConfig {
+        remove_dc: false,
+        high_pass_hz,
+    };
+    assert!(high_pass(None).validate().is_ok());
+    assert!(high_pass(Some(80.0)).validate().is_ok());
+    assert!(high_pass(Some(10.0)).validate().is_err());
+    assert!(high_pass(Some(1_000.0)).validate().is_err());
+
+    let dynamics = serde_json::from_str::<DynamicsConfig>("{}").unwrap();
+    assert!(DynamicsConfig { ratio: 0.5, ..dynamics.clone() }.validate().is_err());
+    assert!(DynamicsConfig { threshold_db: 6.0, ..dynamics.clone() }.validate().is_err());
+    assert!(DynamicsConfig { release_ms: 0.0, ..dynamics.clone() }.validate().is_err());
+    assert!(DynamicsConfig { ceiling_db: 0.5, ..dynamics }.validate().is_err());
+}
+
+#[test]
+fn filter_dates_must_be_calendar_dates() {
+    let from = |date: &str| LibraryFilter {
+        from_date: Some(date.to_string()),
+        ..Default::default()
+    };
+    assert!(from("2025-02-28").validate().is_ok());
+    assert!(from("2025-02-30").validate().is_err());
+    assert!(from("28/02/2025").validate().is_err());
+}
@@ -0,0 +1,33 @@
+use rekt_core::share::{content_disposition, content_type, is_share_request, url_path};
+
+#[test]
+fn links_escape_the_file_name() {
+    assert_eq!(url_path("tok", "take 1 (final).wav"), "/tok/take%201%20%28final%29.wav");
+    assert_eq!(url_path("tok", "naïve.wav"), "/tok/na%C3%AFve.wav");
+}
+
+#[test]
+fn requests_match_however_the_name_is_escaped() {
+    let name = "take 1 (final).wav";
+    assert!(is_share_request(&url_path("tok", name), "tok", name));
+    assert!(is_share_request("/tok/take%201%20(final).wav", "tok", name));
+    assert!(!is_share_request("/other/take%201%20(final).wav", "tok", name));
+    assert!(!is_share_request("/tok/take%201.wav", "tok", name));
+    assert!(!is_share_request("/tok/", "tok", name));
+}
+
+#[test]
+fn content_type_follows_the_extension() {
+    assert_eq!(content_type("a.WAV"), "audio/wav");
+    assert_eq!(content_type("a.opus"), "audio/ogg");
+    assert_eq!(content_type("a.m4a"), "audio/mp4");
+    assert_eq!(content_type("a"), "application/octet-stream");
+}
+
+#[test]
+fn disposition_has_an_ascii_fallback_and_the_exact_name() {
+    assert_eq!(
+        content_disposition("say \"hi\" ünïcode.wav"),
+        "attachment; filename=\"say _hi_ _n_code.wav\"; filename*=UTF-8''say%20%22hi%22%20%C3%BCn%C3%AFcode.wav"
+    );
+}
@@ -0,0 +1,46 @@
+#![cfg(unix)]
+
+use std::path::Path;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use rekt_core::shell::{capture, shell_command, shell_quote, wait_with_timeout};
+
+fn run(command_line: &str) -> (Option<i32>, String) {
+    let mut child = shell_command(command_line).stdout(Stdio::piped()).spawn().unwrap();
+    let stdout = capture(child.stdout.take(), 1024);
+    let code = wait_with_timeout(&mut child, Duration::from_secs(10)).unwrap().unwrap();
+    (code, stdout.finish())
+}
+
+#[test]
+fn quoted_paths_reach_the_command_unchanged() {
+    for path in ["/tmp/plain.wav", "/tmp/it's here.wav", "/tmp/$HOME `id` \"x\";rm.wav", "/tmp/'"] {
+        let command_line = format!("printf %s {}", shell_quote(Path::new(path)));
+        assert_eq!(run(&command_line), (Some(0), path.to_string()));
+    }
+}
+
+#[test]
+fn exit_codes_are_reported() {
+    assert_eq!(run("exit 3").0, Some(3));
+}
+
+#[test]
+fn output_is_cut_at_the_limit() {
+    let mut child = shell_command("yes | head -c 100000").stdout(Stdio::piped()).spawn().unwrap();
+    let stdout = capture(child.stdout.take(), 10);
+    wait_with_timeout(&mut child, Duration::from_secs(10)).unwrap().unwrap();
+    assert_eq!(stdout.finish(), "y\ny\ny\ny\ny\n");
+}
+
+#[test]
+fn timeouts_kill_the_whole_command() {
+    let started = Instant::now();
+    let mut child = shell_command("sleep 30 & sleep 30").stdout(Stdio::piped()).spawn().unwrap();
+    let stdout = capture(child.stdout.take(), 1024);
+    assert_eq!(wait_with_timeout(&mut child, Duration::from_millis(200)).unwrap(), None);
+    // The background sleep holds stdout too; it closes only if the group was killed
+    assert_eq!(stdout.finish(), "");
+    assert!(started.elapsed() < Duration::from_secs(2));
+}
@@ -0,0 +1,94 @@
+use std::time::{Duration, Instant};
+
+use rekt_core::telemetry::{CallbackTimer, TelemetryWarningKind};
+
+const RATE: u32 = 48_000;
+const BUFFER: usize = 480;
+const PERIOD: Duration = Duration::from_millis(10);
+
+// Stereo callbacks of `BUFFER` frames, one per `PERIOD`, except the skipped ones
+fn run(callbacks: u32, skipped: &[u32], busy: Duration) -> CallbackTimer {
+    let mut timer = CallbackTimer::default();
+    timer.begin(2, RATE);
+    let start = Instant::now();
+    for n in 0..callbacks {
+        if !skipped.contains(&n) {
+            timer.record(start + PERIOD * n, BUFFER * 2, busy);
+        }
+    }
+    timer
+}
+
+#[test]
+fn steady_input_is_healthy() {
+    let mut timer = run(1_000, &[], Duration::from_millis(1));
+    let stats = timer.snapshot();
+    assert_eq!(stats.callbacks, 1_000);
+    assert_eq!(stats.frames, 480_000);
+    assert_eq!(stats.max_buffer_frames, BUFFER);
+    assert_eq!(stats.dropped_frames, 0);
+    assert!((stats.max_gap_ms - 10.0).abs() < 0.01);
+    assert!((stats.mean_gap_ms - 10.0).abs() < 0.01);
+    assert!((stats.peak_load - 0.1).abs() < 0.001);
+    assert!(timer.new_warnings().is_empty());
+}
+
+#[test]
+fn missing_buffers_count_as_dropped_and_warn_once() {
+    let skipped = (500..520).collect::<Vec<_>>();
+    let mut timer = run(1_000, &skipped, Duration::from_millis(1));
+    let stats = timer.snapshot();
+    // 20 buffers went missing; two are forgiven as jitter, plus 0.1% for drift
+    assert!((8_000..=BUFFER as u64 * 20).contains(&stats.dropped_frames), "{}", stats.dropped_frames);
+    assert!((stats.max_gap_ms - 210.0).abs() < 0.01);
+
+    let kinds = timer.new_warnings().into_iter().map(|w| w.kind).collect::<Vec<_>>();
+    assert_eq!(kinds, [TelemetryWarningKind::DroppedFrames, TelemetryWarningKind::LongGap]);
+    assert!(timer.new_warnings().is_empty());
+}
+
+#[test]
+fn slow_callbacks_warn_about_load() {
+    let mut timer = run(10, &[], Duration::from_millis(9));
+    let kinds = timer.new_warnings().into_iter().map(|w| w.kind).collect::<Vec<_>>();
+    assert_eq!(kinds, [TelemetryWarningKind::HighLoad]);
+}
+
+#[test]
+fn pauses_are_not_dropouts() {
+    let mut timer = CallbackTimer::default();
+    timer.begin(2, RATE);
+    let start = Instant::now();
+    for n in 0..100 {
+        timer.record(start + PERIOD * n, BUFFER * 2, Duration::ZERO);
+    }
+    timer.pause();
+    for n in 300..400 {
+        timer.record(start + PERIOD * n, BUFFER * 2, Duration::ZERO);
+    }
+
+    let stats = timer.snapshot();
+    assert_eq!(stats.dropped_frames, 0);
+    assert!((stats.max_gap_ms - 10.0).abs() < 0.01);
+    assert!(timer.new_warnings().is_empty());
+}
+
+#[test]
+fn clock_drift_is_measured_across_pauses() {
+    // A device clock running 100 ppm fast delivers each buffer a little early
+    let period = Duration::from_nanos(9_999_000);
+    let mut timer = CallbackTimer::default();
+    timer.begin(2, RATE);
+    let start = Instant::now();
+    for n in 0..4_000 {
+        timer.record(start + period * n, BUFFER * 2, Duration::ZERO);
+    }
+    assert_eq!(timer.snapshot().clock_drift_ppm, None);
+    timer.pause();
+    for n in 5_000..8_000 {
+        timer.record(start + period * n, BUFFER * 2, Duration::ZERO);
+    }
+
+    let drift = timer.snapshot().clock_drift_ppm.unwrap();
+    assert!((drift - 100.0).abs() < 1.0, "{}", drift);
+}
@@ -0,0 +1,79 @@
+use rekt_core::transcript::{Transcript, TranscriptFormat, TranscriptSegment};
+
+fn segment(start_ms: u64, end_ms: u64, text: &str) -> TranscriptSegment {
+    TranscriptSegment {
+        start_ms,
+        end_ms,
+        text: text.to_string(),
+        words: Vec::new(),
+    }
+}
+
+fn transcript() -> Transcript {
+    Transcript {
+        language: Some("en".to_string()),
+        segments: vec![
+            segment(0, 2_500, "Hello there."),
+            segment(2_500, 2_600, "   "),
+            segment(3_723_004, 3_725_000, "Second\nline --> here"),
+        ],
+    }
+}
+
+#[test]
+fn srt_numbers_cues_and_keeps_each_on_one_line() {
+    let srt = transcript().render(TranscriptFormat::Srt).unwrap();
+    assert_eq!(
+        srt,
+        "1\n00:00:00,000 --> 00:00:02,500\nHello there.\n\n\
+         2\n01:02:03,004 --> 01:02:05,000\nSecond line -> here\n\n"
+    );
+}
+
+#[test]
+fn vtt_has_a_header_and_dotted_times() {
+    let vtt = transcript().render(TranscriptFormat::Vtt).unwrap();
+    assert!(vtt.starts_with("WEBVTT\n\n00:00:00.000 --> 00:00:02.500\nHello there.\n\n"));
+    assert!(vtt.contains("01:02:03.004 --> 01:02:05.000\n"));
+}
+
+#[test]
+fn rendered_cues_read_back_the_same() {
+    for format in [TranscriptFormat::Srt, TranscriptFormat::Vtt] {
+        let parsed = Transcript::parse_cues(&transcript().render(format).unwrap());
+        let timings = parsed.segments.iter().map(|s| (s.start_ms, s.end_ms)).collect::<Vec<_>>();
+        assert_eq!(timings, [(0, 2_500), (3_723_004, 3_725_000)]);
+        assert_eq!(parsed.segments[1].text, "Second line -> here");
+    }
+}
+
+#[test]
+fn whisper_console_lines_and_cue_settings_parse() {
+    let parsed = Transcript::parse_cues(
+        "[00:00:01.000 --> 00:00:02.500]  first\n\
+         \n\
+         junk without timing\n\
+         00:05.25 --> 00:06.000 align:start\nsecond\n",
+    );
+    let segments = parsed
+        .segments
+        .iter()
+        .map(|s| (s.start_ms, s.end_ms, s.text.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(segments, [(1_000, 2_500, "first"), (5_250, 6_000, "second")]);
+}
+
+#[test]
+fn out_of_order_segments_are_invalid() {
+    assert!(transcript().validate().is_ok());
+    let backwards = Transcript {
+        language: None,
+        segments: vec![segment(5_000, 6_000, "b"), segment(1_000, 2_000, "a")],
+    };
+    assert!(backwards.validate().is_err());
+    let inverted = Transcript {
+        language: None,
+        segments: vec![segment(2_000, 1_000, "a")],
+    };
+    assert!(inverted.validate().is_err());
+}
@@ -0,0 +1,146 @@
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::RecorderState;
+
+//
+// ====== Screen reader announcements ======
+//
+
+#[cfg(target_os = "windows")]
+const MAIN_WINDOW: &str = "main";
+
+#[derive(Debug, Clone, Serialize)]
+struct AnnouncementEvent {
+    text: String,
+}
+
+/// What the recorder just did, in words, if the change is worth telling the user about
+pub fn describe(previous: RecorderState, next: RecorderState) -> Option<&'static str> {
+    match (previous, next) {
+        (RecorderState::Starting, RecorderState::Recording) => Some("Recording started"),
+        (RecorderState::Recording, RecorderState::Paused) => Some("Recording paused"),
+        (RecorderState::Paused, RecorderState::Recording) => Some("Recording resumed"),
+        (_, RecorderState::Stopping) => Some("Recording stopped"),
+        _ => None,
+    }
+}
+
+// A duration the way it is said: "1 hour 2 minutes", "5 seconds"
+fn spoken_duration(duration_ms: u64) -> String {
+    let total = (duration_ms + 500) / 1000;
+    let units = [(total / 3600, "hour"), (total / 60 % 60, "minute"), (total % 60, "second")];
+    let parts = units
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, unit)| format!("{} {}{}", count, unit, if *count == 1 { "" } else { "s" }))
+        .collect::<Vec<_>>();
+    if parts.is_empty() {
+        "0 seconds".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// Have screen readers read `text` out without moving focus. It also goes to the webview
+/// as `accessibility-announcement`, for an ARIA live region: that is how it reaches
+/// screen readers where there is no native announcement API.
+pub fn announce(app_handle: &AppHandle, text: String) {
+    #[cfg(any(target_os = "macos", target_os = "windows", target_os = "ios"))]
+    {
+        let handle = app_handle.clone();
+        let native = text.clone();
+        // The accessibility APIs all expect the UI thread
+        if let Err(e) = app_handle.run_on_main_thread(move || post(&handle, &native)) {
+            tracing::debug!("Failed to post screen reader announcement: {}", e);
+        }
+    }
+    let _ = app_handle.emit("accessibility-announcement", AnnouncementEvent { text });
+}
+
+/// Announce a recorder state change
+pub fn state_changed(app_handle: &AppHandle, previous: RecorderState, next: RecorderState) {
+    if let Some(text) = describe(previous, next) {
+        announce(app_handle, text.to_string());
+    }
+}
+
+/// Announce that a recording was saved, and how long it is
+pub fn saved(app_handle: &AppHandle, duration_ms: u64) {
+    announce(app_handle, format!("Recording saved, {}", spoken_duration(duration_ms)));
+}
+
+#[cfg(target_os = "macos")]
+fn post(_app_handle: &AppHandle, text: &str) {
+    use objc2::runtime::AnyObject;
+    use objc2::MainThreadMarker;
+    use objc2_app_kit::{
+        NSAccessibilityAnnouncementKey, NSAccessibilityAnnouncementRequestedNotification,
+        NSAccessibilityPostNotificationWithUserInfo, NSAccessibilityPriorityKey, NSAccessibilityPriorityLevel, NSApp,
+    };
+    use objc2_foundation::{NSDictionary, NSNumber, NSString};
+
+    let Some(mtm) = MainThreadMarker::new() else {
+        return;
+    };
+    let text = NSString::from_str(text);
+    // High priority interrupts whatever VoiceOver is reading, as a state change should
+    let priority = NSNumber::new_isize(NSAccessibilityPriorityLevel::High.0);
+    let values: [&AnyObject; 2] = [&text, &priority];
+    // SAFETY: AppKit's own constants, posted on the main thread for the application element
+    unsafe {
+        let info = NSDictionary::from_slices(&[NSAccessibilityAnnouncementKey, NSAccessibilityPriorityKey], &values);
+        NSAccessibilityPostNotificationWithUserInfo(
+            &NSApp(mtm),
+            NSAccessibilityAnnouncementRequestedNotification,
+            Some(&info),
+        );
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn post(app_handle: &AppHandle, text: &str) {
+    use tauri::Manager;
+    use windows::core::BSTR;
+    use windows::Win32::UI::Accessibility::{
+        NotificationKind_ActionCompleted, NotificationProcessing_ImportantMostRecent, UiaHostProviderFromHwnd,
+        UiaRaiseNotificationEvent,
+    };
+
+    let Some(hwnd) = app_handle.get_webview_window(MAIN_WINDOW).and_then(|window| window.hwnd().ok()) else {
+        return;
+    };
+    // SAFETY: `hwnd` is the live main window, owned by this thread
+    let result = unsafe {
+        UiaHostProviderFromHwnd(hwnd).and_then(|provider| {
+            // Announcements with the same activity id replace each other rather than queueing
+            UiaRaiseNotificationEvent(
+                &provider,
+                NotificationKind_ActionCompleted,
+                NotificationProcessing_ImportantMostRecent,
+                &BSTR::from(text),
+                &BSTR::from("rekt-recorder"),
+            )
+        })
+    };
+    if let Err(e) = result {
+        tracing::debug!("Failed to post screen reader announcement: {}", e);
+    }
+}
+
+#[cfg(target_os = "ios")]
+fn post(_app_handle: &AppHandle, text: &str) {
+    use objc2::runtime::AnyObject;
+    use objc2_foundation::NSString;
+
+    #[link(name = "UIKit", kind = "framework")]
+    extern "C" {
+        static UIAccessibilityAnnouncementNotification: u32;
+        fn UIAccessibilityPostNotification(notification: u32, argument: *const AnyObject);
+    }
+
+    let text = NSString::from_str(text);
+    let argument: &AnyObject = &text;
+    // SAFETY: UIKit's own constant, posted on the main thread with the string it expects
+    unsafe { UIAccessibilityPostNotification(UIAccessibilityAnnouncementNotification, argument) };
+}
@@ -0,0 +1,190 @@
+use std::cell::Cell;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use tracing::warn;
+
+use crate::lock::AppLock;
+use crate::RecorderState;
+
+//
+// ====== Audit log of recorder actions ======
+//
+
+const FILE_NAME: &str = "audit.jsonl";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Start,
+    Pause,
+    Resume,
+    Stop,
+    Delete,
+    Export,
+}
+
+/// Who or what asked for an action
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Initiator {
+    /// Any window of the app, including the mini recorder and the recording indicator
+    Ui,
+    /// A global hotkey
+    Hotkey,
+    /// An external controller over OSC or MIDI
+    Api,
+    /// The app by itself: calendar events, meeting detection, sleep, screen lock,
+    /// time limits, retention
+    #[default]
+    Automatic,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// RFC 3339, local time
+    pub timestamp: String,
+    pub action: AuditAction,
+    pub initiator: Initiator,
+    pub path: Option<String>,
+    /// Where an export went, or why an action failed
+    pub detail: Option<String>,
+}
+
+thread_local! {
+    static INITIATOR: Cell<Initiator> = const { Cell::new(Initiator::Automatic) };
+}
+
+/// Run `f` with recorder actions on this thread put down to `initiator`. The recorder
+/// state machine logs its own transitions, so this is how it learns who asked.
+pub fn initiated_by<T>(initiator: Initiator, f: impl FnOnce() -> T) -> T {
+    let previous = INITIATOR.with(|current| current.replace(initiator));
+    let result = f();
+    INITIATOR.with(|current| current.set(previous));
+    result
+}
+
+/// Append-only log of recordings started, paused, stopped, deleted and exported, as
+/// JSON lines in the app data directory. Entries are never rewritten or removed, short
+/// of the user wiping all of their data.
+pub struct AuditLog {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl AuditLog {
+    pub fn open(app_dir: &Path) -> Result<Self, String> {
+        let path = app_dir.join(FILE_NAME);
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open audit log: {}", e))?;
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Drop every entry
+    pub fn clear(&self) -> Result<(), String> {
+        let file = self.file.lock().unwrap();
+        file.set_len(0)
+            .and_then(|_| file.sync_data())
+            .map_err(|e| format!("Failed to clear audit log: {}", e))
+    }
+
+    fn append(&self, entry: &AuditEntry) -> Result<(), String> {
+        let mut line = serde_json::to_string(entry).map_err(|e| format!("Failed to serialize audit entry: {}", e))?;
+        line.push('\n');
+        // One write per line, so a crash can't interleave or split entries
+        let mut file = self.file.lock().unwrap();
+        file.write_all(line.as_bytes())
+            .and_then(|_| file.sync_data())
+            .map_err(|e| format!("Failed to write audit log: {}", e))
+    }
+
+    /// Every entry, oldest first
+    pub fn entries(&self) -> Result<Vec<AuditEntry>, String> {
+        let raw = fs::read_to_string(&self.path).map_err(|e| format!("Failed to read audit log: {}", e))?;
+        Ok(raw
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    warn!("Skipping unreadable audit log line: {}", e);
+                    None
+                }
+            })
+            .collect())
+    }
+}
+
+/// Log `action` as asked for by `initiator`. A failure to log is reported but doesn't
+/// undo or block the action.
+pub fn record_as(
+    app_handle: &AppHandle,
+    initiator: Initiator,
+    action: AuditAction,
+    path: Option<&Path>,
+    detail: Option<String>,
+) {
+    let entry = AuditEntry {
+        timestamp: chrono::Local::now().to_rfc3339(),
+        action,
+        initiator,
+        path: path.map(|p| p.to_string_lossy().to_string()),
+        detail,
+    };
+    if let Err(e) = app_handle.state::<AuditLog>().append(&entry) {
+        warn!("{}", e);
+    }
+}
+
+/// Log `action` as asked for by whoever is acting on this thread; see `initiated_by`
+pub fn record(app_handle: &AppHandle, action: AuditAction, path: Option<&Path>, detail: Option<String>) {
+    record_as(app_handle, INITIATOR.with(Cell::get), action, path, detail);
+}
+
+/// Log an export from the UI, one entry per recording
+pub fn exported(app_handle: &AppHandle, paths: &[String], dest: &str) {
+    for path in paths {
+        record_as(app_handle, Initiator::Ui, AuditAction::Export, Some(Path::new(path)), Some(dest.to_string()));
+    }
+}
+
+/// Log the recorder starting, pausing and resuming. Stopping is logged once the file is
+/// saved, with its path.
+pub fn state_changed(app_handle: &AppHandle, previous: RecorderState, next: RecorderState) {
+    let action = match (previous, next) {
+        (RecorderState::Starting, RecorderState::Recording) => AuditAction::Start,
+        (RecorderState::Recording, RecorderState::Paused) => AuditAction::Pause,
+        (RecorderState::Paused, RecorderState::Recording) => AuditAction::Resume,
+        _ => return,
+    };
+    record(app_handle, action, None, None);
+}
+
+// The audit log, oldest first; with `limit`, only the latest that many entries
+#[tauri::command]
+pub fn get_audit_log(
+    audit: State<'_, AuditLog>,
+    app_lock: State<'_, AppLock>,
+    limit: Option<usize>,
+) -> Result<Vec<AuditEntry>, String> {
+    app_lock.ensure_unlocked()?;
+    let mut entries = audit.entries()?;
+    if let Some(limit) = limit {
+        entries.drain(..entries.len().saturating_sub(limit));
+    }
+    Ok(entries)
+}
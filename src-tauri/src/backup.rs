@@ -0,0 +1,413 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::audit::{self, AuditAction, Initiator};
+use crate::config::{AppConfig, ConfigState};
+use crate::crypto::{self, EncryptionState};
+use crate::library::{self, Library, RecordingEntry};
+use crate::lock::AppLock;
+
+//
+// ====== Library backup and restore ======
+//
+
+const BACKUP_VERSION: u32 = 1;
+const MANIFEST_FILE: &str = "manifest.json";
+const INDEX_FILE: &str = "library.json";
+const CONFIG_FILE: &str = "audio_config.json";
+const RECORDINGS_DIR: &str = "recordings/";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BackupFile {
+    name: String,
+    /// Library path of the recording, which tells apart recordings with the same file
+    /// name in different directories; older backups matched on the file name alone
+    #[serde(default)]
+    source_path: Option<String>,
+    size_bytes: u64,
+    sha256: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BackupManifest {
+    version: u32,
+    created_at: String,
+    /// Set for incremental backups: only recordings changed after this time are included
+    since: Option<String>,
+    recordings: Vec<BackupFile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BackupIndex {
+    recordings: Vec<RecordingEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BackupSummary {
+    path: String,
+    recordings: usize,
+    size_bytes: u64,
+    incremental: bool,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct RestoreSummary {
+    restored: Vec<String>,
+    /// Recordings already in the library with identical content
+    skipped: Vec<String>,
+    profiles_added: usize,
+}
+
+fn backup(library: &Library, config: &ConfigState, dest: &Path, incremental: bool) -> Result<BackupSummary, String> {
+    let app_config = config.get();
+    let since = if incremental {
+        app_config.last_backup_at.clone()
+    } else {
+        None
+    };
+    let since_time = since
+        .as_deref()
+        .map(chrono::DateTime::parse_from_rfc3339)
+        .transpose()
+        .map_err(|e| format!("Stored backup time is invalid: {}", e))?;
+    let started_at = chrono::Local::now();
+
+    let entries = library.entries();
+    let file = File::create(dest).map_err(|e| format!("Failed to create backup file: {}", e))?;
+    let mut zip = ZipWriter::new(file);
+    // WAV barely compresses, so it's stored as-is to keep backups fast
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut manifest = BackupManifest {
+        version: BACKUP_VERSION,
+        created_at: started_at.to_rfc3339(),
+        since: since.clone(),
+        recordings: Vec::new(),
+    };
+
+    for entry in &entries {
+        let path = Path::new(&entry.path);
+        if let Some(since) = since_time {
+            let modified = fs::metadata(path)
+                .and_then(|m| m.modified())
+                .map(chrono::DateTime::<chrono::Local>::from);
+            if matches!(modified, Ok(modified) if modified <= since) {
+                continue;
+            }
+        }
+
+        // Recordings moved to a save directory can share a file name with one in the library
+        let stem_end = entry.file_name.find('.').unwrap_or(entry.file_name.len());
+        let (stem, extensions) = entry.file_name.split_at(stem_end);
+        let mut name = format!("{}{}", RECORDINGS_DIR, entry.file_name);
+        let mut counter = 1;
+        while manifest.recordings.iter().any(|f| f.name.eq_ignore_ascii_case(&name)) {
+            name = format!("{}{}_{}{}", RECORDINGS_DIR, stem, counter, extensions);
+            counter += 1;
+        }
+        let mut source = File::open(path).map_err(|e| format!("Failed to open {}: {}", entry.path, e))?;
+        zip.start_file(name.as_str(), stored)
+            .map_err(|e| format!("Failed to write backup: {}", e))?;
+        let (size_bytes, sha256) = copy_hashed(&mut source, &mut zip)
+            .map_err(|e| format!("Failed to back up {}: {}", entry.path, e))?;
+        manifest.recordings.push(BackupFile {
+            name,
+            source_path: Some(entry.path.clone()),
+            size_bytes,
+            sha256,
+        });
+    }
+
+    // Metadata and settings are always complete so any single archive can be restored
+    let index = serde_json::to_vec_pretty(&serde_json::json!({ "recordings": entries }))
+        .map_err(|e| format!("Failed to serialize library index: {}", e))?;
+    let config_json = serde_json::to_vec_pretty(&app_config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    let manifest_json =
+        serde_json::to_vec_pretty(&manifest).map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    for (name, bytes) in [(INDEX_FILE, index), (CONFIG_FILE, config_json), (MANIFEST_FILE, manifest_json)] {
+        zip.start_file(name, deflated)
+            .and_then(|_| zip.write_all(&bytes).map_err(Into::into))
+            .map_err(|e| format!("Failed to write backup: {}", e))?;
+    }
+    zip.finish().map_err(|e| format!("Failed to finalize backup: {}", e))?;
+
+    config.update(|c| {
+        c.last_backup_at = Some(started_at.to_rfc3339());
+        Ok(())
+    })?;
+
+    Ok(BackupSummary {
+        path: dest.to_string_lossy().to_string(),
+        recordings: manifest.recordings.len(),
+        size_bytes: manifest.recordings.iter().map(|f| f.size_bytes).sum(),
+        incremental: since.is_some(),
+    })
+}
+
+fn copy_hashed(reader: &mut impl Read, writer: &mut impl Write) -> io::Result<(u64, String)> {
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut total = 0u64;
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        writer.write_all(&buffer[..read])?;
+        total += read as u64;
+    }
+    Ok((total, hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()))
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(archive: &mut ZipArchive<File>, name: &str) -> Result<T, String> {
+    let mut file = archive
+        .by_name(name)
+        .map_err(|e| format!("Backup is missing {}: {}", name, e))?;
+    let mut raw = String::new();
+    file.read_to_string(&mut raw)
+        .map_err(|e| format!("Failed to read {} from backup: {}", name, e))?;
+    serde_json::from_str(&raw).map_err(|e| format!("Backup {} is invalid: {}", name, e))
+}
+
+// Check the manifest and every recording's checksum before anything is written
+fn validate(archive: &mut ZipArchive<File>) -> Result<BackupManifest, String> {
+    let manifest: BackupManifest = read_json(archive, MANIFEST_FILE)?;
+    if manifest.version > BACKUP_VERSION {
+        return Err(format!(
+            "Backup was made by a newer version of the app (format {})",
+            manifest.version
+        ));
+    }
+
+    for expected in &manifest.recordings {
+        let file_name = expected
+            .name
+            .strip_prefix(RECORDINGS_DIR)
+            .filter(|n| !n.is_empty() && Path::new(n).file_name() == Some(n.as_ref()))
+            .ok_or_else(|| format!("Backup contains an unexpected file name: {}", expected.name))?;
+        let mut file = archive
+            .by_name(&expected.name)
+            .map_err(|e| format!("Backup is missing {}: {}", file_name, e))?;
+        let (size_bytes, sha256) =
+            copy_hashed(&mut file, &mut io::sink()).map_err(|e| format!("Failed to read {}: {}", file_name, e))?;
+        if size_bytes != expected.size_bytes || sha256 != expected.sha256 {
+            return Err(format!("Backup copy of {} is corrupt", file_name));
+        }
+    }
+
+    Ok(manifest)
+}
+
+fn restore(
+    library: &Library,
+    config: &ConfigState,
+    encryption: &EncryptionState,
+    src: &Path,
+) -> Result<RestoreSummary, String> {
+    let file = File::open(src).map_err(|e| format!("Failed to open backup: {}", e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("Backup is not a valid zip file: {}", e))?;
+    let manifest = validate(&mut archive)?;
+    let index: BackupIndex = read_json(&mut archive, INDEX_FILE)?;
+    let backup_config: AppConfig = read_json(&mut archive, CONFIG_FILE)?;
+
+    // Pair every file with its metadata and settle folders before anything is written, so
+    // a problem can't leave the restore half done
+    let mut planned = Vec::new();
+    for backed_up in &manifest.recordings {
+        let file_name = &backed_up.name[RECORDINGS_DIR.len()..];
+        let entry = index.recordings.iter().find(|e| match &backed_up.source_path {
+            Some(source_path) => &e.path == source_path,
+            None => e.file_name == file_name,
+        });
+        match entry {
+            Some(entry) => planned.push((backed_up, file_name, entry.clone())),
+            None => warn!("Backup has no metadata for {}, skipping", file_name),
+        }
+    }
+    let folders = plan_folders(&library.folders(), planned.iter().filter_map(|(_, _, e)| e.folder.as_deref()));
+
+    let mut summary = RestoreSummary::default();
+    for (backed_up, file_name, mut entry) in planned {
+        let mut dest = library.dir().join(file_name);
+        if dest.exists() {
+            if library::hash_file(&dest)? == backed_up.sha256 {
+                summary.skipped.push(dest.to_string_lossy().to_string());
+                continue;
+            }
+            // A different recording took this name since the backup was made
+            dest = free_name(library.dir(), file_name);
+        }
+
+        let mut source = archive
+            .by_name(&backed_up.name)
+            .map_err(|e| format!("Failed to read {} from backup: {}", file_name, e))?;
+        let mut out = File::create(&dest).map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?;
+        if let Err(e) = io::copy(&mut source, &mut out) {
+            let _ = fs::remove_file(&dest);
+            return Err(format!("Failed to restore {}: {}", file_name, e));
+        }
+
+        entry.path = dest.to_string_lossy().to_string();
+        entry.file_name = dest
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        entry.encrypted = crypto::is_encrypted(&dest);
+        entry.restored_at = Some(chrono::Local::now().to_rfc3339());
+        if let Err(e) = crypto::seal_new_recording(config, encryption, &mut entry) {
+            let _ = fs::remove_file(&dest);
+            return Err(format!("Failed to restore {}: {}", file_name, e));
+        }
+        // Per-machine state that doesn't carry over
+        entry.sync_status = None;
+        entry.spectrogram = None;
+        entry.folder = entry.folder.and_then(|folder| folders.get(&folder).cloned());
+        if let Some(ref folder) = entry.folder {
+            if !library.folders().contains(folder) {
+                library.create_folder(folder)?;
+            }
+        }
+        library.add(entry.clone())?;
+        summary.restored.push(entry.path);
+    }
+
+    // Settings are merged: anything the user already configured here wins
+    config.update(|c| {
+        for profile in backup_config.profiles {
+            if !c.profiles.iter().any(|p| p.name == profile.name) {
+                c.profiles.push(profile);
+                summary.profiles_added += 1;
+            }
+        }
+        for filter in backup_config.saved_filters {
+            if !c.saved_filters.iter().any(|f| f.name == filter.name) {
+                c.saved_filters.push(filter);
+            }
+        }
+        if c.active_profile.is_none() {
+            c.active_profile = backup_config.active_profile;
+        }
+        Ok(())
+    })?;
+
+    Ok(summary)
+}
+
+// The library folder each backed-up folder name files into. Folder names are unique
+// ignoring case, so one differing from an existing folder only in case joins it.
+fn plan_folders<'a>(existing: &[String], wanted: impl Iterator<Item = &'a str>) -> HashMap<String, String> {
+    let mut folders = existing.to_vec();
+    let mut plan = HashMap::new();
+    for name in wanted {
+        if plan.contains_key(name) {
+            continue;
+        }
+        let target = match folders.iter().find(|f| f.eq_ignore_ascii_case(name)) {
+            Some(folder) => folder.clone(),
+            None => {
+                folders.push(name.to_string());
+                name.to_string()
+            }
+        };
+        plan.insert(name.to_string(), target);
+    }
+    plan
+}
+
+fn free_name(dir: &Path, file_name: &str) -> PathBuf {
+    let path = Path::new(file_name);
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let extension = path.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default();
+
+    let mut counter = 1;
+    loop {
+        let candidate = dir.join(format!("{}_restored_{}.{}", stem, counter, extension));
+        if !candidate.exists() {
+            return candidate;
+        }
+        counter += 1;
+    }
+}
+
+//
+// ====== Backup commands ======
+//
+
+// Write recordings, library metadata and settings to a zip. An incremental backup
+// only includes recordings changed since the last successful backup.
+#[tauri::command]
+pub async fn backup_library(
+    app_handle: AppHandle,
+    app_lock: State<'_, AppLock>,
+    dest_path: String,
+    incremental: Option<bool>,
+) -> Result<BackupSummary, String> {
+    app_lock.ensure_unlocked()?;
+
+    let dest = PathBuf::from(dest_path);
+    let audit_handle = app_handle.clone();
+    let summary = tauri::async_runtime::spawn_blocking(move || {
+        let result = backup(
+            &app_handle.state::<Library>(),
+            &app_handle.state::<ConfigState>(),
+            &dest,
+            incremental.unwrap_or(false),
+        );
+        if result.is_err() {
+            let _ = fs::remove_file(&dest);
+        }
+        result
+    })
+    .await
+    .map_err(|e| format!("Backup failed: {}", e))??;
+
+    info!("Backed up {} recordings to {}", summary.recordings, summary.path);
+    audit::record_as(
+        &audit_handle,
+        Initiator::Ui,
+        AuditAction::Export,
+        None,
+        Some(format!("Backup of {} recordings to {}", summary.recordings, summary.path)),
+    );
+    Ok(summary)
+}
+
+// Validate a backup and merge it into the current library
+#[tauri::command]
+pub async fn restore_library(
+    app_handle: AppHandle,
+    app_lock: State<'_, AppLock>,
+    src_path: String,
+) -> Result<RestoreSummary, String> {
+    app_lock.ensure_unlocked()?;
+
+    let summary = tauri::async_runtime::spawn_blocking(move || {
+        restore(
+            &app_handle.state::<Library>(),
+            &app_handle.state::<ConfigState>(),
+            &app_handle.state::<EncryptionState>(),
+            Path::new(&src_path),
+        )
+    })
+    .await
+    .map_err(|e| format!("Restore failed: {}", e))??;
+
+    info!(
+        "Restored {} recordings, {} already present",
+        summary.restored.len(),
+        summary.skipped.len()
+    );
+    Ok(summary)
+}
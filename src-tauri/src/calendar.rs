@@ -0,0 +1,298 @@
+use std::collections::HashSet;
+use std::fs;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use base64::prelude::*;
+use rekt_core::calendar::{Calendar, CalendarEvent};
+use rekt_core::library::RecordingEntry;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tracing::{info, warn};
+
+use crate::config::ConfigState;
+use crate::countdown;
+use crate::lock::AppLock;
+use crate::notifications;
+use crate::secrets;
+use crate::RecordingState;
+
+//
+// ====== Calendar-aware recording ======
+//
+
+const POLL_INTERVAL: Duration = Duration::from_secs(20);
+// How often the calendar itself is read again
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+// A meeting that began longer ago than this when it's first noticed, e.g. because the
+// app was started late, is not acted on
+const START_WINDOW_SECS: i64 = 120;
+// How far ahead `list_meetings` looks
+const UPCOMING_HOURS: i64 = 24;
+// Tags longer than this are refused by the library
+const MAX_TITLE_TAG_LEN: usize = 64;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MeetingAction {
+    /// Notify and emit `meeting-started`; the frontend calls `record_meeting` if wanted
+    #[default]
+    Offer,
+    /// Start recording straight away, if nothing else is being recorded
+    Record,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarConfig {
+    /// Path to an `.ics` file, or an http(s) URL serving one, such as a CalDAV
+    /// calendar's export link
+    pub source: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Only accepted as input; it is moved to the OS keychain and never written to config
+    #[serde(default, skip_serializing)]
+    pub password: String,
+    #[serde(default)]
+    pub action: MeetingAction,
+    /// Stop recordings started for a meeting when the meeting is scheduled to end
+    #[serde(default)]
+    pub stop_at_end: bool,
+}
+
+/// A meeting as the frontend sees it
+#[derive(Debug, Clone, Serialize)]
+pub struct Meeting {
+    uid: String,
+    title: String,
+    start: String,
+    end: String,
+}
+
+impl From<&CalendarEvent> for Meeting {
+    fn from(event: &CalendarEvent) -> Self {
+        Self {
+            uid: event.uid.clone(),
+            title: event.title.clone(),
+            start: event.start.to_rfc3339(),
+            end: event.end.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Fetched {
+    source: String,
+    at: Option<Instant>,
+    calendar: Calendar,
+}
+
+/// The calendar as last read, and which meetings have been acted on
+#[derive(Default)]
+pub struct CalendarState {
+    fetched: Mutex<Fetched>,
+    // Meeting occurrences already offered or recorded, by UID and start
+    handled: Mutex<HashSet<(String, i64)>>,
+    // Recording session started for a meeting, with the title to tag it with
+    recording: Mutex<Option<(u64, String)>>,
+}
+
+impl CalendarState {
+    /// Drop the cached calendar and any meeting being tracked, e.g. after all data is wiped
+    pub fn forget(&self) {
+        *self.fetched.lock().unwrap() = Fetched::default();
+        self.handled.lock().unwrap().clear();
+        *self.recording.lock().unwrap() = None;
+    }
+}
+
+fn read_source(config: &CalendarConfig) -> Result<String, String> {
+    if !config.source.starts_with("http://") && !config.source.starts_with("https://") {
+        return fs::read_to_string(&config.source).map_err(|e| format!("Failed to read calendar: {}", e));
+    }
+
+    let agent = ureq::AgentBuilder::new().timeout(Duration::from_secs(30)).build();
+    let mut request = agent.get(&config.source);
+    if let Some(username) = config.username.as_deref().filter(|u| !u.is_empty()) {
+        let password = secrets::get_secret(secrets::CALDAV_PASSWORD)?.unwrap_or_default();
+        let credentials = BASE64_STANDARD.encode(format!("{}:{}", username, password));
+        request = request.set("Authorization", &format!("Basic {}", credentials));
+    }
+    request
+        .call()
+        .map_err(|e| format!("Failed to fetch calendar: {}", e))?
+        .into_string()
+        .map_err(|e| format!("Failed to fetch calendar: {}", e))
+}
+
+// Meetings overlapping `from..to`, reading the calendar again if it's stale
+fn meetings(
+    app_handle: &AppHandle,
+    config: &CalendarConfig,
+    from: chrono::DateTime<chrono::Local>,
+    to: chrono::DateTime<chrono::Local>,
+) -> Result<Vec<CalendarEvent>, String> {
+    let calendar_state = app_handle.state::<CalendarState>();
+    let mut fetched = calendar_state.fetched.lock().unwrap();
+    let stale = fetched.source != config.source || fetched.at.is_none_or(|at| at.elapsed() >= REFRESH_INTERVAL);
+    if stale {
+        // Try again on the next refresh rather than every poll if the source is down
+        fetched.source = config.source.clone();
+        fetched.at = Some(Instant::now());
+        fetched.calendar = Calendar::parse(&read_source(config)?)?;
+    }
+    Ok(fetched.calendar.events_between(from, to))
+}
+
+// Record `event`, tagging the recording with its title once saved
+fn record(app_handle: &AppHandle, config: &CalendarConfig, event: &CalendarEvent) -> Result<(), String> {
+    crate::start_recording_internal(app_handle)?;
+    let state = app_handle.state::<Arc<RecordingState>>();
+    let session = state.session.load(Ordering::SeqCst);
+    *app_handle.state::<CalendarState>().recording.lock().unwrap() = Some((session, event.title.clone()));
+    info!("Recording meeting '{}'", event.title);
+
+    if config.stop_at_end {
+        let remaining = (event.end - chrono::Local::now()).to_std().unwrap_or_default();
+        countdown::limit(app_handle.clone(), remaining);
+    }
+    let _ = app_handle.emit("meeting-recording-started", Meeting::from(event));
+    Ok(())
+}
+
+/// Tag a recording being saved with the title of the meeting it was started for
+pub fn tag_recording(app_handle: &AppHandle, session: u64, entry: &mut RecordingEntry) {
+    let calendar_state = app_handle.state::<CalendarState>();
+    let mut recording = calendar_state.recording.lock().unwrap();
+    let Some((_, title)) = recording.take_if(|(meeting_session, _)| *meeting_session == session) else {
+        return;
+    };
+    let tag = title.trim().chars().take(MAX_TITLE_TAG_LEN).collect::<String>();
+    if !tag.is_empty() && !entry.tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+        entry.tags.push(tag);
+    }
+}
+
+// Act on any meeting that has just begun
+fn check(app_handle: &AppHandle, config: &CalendarConfig) -> Result<(), String> {
+    let now = chrono::Local::now();
+    let begun = meetings(app_handle, config, now, now + chrono::Duration::seconds(1))?
+        .into_iter()
+        .filter(|event| (now - event.start).num_seconds() < START_WINDOW_SECS);
+
+    for event in begun {
+        let key = (event.uid.clone(), event.start.timestamp());
+        if !app_handle.state::<CalendarState>().handled.lock().unwrap().insert(key) {
+            continue;
+        }
+        info!("Meeting '{}' has begun", event.title);
+
+        let recording = app_handle.state::<Arc<RecordingState>>().is_recording();
+        match config.action {
+            MeetingAction::Record if !recording => {
+                if let Err(e) = record(app_handle, config, &event) {
+                    warn!("Failed to record meeting '{}': {}", event.title, e);
+                    notifications::notify(app_handle, "Meeting not recorded", &e);
+                }
+            }
+            _ => {
+                let _ = app_handle.emit("meeting-started", Meeting::from(&event));
+                notifications::notify(app_handle, "Meeting starting", &event.title);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Watch the configured calendar for meetings beginning
+pub fn watch(app_handle: AppHandle) {
+    thread::spawn(move || loop {
+        if let Some(config) = app_handle.state::<ConfigState>().get().calendar {
+            if let Err(e) = check(&app_handle, &config) {
+                warn!("Calendar check failed: {}", e);
+            }
+        }
+        thread::sleep(POLL_INTERVAL);
+    });
+}
+
+//
+// ====== Calendar commands ======
+//
+
+// Store calendar settings, or remove them with `None`
+#[tauri::command]
+pub fn set_calendar(
+    app_handle: AppHandle,
+    config: State<'_, ConfigState>,
+    calendar: Option<CalendarConfig>,
+) -> Result<(), String> {
+    if let Some(ref calendar) = calendar {
+        if calendar.source.trim().is_empty() {
+            return Err("Calendar source cannot be empty".to_string());
+        }
+    }
+
+    match calendar {
+        Some(ref calendar) if !calendar.password.is_empty() => {
+            secrets::set_secret(secrets::CALDAV_PASSWORD, &calendar.password)?
+        }
+        Some(_) => {}
+        None => secrets::delete_secret(secrets::CALDAV_PASSWORD)?,
+    }
+
+    // Read the new source on the next check
+    app_handle.state::<CalendarState>().fetched.lock().unwrap().at = None;
+    config.update(|c| {
+        c.calendar = calendar.clone().map(|calendar| CalendarConfig {
+            password: String::new(),
+            ..calendar
+        });
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn get_calendar(config: State<'_, ConfigState>) -> Option<CalendarConfig> {
+    config.get().calendar
+}
+
+// Meetings in progress or starting within the next day, earliest first
+#[tauri::command]
+pub async fn list_meetings(app_handle: AppHandle, app_lock: State<'_, AppLock>) -> Result<Vec<Meeting>, String> {
+    app_lock.ensure_unlocked()?;
+    let config = app_handle
+        .state::<ConfigState>()
+        .get()
+        .calendar
+        .ok_or_else(|| "No calendar is configured".to_string())?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let now = chrono::Local::now();
+        let events = meetings(&app_handle, &config, now, now + chrono::Duration::hours(UPCOMING_HOURS))?;
+        Ok(events.iter().map(Meeting::from).collect())
+    })
+    .await
+    .map_err(|e| format!("Listing meetings failed: {}", e))?
+}
+
+// Record a meeting in progress, e.g. one offered with `meeting-started`
+#[tauri::command]
+pub async fn record_meeting(app_handle: AppHandle, app_lock: State<'_, AppLock>, uid: String) -> Result<(), String> {
+    app_lock.ensure_unlocked()?;
+    let config = app_handle
+        .state::<ConfigState>()
+        .get()
+        .calendar
+        .ok_or_else(|| "No calendar is configured".to_string())?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let now = chrono::Local::now();
+        let event = meetings(&app_handle, &config, now, now + chrono::Duration::seconds(1))?
+            .into_iter()
+            .find(|event| event.uid == uid)
+            .ok_or_else(|| "That meeting isn't in progress".to_string())?;
+        record(&app_handle, &config, &event)
+    })
+    .await
+    .map_err(|e| format!("Recording meeting failed: {}", e))?
+}
@@ -0,0 +1,108 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::config::ConfigState;
+use crate::cues;
+use crate::speak;
+use crate::{RecorderState, RecordingState};
+
+//
+// ====== Periodic compliance tone ======
+//
+
+// Within the usual rules for recorded calls: 1260-1540 Hz for 170-230 ms
+const FREQUENCY: f32 = 1400.0;
+const BEEP_MS: u32 = 200;
+const MIN_INTERVAL_SECS: u32 = 5;
+const CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A beep every so often while recording, where the law wants the other party reminded
+/// that they are being recorded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ComplianceToneConfig {
+    pub enabled: bool,
+    /// At least 5
+    pub interval_secs: u32,
+}
+
+impl Default for ComplianceToneConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 15,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ComplianceToneState {
+    stop_flag: Mutex<Option<Arc<AtomicBool>>>,
+    // Interval used for the recording in progress or just finished, for its library entry
+    interval_secs: Mutex<Option<u32>>,
+}
+
+fn start(app_handle: &AppHandle) {
+    stop(app_handle);
+    let config = app_handle
+        .state::<ConfigState>()
+        .active_profile()
+        .map(|p| p.compliance_tone)
+        .filter(|c| c.enabled);
+    let interval_secs = config.map(|c| c.interval_secs.max(MIN_INTERVAL_SECS));
+    let state = app_handle.state::<ComplianceToneState>();
+    *state.interval_secs.lock().unwrap() = interval_secs;
+    let Some(interval_secs) = interval_secs else {
+        return;
+    };
+
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    *state.stop_flag.lock().unwrap() = Some(Arc::clone(&stop_flag));
+    info!("Playing a compliance tone every {} s", interval_secs);
+    let app_handle = app_handle.clone();
+    thread::spawn(move || {
+        let recorder = app_handle.state::<Arc<RecordingState>>();
+        let interval = Duration::from_secs(interval_secs as u64);
+        // The first beep goes as recording starts
+        let mut next_beep = Instant::now();
+        while !stop_flag.load(Ordering::SeqCst) {
+            if Instant::now() >= next_beep {
+                // Nothing is captured while paused, so there is nothing to warn about
+                if recorder.recorder_state() == RecorderState::Recording {
+                    let beep = cues::tones(&[FREQUENCY], BEEP_MS);
+                    if let Err(e) = speak::play_on_output(&app_handle, beep, Arc::clone(&stop_flag)) {
+                        warn!("Failed to play the compliance tone: {}", e);
+                    }
+                }
+                next_beep += interval;
+            }
+            thread::sleep(CHECK_INTERVAL);
+        }
+    });
+}
+
+fn stop(app_handle: &AppHandle) {
+    if let Some(flag) = app_handle.state::<ComplianceToneState>().stop_flag.lock().unwrap().take() {
+        flag.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Beep while recording when the active profile asks for it
+pub fn state_changed(app_handle: &AppHandle, previous: RecorderState, next: RecorderState) {
+    match (previous, next) {
+        (RecorderState::Starting, RecorderState::Recording) => start(app_handle),
+        (_, RecorderState::Stopping) | (_, RecorderState::Idle) => stop(app_handle),
+        _ => {}
+    }
+}
+
+/// The tone interval of the recording just finished, if it had the tone
+pub fn take_interval(app_handle: &AppHandle) -> Option<u32> {
+    app_handle.state::<ComplianceToneState>().interval_secs.lock().unwrap().take()
+}
@@ -0,0 +1,391 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+use tracing::{error, info, warn};
+
+use crate::calendar::CalendarConfig;
+use crate::compliance::ComplianceToneConfig;
+use crate::crypto::EncryptionConfig;
+use crate::cues::CueConfig;
+use crate::device_follow::FollowDefaultInput;
+use crate::dsp::InputFilterConfig;
+use crate::eq::{self, EqBand};
+use crate::focus::FocusModeConfig;
+use crate::hooks::PostHook;
+#[cfg(desktop)]
+use crate::hotkeys::HotkeyPreset;
+use crate::library::SavedFilter;
+use crate::meeting_detect::MeetingDetectConfig;
+use crate::metronome::MetronomeConfig;
+use crate::midi::MidiConfig;
+use crate::monitor;
+use crate::osc::OscConfig;
+use crate::overlay::OverlayConfig;
+use crate::pipeline::PipelineStage;
+use crate::processing::DynamicsConfig;
+use crate::retention::RetentionPolicy;
+#[cfg(desktop)]
+use crate::screen_lock::ScreenLockConfig;
+use crate::silence::SilenceWarningConfig;
+use crate::stream::StreamTarget;
+use crate::sync::WebDavConfig;
+use crate::system_audio::DuckConfig;
+use crate::transcription::TranscriptionConfig;
+use crate::translation::TranslationConfig;
+use crate::voice_commands::VoiceCommandConfig;
+
+//
+// ====== Persistent app configuration ======
+//
+
+const CONFIG_FILE: &str = "audio_config.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingProfile {
+    pub name: String,
+    #[serde(default)]
+    pub channels: Option<u16>,
+    #[serde(default)]
+    pub sample_rate: Option<u32>,
+    /// Post-processing stages run in order after each recording is saved
+    #[serde(default)]
+    pub pipeline: Vec<PipelineStage>,
+    /// DC removal and high-pass applied while capturing
+    #[serde(default)]
+    pub input_filter: Option<InputFilterConfig>,
+    /// Compressor and limiter applied when exporting without explicit settings
+    #[serde(default)]
+    pub export_dynamics: Option<DynamicsConfig>,
+    #[serde(default)]
+    pub cues: CueConfig,
+    #[serde(default)]
+    pub compliance_tone: ComplianceToneConfig,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub profiles: Vec<RecordingProfile>,
+    pub active_profile: Option<String>,
+    pub post_hooks: Vec<PostHook>,
+    pub webdav: Option<WebDavConfig>,
+    pub encryption: Option<EncryptionConfig>,
+    pub stream_target: Option<StreamTarget>,
+    pub osc: Option<OscConfig>,
+    pub midi: Option<MidiConfig>,
+    pub silence_warning: SilenceWarningConfig,
+    /// Start a new recording segment after the computer wakes from sleep
+    pub resume_after_sleep: bool,
+    pub focus_mode: FocusModeConfig,
+    pub duck_system_audio: DuckConfig,
+    pub saved_filters: Vec<SavedFilter>,
+    pub retention: Option<RetentionPolicy>,
+    /// When `backup_library` last succeeded; incremental backups start from here
+    pub last_backup_at: Option<String>,
+    /// Where new recordings are moved after saving; the app data dir when unset
+    pub save_dir: Option<String>,
+    /// Folder whose new audio files are imported automatically
+    pub watch_folder: Option<String>,
+    pub playback_eq: Vec<EqBand>,
+    pub metronome: MetronomeConfig,
+    pub voice_commands: VoiceCommandConfig,
+    /// Write wall-clock timestamps taken through each recording to a sidecar file
+    pub timestamp_sidecar: bool,
+    /// Calendar watched for meetings to offer or start recording
+    pub calendar: Option<CalendarConfig>,
+    pub meeting_detection: MeetingDetectConfig,
+    pub follow_default_input: FollowDefaultInput,
+    /// Last result of `measure_roundtrip_latency`; overdub takes are lined up with it
+    pub roundtrip_latency_ms: Option<f64>,
+    pub transcription: TranscriptionConfig,
+    pub translation: TranslationConfig,
+    /// Speak recorder state changes, e.g. "recording started"
+    pub voice_confirmations: bool,
+    /// Seconds of audio kept from before each recording starts; 0 turns it off
+    pub pre_roll_secs: u32,
+    pub overlay_indicator: OverlayConfig,
+    #[cfg(desktop)]
+    pub screen_lock: ScreenLockConfig,
+    #[cfg(desktop)]
+    pub hotkeys: Vec<HotkeyPreset>,
+}
+
+/// App configuration persisted as `audio_config.json` in the app data directory
+pub struct ConfigState {
+    config: Mutex<AppConfig>,
+    writer: ConfigWriter,
+    // Why the file on disk couldn't be used, if it couldn't
+    load_error: Option<String>,
+    // Cleared when an unreadable file couldn't be moved aside, so it is never overwritten
+    persist: bool,
+}
+
+#[derive(Default)]
+struct PendingWrite {
+    json: Option<String>,
+    writing: bool,
+}
+
+/// Writes the config file on its own thread so commands never wait on the disk. A burst
+/// of changes is written once, with only the newest config.
+struct ConfigWriter {
+    pending: Arc<(Mutex<PendingWrite>, Condvar)>,
+}
+
+// Write to a temp file and rename so a crash never leaves a half-written file
+pub fn write_atomically(path: &Path, json: &str) -> std::io::Result<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    fs::write(&tmp_path, json)?;
+    fs::rename(&tmp_path, path)
+}
+
+impl ConfigWriter {
+    fn spawn(path: PathBuf) -> Self {
+        let pending = Arc::new((Mutex::new(PendingWrite::default()), Condvar::new()));
+        let shared = Arc::clone(&pending);
+        thread::spawn(move || {
+            let (lock, changed) = &*shared;
+            loop {
+                let json = {
+                    let mut pending = changed.wait_while(lock.lock().unwrap(), |p| p.json.is_none()).unwrap();
+                    pending.writing = true;
+                    pending.json.take().unwrap_or_default()
+                };
+                if let Err(e) = write_atomically(&path, &json) {
+                    error!("Failed to write config: {}", e);
+                }
+                lock.lock().unwrap().writing = false;
+                changed.notify_all();
+            }
+        });
+        Self { pending }
+    }
+
+    fn write(&self, json: String) {
+        let (lock, changed) = &*self.pending;
+        lock.lock().unwrap().json = Some(json);
+        changed.notify_all();
+    }
+
+    /// Block until everything handed to `write` is on disk
+    fn flush(&self) {
+        let (lock, changed) = &*self.pending;
+        let _idle = changed
+            .wait_while(lock.lock().unwrap(), |p| p.json.is_some() || p.writing)
+            .unwrap();
+    }
+}
+
+// Whether a section passed its command's checks; says so in the log if not
+fn is_valid(section: &str, checked: Result<(), String>) -> bool {
+    checked
+        .map_err(|e| warn!("{} settings are invalid, using defaults: {}", section, e))
+        .is_ok()
+}
+
+/// Put back the defaults of each section holding values its command would have
+/// refused, as a hand-edited file can. Sections that pass are left alone.
+fn reset_invalid(config: &mut AppConfig) {
+    if !is_valid("Metronome", config.metronome.validate()) {
+        config.metronome = MetronomeConfig {
+            enabled: config.metronome.enabled,
+            ..MetronomeConfig::default()
+        };
+    }
+    if !is_valid("Silence warning", config.silence_warning.validate()) {
+        config.silence_warning = SilenceWarningConfig::default();
+    }
+    if !is_valid("Playback EQ", eq::validate_bands(&config.playback_eq)) {
+        config.playback_eq = Vec::new();
+    }
+    if !is_valid("System audio ducking", config.duck_system_audio.validate()) {
+        config.duck_system_audio = DuckConfig::default();
+    }
+    if !is_valid("Voice command", config.voice_commands.validate()) {
+        config.voice_commands = VoiceCommandConfig::default();
+    }
+    if !is_valid("Pre-roll", monitor::validate_pre_roll(config.pre_roll_secs)) {
+        config.pre_roll_secs = 0;
+    }
+    if !is_valid("Retention", config.retention.as_ref().map_or(Ok(()), RetentionPolicy::validate)) {
+        config.retention = None;
+    }
+    if !is_valid("Stream target", config.stream_target.as_ref().map_or(Ok(()), StreamTarget::validate)) {
+        config.stream_target = None;
+    }
+    for profile in &mut config.profiles {
+        let section = format!("Input filter of profile '{}'", profile.name);
+        if !is_valid(&section, profile.input_filter.as_ref().map_or(Ok(()), InputFilterConfig::validate)) {
+            profile.input_filter = None;
+        }
+        let section = format!("Export dynamics of profile '{}'", profile.name);
+        if !is_valid(&section, profile.export_dynamics.as_ref().map_or(Ok(()), DynamicsConfig::validate)) {
+            profile.export_dynamics = None;
+        }
+    }
+    config
+        .saved_filters
+        .retain(|saved| is_valid(&format!("Saved filter '{}'", saved.name), saved.filter.validate()));
+}
+
+impl ConfigState {
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+
+        let path = dir.join(CONFIG_FILE);
+        let mut load_error = None;
+        let mut persist = true;
+        let mut config = if path.exists() {
+            let raw = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read config: {}", e))?;
+            serde_json::from_str(&raw).unwrap_or_else(|e| {
+                // Keep the user's file rather than let the first change write defaults over it
+                let backup = dir.join(format!("{}.bak", CONFIG_FILE));
+                let error = match fs::rename(&path, &backup) {
+                    Ok(()) => format!("Settings file is invalid and was moved to {}: {}", backup.display(), e),
+                    Err(rename_error) => {
+                        persist = false;
+                        format!(
+                            "Settings file is invalid and couldn't be moved aside ({}); changes won't be saved: {}",
+                            rename_error, e
+                        )
+                    }
+                };
+                warn!("{}", error);
+                load_error = Some(error);
+                AppConfig::default()
+            })
+        } else {
+            AppConfig::default()
+        };
+        reset_invalid(&mut config);
+
+        Ok(Self {
+            config: Mutex::new(config),
+            writer: ConfigWriter::spawn(path),
+            load_error,
+            persist,
+        })
+    }
+
+    pub fn get(&self) -> AppConfig {
+        self.config.lock().unwrap().clone()
+    }
+
+    /// Apply a change and queue it for writing to disk; nothing changes if `f` fails
+    pub fn update<F>(&self, f: F) -> Result<(), String>
+    where
+        F: FnOnce(&mut AppConfig) -> Result<(), String>,
+    {
+        let mut config = self.config.lock().unwrap();
+        let mut updated = config.clone();
+        f(&mut updated)?;
+
+        let json = serde_json::to_string_pretty(&updated)
+            .map_err(|e| format!("Failed to serialize config: {}", e))?;
+        if self.persist {
+            self.writer.write(json);
+        }
+
+        *config = updated;
+        Ok(())
+    }
+
+    /// Wait for queued changes to reach the disk, e.g. before the app exits
+    pub fn flush(&self) {
+        self.writer.flush();
+    }
+
+    /// Why the settings file couldn't be loaded, in which case the defaults are in use
+    pub fn load_error(&self) -> Option<&str> {
+        self.load_error.as_deref()
+    }
+
+    pub fn active_profile(&self) -> Option<RecordingProfile> {
+        let config = self.config.lock().unwrap();
+        let name = config.active_profile.as_ref()?;
+        config.profiles.iter().find(|p| &p.name == name).cloned()
+    }
+}
+
+// Why the settings file couldn't be loaded at startup, if it couldn't
+#[tauri::command]
+pub fn get_config_load_error(config: State<'_, ConfigState>) -> Option<String> {
+    config.load_error().map(str::to_string)
+}
+
+//
+// ====== Profile commands ======
+//
+
+#[tauri::command]
+pub fn list_profiles(config: State<'_, ConfigState>) -> Vec<RecordingProfile> {
+    config.get().profiles
+}
+
+// Create a profile or replace the one with the same name
+#[tauri::command]
+pub fn save_profile(config: State<'_, ConfigState>, profile: RecordingProfile) -> Result<(), String> {
+    if profile.name.trim().is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+    if let Some(ref filter) = profile.input_filter {
+        filter.validate()?;
+    }
+    if let Some(ref dynamics) = profile.export_dynamics {
+        dynamics.validate()?;
+    }
+
+    config.update(|c| {
+        match c.profiles.iter_mut().find(|p| p.name == profile.name) {
+            Some(existing) => *existing = profile.clone(),
+            None => c.profiles.push(profile.clone()),
+        }
+        Ok(())
+    })?;
+
+    info!("Saved profile '{}'", profile.name);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_profile(config: State<'_, ConfigState>, name: String) -> Result<(), String> {
+    config.update(|c| {
+        let before = c.profiles.len();
+        c.profiles.retain(|p| p.name != name);
+        if c.profiles.len() == before {
+            return Err(format!("No profile named '{}'", name));
+        }
+        if c.active_profile.as_deref() == Some(name.as_str()) {
+            c.active_profile = None;
+        }
+        Ok(())
+    })
+}
+
+// Select the profile used for new recordings, or clear it with `None`
+#[tauri::command]
+pub fn set_active_profile(
+    app_handle: AppHandle,
+    config: State<'_, ConfigState>,
+    name: Option<String>,
+) -> Result<(), String> {
+    config.update(|c| {
+        if let Some(ref name) = name {
+            if !c.profiles.iter().any(|p| &p.name == name) {
+                return Err(format!("No profile named '{}'", name));
+            }
+        }
+        c.active_profile = name.clone();
+        Ok(())
+    })?;
+    // The profile may record in another format, which the pre-roll has to match
+    monitor::restart(&app_handle);
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tracing::{info, warn};
+
+use crate::audit::{self, Initiator};
+use crate::RecordingState;
+
+//
+// ====== Recording time limits ======
+//
+
+// Countdown events are only sent for the final stretch
+const COUNTDOWN_SECONDS: u64 = 10;
+const MAX_START_DELAY_SECONDS: u64 = 60;
+
+/// The delayed start counting down, if any
+#[derive(Default)]
+pub struct DelayedStartState {
+    // Bumped by every delayed start and cancel so only the newest one goes ahead
+    pending: Arc<AtomicU64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct RecordingCountdownEvent {
+    remaining_seconds: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct DelayedStartEvent {
+    started: bool,
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct RecordingLimitReachedEvent {
+    path: Option<String>,
+    error: Option<String>,
+}
+
+/// Stop and save the current recording once it has run for `max_duration`.
+/// A recording stopped early, or replaced by a new one, is left alone.
+pub fn limit(app_handle: AppHandle, max_duration: Duration) {
+    let state = Arc::clone(&app_handle.state::<Arc<RecordingState>>());
+    let session = state.session.load(Ordering::SeqCst);
+    let deadline = Instant::now() + max_duration;
+
+    thread::spawn(move || {
+        let active = || state.is_recording() && state.session.load(Ordering::SeqCst) == session;
+
+        loop {
+            if !active() {
+                return;
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+
+            // Round up so the last event before stopping says 1, not 0
+            let remaining_seconds = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+            if remaining_seconds <= COUNTDOWN_SECONDS {
+                let _ = app_handle.emit("recording-countdown", RecordingCountdownEvent { remaining_seconds });
+            }
+            // Wake on the next whole second of remaining time
+            let tick = remaining - Duration::from_secs(remaining_seconds - 1);
+            thread::sleep(tick);
+        }
+
+        info!("Recording reached its {:?} limit", max_duration);
+        let event = match crate::stop_recording_internal(&app_handle) {
+            Ok(path) => RecordingLimitReachedEvent {
+                path: Some(path.to_string_lossy().to_string()),
+                error: None,
+            },
+            Err(e) => {
+                warn!("Failed to stop recording at its time limit: {}", e);
+                RecordingLimitReachedEvent {
+                    path: None,
+                    error: Some(e.to_string()),
+                }
+            }
+        };
+        let _ = app_handle.emit("recording-limit-reached", event);
+    });
+}
+
+//
+// ====== Delayed start ======
+//
+
+// Count down `seconds` with a `recording-start-countdown` event each second, then start
+// recording (limited to `max_duration` seconds if given). Returns immediately; the outcome
+// arrives as `recording-delayed-start`.
+#[tauri::command]
+pub fn start_recording_with_delay(
+    app_handle: AppHandle,
+    delayed_start: State<'_, DelayedStartState>,
+    seconds: u64,
+    max_duration: Option<u64>,
+) -> Result<(), String> {
+    if !(1..=MAX_START_DELAY_SECONDS).contains(&seconds) {
+        return Err(format!("Delay must be between 1 and {} seconds", MAX_START_DELAY_SECONDS));
+    }
+    if max_duration == Some(0) {
+        return Err("Maximum duration must be at least one second".to_string());
+    }
+    if app_handle.state::<Arc<RecordingState>>().is_recording() {
+        return Err("Already recording".to_string());
+    }
+
+    let latest = Arc::clone(&delayed_start.pending);
+    let pending = latest.fetch_add(1, Ordering::SeqCst) + 1;
+    thread::spawn(move || {
+        for remaining_seconds in (1..=seconds).rev() {
+            if latest.load(Ordering::SeqCst) != pending {
+                return;
+            }
+            let _ = app_handle.emit("recording-start-countdown", RecordingCountdownEvent { remaining_seconds });
+            thread::sleep(Duration::from_secs(1));
+        }
+        if latest.load(Ordering::SeqCst) != pending {
+            return;
+        }
+
+        // Asked for in the UI, just earlier
+        let result = audit::initiated_by(Initiator::Ui, || crate::start_recording_internal(&app_handle));
+        if result.is_ok() {
+            if let Some(seconds) = max_duration {
+                limit(app_handle.clone(), Duration::from_secs(seconds));
+            }
+        }
+        let event = match result {
+            Ok(_) => DelayedStartEvent {
+                started: true,
+                error: None,
+            },
+            Err(e) => {
+                warn!("Delayed start failed: {}", e);
+                DelayedStartEvent {
+                    started: false,
+                    error: Some(e.to_string()),
+                }
+            }
+        };
+        let _ = app_handle.emit("recording-delayed-start", event);
+    });
+    Ok(())
+}
+
+// Abandon a delayed start that hasn't begun recording yet
+#[tauri::command]
+pub fn cancel_delayed_start(delayed_start: State<'_, DelayedStartState>) {
+    delayed_start.pending.fetch_add(1, Ordering::SeqCst);
+}
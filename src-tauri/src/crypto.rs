@@ -0,0 +1,212 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use base64::prelude::*;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::OsRng;
+use rekt_core::crypto::{derive_key, open, seal};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use tracing::info;
+
+use crate::config::ConfigState;
+use crate::library::RecordingEntry;
+
+//
+// ====== Encryption at rest ======
+//
+
+/// Suffix appended to encrypted recordings, e.g. `recording_20250101_120000.wav.enc`
+pub const ENCRYPTED_EXTENSION: &str = "enc";
+
+const SALT_LEN: usize = 16;
+const VERIFIER_PLAINTEXT: &[u8] = b"rekt-encryption-check";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptionConfig {
+    /// Encrypt newly finished recordings
+    pub enabled: bool,
+    /// Argon2 salt for deriving the key from the passphrase
+    pub salt: String,
+    /// A known value encrypted with the key, used to check passphrases
+    pub verifier: String,
+}
+
+/// The derived key lives only in memory for the current session
+#[derive(Default)]
+pub struct EncryptionState {
+    key: Mutex<Option<[u8; 32]>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EncryptionStatus {
+    enabled: bool,
+    configured: bool,
+    unlocked: bool,
+}
+
+impl EncryptionState {
+    pub fn is_unlocked(&self) -> bool {
+        self.key.lock().unwrap().is_some()
+    }
+
+    fn key(&self) -> Result<[u8; 32], String> {
+        self.key
+            .lock()
+            .unwrap()
+            .ok_or_else(|| "Encryption is locked; enter the passphrase first".to_string())
+    }
+
+    /// Encrypt `path` to `path.enc` and remove the plaintext, returning the new path
+    pub fn encrypt_file(&self, path: &Path) -> Result<PathBuf, String> {
+        let plaintext = fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+        let sealed = seal(&self.key()?, &plaintext)?;
+
+        let mut encrypted_path = path.as_os_str().to_owned();
+        encrypted_path.push(format!(".{}", ENCRYPTED_EXTENSION));
+        let encrypted_path = PathBuf::from(encrypted_path);
+
+        fs::write(&encrypted_path, sealed)
+            .map_err(|e| format!("Failed to write encrypted file: {}", e))?;
+        fs::remove_file(path).map_err(|e| format!("Failed to remove plaintext file: {}", e))?;
+        Ok(encrypted_path)
+    }
+
+    /// Decrypt an encrypted recording into memory
+    pub fn decrypt_file(&self, path: &Path) -> Result<Vec<u8>, String> {
+        let sealed = fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+        open(&self.key()?, &sealed)
+    }
+}
+
+pub fn is_encrypted(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.eq_ignore_ascii_case(ENCRYPTED_EXTENSION))
+        .unwrap_or(false)
+}
+
+/// Encrypt a file that is about to be added to the library when encryption is on, and
+/// point its entry at the encrypted file. Recordings, imports and restores all go
+/// through here, so none of them is left on disk as plaintext.
+pub fn seal_new_recording(
+    config: &ConfigState,
+    encryption: &EncryptionState,
+    entry: &mut RecordingEntry,
+) -> Result<(), String> {
+    let path = PathBuf::from(&entry.path);
+    if !config.get().encryption.is_some_and(|e| e.enabled) || is_encrypted(&path) {
+        return Ok(());
+    }
+    let encrypted_path = encryption.encrypt_file(&path)?;
+    entry.path = encrypted_path.to_string_lossy().to_string();
+    entry.size_bytes = fs::metadata(&encrypted_path).map(|m| m.len()).unwrap_or(0);
+    entry.encrypted = true;
+    Ok(())
+}
+
+/// Read a recording, decrypting it first if it is encrypted
+pub fn read_recording(encryption: &EncryptionState, path: &Path) -> Result<Vec<u8>, String> {
+    if is_encrypted(path) {
+        encryption.decrypt_file(path)
+    } else {
+        fs::read(path).map_err(|e| format!("Failed to read file: {}", e))
+    }
+}
+
+// Derive the key from `passphrase` and check it against the stored verifier
+fn unlock_with(encryption: &EncryptionState, settings: &EncryptionConfig, passphrase: &str) -> Result<(), String> {
+    let salt = BASE64_STANDARD
+        .decode(&settings.salt)
+        .map_err(|e| format!("Stored salt is invalid: {}", e))?;
+    let verifier = BASE64_STANDARD
+        .decode(&settings.verifier)
+        .map_err(|e| format!("Stored verifier is invalid: {}", e))?;
+
+    let key = derive_key(passphrase, &salt)?;
+    match open(&key, &verifier) {
+        Ok(plaintext) if plaintext == VERIFIER_PLAINTEXT => {
+            *encryption.key.lock().unwrap() = Some(key);
+            Ok(())
+        }
+        _ => Err("Incorrect passphrase".to_string()),
+    }
+}
+
+//
+// ====== Encryption commands ======
+//
+
+// Turn encryption of new recordings on or off. The first time it is enabled the
+// passphrase sets up the key; afterwards it must match the original passphrase.
+#[tauri::command]
+pub fn set_encryption(
+    config: State<'_, ConfigState>,
+    encryption: State<'_, EncryptionState>,
+    enabled: bool,
+    passphrase: Option<String>,
+) -> Result<(), String> {
+    let existing = config.get().encryption;
+
+    let settings = match (existing, passphrase) {
+        (Some(settings), Some(passphrase)) => {
+            unlock_with(&encryption, &settings, &passphrase)?;
+            settings
+        }
+        (Some(settings), None) if encryption.is_unlocked() || !enabled => settings,
+        (Some(_), None) => return Err("Passphrase required to enable encryption".to_string()),
+        (None, Some(passphrase)) => {
+            if passphrase.len() < 8 {
+                return Err("Passphrase must be at least 8 characters".to_string());
+            }
+            let mut salt = [0u8; SALT_LEN];
+            OsRng.fill_bytes(&mut salt);
+            let key = derive_key(&passphrase, &salt)?;
+            let verifier = seal(&key, VERIFIER_PLAINTEXT)?;
+            *encryption.key.lock().unwrap() = Some(key);
+            EncryptionConfig {
+                enabled,
+                salt: BASE64_STANDARD.encode(salt),
+                verifier: BASE64_STANDARD.encode(verifier),
+            }
+        }
+        (None, None) if !enabled => return Ok(()),
+        (None, None) => return Err("Passphrase required to enable encryption".to_string()),
+    };
+
+    // The salt and verifier are kept when disabling so existing encrypted files stay readable
+    config.update(|c| {
+        c.encryption = Some(EncryptionConfig { enabled, ..settings });
+        Ok(())
+    })?;
+
+    info!("Encryption of new recordings {}", if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+// Load the key for this session so encrypted recordings can be played and new ones saved
+#[tauri::command]
+pub fn unlock_encryption(
+    config: State<'_, ConfigState>,
+    encryption: State<'_, EncryptionState>,
+    passphrase: String,
+) -> Result<(), String> {
+    let settings = config
+        .get()
+        .encryption
+        .ok_or_else(|| "Encryption has not been set up".to_string())?;
+    unlock_with(&encryption, &settings, &passphrase)
+}
+
+#[tauri::command]
+pub fn get_encryption_status(
+    config: State<'_, ConfigState>,
+    encryption: State<'_, EncryptionState>,
+) -> EncryptionStatus {
+    let settings = config.get().encryption;
+    EncryptionStatus {
+        enabled: settings.as_ref().is_some_and(|s| s.enabled),
+        configured: settings.is_some(),
+        unlocked: encryption.is_unlocked(),
+    }
+}
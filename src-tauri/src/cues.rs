@@ -0,0 +1,92 @@
+use std::f32::consts::PI;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use rekt_core::processing::AudioBuffer;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tracing::warn;
+
+use crate::config::ConfigState;
+use crate::speak;
+use crate::RecorderState;
+
+//
+// ====== Start and stop cues ======
+//
+
+const SAMPLE_RATE: u32 = 48_000;
+// Quiet enough not to startle on headphones
+const LEVEL: f32 = 0.2;
+const NOTE_MS: u32 = 70;
+const GAP_MS: u32 = 30;
+const FADE_MS: u32 = 5;
+// A fifth up to start, down to stop
+const START_NOTES: [f32; 2] = [880.0, 1318.5];
+const STOP_NOTES: [f32; 2] = [1318.5, 880.0];
+
+/// Confirmation that recording started or stopped, for recording without looking
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CueConfig {
+    /// Short tones on the output device. They are played, never mixed into the
+    /// recording, but a microphone near a speaker will hear them; headphones avoid that.
+    pub tones: bool,
+    /// A tap of the vibration motor; phones only
+    pub haptics: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cue {
+    Start,
+    Stop,
+}
+
+/// Notes of `note_ms` one after another, each faded in and out so they don't click
+pub fn tones(notes: &[f32], note_ms: u32) -> AudioBuffer {
+    let note = (SAMPLE_RATE * note_ms / 1000) as usize;
+    let gap = (SAMPLE_RATE * GAP_MS / 1000) as usize;
+    let fade = (SAMPLE_RATE * FADE_MS / 1000) as f32;
+    let mut samples = Vec::with_capacity(notes.len() * (note + gap));
+    for frequency in notes {
+        samples.extend((0..note).map(|i| {
+            let envelope = (i.min(note - 1 - i) as f32 / fade).min(1.0);
+            (2.0 * PI * frequency * i as f32 / SAMPLE_RATE as f32).sin() * envelope * LEVEL
+        }));
+        samples.extend(std::iter::repeat_n(0.0, gap));
+    }
+    AudioBuffer {
+        channels: 1,
+        sample_rate: SAMPLE_RATE,
+        samples,
+    }
+}
+
+/// Give the cues the active profile asks for, if any
+pub fn play(app_handle: &AppHandle, cue: Cue) {
+    let Some(config) = app_handle.state::<ConfigState>().active_profile().map(|p| p.cues) else {
+        return;
+    };
+    if config.tones {
+        let notes = match cue {
+            Cue::Start => &START_NOTES,
+            Cue::Stop => &STOP_NOTES,
+        };
+        if let Err(e) = speak::play_on_output(app_handle, tones(notes, NOTE_MS), Arc::new(AtomicBool::new(false))) {
+            warn!("Failed to play the {:?} cue: {}", cue, e);
+        }
+    }
+    #[cfg(mobile)]
+    if config.haptics {
+        crate::mobile::haptic(app_handle, cue);
+    }
+}
+
+/// The cue for a recorder state change, if it has one
+pub fn for_transition(previous: RecorderState, next: RecorderState) -> Option<Cue> {
+    match (previous, next) {
+        (RecorderState::Starting, RecorderState::Recording) => Some(Cue::Start),
+        (_, RecorderState::Stopping) => Some(Cue::Stop),
+        _ => None,
+    }
+}
@@ -0,0 +1,119 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rodio::buffer::SamplesBuffer;
+use rodio::{Sink, Source};
+use serde::Serialize;
+use symphonia::core::io::MediaSource;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::error;
+
+pub use rekt_core::playback::*;
+
+use crate::eq;
+use crate::transcript;
+use crate::AudioPlaybackState;
+
+//
+// ====== Playback ======
+//
+
+// How often a playing file checks whether it has been stopped or replaced, and
+// how often the transcript position is updated
+const STOP_POLL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Serialize, Clone)]
+struct AudioPlaybackErrorEvent {
+    playback_id: String,
+    #[serde(flatten)]
+    error: PlaybackError,
+}
+
+/// Log a playback failure and tell the frontend with an `audio-playback-error` event
+pub fn report(app_handle: &AppHandle, playback_id: &str, error: &PlaybackError) {
+    error!("Playback {} failed: {}", playback_id, error);
+    let _ = app_handle.emit(
+        "audio-playback-error",
+        AudioPlaybackErrorEvent {
+            playback_id: playback_id.to_string(),
+            error: error.clone(),
+        },
+    );
+}
+
+/// Decode `media` and play it through the playback EQ, from `start` if given, until it
+/// ends or `playback_id` stops being the current playback
+pub fn play(
+    app_handle: &AppHandle,
+    stream_handle: &rodio::OutputStreamHandle,
+    playback_id: &str,
+    path: Option<&str>,
+    media: Box<dyn MediaSource>,
+    hint: Option<FormatHint>,
+    start: Option<Duration>,
+) -> Result<PlaybackEnd, PlaybackError> {
+    let mut source = SymphoniaSource::open(media, hint)?;
+    if let Some(start) = start {
+        source.seek(start)?;
+    }
+    let failure = source.failure();
+    let control = source.control(path.map(str::to_string));
+    let sink = Sink::try_new(stream_handle)
+        .map_err(|e| PlaybackError::new(PlaybackErrorKind::Output, format!("Failed to create sink: {}", e)))?;
+
+    let eq = Arc::clone(app_handle.state::<Arc<eq::PlaybackEq>>().inner());
+    sink.append(eq::Equalized::new(source.convert_samples::<i16>(), eq));
+
+    let playback_state = app_handle.state::<AudioPlaybackState>();
+    *playback_state.control.lock().unwrap() = Some(control.clone());
+    let mut follower = path.and_then(|path| transcript::Follower::new(app_handle, playback_id, path));
+    while !sink.empty() {
+        let current = playback_state.current_playback_id.lock().unwrap().as_deref() == Some(playback_id);
+        if !current {
+            sink.stop();
+            return Ok(PlaybackEnd::Stopped {
+                position: control.position(),
+            });
+        }
+        if let Some(follower) = follower.as_mut() {
+            follower.update(control.position());
+        }
+        thread::sleep(STOP_POLL);
+    }
+    if playback_state.current_playback_id.lock().unwrap().as_deref() == Some(playback_id) {
+        *playback_state.control.lock().unwrap() = None;
+    }
+
+    let failure = failure.lock().unwrap().take();
+    failure.map_or(Ok(PlaybackEnd::Finished), Err)
+}
+
+/// Play raw interleaved `samples` through the playback EQ until they end or `playback_id`
+/// stops being the current playback
+pub fn play_samples(
+    app_handle: &AppHandle,
+    stream_handle: &rodio::OutputStreamHandle,
+    playback_id: &str,
+    channels: u16,
+    sample_rate: u32,
+    samples: Vec<i16>,
+) -> Result<PlaybackEnd, PlaybackError> {
+    let sink = Sink::try_new(stream_handle)
+        .map_err(|e| PlaybackError::new(PlaybackErrorKind::Output, format!("Failed to create sink: {}", e)))?;
+    let eq = Arc::clone(app_handle.state::<Arc<eq::PlaybackEq>>().inner());
+    sink.append(eq::Equalized::new(SamplesBuffer::new(channels, sample_rate, samples), eq));
+
+    let playback_state = app_handle.state::<AudioPlaybackState>();
+    let started = Instant::now();
+    while !sink.empty() {
+        if playback_state.current_playback_id.lock().unwrap().as_deref() != Some(playback_id) {
+            sink.stop();
+            return Ok(PlaybackEnd::Stopped {
+                position: started.elapsed(),
+            });
+        }
+        thread::sleep(STOP_POLL);
+    }
+    Ok(PlaybackEnd::Finished)
+}
@@ -0,0 +1,183 @@
+use std::fmt;
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "linux")]
+use std::path::Path;
+use std::time::Duration;
+
+use rekt_core::backend::StreamFormat;
+use serde::Serialize;
+
+use crate::config::RecordingProfile;
+
+//
+// ====== Start-of-recording device sanity checks ======
+//
+
+/// How long to wait for the first real samples before warning
+pub const SIGNAL_GRACE_PERIOD: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DeviceMismatchEvent {
+    device_name: String,
+    requested_channels: Option<u16>,
+    requested_sample_rate: Option<u32>,
+    actual_channels: u16,
+    actual_sample_rate: u32,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum NoSignalReason {
+    /// The device delivered no samples at all
+    NoSamples,
+    /// Samples arrive but are all exactly zero, typical of a muted or disconnected interface
+    DigitalSilence,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct NoSignalEvent {
+    device_name: String,
+    reason: NoSignalReason,
+}
+
+/// The format to open the input in: the active profile's, else what `set_audio_config`
+/// stored; zero means "not configured"
+pub fn requested_format(profile: Option<&RecordingProfile>, stored: (u16, u32)) -> StreamFormat {
+    StreamFormat {
+        channels: profile.and_then(|p| p.channels).unwrap_or(stored.0),
+        sample_rate: profile.and_then(|p| p.sample_rate).unwrap_or(stored.1),
+    }
+}
+
+/// Compare what the device negotiated with what was requested
+pub fn check_format(device_name: &str, requested: StreamFormat, actual: (u16, u32)) -> Option<DeviceMismatchEvent> {
+    let requested_channels = (requested.channels != 0).then_some(requested.channels);
+    let requested_sample_rate = (requested.sample_rate != 0).then_some(requested.sample_rate);
+
+    let channels_differ = requested_channels.is_some_and(|c| c != actual.0);
+    let rate_differs = requested_sample_rate.is_some_and(|r| r != actual.1);
+    (channels_differ || rate_differs).then(|| DeviceMismatchEvent {
+        device_name: device_name.to_string(),
+        requested_channels,
+        requested_sample_rate,
+        actual_channels: actual.0,
+        actual_sample_rate: actual.1,
+    })
+}
+
+/// Look at what was captured during the grace period
+pub fn check_signal(device_name: &str, captured: &[i16]) -> Option<NoSignalEvent> {
+    let reason = if captured.is_empty() {
+        NoSignalReason::NoSamples
+    } else if captured.iter().all(|&s| s == 0) {
+        NoSignalReason::DigitalSilence
+    } else {
+        return None;
+    };
+
+    Some(NoSignalEvent {
+        device_name: device_name.to_string(),
+        reason,
+    })
+}
+
+//
+// ====== Devices held by other programs ======
+//
+
+// Phrases backends use when a device is held exclusively: ALSA's EBUSY, WASAPI's
+// AUDCLNT_E_DEVICE_IN_USE and Core Audio's hog mode
+const BUSY_ERRORS: [&str; 5] = ["busy", "in use", "0x8889000a", "hog", "exclusive"];
+
+/// Another program holds the input device, so it can't be recorded from
+#[derive(Debug, Serialize, Clone)]
+pub struct DeviceBusy {
+    pub device: String,
+    /// The program holding it, when the platform says
+    pub app: Option<String>,
+}
+
+impl fmt::Display for DeviceBusy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.app {
+            Some(app) => write!(f, "{} is in use by {}; close it there and try again", self.device, app),
+            None => write!(f, "{} is in use by another application; close it there and try again", self.device),
+        }
+    }
+}
+
+// Programs other than this one capturing straight from an ALSA card, which no one else
+// can then open. `card` limits the search to a card's `/proc/asound` id.
+#[cfg(target_os = "linux")]
+fn alsa_capture_owners(card: Option<&str>) -> Vec<String> {
+    let cards = match card {
+        Some(card) => vec![Path::new("/proc/asound").join(card)],
+        None => fs::read_dir("/proc/asound")
+            .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+            .unwrap_or_default(),
+    };
+    let substreams = cards
+        .iter()
+        .flat_map(|card| fs::read_dir(card).into_iter().flatten().flatten())
+        .filter(|pcm| pcm.file_name().to_string_lossy().ends_with('c'))
+        .flat_map(|pcm| fs::read_dir(pcm.path()).into_iter().flatten().flatten());
+
+    let mut owners = Vec::new();
+    for substream in substreams {
+        let Ok(status) = fs::read_to_string(substream.path().join("status")) else {
+            continue;
+        };
+        let owner = status
+            .lines()
+            .find_map(|line| line.strip_prefix("owner_pid"))
+            .and_then(|value| value.trim_start_matches([' ', '\t', ':']).trim().parse::<u32>().ok())
+            .filter(|&pid| pid != std::process::id());
+        if let Some(pid) = owner {
+            let name = fs::read_to_string(format!("/proc/{}/comm", pid)).unwrap_or_else(|_| format!("process {}", pid));
+            owners.push(name.trim().to_string());
+        }
+    }
+    owners
+}
+
+/// Check before opening `device` whether another program already has it to itself.
+/// Only direct ALSA hardware devices (`hw:CARD=...`) can be checked up front; shared
+/// devices behind a sound server are never exclusive.
+pub fn find_busy(device: &str) -> Option<DeviceBusy> {
+    #[cfg(target_os = "linux")]
+    {
+        let card = device
+            .strip_prefix("hw:CARD=")
+            .map(|rest| rest.split(',').next().unwrap_or(rest))?;
+        let app = alsa_capture_owners(Some(card)).into_iter().next()?;
+        Some(DeviceBusy {
+            device: device.to_string(),
+            app: Some(app),
+        })
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = device;
+        None
+    }
+}
+
+/// Make sense of a failure to open or start `device`: if the backend's `error` says it
+/// is held by another program, find out which one where the platform allows
+pub fn diagnose_busy(device: &str, error: &str) -> Option<DeviceBusy> {
+    let error = error.to_lowercase();
+    if !BUSY_ERRORS.iter().any(|phrase| error.contains(phrase)) {
+        return None;
+    }
+    #[cfg(target_os = "linux")]
+    let app = alsa_capture_owners(None).into_iter().next();
+    #[cfg(target_os = "windows")]
+    let app = crate::meeting_detect::microphone_users().ok().and_then(|users| users.into_iter().next());
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    let app = None;
+    Some(DeviceBusy {
+        device: device.to_string(),
+        app,
+    })
+}
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use rekt_core::backend::AudioBackend;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tracing::{error, info, warn};
+
+use crate::config::ConfigState;
+use crate::monitor;
+use crate::{RecorderState, RecordingState};
+
+//
+// ====== Following the system default input ======
+//
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WhileRecording {
+    /// Carry on with the device the recording started on
+    #[default]
+    KeepDevice,
+    /// Save what was recorded so far and continue in a new file on the new device
+    NewSegment,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FollowDefaultInput {
+    /// Move to the new default input when it changes, e.g. when a headset is plugged in
+    pub enabled: bool,
+    pub while_recording: WhileRecording,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum FollowOutcome {
+    /// Following is off, or a device pair is configured
+    NotFollowed,
+    /// Nothing was recording; whatever listens to the input moved over
+    Switched,
+    /// A recording in progress stayed on its device
+    KeptDevice,
+    /// A recording in progress was saved and continued on the new device
+    NewSegment,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct DefaultInputChangedEvent {
+    previous: String,
+    device: String,
+    outcome: FollowOutcome,
+    /// The segment saved before moving to the new device
+    saved_path: Option<String>,
+    error: Option<String>,
+}
+
+// Save the recording so far and carry on on the new default input
+fn new_segment(app_handle: &AppHandle, event: &mut DefaultInputChangedEvent) {
+    match crate::stop_recording_internal(app_handle) {
+        Ok(path) => {
+            info!("Saved {} before switching input", path.display());
+            event.saved_path = Some(path.to_string_lossy().to_string());
+        }
+        Err(e) => {
+            error!("Failed to save the recording before switching input: {}", e);
+            event.error = Some(e.to_string());
+            return;
+        }
+    }
+    match crate::start_recording_internal(app_handle) {
+        Ok(_) => event.outcome = FollowOutcome::NewSegment,
+        Err(e) => {
+            warn!("Failed to continue recording on {}: {}", event.device, e);
+            event.error = Some(e.to_string());
+        }
+    }
+}
+
+fn default_input_changed(app_handle: &AppHandle, previous: String, device: String) {
+    info!("Default input changed from {} to {}", previous, device);
+    let config = app_handle.state::<ConfigState>().get().follow_default_input;
+    let state = app_handle.state::<Arc<RecordingState>>();
+    let mut event = DefaultInputChangedEvent {
+        previous,
+        device,
+        outcome: FollowOutcome::NotFollowed,
+        saved_path: None,
+        error: None,
+    };
+
+    // A configured pair names its devices, so the default doesn't matter to it
+    if config.enabled && state.device_pair.lock().unwrap().is_none() {
+        match (state.recorder_state(), config.while_recording) {
+            (RecorderState::Idle, _) => {
+                monitor::restart(app_handle);
+                event.outcome = FollowOutcome::Switched;
+            }
+            // A paused recording would come back unpaused in a new segment
+            (RecorderState::Recording, WhileRecording::NewSegment) => new_segment(app_handle, &mut event),
+            _ => event.outcome = FollowOutcome::KeptDevice,
+        }
+    }
+    let _ = app_handle.emit("default-input-changed", event);
+}
+
+/// Watch for the system default input device changing
+pub fn watch(app_handle: AppHandle) {
+    thread::spawn(move || {
+        let backend = Arc::clone(app_handle.state::<Arc<dyn AudioBackend>>().inner());
+        let mut current = backend.default_input().ok().map(|device| device.name);
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            // Devices can fail to answer while busy or mid-switch; keep the last known name
+            let Ok(device) = backend.default_input() else {
+                continue;
+            };
+            match current.replace(device.name.clone()) {
+                Some(previous) if previous != device.name => default_input_changed(&app_handle, previous, device.name),
+                _ => {}
+            }
+        }
+    });
+}
+
+//
+// ====== Default input commands ======
+//
+
+#[tauri::command]
+pub fn set_follow_default_input(config: State<'_, ConfigState>, settings: FollowDefaultInput) -> Result<(), String> {
+    config.update(|c| {
+        c.follow_default_input = settings.clone();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn get_follow_default_input(config: State<'_, ConfigState>) -> FollowDefaultInput {
+    config.get().follow_default_input
+}
@@ -0,0 +1,112 @@
+use std::io::Cursor;
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::thread;
+
+use rekt_core::edits::EditList;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::crypto::{self, EncryptionState};
+use crate::decode::{self, FormatHint};
+use crate::library::Library;
+use crate::lock::AppLock;
+use crate::processing;
+use crate::{AudioPlaybackEvent, AudioPlaybackResponse, AudioPlaybackState};
+
+//
+// ====== Edit list commands ======
+//
+
+// Store cuts and reorderings for a recording, replacing any it had; the file is untouched
+#[tauri::command]
+pub fn set_edit_list(
+    library: State<'_, Library>,
+    app_lock: State<'_, AppLock>,
+    path: String,
+    edits: EditList,
+) -> Result<(), String> {
+    app_lock.ensure_unlocked()?;
+    let entry = library
+        .get(&path)
+        .ok_or_else(|| format!("Recording not found in library: {}", path))?;
+    edits.validate(entry.duration_ms)?;
+    library.update(&path, |entry| entry.edits = Some(edits))?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_edit_list(
+    library: State<'_, Library>,
+    app_lock: State<'_, AppLock>,
+    path: String,
+) -> Result<Option<EditList>, String> {
+    app_lock.ensure_unlocked()?;
+    library
+        .get(&path)
+        .map(|entry| entry.edits)
+        .ok_or_else(|| format!("Recording not found in library: {}", path))
+}
+
+// Go back to the recording as it was made
+#[tauri::command]
+pub fn clear_edit_list(library: State<'_, Library>, app_lock: State<'_, AppLock>, path: String) -> Result<(), String> {
+    app_lock.ensure_unlocked()?;
+    library.update(&path, |entry| entry.edits = None)?;
+    Ok(())
+}
+
+// Play the recording with its edit list applied, as an export would render it
+#[tauri::command]
+pub async fn preview_edits(
+    app_handle: AppHandle,
+    playback_state: State<'_, AudioPlaybackState>,
+    app_lock: State<'_, AppLock>,
+    path: String,
+) -> Result<AudioPlaybackResponse, String> {
+    app_lock.ensure_unlocked()?;
+    let edits = app_handle
+        .state::<Library>()
+        .get(&path)
+        .ok_or_else(|| format!("Recording not found in library: {}", path))?
+        .edits
+        .ok_or_else(|| "Recording has no edits".to_string())?;
+
+    let rendered = {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let bytes = crypto::read_recording(&app_handle.state::<EncryptionState>(), Path::new(&path))?;
+            processing::wav_bytes(&edits.apply(&processing::read_wav_bytes(&bytes)?))
+        })
+        .await
+        .map_err(|e| format!("Rendering edits failed: {}", e))??
+    };
+
+    crate::stop_audio_internal(&playback_state);
+    let stream_handle = crate::ensure_output_stream(&playback_state)?;
+    let playback_id = nanoid::nanoid!();
+    *playback_state.current_playback_id.lock().unwrap() = Some(playback_id.clone());
+    playback_state.is_playing.store(true, Ordering::SeqCst);
+
+    // No library path: positions in the edit don't match the recording's transcript or resume point
+    thread::spawn(move || {
+        let played = decode::play(
+            &app_handle,
+            &stream_handle,
+            &playback_id,
+            None,
+            Box::new(Cursor::new(rendered)),
+            Some(FormatHint::Extension("wav")),
+            None,
+        );
+        if let Err(e) = played {
+            decode::report(&app_handle, &playback_id, &e);
+        }
+        let _ = app_handle.emit("audio-playback-stopped", AudioPlaybackEvent { playback_id });
+    });
+
+    Ok(AudioPlaybackResponse {
+        success: true,
+        is_playing: true,
+        error: None,
+    })
+}
@@ -0,0 +1,155 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rekt_core::effects;
+pub use rekt_core::effects::ProcessOptions;
+use tauri::{AppHandle, Manager, State};
+use tracing::info;
+
+use crate::jobs::{self, JobContext, JobKind};
+use crate::library::{self, Library};
+use crate::lock::AppLock;
+use crate::processing;
+
+//
+// ====== Offline effect jobs ======
+//
+
+/// Render a time-stretched / pitch-shifted copy for a job and return its path
+pub fn run_process_job(job: &JobContext, path: &str, options: &ProcessOptions) -> Result<String, String> {
+    let source = Path::new(path);
+    jobs::ensure_plaintext(source)?;
+    let buffer = processing::read_wav(source)?;
+    let processed = effects::process(buffer, options, |fraction| {
+        job.check_cancelled()?;
+        job.progress(fraction);
+        Ok(())
+    })?;
+
+    let output = processing::derived_path(source, "processed", "wav");
+    processing::write_wav(&output, &processed)?;
+    let output = library::add_derived(job.app_handle(), source, &output)?;
+    info!(
+        "Processed {} at {}x tempo, {:+} semitones, stereo {:?} into {}",
+        path, options.tempo_ratio, options.pitch_semitones, options.stereo, output
+    );
+    Ok(output)
+}
+
+/// Write a reversed copy for a job and return its path
+pub fn run_reverse_job(job: &JobContext, path: &str, output: Option<&str>) -> Result<String, String> {
+    let source = Path::new(path);
+    jobs::ensure_plaintext(source)?;
+    let output = output
+        .map(PathBuf::from)
+        .unwrap_or_else(|| processing::derived_path(source, "reversed", "wav"));
+
+    let reversed = processing::reverse(source, &output, |fraction| {
+        job.check_cancelled()?;
+        job.progress(fraction);
+        Ok(())
+    });
+    if let Err(e) = reversed {
+        let _ = fs::remove_file(&output);
+        return Err(e);
+    }
+
+    let output = library::add_derived(job.app_handle(), source, &output)?;
+    info!("Reversed {} into {}", path, output);
+    Ok(output)
+}
+
+/// Write each side of a stereo recording to its own file for a job; returns the left
+/// and right paths
+pub fn run_split_job(job: &JobContext, path: &str) -> Result<Vec<String>, String> {
+    let source = Path::new(path);
+    jobs::ensure_plaintext(source)?;
+    let left = processing::derived_path(source, "L", "wav");
+    let right = processing::derived_path(source, "R", "wav");
+    let split = processing::split_channels(source, &left, &right, |fraction| {
+        job.check_cancelled()?;
+        job.progress(fraction);
+        Ok(())
+    });
+    if let Err(e) = split {
+        let _ = fs::remove_file(&left);
+        let _ = fs::remove_file(&right);
+        return Err(e);
+    }
+
+    let left_path = library::add_derived(job.app_handle(), source, &left)?;
+    let right_path = library::add_derived(job.app_handle(), source, &right)?;
+    info!("Split {} into {} and {}", path, left_path, right_path);
+    Ok(vec![left_path, right_path])
+}
+
+//
+// ====== Effect commands ======
+//
+
+// Queue a slowed-down, sped-up, transposed or re-imaged stereo copy of a recording; returns the job id
+#[tauri::command]
+pub fn process_recording(
+    app_handle: AppHandle,
+    app_lock: State<'_, AppLock>,
+    path: String,
+    options: ProcessOptions,
+) -> Result<u64, String> {
+    app_lock.ensure_unlocked()?;
+    options.validate()?;
+    if app_handle.state::<Library>().get(&path).is_none() {
+        return Err(format!("Recording not found in library: {}", path));
+    }
+    jobs::enqueue(&app_handle, JobKind::Process { path, options })
+}
+
+// Queue a copy of a recording played backwards, named `output` or a `_reversed` file next
+// to it; returns the job id
+#[tauri::command]
+pub fn reverse_recording(
+    app_handle: AppHandle,
+    app_lock: State<'_, AppLock>,
+    path: String,
+    output: Option<String>,
+) -> Result<u64, String> {
+    app_lock.ensure_unlocked()?;
+    if app_handle.state::<Library>().get(&path).is_none() {
+        return Err(format!("Recording not found in library: {}", path));
+    }
+    let source = Path::new(&path);
+    jobs::ensure_plaintext(source)?;
+    // Only a file name, so the copy lands beside the recording and nowhere else on disk
+    let output = match output {
+        Some(name) => {
+            let name = Path::new(&name);
+            if name.file_name() != Some(name.as_os_str()) {
+                return Err("Output must be a file name, without a directory".to_string());
+            }
+            if !name.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("wav")) {
+                return Err("Output must be a .wav file".to_string());
+            }
+            let output = source.with_file_name(name);
+            if output == source {
+                return Err("Output must be a different file from the recording".to_string());
+            }
+            if output.exists() {
+                return Err(format!("Output already exists: {}", output.display()));
+            }
+            Some(output.to_string_lossy().to_string())
+        }
+        None => None,
+    };
+    jobs::enqueue(&app_handle, JobKind::Reverse { path, output })
+}
+
+// Queue splitting a stereo recording into `_L` and `_R` mono files, e.g. when each side of
+// a 2-channel interface carried a different mic; returns the job id
+#[tauri::command]
+pub fn split_channels(app_handle: AppHandle, app_lock: State<'_, AppLock>, path: String) -> Result<u64, String> {
+    app_lock.ensure_unlocked()?;
+    if app_handle.state::<Library>().get(&path).is_none() {
+        return Err(format!("Recording not found in library: {}", path));
+    }
+    jobs::ensure_plaintext(Path::new(&path))?;
+    jobs::enqueue(&app_handle, JobKind::SplitChannels { path })
+}
@@ -0,0 +1,186 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rodio::Source;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::config::ConfigState;
+use crate::dsp::Biquad;
+
+//
+// ====== Playback equalizer ======
+//
+
+const MAX_BANDS: usize = 5;
+
+fn default_q() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EqBandKind {
+    LowShelf,
+    Peaking,
+    HighShelf,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EqBand {
+    pub kind: EqBandKind,
+    pub frequency_hz: f32,
+    pub gain_db: f32,
+    #[serde(default = "default_q")]
+    pub q: f32,
+}
+
+/// Check a whole EQ the way `set_playback_eq` does
+pub fn validate_bands(bands: &[EqBand]) -> Result<(), String> {
+    if bands.len() > MAX_BANDS {
+        return Err(format!("At most {} EQ bands are supported", MAX_BANDS));
+    }
+    bands.iter().try_for_each(EqBand::validate)
+}
+
+impl EqBand {
+    fn validate(&self) -> Result<(), String> {
+        if !(20.0..=20_000.0).contains(&self.frequency_hz) {
+            return Err("EQ frequency must be between 20 Hz and 20 kHz".to_string());
+        }
+        if !(-24.0..=24.0).contains(&self.gain_db) {
+            return Err("EQ gain must be between -24 and +24 dB".to_string());
+        }
+        if !(0.1..=10.0).contains(&self.q) {
+            return Err("EQ Q must be between 0.1 and 10".to_string());
+        }
+        Ok(())
+    }
+
+    fn filter(&self, sample_rate: u32) -> Biquad {
+        // Keep the band below Nyquist for low sample rates
+        let frequency = self.frequency_hz.min(sample_rate as f32 * 0.45);
+        match self.kind {
+            EqBandKind::LowShelf => Biquad::low_shelf(frequency, self.gain_db, self.q, sample_rate),
+            EqBandKind::Peaking => Biquad::peaking(frequency, self.gain_db, self.q, sample_rate),
+            EqBandKind::HighShelf => Biquad::high_shelf(frequency, self.gain_db, self.q, sample_rate),
+        }
+    }
+}
+
+/// Current EQ curve, shared with every playing source so changes apply immediately
+#[derive(Default)]
+pub struct PlaybackEq {
+    bands: Mutex<Vec<EqBand>>,
+    generation: AtomicU64,
+}
+
+impl PlaybackEq {
+    pub fn set(&self, bands: Vec<EqBand>) {
+        *self.bands.lock().unwrap() = bands;
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Source adapter that runs the playback EQ over 16-bit audio
+pub struct Equalized<S> {
+    inner: S,
+    eq: Arc<PlaybackEq>,
+    generation: u64,
+    channels: usize,
+    // One filter chain per channel
+    filters: Vec<Vec<Biquad>>,
+    channel: usize,
+}
+
+impl<S: Source<Item = i16>> Equalized<S> {
+    pub fn new(inner: S, eq: Arc<PlaybackEq>) -> Self {
+        let mut equalized = Self {
+            channels: inner.channels().max(1) as usize,
+            inner,
+            eq,
+            generation: u64::MAX,
+            filters: Vec::new(),
+            channel: 0,
+        };
+        equalized.rebuild();
+        equalized
+    }
+
+    fn rebuild(&mut self) {
+        self.generation = self.eq.generation.load(Ordering::SeqCst);
+        let sample_rate = self.inner.sample_rate();
+        let bands = self.eq.bands.lock().unwrap();
+        let chain = bands.iter().map(|band| band.filter(sample_rate)).collect::<Vec<_>>();
+        self.filters = vec![chain; self.channels];
+    }
+}
+
+impl<S: Source<Item = i16>> Iterator for Equalized<S> {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        // Only swap filters between frames so channels stay aligned
+        if self.channel == 0 && self.eq.generation.load(Ordering::Relaxed) != self.generation {
+            self.rebuild();
+        }
+
+        let sample = self.inner.next()?;
+        let chain = &mut self.filters[self.channel];
+        self.channel = (self.channel + 1) % self.channels;
+        if chain.is_empty() {
+            return Some(sample);
+        }
+
+        let mut x = sample as f32;
+        for filter in chain.iter_mut() {
+            x = filter.process(x);
+        }
+        Some(x.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16)
+    }
+}
+
+impl<S: Source<Item = i16>> Source for Equalized<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+//
+// ====== EQ commands ======
+//
+
+// Replace the playback EQ; an empty list turns it off. Applies to audio already playing.
+#[tauri::command]
+pub fn set_playback_eq(
+    config: State<'_, ConfigState>,
+    eq: State<'_, Arc<PlaybackEq>>,
+    bands: Vec<EqBand>,
+) -> Result<(), String> {
+    validate_bands(&bands)?;
+
+    config.update(|c| {
+        c.playback_eq = bands.clone();
+        Ok(())
+    })?;
+    eq.set(bands);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_playback_eq(config: State<'_, ConfigState>) -> Vec<EqBand> {
+    config.get().playback_eq
+}
@@ -0,0 +1,374 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use rekt_core::transcript::TranscriptFormat;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use tracing::info;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::audit;
+use crate::config::ConfigState;
+use crate::crypto::{self, EncryptionState};
+use crate::jobs::{self, JobContext, JobKind};
+use crate::library::{Library, Marker, RecordingEntry};
+use crate::lock::AppLock;
+use crate::processing::{self, DynamicsConfig, StereoConfig};
+
+//
+// ====== Exporting recordings as a zip bundle ======
+//
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    #[default]
+    Wav,
+    Mp3,
+    Flac,
+    Opus,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Wav => "wav",
+            ExportFormat::Mp3 => "mp3",
+            ExportFormat::Flac => "flac",
+            ExportFormat::Opus => "opus",
+        }
+    }
+
+    pub fn codec_args(&self) -> &'static [&'static str] {
+        match self {
+            ExportFormat::Wav => &["-codec:a", "pcm_s16le"],
+            ExportFormat::Mp3 => &["-codec:a", "libmp3lame", "-b:a", "192k"],
+            ExportFormat::Flac => &["-codec:a", "flac"],
+            ExportFormat::Opus => &["-codec:a", "libopus", "-b:a", "96k"],
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ManifestRecording {
+    /// Name of the file inside the zip
+    file: String,
+    source_path: String,
+    created_at: String,
+    duration_ms: u64,
+    channels: u16,
+    sample_rate: u32,
+    note: Option<String>,
+    tags: Vec<String>,
+    folder: Option<String>,
+    session: Option<String>,
+    take: Option<u32>,
+    clock_drift_ppm: Option<f64>,
+    markers: Vec<Marker>,
+    /// SRT file inside the zip with the transcript, if there is one
+    transcript: Option<String>,
+    /// SRT files inside the zip with each translation, named with its language
+    translations: Vec<String>,
+    summary: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportManifest {
+    exported_at: String,
+    format: ExportFormat,
+    dynamics: Option<DynamicsConfig>,
+    stereo: Option<StereoConfig>,
+    recordings: Vec<ManifestRecording>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportSummary {
+    path: String,
+    recordings: usize,
+    size_bytes: u64,
+}
+
+/// Optional processing applied to each recording on its way into an export
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExportProcessing<'a> {
+    pub dynamics: Option<&'a DynamicsConfig>,
+    pub stereo: Option<&'a StereoConfig>,
+}
+
+/// Produce the recording as `format` in memory, decrypting first if needed, applying
+/// its edit list and running it through the stereo and dynamics processing when given
+pub fn render(
+    encryption: &EncryptionState,
+    entry: &RecordingEntry,
+    format: ExportFormat,
+    options: ExportProcessing,
+) -> Result<Vec<u8>, String> {
+    let dir = tempfile::tempdir().map_err(|e| format!("Failed to create temp directory: {}", e))?;
+    let output = dir.path().join(format!("output.{}", format.extension()));
+    render_to(encryption, entry, format, options, &output)?;
+    fs::read(&output).map_err(|e| format!("Failed to read converted file: {}", e))
+}
+
+/// Like `render`, but writes the result to `output` instead of holding it in memory
+pub fn render_to(
+    encryption: &EncryptionState,
+    entry: &RecordingEntry,
+    format: ExportFormat,
+    options: ExportProcessing,
+    output: &Path,
+) -> Result<(), String> {
+    let source = Path::new(&entry.path);
+    let encrypted = crypto::is_encrypted(source);
+    let processed = options.dynamics.is_some() || options.stereo.is_some() || entry.edits.is_some();
+    // Plain recordings that need no processing go straight from the library
+    if !processed && !encrypted {
+        return match format {
+            ExportFormat::Wav => fs::copy(source, output)
+                .map(|_| ())
+                .map_err(|e| format!("Failed to copy {}: {}", entry.path, e)),
+            _ => processing::encode_with_ffmpeg(source, output, format.codec_args()),
+        };
+    }
+
+    let wav = crypto::read_recording(encryption, source)?;
+    if !processed && format == ExportFormat::Wav {
+        return fs::write(output, wav).map_err(|e| format!("Failed to write file: {}", e));
+    }
+
+    // ffmpeg needs real files; the temp dir keeps decrypted audio out of the library
+    let dir = tempfile::tempdir().map_err(|e| format!("Failed to create temp directory: {}", e))?;
+    let input = dir.path().join("input.wav");
+    if processed {
+        let mut buffer = processing::read_wav_bytes(&wav)?;
+        drop(wav);
+        if let Some(edits) = &entry.edits {
+            buffer = edits.apply(&buffer);
+        }
+        if let Some(stereo) = options.stereo {
+            processing::apply_stereo(&mut buffer, stereo);
+        }
+        match options.dynamics {
+            Some(dynamics) => {
+                processing::compress(&mut buffer, dynamics);
+                processing::limit(&mut buffer, dynamics.ceiling_db);
+            }
+            None if options.stereo.is_some() => processing::limit(&mut buffer, processing::STEREO_CEILING_DB),
+            None => {}
+        }
+        if format == ExportFormat::Wav {
+            return processing::write_wav(output, &buffer);
+        }
+        processing::write_wav(&input, &buffer)?;
+    } else {
+        fs::write(&input, wav).map_err(|e| format!("Failed to write temp file: {}", e))?;
+    }
+    processing::encode_with_ffmpeg(&input, output, format.codec_args())
+}
+
+// Library file name without the encryption suffix or audio extension
+fn export_stem(entry: &RecordingEntry) -> String {
+    let name = entry
+        .file_name
+        .strip_suffix(&format!(".{}", crypto::ENCRYPTED_EXTENSION))
+        .unwrap_or(&entry.file_name);
+    Path::new(name)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "recording".to_string())
+}
+
+fn write_text(zip: &mut ZipWriter<File>, name: &str, options: SimpleFileOptions, text: &str) -> Result<(), String> {
+    zip.start_file(name, options)
+        .and_then(|_| zip.write_all(text.as_bytes()).map_err(Into::into))
+        .map_err(|e| format!("Failed to write export: {}", e))
+}
+
+fn export(
+    job: &JobContext,
+    library: &Library,
+    encryption: &EncryptionState,
+    paths: &[String],
+    dest: &Path,
+    format: ExportFormat,
+    options: ExportProcessing,
+) -> Result<ExportSummary, String> {
+    let entries = paths
+        .iter()
+        .map(|path| library.get(path).ok_or_else(|| format!("Recording not found in library: {}", path)))
+        .collect::<Result<Vec<_>, _>>()?;
+    if entries.is_empty() {
+        return Err("No recordings to export".to_string());
+    }
+
+    let file = File::create(dest).map_err(|e| format!("Failed to create export file: {}", e))?;
+    let mut zip = ZipWriter::new(file);
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    // Each recording is rendered here and copied into the zip, so only one is on disk at a time
+    let scratch = tempfile::tempdir().map_err(|e| format!("Failed to create temp directory: {}", e))?;
+    let mut manifest = ExportManifest {
+        exported_at: chrono::Local::now().to_rfc3339(),
+        format,
+        dynamics: options.dynamics.cloned(),
+        stereo: options.stereo.cloned(),
+        recordings: Vec::new(),
+    };
+    let mut size_bytes = 0;
+
+    let count = entries.len();
+    for (index, entry) in entries.into_iter().enumerate() {
+        job.check_cancelled()?;
+        job.progress(index as f32 / count as f32);
+        let stem = export_stem(&entry);
+        let mut name = format!("{}.{}", stem, format.extension());
+        let mut counter = 1;
+        while manifest.recordings.iter().any(|r| r.file == name) {
+            name = format!("{}_{}.{}", stem, counter, format.extension());
+            counter += 1;
+        }
+
+        let rendered = scratch.path().join(format!("recording.{}", format.extension()));
+        render_to(encryption, &entry, format, options, &rendered)?;
+        let mut source = File::open(&rendered).map_err(|e| format!("Failed to read converted file: {}", e))?;
+        zip.start_file(name.as_str(), stored)
+            .map_err(|e| format!("Failed to write export: {}", e))?;
+        size_bytes += io::copy(&mut source, &mut zip).map_err(|e| format!("Failed to write export: {}", e))?;
+        drop(source);
+        let _ = fs::remove_file(&rendered);
+
+        let name_stem = name.strip_suffix(&format!(".{}", format.extension())).unwrap_or(&name);
+        let mut transcript = None;
+        if let Some(text) = &entry.transcript {
+            let file = format!("{}.srt", name_stem);
+            write_text(&mut zip, &file, deflated, &text.render(TranscriptFormat::Srt)?)?;
+            transcript = Some(file);
+        }
+        let mut translations = Vec::new();
+        for translation in &entry.translations {
+            let language = translation.language.as_deref().unwrap_or("unknown");
+            let file = format!("{}.{}.srt", name_stem, language);
+            write_text(&mut zip, &file, deflated, &translation.render(TranscriptFormat::Srt)?)?;
+            translations.push(file);
+        }
+
+        manifest.recordings.push(ManifestRecording {
+            file: name,
+            source_path: entry.path,
+            created_at: entry.created_at,
+            duration_ms: entry.edits.as_ref().map_or(entry.duration_ms, |edits| edits.duration_ms()),
+            channels: entry.channels,
+            sample_rate: entry.sample_rate,
+            note: entry.note,
+            tags: entry.tags,
+            folder: entry.folder,
+            session: entry.session,
+            take: entry.take,
+            clock_drift_ppm: entry.clock_drift_ppm,
+            markers: entry.markers,
+            transcript,
+            translations,
+            summary: entry.summary,
+        });
+    }
+
+    let manifest_json =
+        serde_json::to_vec_pretty(&manifest).map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    zip.start_file(MANIFEST_FILE, SimpleFileOptions::default())
+        .and_then(|_| zip.write_all(&manifest_json).map_err(Into::into))
+        .map_err(|e| format!("Failed to write export: {}", e))?;
+    zip.finish().map_err(|e| format!("Failed to finalize export: {}", e))?;
+
+    Ok(ExportSummary {
+        path: dest.to_string_lossy().to_string(),
+        recordings: manifest.recordings.len(),
+        size_bytes,
+    })
+}
+
+//
+// ====== Export commands ======
+//
+
+/// Write the export zip for a job and return its path
+pub fn run_job(
+    job: &JobContext,
+    paths: &[String],
+    dest_zip: &str,
+    format: ExportFormat,
+    dynamics: Option<&DynamicsConfig>,
+    stereo: Option<&StereoConfig>,
+) -> Result<String, String> {
+    let app_handle = job.app_handle();
+    let dest = PathBuf::from(dest_zip);
+    let summary = export(
+        job,
+        &app_handle.state::<Library>(),
+        &app_handle.state::<EncryptionState>(),
+        paths,
+        &dest,
+        format,
+        ExportProcessing { dynamics, stereo },
+    )
+    .inspect_err(|_| {
+        let _ = fs::remove_file(&dest);
+    })?;
+
+    info!(
+        "Exported {} recordings ({} bytes) to {}",
+        summary.recordings, summary.size_bytes, summary.path
+    );
+    Ok(summary.path)
+}
+
+// Queue bundling recordings, converted to `format` (WAV by default), with their transcripts
+// and translations as SRT and a manifest of their metadata. `dynamics` defaults to the
+// active profile's export dynamics, if it has any; `stereo` applies mid/side decoding or
+// width changes to stereo recordings.
+// Returns the job id; the zip path arrives as the job's output.
+#[tauri::command]
+pub fn export_recordings(
+    app_handle: AppHandle,
+    app_lock: State<'_, AppLock>,
+    paths: Vec<String>,
+    dest_zip: String,
+    format: Option<ExportFormat>,
+    dynamics: Option<DynamicsConfig>,
+    stereo: Option<StereoConfig>,
+) -> Result<u64, String> {
+    app_lock.ensure_unlocked()?;
+    if paths.is_empty() {
+        return Err("No recordings to export".to_string());
+    }
+    let library = app_handle.state::<Library>();
+    if let Some(missing) = paths.iter().find(|path| library.get(path).is_none()) {
+        return Err(format!("Recording not found in library: {}", missing));
+    }
+    let dynamics = dynamics.or_else(|| {
+        app_handle
+            .state::<ConfigState>()
+            .active_profile()
+            .and_then(|p| p.export_dynamics)
+    });
+    if let Some(ref dynamics) = dynamics {
+        dynamics.validate()?;
+    }
+    if let Some(ref stereo) = stereo {
+        stereo.validate()?;
+    }
+
+    audit::exported(&app_handle, &paths, &dest_zip);
+    jobs::enqueue(
+        &app_handle,
+        JobKind::Export {
+            paths,
+            dest_zip,
+            format: format.unwrap_or_default(),
+            dynamics,
+            stereo,
+        },
+    )
+}
@@ -0,0 +1,221 @@
+use std::path::Path;
+use std::process::Command;
+
+use tauri::State;
+
+use crate::crypto;
+use crate::lock::AppLock;
+
+//
+// ====== Desktop integration for recording files ======
+//
+
+// Open the platform file manager with the recording selected
+#[tauri::command]
+pub fn show_in_folder(app_lock: State<'_, AppLock>, path: String) -> Result<(), String> {
+    app_lock.ensure_unlocked()?;
+    let path = Path::new(&path);
+    if !path.exists() {
+        return Err(format!("File not found: {}", path.display()));
+    }
+
+    reveal(path)
+}
+
+#[cfg(target_os = "windows")]
+fn reveal(path: &Path) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+
+    // Explorer wants "/select," glued to a quoted path, which normal arg quoting breaks
+    Command::new("explorer")
+        .raw_arg(format!("/select,\"{}\"", path.display()))
+        .spawn()
+        .map_err(|e| format!("Failed to launch Explorer: {}", e))?;
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn reveal(path: &Path) -> Result<(), String> {
+    Command::new("open")
+        .arg("-R")
+        .arg(path)
+        .spawn()
+        .map_err(|e| format!("Failed to launch Finder: {}", e))?;
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn reveal(path: &Path) -> Result<(), String> {
+    // Most XDG file managers implement the FileManager1 interface, which can select the file
+    let selected = Command::new("dbus-send")
+        .args([
+            "--session",
+            "--print-reply",
+            "--dest=org.freedesktop.FileManager1",
+            "--type=method_call",
+            "/org/freedesktop/FileManager1",
+            "org.freedesktop.FileManager1.ShowItems",
+        ])
+        .arg(format!("array:string:{}", file_uri(path)))
+        .arg("string:")
+        .output()
+        .map(|out| out.status.success())
+        .unwrap_or(false);
+
+    if selected {
+        return Ok(());
+    }
+
+    // Fall back to just opening the containing directory
+    let parent = path
+        .parent()
+        .ok_or_else(|| "Recording has no parent directory".to_string())?;
+    Command::new("xdg-open")
+        .arg(parent)
+        .spawn()
+        .map_err(|e| format!("Failed to launch file manager: {}", e))?;
+    Ok(())
+}
+
+// Put the recording on the clipboard as a file, or as raw WAV data when `include_audio` is set
+#[tauri::command]
+pub fn copy_recording_to_clipboard(
+    app_lock: State<'_, AppLock>,
+    path: String,
+    include_audio: Option<bool>,
+) -> Result<(), String> {
+    app_lock.ensure_unlocked()?;
+    let path = Path::new(&path);
+    if !path.exists() {
+        return Err(format!("File not found: {}", path.display()));
+    }
+
+    if include_audio.unwrap_or(false) {
+        // The file's bytes go on the clipboard as WAV, which ciphertext isn't
+        if crypto::is_encrypted(path) {
+            return Err("Encrypted recordings can't be copied as audio; copy the file instead".to_string());
+        }
+        copy_audio(path)
+    } else {
+        copy_file_reference(path)
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn copy_file_reference(path: &Path) -> Result<(), String> {
+    run_powershell(&format!(
+        "Set-Clipboard -LiteralPath '{}'",
+        powershell_escape(path)
+    ))
+}
+
+#[cfg(target_os = "windows")]
+fn copy_audio(path: &Path) -> Result<(), String> {
+    run_powershell(&format!(
+        "Add-Type -AssemblyName System.Windows.Forms; \
+         [System.Windows.Forms.Clipboard]::SetAudio([System.IO.File]::ReadAllBytes('{}'))",
+        powershell_escape(path)
+    ))
+}
+
+#[cfg(target_os = "windows")]
+fn run_powershell(script: &str) -> Result<(), String> {
+    // The clipboard APIs need a single-threaded apartment
+    let status = Command::new("powershell")
+        .args(["-NoProfile", "-STA", "-Command", script])
+        .status()
+        .map_err(|e| format!("Failed to launch PowerShell: {}", e))?;
+    if !status.success() {
+        return Err("PowerShell failed to set the clipboard".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn powershell_escape(path: &Path) -> String {
+    path.display().to_string().replace('\'', "''")
+}
+
+#[cfg(target_os = "macos")]
+fn copy_file_reference(path: &Path) -> Result<(), String> {
+    let script = format!(
+        "set the clipboard to (POSIX file \"{}\")",
+        path.display().to_string().replace('\\', "\\\\").replace('"', "\\\"")
+    );
+    let status = Command::new("osascript")
+        .args(["-e", &script])
+        .status()
+        .map_err(|e| format!("Failed to launch osascript: {}", e))?;
+    if !status.success() {
+        return Err("osascript failed to set the clipboard".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn copy_audio(_path: &Path) -> Result<(), String> {
+    Err("Copying raw audio to the clipboard is not supported on macOS; copy the file instead".to_string())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn copy_file_reference(path: &Path) -> Result<(), String> {
+    pipe_to_clipboard("text/uri-list", format!("{}\r\n", file_uri(path)).as_bytes())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn copy_audio(path: &Path) -> Result<(), String> {
+    let data = std::fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    pipe_to_clipboard("audio/wav", &data)
+}
+
+// Hand the data to wl-copy on Wayland or xclip on X11; both keep serving it after we exit
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn pipe_to_clipboard(mime_type: &str, data: &[u8]) -> Result<(), String> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut command = if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        let mut cmd = Command::new("wl-copy");
+        cmd.args(["--type", mime_type]);
+        cmd
+    } else {
+        let mut cmd = Command::new("xclip");
+        cmd.args(["-selection", "clipboard", "-t", mime_type, "-i"]);
+        cmd
+    };
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to launch clipboard tool (is wl-copy or xclip installed?): {}", e))?;
+
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| "Failed to open clipboard tool stdin".to_string())?
+        .write_all(data)
+        .map_err(|e| format!("Failed to write to clipboard: {}", e))?;
+
+    let status = child
+        .wait()
+        .map_err(|e| format!("Clipboard tool failed: {}", e))?;
+    if !status.success() {
+        return Err("Clipboard tool exited with an error".to_string());
+    }
+    Ok(())
+}
+
+// Build a file:// URI, percent-encoding anything outside the unreserved set
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
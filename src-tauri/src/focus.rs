@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::config::ConfigState;
+
+//
+// ====== Focus mode while recording ======
+//
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FocusModeConfig {
+    /// Hold back this app's system notifications while recording
+    pub suppress_notifications: bool,
+    /// Mute system output while recording so other apps' sounds don't bleed in
+    pub mute_output: bool,
+}
+
+#[tauri::command]
+pub fn set_focus_mode(config: State<'_, ConfigState>, settings: FocusModeConfig) -> Result<(), String> {
+    config.update(|c| {
+        c.focus_mode = settings.clone();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn get_focus_mode(config: State<'_, ConfigState>) -> FocusModeConfig {
+    config.get().focus_mode
+}
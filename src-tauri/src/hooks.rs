@@ -0,0 +1,157 @@
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::thread;
+use std::time::Duration;
+
+pub use rekt_core::shell::{capture, kill, shell_command, shell_quote, wait_with_timeout};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+use tracing::{info, warn};
+
+use crate::config::ConfigState;
+
+//
+// ====== User-defined post-recording hooks ======
+//
+
+const DEFAULT_HOOK_TIMEOUT_SECS: u64 = 60;
+const MAX_CAPTURED_OUTPUT: usize = 16 * 1024;
+/// Output kept from tools whose output is the result, like transcribers and translators
+pub const MAX_TOOL_OUTPUT: usize = 16 * 1024 * 1024;
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostHook {
+    pub id: String,
+    pub name: String,
+    /// Shell command line; `{path}` is replaced with the quoted recording path
+    pub command: String,
+    pub timeout_secs: u64,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PostHookEvent {
+    hook_id: String,
+    name: String,
+    recording_path: String,
+    exit_code: Option<i32>,
+    timed_out: bool,
+    stdout: String,
+    stderr: String,
+    error: Option<String>,
+}
+
+/// Run every enabled hook one after another in the background
+pub fn spawn(app_handle: AppHandle, recording: PathBuf, hooks: Vec<PostHook>) {
+    let hooks = hooks.into_iter().filter(|h| h.enabled).collect::<Vec<_>>();
+    if hooks.is_empty() {
+        return;
+    }
+
+    thread::spawn(move || {
+        for hook in hooks {
+            let event = run_hook(&hook, &recording);
+            match (&event.error, event.timed_out) {
+                (Some(e), _) => warn!("Post hook '{}' failed: {}", hook.name, e),
+                (None, true) => warn!("Post hook '{}' timed out", hook.name),
+                (None, false) => info!("Post hook '{}' exited with {:?}", hook.name, event.exit_code),
+            }
+            let _ = app_handle.emit("post-hook-finished", event);
+        }
+    });
+}
+
+fn run_hook(hook: &PostHook, recording: &Path) -> PostHookEvent {
+    let mut event = PostHookEvent {
+        hook_id: hook.id.clone(),
+        name: hook.name.clone(),
+        recording_path: recording.to_string_lossy().to_string(),
+        exit_code: None,
+        timed_out: false,
+        stdout: String::new(),
+        stderr: String::new(),
+        error: None,
+    };
+
+    let command_line = hook.command.replace("{path}", &shell_quote(recording));
+    let mut child = match shell_command(&command_line)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            event.error = Some(format!("Failed to start command: {}", e));
+            return event;
+        }
+    };
+
+    // Drain the pipes on their own threads so a chatty hook can't block on a full pipe
+    let stdout_reader = capture(child.stdout.take(), MAX_CAPTURED_OUTPUT);
+    let stderr_reader = capture(child.stderr.take(), MAX_CAPTURED_OUTPUT);
+
+    match wait_with_timeout(&mut child, Duration::from_secs(hook.timeout_secs)) {
+        Ok(Some(code)) => event.exit_code = code,
+        Ok(None) => event.timed_out = true,
+        Err(e) => event.error = Some(e),
+    }
+
+    event.stdout = stdout_reader.finish();
+    event.stderr = stderr_reader.finish();
+    event
+}
+
+//
+// ====== Hook management commands ======
+//
+
+#[tauri::command]
+pub fn add_post_hook(
+    config: State<'_, ConfigState>,
+    name: String,
+    command: String,
+    timeout_secs: Option<u64>,
+) -> Result<PostHook, String> {
+    if command.trim().is_empty() {
+        return Err("Hook command cannot be empty".to_string());
+    }
+
+    let hook = PostHook {
+        id: nanoid::nanoid!(),
+        name,
+        command,
+        timeout_secs: timeout_secs.unwrap_or(DEFAULT_HOOK_TIMEOUT_SECS).max(1),
+        enabled: true,
+    };
+
+    config.update(|c| {
+        c.post_hooks.push(hook.clone());
+        Ok(())
+    })?;
+
+    info!("Added post hook '{}'", hook.name);
+    Ok(hook)
+}
+
+#[tauri::command]
+pub fn list_post_hooks(config: State<'_, ConfigState>) -> Vec<PostHook> {
+    config.get().post_hooks
+}
+
+#[tauri::command]
+pub fn remove_post_hook(config: State<'_, ConfigState>, id: String) -> Result<(), String> {
+    config.update(|c| {
+        let before = c.post_hooks.len();
+        c.post_hooks.retain(|h| h.id != id);
+        if c.post_hooks.len() == before {
+            return Err(format!("No post hook with id '{}'", id));
+        }
+        Ok(())
+    })
+}
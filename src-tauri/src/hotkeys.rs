@@ -0,0 +1,128 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use tracing::{info, warn};
+
+use crate::config::ConfigState;
+use crate::remote::{self, RemoteAction};
+use crate::RecordingState;
+
+//
+// ====== Global hotkey presets ======
+//
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotkeyPreset {
+    /// Accelerator such as `Ctrl+Alt+1` or `CommandOrControl+Shift+R`
+    pub shortcut: String,
+    /// Profile to record with; `None` keeps whichever profile is active
+    #[serde(default)]
+    pub profile: Option<String>,
+}
+
+fn parse(shortcut: &str) -> Result<Shortcut, String> {
+    Shortcut::from_str(shortcut).map_err(|e| format!("Invalid shortcut '{}': {}", shortcut, e))
+}
+
+/// Replace every registered shortcut with `presets`
+pub fn register_all(app_handle: &AppHandle, presets: &[HotkeyPreset]) -> Result<(), String> {
+    let shortcuts = app_handle.global_shortcut();
+    shortcuts
+        .unregister_all()
+        .map_err(|e| format!("Failed to clear hotkeys: {}", e))?;
+
+    for preset in presets {
+        let profile = preset.profile.clone();
+        shortcuts
+            .on_shortcut(parse(&preset.shortcut)?, move |app_handle, _, event| {
+                if event.state == ShortcutState::Pressed {
+                    toggle(app_handle.clone(), profile.clone());
+                }
+            })
+            .map_err(|e| format!("Failed to register hotkey '{}': {}", preset.shortcut, e))?;
+    }
+
+    info!("Registered {} hotkey preset(s)", presets.len());
+    Ok(())
+}
+
+// Stop if recording, otherwise switch to the preset's profile and start
+fn toggle(app_handle: AppHandle, profile: Option<String>) {
+    thread::spawn(move || {
+        let recording = app_handle
+            .state::<Arc<RecordingState>>()
+            .is_recording();
+        if recording {
+            remote::perform(&app_handle, "hotkey", RemoteAction::StopRecording);
+            return;
+        }
+
+        if let Some(name) = profile {
+            let switched = app_handle.state::<ConfigState>().update(|c| {
+                if !c.profiles.iter().any(|p| p.name == name) {
+                    return Err(format!("No profile named '{}'", name));
+                }
+                c.active_profile = Some(name.clone());
+                Ok(())
+            });
+            if let Err(e) = switched {
+                warn!("Hotkey preset not started: {}", e);
+                return;
+            }
+        }
+        remote::perform(&app_handle, "hotkey", RemoteAction::StartRecording);
+    });
+}
+
+//
+// ====== Hotkey commands ======
+//
+
+#[tauri::command]
+pub fn list_hotkey_presets(config: State<'_, ConfigState>) -> Vec<HotkeyPreset> {
+    config.get().hotkeys
+}
+
+// Bind a shortcut to start recording with a profile, replacing any preset on the same keys
+#[tauri::command]
+pub fn set_hotkey_preset(
+    app_handle: AppHandle,
+    config: State<'_, ConfigState>,
+    shortcut: String,
+    profile: Option<String>,
+) -> Result<(), String> {
+    let parsed = parse(&shortcut)?;
+    let current = config.get();
+    if let Some(ref name) = profile {
+        if !current.profiles.iter().any(|p| &p.name == name) {
+            return Err(format!("No profile named '{}'", name));
+        }
+    }
+
+    let mut presets = current.hotkeys;
+    presets.retain(|p| parse(&p.shortcut).ok() != Some(parsed));
+    presets.push(HotkeyPreset { shortcut, profile });
+
+    register_all(&app_handle, &presets)?;
+    config.update(|c| {
+        c.hotkeys = presets.clone();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn remove_hotkey_preset(app_handle: AppHandle, config: State<'_, ConfigState>, shortcut: String) -> Result<(), String> {
+    let parsed = parse(&shortcut)?;
+    let mut presets = config.get().hotkeys;
+    presets.retain(|p| parse(&p.shortcut).ok() != Some(parsed));
+
+    register_all(&app_handle, &presets)?;
+    config.update(|c| {
+        c.hotkeys = presets.clone();
+        Ok(())
+    })
+}
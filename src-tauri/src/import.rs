@@ -0,0 +1,224 @@
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use rodio::Source;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tracing::{info, warn};
+
+use crate::config::ConfigState;
+use crate::crypto::{self, EncryptionState};
+use crate::library::{Library, RecordingEntry, RecordingSource};
+use crate::lock::AppLock;
+use crate::storage;
+
+//
+// ====== Importing external audio into the library ======
+//
+
+const SUPPORTED_EXTENSIONS: [&str; 3] = ["wav", "mp3", "flac"];
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ImportFailure {
+    path: String,
+    error: String,
+}
+
+/// An imported file whose content was already in the library
+#[derive(Debug, Serialize, Clone)]
+pub struct ImportDuplicate {
+    path: String,
+    duplicate_of: String,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ImportCompleteEvent {
+    imported: Vec<RecordingEntry>,
+    failed: Vec<ImportFailure>,
+    duplicates: Vec<ImportDuplicate>,
+}
+
+/// Whether `path` has an extension the importer understands
+pub fn is_supported(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| SUPPORTED_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// Copy or convert each file into the library, in the save directory if one is set,
+/// and index it
+pub fn import_files(app_handle: &AppHandle, paths: &[PathBuf]) -> ImportCompleteEvent {
+    let library = app_handle.state::<Library>();
+    let mut result = ImportCompleteEvent::default();
+
+    for path in paths {
+        match import_file(app_handle, path) {
+            Ok(entry) => {
+                info!("Imported {} as {}", path.display(), entry.path);
+                if let Some(original) = library.find_duplicate(&entry) {
+                    warn!("{} has the same content as {}", entry.path, original.path);
+                    result.duplicates.push(ImportDuplicate {
+                        path: entry.path.clone(),
+                        duplicate_of: original.path,
+                    });
+                }
+                result.imported.push(entry);
+            }
+            Err(error) => {
+                warn!("Failed to import {}: {}", path.display(), error);
+                result.failed.push(ImportFailure {
+                    path: path.to_string_lossy().to_string(),
+                    error,
+                });
+            }
+        }
+    }
+
+    result
+}
+
+fn import_file(app_handle: &AppHandle, source: &Path) -> Result<RecordingEntry, String> {
+    let library = app_handle.state::<Library>();
+    if !source.is_file() {
+        return Err("Not a file".to_string());
+    }
+
+    let extension = source
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if !SUPPORTED_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(format!(
+            "Unsupported file type '{}', expected one of {:?}",
+            extension, SUPPORTED_EXTENSIONS
+        ));
+    }
+
+    let dest = unique_destination(library.dir(), source);
+
+    // WAVs that hound can parse are copied as-is, everything else is decoded to 16-bit PCM
+    let copied = extension == "wav" && hound::WavReader::open(source).is_ok();
+    let outcome = if copied {
+        fs::copy(source, &dest)
+            .map(|_| ())
+            .map_err(|e| format!("Failed to copy file: {}", e))
+    } else {
+        convert_to_wav(source, &dest)
+    };
+
+    if let Err(e) = outcome {
+        let _ = fs::remove_file(&dest);
+        return Err(e);
+    }
+
+    let mut entry = match app_handle.state::<Library>().probe(&dest) {
+        Ok(entry) => entry,
+        Err(e) => {
+            let _ = fs::remove_file(&dest);
+            return Err(e);
+        }
+    };
+    entry.created_at = chrono::Local::now().to_rfc3339();
+    entry.source = RecordingSource::Imported;
+    entry.original_path = Some(source.to_string_lossy().to_string());
+    let config = app_handle.state::<ConfigState>();
+    if let Err(e) = crypto::seal_new_recording(&config, &app_handle.state::<EncryptionState>(), &mut entry) {
+        let _ = fs::remove_file(&dest);
+        return Err(e);
+    }
+    // Into the save directory when one is set, the way new recordings go
+    let path = storage::relocate(app_handle, Path::new(&entry.path));
+    entry.file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    entry.path = path.to_string_lossy().to_string();
+
+    library.add(entry.clone())?;
+    Ok(entry)
+}
+
+fn convert_to_wav(source: &Path, dest: &Path) -> Result<(), String> {
+    let file = File::open(source).map_err(|e| format!("Failed to open file: {}", e))?;
+    let decoder = rodio::Decoder::new(BufReader::new(file))
+        .map_err(|e| format!("Failed to decode audio: {}", e))?;
+
+    let spec = hound::WavSpec {
+        channels: decoder.channels(),
+        sample_rate: decoder.sample_rate(),
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut writer = hound::WavWriter::create(dest, spec)
+        .map_err(|e| format!("Failed to create WAV file: {}", e))?;
+
+    let mut written = 0usize;
+    for sample in decoder {
+        writer
+            .write_sample(sample)
+            .map_err(|e| format!("Failed to write sample: {}", e))?;
+        written += 1;
+    }
+
+    writer
+        .finalize()
+        .map_err(|e| format!("Failed to finalize WAV: {}", e))?;
+
+    if written == 0 {
+        return Err("File contains no audio".to_string());
+    }
+    Ok(())
+}
+
+// Pick `import_<stem>.wav` in `dir`, adding a counter if the name is taken
+fn unique_destination(dir: &Path, source: &Path) -> PathBuf {
+    let stem: String = source
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "audio".to_string())
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+
+    let mut candidate = dir.join(format!("import_{}.wav", stem));
+    let mut counter = 1;
+    while candidate.exists() {
+        candidate = dir.join(format!("import_{}_{}.wav", stem, counter));
+        counter += 1;
+    }
+    candidate
+}
+
+/// Import files dropped onto a window without blocking the event loop
+pub fn import_dropped_files(app_handle: AppHandle, paths: Vec<PathBuf>) {
+    if let Err(e) = app_handle.state::<AppLock>().ensure_unlocked() {
+        warn!("Ignoring {} dropped files: {}", paths.len(), e);
+        return;
+    }
+    std::thread::spawn(move || {
+        let result = import_files(&app_handle, &paths);
+        let _ = app_handle.emit("import-complete", result);
+    });
+}
+
+// Import files picked in the UI
+#[tauri::command]
+pub async fn import_recordings(
+    paths: Vec<String>,
+    app_handle: AppHandle,
+    app_lock: State<'_, AppLock>,
+) -> Result<ImportCompleteEvent, String> {
+    app_lock.ensure_unlocked()?;
+    let paths = paths.into_iter().map(PathBuf::from).collect::<Vec<_>>();
+    // Decoding, converting and hashing would otherwise hold up the async runtime
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let result = import_files(&app_handle, &paths);
+        let _ = app_handle.emit("import-complete", result.clone());
+        result
+    })
+    .await
+    .map_err(|e| format!("Import failed: {}", e))?;
+    Ok(result)
+}
@@ -0,0 +1,50 @@
+use tauri::{AppHandle, Manager};
+use tracing::debug;
+
+//
+// ====== Recording indicator on the dock / taskbar icon ======
+//
+
+const MAIN_WINDOW: &str = "main";
+
+/// Mark (or unmark) the app icon so recording stays visible while the window is hidden
+pub fn set_recording_badge(app_handle: &AppHandle, recording: bool) {
+    let Some(window) = app_handle.get_webview_window(MAIN_WINDOW) else {
+        return;
+    };
+
+    #[cfg(target_os = "macos")]
+    let result = window.set_badge_label(recording.then(|| "REC".to_string()));
+
+    // Windows has no badges; a small red dot overlay on the taskbar button does the job
+    #[cfg(target_os = "windows")]
+    let result = window.set_overlay_icon(recording.then(|| tauri::image::Image::new_owned(red_dot(), DOT_SIZE, DOT_SIZE)));
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let result = window.set_badge_count(recording.then_some(1));
+
+    if let Err(e) = result {
+        // Not every desktop environment supports badges
+        debug!("Failed to update recording badge: {}", e);
+    }
+}
+
+#[cfg(target_os = "windows")]
+const DOT_SIZE: u32 = 16;
+
+#[cfg(target_os = "windows")]
+fn red_dot() -> Vec<u8> {
+    let center = (DOT_SIZE as f32 - 1.0) / 2.0;
+    let radius = DOT_SIZE as f32 / 2.0 - 1.0;
+    (0..DOT_SIZE * DOT_SIZE)
+        .flat_map(|i| {
+            let (x, y) = ((i % DOT_SIZE) as f32, (i / DOT_SIZE) as f32);
+            let inside = (x - center).hypot(y - center) <= radius;
+            if inside {
+                [220, 30, 30, 255]
+            } else {
+                [0, 0, 0, 0]
+            }
+        })
+        .collect()
+}
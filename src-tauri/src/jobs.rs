@@ -0,0 +1,583 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tracing::{info, warn};
+
+use crate::config;
+use crate::crypto;
+use crate::effects::{self, ProcessOptions};
+use crate::export::{self, ExportFormat};
+use crate::library::{self, Library};
+use crate::lock::AppLock;
+use crate::pipeline;
+use crate::sessions;
+use crate::processing::{self, DynamicsConfig, StereoConfig};
+use crate::spectrogram;
+use crate::storage;
+use crate::sync;
+use crate::transcription::{self, TranscriberKind};
+use crate::translation;
+use crate::versions;
+
+//
+// ====== Background job queue ======
+//
+
+const JOBS_FILE: &str = "jobs.json";
+const WORKERS: usize = 2;
+// Finished jobs kept around for `list_jobs`; older ones are dropped
+const MAX_FINISHED_JOBS: usize = 100;
+const CANCEL_POLL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JobKind {
+    Transcribe {
+        path: String,
+        /// The configured default when unset
+        #[serde(default)]
+        backend: Option<TranscriberKind>,
+    },
+    /// The recording's transcript into another language
+    Translate {
+        path: String,
+        target_language: String,
+    },
+    /// A short summary of the recording's transcript, transcribing it first if needed
+    Summarize {
+        path: String,
+    },
+    Convert {
+        path: String,
+        format: ExportFormat,
+    },
+    Normalize {
+        path: String,
+        #[serde(default = "pipeline::default_target_peak_db")]
+        target_peak_db: f32,
+        /// Replace the recording instead of writing a copy; the original is kept as a version
+        #[serde(default)]
+        in_place: bool,
+    },
+    Upload {
+        path: String,
+    },
+    /// A recording moved into the save directory after the move at the end of recording failed
+    Relocate {
+        path: String,
+    },
+    Spectrogram {
+        path: String,
+        width: u32,
+        height: u32,
+    },
+    Export {
+        paths: Vec<String>,
+        dest_zip: String,
+        #[serde(default)]
+        format: ExportFormat,
+        #[serde(default)]
+        dynamics: Option<DynamicsConfig>,
+        #[serde(default)]
+        stereo: Option<StereoConfig>,
+    },
+    Process {
+        path: String,
+        options: ProcessOptions,
+    },
+    Reverse {
+        path: String,
+        /// Always beside the source; defaults to a `_reversed` file
+        #[serde(default)]
+        output: Option<String>,
+    },
+    /// Each side of a stereo recording as its own mono file
+    SplitChannels {
+        path: String,
+    },
+    /// Recordings joined end to end into `dest`, e.g. a session's takes
+    Concatenate {
+        paths: Vec<String>,
+        dest: String,
+        #[serde(default)]
+        format: ExportFormat,
+    },
+}
+
+impl JobKind {
+    // Library recordings the job reads
+    fn paths(&self) -> Vec<&str> {
+        match self {
+            JobKind::Transcribe { path, .. }
+            | JobKind::Translate { path, .. }
+            | JobKind::Summarize { path }
+            | JobKind::Convert { path, .. }
+            | JobKind::Normalize { path, .. }
+            | JobKind::Upload { path }
+            | JobKind::Relocate { path }
+            | JobKind::Spectrogram { path, .. }
+            | JobKind::Process { path, .. }
+            | JobKind::Reverse { path, .. }
+            | JobKind::SplitChannels { path } => vec![path],
+            JobKind::Export { paths, .. } | JobKind::Concatenate { paths, .. } => {
+                paths.iter().map(String::as_str).collect()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: u64,
+    #[serde(flatten)]
+    pub kind: JobKind,
+    pub status: JobStatus,
+    /// 0.0 to 1.0
+    pub progress: f32,
+    /// File or URL the job produced
+    pub output: Option<String>,
+    /// Further files, from jobs that write more than one
+    #[serde(default)]
+    pub more_outputs: Vec<String>,
+    pub error: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Default)]
+struct QueueState {
+    jobs: Vec<Job>,
+    next_id: u64,
+    // Cancellation flags of the jobs currently running
+    running: HashMap<u64, Arc<AtomicBool>>,
+}
+
+/// Jobs persisted as `jobs.json` so queued and interrupted work resumes after a restart
+pub struct JobQueue {
+    path: PathBuf,
+    state: Mutex<QueueState>,
+    wake: Condvar,
+}
+
+impl JobQueue {
+    pub fn open(dir: &Path) -> Result<Self, String> {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create jobs directory: {}", e))?;
+
+        let path = dir.join(JOBS_FILE);
+        let mut jobs: Vec<Job> = if path.exists() {
+            let raw = fs::read_to_string(&path).map_err(|e| format!("Failed to read job queue: {}", e))?;
+            serde_json::from_str(&raw).unwrap_or_else(|e| {
+                warn!("Job queue is corrupt, starting empty: {}", e);
+                Vec::new()
+            })
+        } else {
+            Vec::new()
+        };
+
+        // Whatever was running when the app quit starts over
+        for job in jobs.iter_mut().filter(|job| job.status == JobStatus::Running) {
+            job.status = JobStatus::Queued;
+            job.progress = 0.0;
+        }
+        let pending = jobs.iter().filter(|job| job.status == JobStatus::Queued).count();
+        if pending > 0 {
+            info!("Resuming {} queued jobs", pending);
+        }
+
+        let queue = Self {
+            path,
+            state: Mutex::new(QueueState {
+                next_id: jobs.iter().map(|job| job.id + 1).max().unwrap_or(1),
+                jobs,
+                running: HashMap::new(),
+            }),
+            wake: Condvar::new(),
+        };
+        queue.persist(&queue.state.lock().unwrap().jobs)?;
+        Ok(queue)
+    }
+
+    fn persist(&self, jobs: &[Job]) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(jobs).map_err(|e| format!("Failed to serialize job queue: {}", e))?;
+        config::write_atomically(&self.path, &json).map_err(|e| format!("Failed to write job queue: {}", e))
+    }
+
+    pub fn list(&self) -> Vec<Job> {
+        self.state.lock().unwrap().jobs.clone()
+    }
+
+    /// Forget every job, finished or queued. Refused while any is running, since a
+    /// running job would go on writing its output.
+    pub fn clear(&self) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        if !state.running.is_empty() {
+            return Err("Jobs are still running; cancel them or wait for them to finish".to_string());
+        }
+        state.jobs.clear();
+        self.persist(&state.jobs)
+    }
+
+    fn enqueue(&self, app_handle: &AppHandle, kind: JobKind) -> Result<u64, String> {
+        let mut state = self.state.lock().unwrap();
+        let now = chrono::Local::now().to_rfc3339();
+        let job = Job {
+            id: state.next_id,
+            kind,
+            status: JobStatus::Queued,
+            progress: 0.0,
+            output: None,
+            more_outputs: Vec::new(),
+            error: None,
+            created_at: now.clone(),
+            updated_at: now,
+        };
+        state.next_id += 1;
+        state.jobs.push(job.clone());
+        self.persist(&state.jobs)?;
+        drop(state);
+
+        emit(app_handle, &job);
+        self.wake.notify_one();
+        Ok(job.id)
+    }
+
+    fn cancel(&self, app_handle: &AppHandle, id: u64) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(flag) = state.running.get(&id) {
+            // The worker notices at its next checkpoint and records the cancellation
+            flag.store(true, Ordering::SeqCst);
+            return Ok(());
+        }
+
+        let job = state
+            .jobs
+            .iter_mut()
+            .find(|job| job.id == id)
+            .ok_or_else(|| format!("No job with id {}", id))?;
+        if job.status != JobStatus::Queued {
+            return Err(format!("Job {} has already finished", id));
+        }
+        job.status = JobStatus::Cancelled;
+        job.updated_at = chrono::Local::now().to_rfc3339();
+        let job = job.clone();
+        self.persist(&state.jobs)?;
+        drop(state);
+
+        emit(app_handle, &job);
+        Ok(())
+    }
+
+    // Mark the first queued job matching `wanted` cancelled on behalf of job `by`, which
+    // does its work instead
+    fn take_over(&self, app_handle: &AppHandle, by: u64, wanted: impl Fn(&JobKind) -> bool) -> Option<JobKind> {
+        let mut state = self.state.lock().unwrap();
+        let job = state
+            .jobs
+            .iter_mut()
+            .find(|job| job.status == JobStatus::Queued && wanted(&job.kind))?;
+        job.status = JobStatus::Cancelled;
+        job.error = Some(format!("Done as part of job {}", by));
+        job.updated_at = chrono::Local::now().to_rfc3339();
+        let job = job.clone();
+        if let Err(e) = self.persist(&state.jobs) {
+            warn!("{}", e);
+        }
+        drop(state);
+
+        emit(app_handle, &job);
+        Some(job.kind)
+    }
+
+    // Block until a queued job is available and mark it running
+    fn next(&self, app_handle: &AppHandle) -> (Job, Arc<AtomicBool>) {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(job) = state.jobs.iter_mut().find(|job| job.status == JobStatus::Queued) {
+                job.status = JobStatus::Running;
+                job.updated_at = chrono::Local::now().to_rfc3339();
+                let job = job.clone();
+                let cancelled = Arc::new(AtomicBool::new(false));
+                state.running.insert(job.id, cancelled.clone());
+                if let Err(e) = self.persist(&state.jobs) {
+                    warn!("{}", e);
+                }
+                drop(state);
+
+                emit(app_handle, &job);
+                return (job, cancelled);
+            }
+            state = self.wake.wait(state).unwrap();
+        }
+    }
+
+    fn set_progress(&self, app_handle: &AppHandle, id: u64, progress: f32) {
+        let mut state = self.state.lock().unwrap();
+        let Some(job) = state.jobs.iter_mut().find(|job| job.id == id) else {
+            return;
+        };
+        // Progress isn't persisted; an interrupted job starts over anyway
+        job.progress = progress.clamp(0.0, 1.0);
+        let job = job.clone();
+        drop(state);
+
+        emit(app_handle, &job);
+    }
+
+    fn finish(&self, app_handle: &AppHandle, id: u64, result: Result<Vec<String>, String>) {
+        let mut state = self.state.lock().unwrap();
+        let cancelled = state
+            .running
+            .remove(&id)
+            .is_some_and(|flag| flag.load(Ordering::SeqCst));
+        let Some(job) = state.jobs.iter_mut().find(|job| job.id == id) else {
+            return;
+        };
+
+        match result {
+            Ok(outputs) => {
+                job.status = JobStatus::Completed;
+                job.progress = 1.0;
+                let mut outputs = outputs.into_iter();
+                job.output = outputs.next();
+                job.more_outputs = outputs.collect();
+            }
+            Err(_) if cancelled => job.status = JobStatus::Cancelled,
+            Err(e) => {
+                warn!("Job {} failed: {}", id, e);
+                job.status = JobStatus::Failed;
+                job.error = Some(e);
+            }
+        }
+        job.updated_at = chrono::Local::now().to_rfc3339();
+        let job = job.clone();
+
+        let finished = state.jobs.iter().filter(|job| job.status.is_finished()).count();
+        let mut excess = finished.saturating_sub(MAX_FINISHED_JOBS);
+        state.jobs.retain(|job| {
+            let drop_job = excess > 0 && job.status.is_finished();
+            if drop_job {
+                excess -= 1;
+            }
+            !drop_job
+        });
+        if let Err(e) = self.persist(&state.jobs) {
+            warn!("{}", e);
+        }
+        drop(state);
+
+        emit(app_handle, &job);
+    }
+}
+
+fn emit(app_handle: &AppHandle, job: &Job) {
+    let _ = app_handle.emit("job-progress", job);
+}
+
+/// Handle given to running jobs for reporting progress and noticing cancellation
+pub struct JobContext {
+    app_handle: AppHandle,
+    id: u64,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl JobContext {
+    pub fn app_handle(&self) -> &AppHandle {
+        &self.app_handle
+    }
+
+    pub fn progress(&self, progress: f32) {
+        self.app_handle
+            .state::<JobQueue>()
+            .set_progress(&self.app_handle, self.id, progress);
+    }
+
+    /// Err once the job has been cancelled; call between units of work
+    pub fn check_cancelled(&self) -> Result<(), String> {
+        if self.cancelled.load(Ordering::SeqCst) {
+            return Err("Job was cancelled".to_string());
+        }
+        Ok(())
+    }
+
+    /// Take a queued job matching `wanted` off the queue so this one can do its work,
+    /// rather than waiting for it on a worker that may never free up
+    pub fn take_over(&self, wanted: impl Fn(&JobKind) -> bool) -> Option<JobKind> {
+        self.app_handle
+            .state::<JobQueue>()
+            .take_over(&self.app_handle, self.id, wanted)
+    }
+
+    /// Sleep that ends early if the job is cancelled
+    pub fn sleep(&self, duration: Duration) -> Result<(), String> {
+        let deadline = Instant::now() + duration;
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            self.check_cancelled()?;
+            thread::sleep(remaining.min(CANCEL_POLL));
+        }
+        self.check_cancelled()
+    }
+}
+
+/// Start the worker threads; call once the library is managed
+pub fn start_workers(app_handle: &AppHandle) {
+    for _ in 0..WORKERS {
+        let app_handle = app_handle.clone();
+        thread::spawn(move || loop {
+            let queue = app_handle.state::<JobQueue>();
+            let (job, cancelled) = queue.next(&app_handle);
+            let context = JobContext {
+                app_handle: app_handle.clone(),
+                id: job.id,
+                cancelled,
+            };
+            let result = run(&context, &job.kind);
+            queue.finish(&app_handle, job.id, result);
+        });
+    }
+}
+
+/// Queue a job from elsewhere in the app
+pub fn enqueue(app_handle: &AppHandle, kind: JobKind) -> Result<u64, String> {
+    app_handle.state::<JobQueue>().enqueue(app_handle, kind)
+}
+
+// Returns the files or URLs the job produced, if any
+fn run(context: &JobContext, kind: &JobKind) -> Result<Vec<String>, String> {
+    match kind {
+        JobKind::Transcribe { path, backend } => transcription::run_job(context, path, *backend).map(|_| Vec::new()),
+        JobKind::Translate { path, target_language } => {
+            translation::run_job(context, path, target_language).map(|_| Vec::new())
+        }
+        JobKind::Summarize { path } => translation::run_summary_job(context, path).map(|_| Vec::new()),
+        JobKind::Convert { path, format } => {
+            let path = Path::new(path);
+            if *format == ExportFormat::Wav {
+                return Err("Recording is already a WAV file".to_string());
+            }
+            ensure_plaintext(path)?;
+            let output = processing::derived_path(path, "converted", format.extension());
+            processing::encode_with_ffmpeg_until(path, &output, format.codec_args(), || context.check_cancelled())?;
+            info!("Converted {} to {}", path.display(), output.display());
+            Ok(vec![output.to_string_lossy().to_string()])
+        }
+        JobKind::Normalize {
+            path,
+            target_peak_db,
+            in_place,
+        } => {
+            let path = Path::new(path);
+            ensure_plaintext(path)?;
+            let mut buffer = processing::read_wav(path)?;
+            context.check_cancelled()?;
+            context.progress(0.5);
+            processing::normalize(&mut buffer, *target_peak_db);
+            if *in_place {
+                versions::overwrite(&context.app_handle, path, "normalize", |output| {
+                    processing::write_wav(output, &buffer)
+                })?;
+                info!("Normalized {} in place", path.display());
+                return Ok(vec![path.to_string_lossy().to_string()]);
+            }
+            let output = processing::derived_path(path, "normalized", "wav");
+            processing::write_wav(&output, &buffer)?;
+            let output = library::add_derived(&context.app_handle, path, &output)?;
+            info!("Normalized {} to {}", path.display(), output);
+            Ok(vec![output])
+        }
+        JobKind::Upload { path } => sync::run_upload(context, Path::new(path)).map(|output| vec![output]),
+        JobKind::Relocate { path } => storage::run_job(context, path).map(|output| output.into_iter().collect()),
+        JobKind::Spectrogram { path, width, height } => {
+            ensure_plaintext(Path::new(path))?;
+            spectrogram::run_job(context, path, *width, *height).map(|output| vec![output])
+        }
+        JobKind::Export {
+            paths,
+            dest_zip,
+            format,
+            dynamics,
+            stereo,
+        } => {
+            let output = export::run_job(context, paths, dest_zip, *format, dynamics.as_ref(), stereo.as_ref())?;
+            Ok(vec![output])
+        }
+        JobKind::Process { path, options } => {
+            effects::run_process_job(context, path, options).map(|output| vec![output])
+        }
+        JobKind::Reverse { path, output } => {
+            effects::run_reverse_job(context, path, output.as_deref()).map(|output| vec![output])
+        }
+        JobKind::SplitChannels { path } => effects::run_split_job(context, path),
+        JobKind::Concatenate { paths, dest, format } => {
+            sessions::run_concatenate_job(context, paths, dest, *format).map(|output| vec![output])
+        }
+    }
+}
+
+/// Refuse an encrypted recording: processing writes its output next to the source, which
+/// would leave plaintext beside encrypted files
+pub fn ensure_plaintext(path: &Path) -> Result<(), String> {
+    if crypto::is_encrypted(path) {
+        return Err("Encrypted recordings can only be processed through export".to_string());
+    }
+    Ok(())
+}
+
+//
+// ====== Job commands ======
+//
+
+// Queue a job for a library recording and return its id; progress arrives as `job-progress` events
+#[tauri::command]
+pub fn enqueue_job(
+    app_handle: AppHandle,
+    app_lock: State<'_, AppLock>,
+    library: State<'_, Library>,
+    job: JobKind,
+) -> Result<u64, String> {
+    app_lock.ensure_unlocked()?;
+    if let Some(missing) = job.paths().into_iter().find(|path| library.get(path).is_none()) {
+        return Err(format!("Recording not found in library: {}", missing));
+    }
+    enqueue(&app_handle, job)
+}
+
+#[tauri::command]
+pub fn list_jobs(app_lock: State<'_, AppLock>, queue: State<'_, JobQueue>) -> Result<Vec<Job>, String> {
+    app_lock.ensure_unlocked()?;
+    Ok(queue.list())
+}
+
+// Cancel a queued job, or ask a running one to stop at its next checkpoint
+#[tauri::command]
+pub fn cancel_job(
+    app_handle: AppHandle,
+    app_lock: State<'_, AppLock>,
+    queue: State<'_, JobQueue>,
+    id: u64,
+) -> Result<(), String> {
+    app_lock.ensure_unlocked()?;
+    queue.cancel(&app_handle, id)
+}
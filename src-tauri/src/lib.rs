@@ -1,215 +1,361 @@
+use std::fmt;
 use std::fs::File;
-use std::io::{Read, Write, BufReader};
-// use std::path::PathBuf;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
 use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
 use std::time::Duration;
 
 use base64::prelude::*;
-use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::SampleFormat;
 use serde::Serialize;
+use symphonia::core::io::MediaSource;
 use tauri::{AppHandle, Manager, State, Emitter};
 use tempfile::NamedTempFile;
+use tracing::{debug, error, info, warn};
+
+mod announce;
+mod audit;
+mod backup;
+mod calendar;
+mod compliance;
+mod config;
+mod countdown;
+mod crypto;
+mod cues;
+mod decode;
+mod device_check;
+mod device_follow;
+mod edits;
+mod effects;
+mod eq;
+mod export;
+mod files;
+mod focus;
+mod hooks;
+#[cfg(desktop)]
+mod hotkeys;
+mod import;
+mod indicator;
+mod jobs;
+mod library;
+mod lock;
+mod logging;
+mod meeting_detect;
+mod metronome;
+mod mic_test;
+mod midi;
+#[cfg(desktop)]
+mod mini_recorder;
+#[cfg(mobile)]
+mod mobile;
+mod monitor;
+mod navigation;
+mod notifications;
+mod osc;
+mod overdub;
+mod overlay;
+mod permissions;
+mod pipeline;
+mod power;
+mod privacy;
+mod quality;
+mod remote;
+mod retention;
+#[cfg(desktop)]
+mod screen_lock;
+mod secrets;
+mod sessions;
+mod setup;
+mod share;
+mod share_sheet;
+mod silence;
+mod speak;
+mod spectrogram;
+mod storage;
+mod stream;
+mod sync;
+mod system_audio;
+mod tempo;
+mod test_tone;
+mod transcript;
+mod transcription;
+mod translation;
+mod tuner;
+mod versions;
+mod voice_commands;
+mod watch;
+
+use rekt_core::backend::{AudioBackend, AudioStream, CpalBackend, DeviceInfo, StreamFormat};
+use rekt_core::dual_mono::{DevicePair, DualMono};
+use rekt_core::latency::{self, RoundTrip};
+use rekt_core::recording::{self, RecorderState, RecordingState};
+use rekt_core::self_test::SelfTestReport;
+use rekt_core::telemetry::SessionTelemetry;
+use rekt_core::{dsp, processing, spectrum};
+
+use audit::{AuditAction, Initiator};
+use config::ConfigState;
+use crypto::EncryptionState;
+use device_check::DeviceBusy;
+use decode::{FormatHint, PlaybackEnd, PlaybackError, PlaybackErrorKind};
+use library::{Library, Marker};
+use lock::AppLock;
 
 //
 // ====== AUDIO INPUT (RECORDING) STATE ======
 //
 
-struct AudioInputStream {
-    #[allow(dead_code)] // We only hold the stream to keep it alive
-    stream: Box<dyn StreamTrait>,
-}
+// How long starting a recording waits for the input device to deliver
+const START_TIMEOUT: Duration = Duration::from_secs(5);
 
-unsafe impl Send for AudioInputStream {}
-unsafe impl Sync for AudioInputStream {}
+// Roughly 15 spectrum updates per second
+const SPECTRUM_INTERVAL: Duration = Duration::from_millis(66);
 
-#[derive(Default)]
-struct RecordingState {
-    is_recording: AtomicBool,
-    audio_data: Mutex<Vec<i16>>,
-    channels: Mutex<u16>,
-    sample_rate: Mutex<u32>,
-    input_stream: Mutex<Option<AudioInputStream>>,
+// The format the input is opened in, from the active profile or `set_audio_config`
+fn requested_format(app_handle: &AppHandle) -> StreamFormat {
+    let state = app_handle.state::<Arc<RecordingState>>();
+    let stored = (*state.channels.lock().unwrap(), *state.sample_rate.lock().unwrap());
+    device_check::requested_format(app_handle.state::<ConfigState>().active_profile().as_ref(), stored)
 }
 
-/// Background recorder spawns a thread that keeps recording
-struct BackgroundRecorder {
-    join_handle: Option<thread::JoinHandle<()>>,
-    stop_flag: Arc<AtomicBool>,
+// Open both devices of a pair at `sample_rate`, the left one reporting itself as the
+// joined stereo input
+fn open_pair(
+    backend: &dyn AudioBackend,
+    state: &Arc<RecordingState>,
+    pair: &DevicePair,
+    sample_rate: u32,
+) -> Result<(AudioStream, AudioStream, Arc<DualMono>), String> {
+    let joiner = DualMono::new();
+    let (left_input, right_input) = RecordingState::capture_pair(state, &joiner);
+    let format = StreamFormat {
+        channels: 0,
+        sample_rate,
+    };
+    let mut left = backend.open_input(Some(&pair.left), format, left_input)?;
+    let right = backend.open_input(Some(&pair.right), format, right_input)?;
+    joiner.configure(&left.device, &right.device);
+    info!(
+        "Pairing {} ({} Hz) and {} ({} Hz) into stereo",
+        left.device.name, left.device.sample_rate, right.device.name, right.device.sample_rate
+    );
+    left.device = DeviceInfo {
+        name: format!("{} + {}", left.device.name, right.device.name),
+        channels: 2,
+        ..left.device.clone()
+    };
+    Ok((left, right, joiner))
 }
 
-impl Default for BackgroundRecorder {
-    fn default() -> Self {
-        Self {
-            join_handle: None,
-            stop_flag: Arc::new(AtomicBool::new(false)),
-        }
-    }
+/// Background recorder spawns a thread that keeps recording until the recorder
+/// leaves the recording states
+#[derive(Default)]
+struct BackgroundRecorder {
+    join_handle: Option<thread::JoinHandle<()>>,
 }
 
 impl BackgroundRecorder {
-    fn start(&mut self, state: Arc<RecordingState>) -> Result<(), String> {
+    /// Open the input on a new thread and return once audio is flowing, with the device
+    /// it settled on. `session` ties the thread to this start: should it outlive the
+    /// wait, it winds down instead of joining a later recording.
+    fn start(&mut self, state: Arc<RecordingState>, app_handle: AppHandle, session: u64) -> Result<DeviceInfo, StartError> {
         // Make sure we're not already recording
         if self.join_handle.is_some() {
-            return Err("Already recording".to_string());
+            return Err("Already recording".to_string().into());
         }
         
-        state.is_recording.store(false, Ordering::SeqCst); // Reset in case.
-        self.stop_flag.store(false, Ordering::SeqCst);
-
         // Clone arcs for the thread
-        let stop_flag = Arc::clone(&self.stop_flag);
         let thread_state = Arc::clone(&state);
+        let (ready_sender, ready) = mpsc::sync_channel(1);
 
         // Create the thread
         let handle = thread::spawn(move || {
-            println!("Recording thread started");
-            
-            // Clear audio buffer before new recording
-            {
-                let mut audio_data = thread_state.audio_data.lock().unwrap();
-                audio_data.clear();
+            info!("Recording thread started");
+
+            // ALWAYS initialize the input stream each time, on the default input device
+            // or the configured pair of devices
+            let backend = Arc::clone(app_handle.state::<Arc<dyn AudioBackend>>().inner());
+            let device_pair = thread_state.device_pair.lock().unwrap().clone();
+            let wanted = match &device_pair {
+                Some(pair) => vec![pair.left.clone(), pair.right.clone()],
+                None => backend.default_input().map(|device| vec![device.name]).unwrap_or_default(),
+            };
+            // Name the program holding the device, rather than fail with a stream error
+            if let Some(busy) = wanted.iter().find_map(|name| device_check::find_busy(name)) {
+                warn!("{}", busy);
+                let _ = ready_sender.send(Err(StartError::DeviceBusy(busy)));
+                return;
             }
-
-            // ALWAYS initialize the input stream each time
-            let host = cpal::default_host();
-
-            // Get the default input device
-            let device = match host.default_input_device() {
-                Some(dev) => dev,
-                None => {
-                    println!("Error: No input device available");
-                    return;
-                }
+            let wanted = if wanted.is_empty() { "The input device".to_string() } else { wanted.join(" + ") };
+
+            let requested = requested_format(&app_handle);
+            let opened = match &device_pair {
+                Some(pair) => open_pair(backend.as_ref(), &thread_state, pair, requested.sample_rate)
+                    .map(|(left, right, joiner)| (left, Some((right, joiner)))),
+                None => backend
+                    .open_input(None, requested, RecordingState::capture(&thread_state))
+                    .map(|stream| (stream, None)),
             };
-
-            println!("Using input device: {}", device.name().unwrap_or_else(|_| "unknown".to_string()));
-
-            // Get default config for this device
-            let config = match device.default_input_config() {
-                Ok(cfg) => cfg,
+            let (stream, paired) = match opened {
+                Ok(opened) => opened,
                 Err(e) => {
-                    println!("Error getting default input config: {}", e);
+                    error!("{}", e);
+                    let _ = ready_sender.send(Err(StartError::opening(&wanted, e)));
                     return;
                 }
             };
 
-            // Store the actual device format into the state
-            let actual_channels = config.channels();
-            let actual_sample_rate = config.sample_rate().0;
-
-            {
-                let mut ch_lock = thread_state.channels.lock().unwrap();
-                let mut sr_lock = thread_state.sample_rate.lock().unwrap();
-                *ch_lock = actual_channels;
-                *sr_lock = actual_sample_rate;
+            // Starting gave up on us while the device was opening
+            if thread_state.session.load(Ordering::SeqCst) != session {
+                warn!("Input device opened after its recording gave up waiting; closing it");
+                return;
             }
 
-            println!("Recording with {} channel(s) at {} Hz", actual_channels, actual_sample_rate);
-
-            let err_fn = |err| eprintln!("An error occurred on the input stream: {}", err);
-
-            let i16_state = Arc::clone(&thread_state);
-            let u16_state = Arc::clone(&thread_state);
-            let f32_state = Arc::clone(&thread_state);
-
-            // Build the input stream using the actual default config
-            let stream = match config.sample_format() {
-                SampleFormat::I16 => device.build_input_stream(
-                    &config.into(),
-                    move |data: &[i16], _: &cpal::InputCallbackInfo| {
-                        if i16_state.is_recording.load(Ordering::SeqCst) {
-                            if let Ok(mut audio_data) = i16_state.audio_data.lock() {
-                                audio_data.extend_from_slice(data);
-                            }
-                        }
-                    },
-                    err_fn,
-                    None,
-                ),
-                SampleFormat::U16 => device.build_input_stream(
-                    &config.into(),
-                    move |data: &[u16], _: &cpal::InputCallbackInfo| {
-                        if u16_state.is_recording.load(Ordering::SeqCst) {
-                            if let Ok(mut audio_data) = u16_state.audio_data.lock() {
-                                for &sample in data {
-                                    let sample = ((sample as i32) - 32768) as i16;
-                                    audio_data.push(sample);
-                                }
-                            }
-                        }
-                    },
-                    err_fn,
-                    None,
-                ),
-                SampleFormat::F32 => device.build_input_stream(
-                    &config.into(),
-                    move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                        if f32_state.is_recording.load(Ordering::SeqCst) {
-                            if let Ok(mut audio_data) = f32_state.audio_data.lock() {
-                                for &sample in data {
-                                    let clamped = sample.clamp(-1.0, 1.0);
-                                    let converted = (clamped * i16::MAX as f32) as i16;
-                                    audio_data.push(converted);
-                                }
-                            }
-                        }
-                    },
-                    err_fn,
-                    None,
-                ),
-                _ => {
-                    println!("Unsupported sample format.");
-                    return;
+            let device = stream.device.clone();
+            let device_name = device.name.clone();
+            info!("Using input device: {}", device_name);
+
+            // The stream's own format is what the samples are in, whatever was asked for
+            let actual_channels = stream.device.channels;
+            let actual_sample_rate = stream.device.sample_rate;
+            thread_state.begin_capture(session, actual_channels, actual_sample_rate);
+
+            // Open the take with what the idle monitor heard just before it; a pair of
+            // devices has no single monitored input to take it from
+            let pre_roll = thread_state.pre_roll.lock().unwrap().take(actual_channels, actual_sample_rate);
+            match pre_roll {
+                Some(samples) if device_pair.is_none() => {
+                    info!("Starting with {} pre-roll samples", samples.len());
+                    *thread_state.lead_in.lock().unwrap() = samples;
                 }
-            };
-
-            let stream = match stream {
-                Ok(s) => s,
-                Err(e) => {
-                    println!("Error building input stream: {}", e);
-                    return;
-                }
-            };
+                Some(_) => {}
+                None => debug!("No pre-roll to start with"),
+            }
 
-            // Store the stream in our state so it won't get dropped
+            // Warn rather than silently record in a different format than was asked for
+            if let Some(mismatch) =
+                device_check::check_format(&device_name, requested, (actual_channels, actual_sample_rate))
             {
-                let mut input_stream = thread_state.input_stream.lock().unwrap();
-                *input_stream = Some(AudioInputStream {
-                    stream: Box::new(stream),
-                });
+                warn!("Input device format differs from the configured one: {:?}", mismatch);
+                let _ = app_handle.emit("device-mismatch", mismatch);
             }
 
-            // Start the stream
-            if let Err(e) = thread_state.input_stream.lock().unwrap().as_ref().unwrap().stream.play() {
-                println!("Error starting input stream: {}", e);
+            *thread_state.input_filter.lock().unwrap() = app_handle
+                .state::<ConfigState>()
+                .active_profile()
+                .and_then(|profile| profile.input_filter)
+                .map(|filter| dsp::InputFilter::new(&filter, actual_channels, actual_sample_rate));
+
+            info!("Recording with {} channel(s) at {} Hz", actual_channels, actual_sample_rate);
+            thread_state.telemetry.lock().unwrap().begin(actual_channels, actual_sample_rate);
+
+            // Store the streams in our state so they won't get dropped, then start them. The
+            // right-hand device of a pair goes first so its audio is waiting for the left.
+            let joiner = paired.map(|(right, joiner)| {
+                *thread_state.paired_stream.lock().unwrap() = Some(right);
+                joiner
+            });
+            let started = match thread_state.paired_stream.lock().unwrap().as_ref() {
+                Some(right) => right.play(),
+                None => Ok(()),
+            }
+            .and_then(|_| thread_state.input_stream.lock().unwrap().insert(stream).play());
+            if let Err(e) = started {
+                error!("{}", e);
+                thread_state.input_stream.lock().unwrap().take();
+                thread_state.paired_stream.lock().unwrap().take();
+                let _ = ready_sender.send(Err(StartError::opening(&device_name, e)));
                 return;
             }
+            let _ = ready_sender.send(Ok(device));
+
+            // Keep the thread alive until we stop, publishing the spectrum of the latest audio
+            // and watching for a dead or muted input
+            let mut analyzer = spectrum::SpectrumAnalyzer::default();
+            let mut silence = silence::SilenceDetector::new(app_handle.state::<ConfigState>().get().silence_warning);
+            let started_at = std::time::Instant::now();
+            let mut signal_checked = false;
+            let current = || {
+                thread_state.session.load(Ordering::SeqCst) == session
+                    && matches!(
+                        thread_state.recorder_state(),
+                        RecorderState::Starting | RecorderState::Recording | RecorderState::Paused
+                    )
+            };
+            while current() {
+                thread::sleep(SPECTRUM_INTERVAL);
+
+                if !signal_checked && started_at.elapsed() >= device_check::SIGNAL_GRACE_PERIOD {
+                    signal_checked = true;
+                    let no_signal = device_check::check_signal(&device_name, &thread_state.audio_data.lock().unwrap());
+                    if let Some(no_signal) = no_signal {
+                        warn!("No usable signal from {}: {:?}", device_name, no_signal);
+                        let _ = app_handle.emit("no-signal", no_signal);
+                    }
+                }
 
-            // Indicate recording is now active
-            thread_state.is_recording.store(true, Ordering::SeqCst);
+                let recent = {
+                    let audio_data = thread_state.audio_data.lock().unwrap();
+                    let wanted = spectrum::FFT_SIZE * actual_channels as usize;
+                    audio_data[audio_data.len().saturating_sub(wanted)..].to_vec()
+                };
+                let event = analyzer.analyze(&recent, actual_channels, actual_sample_rate);
+                let _ = app_handle.emit("audio-spectrum", event);
+
+                // Tell the user while they can still do something about a struggling machine
+                for warning in thread_state.telemetry.lock().unwrap().new_warnings() {
+                    warn!("Capture is struggling: {}", warning.message);
+                    let _ = app_handle.emit("audio-dropout-warning", warning);
+                }
 
-            // Keep the thread alive until we stop
-            while !stop_flag.load(Ordering::SeqCst) {
-                thread::sleep(Duration::from_millis(100));
+                let level = processing::rms(&recent.iter().map(|&s| s as f32 / i16::MAX as f32).collect::<Vec<_>>());
+                if let Some(warning) = silence.update(processing::to_db(level)) {
+                    warn!("Input has been silent for {} s", warning.silent_secs);
+                    if silence.notify() {
+                        notifications::notify(
+                            &app_handle,
+                            "Recording is silent",
+                            &format!("No input for {} seconds. Check that the microphone isn't muted.", warning.silent_secs),
+                        );
+                    }
+                    let _ = app_handle.emit("silence-warning", warning);
+                }
             }
 
-            // Turn off recording
-            thread_state.is_recording.store(false, Ordering::SeqCst);
+            // Release the device and let live consumers know the audio has ended
+            thread_state.input_stream.lock().unwrap().take();
+            thread_state.paired_stream.lock().unwrap().take();
+            if let Some(joiner) = joiner {
+                info!("Right input of the pair ended {:+.0} ppm off the left's clock", joiner.drift_ppm());
+            }
+            thread_state.taps.lock().unwrap().clear();
 
-            println!("Recording thread stopped");
+            info!("Recording thread stopped");
         });
 
         self.join_handle = Some(handle);
-        Ok(())
+        match ready.recv_timeout(START_TIMEOUT) {
+            Ok(Ok(device)) => Ok(device),
+            Ok(Err(e)) => {
+                self.stop()?;
+                Err(e)
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                self.stop()?;
+                Err("The recording thread stopped before capture began".to_string().into())
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                // The device open may be hung; leave the thread to notice it's stale
+                self.join_handle = None;
+                Err(format!("The input device didn't start within {} seconds", START_TIMEOUT.as_secs()).into())
+            }
+        }
     }
 
+    // The thread winds down by itself once the recorder is stopping
     fn stop(&mut self) -> Result<(), String> {
-        // Signal the thread to stop
-        self.stop_flag.store(true, Ordering::SeqCst);
-
         // Join the thread if it exists
         if let Some(handle) = self.join_handle.take() {
             handle.join().map_err(|_| "Failed to join recording thread".to_string())?;
@@ -225,6 +371,8 @@ impl BackgroundRecorder {
 // ====== AUDIO OUTPUT (PLAYBACK) STATE ======
 //
 
+const MAX_REPLAY_SECS: f32 = 60.0;
+
 struct AudioOutputStream {
     #[allow(dead_code)] // Kept alive
     stream: rodio::OutputStream,
@@ -240,12 +388,16 @@ struct AudioPlaybackState {
     current_playback_id: Mutex<Option<String>>,
     output_stream: Mutex<Option<AudioOutputStream>>,
     device_initialized: AtomicBool,
+    // Seek handle for the file playing now, if any
+    control: Mutex<Option<decode::PlaybackControl>>,
 }
 
 #[derive(Debug, Serialize)]
 struct AudioRecordingResponse {
     success: bool,
     path: Option<String>,
+    /// Set when the input delivered nothing, so there is no file
+    no_audio_captured: bool,
     error: Option<String>,
 }
 
@@ -264,6 +416,12 @@ struct AudioPlaybackResponse {
     error: Option<String>,
 }
 
+#[derive(Debug, Serialize, Clone)]
+struct RecordingSaveProgressEvent {
+    path: String,
+    progress: f32,
+}
+
 #[derive(Debug, Serialize, Clone)]
 struct AudioPlaybackEvent {
     playback_id: String,
@@ -273,59 +431,294 @@ struct AudioPlaybackEvent {
 struct AudioConfigResponse {
     success: bool,
     device_name: String,
-    available_devices: Vec<AudioDeviceInfo>,
-    current_device: AudioDeviceInfo,
+    available_devices: Vec<DeviceInfo>,
+    current_device: DeviceInfo,
     error: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
-struct AudioDeviceInfo {
-    name: String,
-    channels: u16,
-    sample_rate: u32,
-    formats: Vec<String>,
-}
-
 //
 // ========== Tauri Commands ==========
 //
 
-// Start recording
+// Start recording; with `max_duration` (seconds) it stops and saves itself at the limit.
+// Returns once audio is really being captured, with the device and format in use.
 #[tauri::command]
-fn start_recording(
-    state: State<'_, Arc<RecordingState>>,
-    recorder: State<'_, Mutex<BackgroundRecorder>>,
-) -> Result<(), String> {
-    if state.is_recording.load(Ordering::SeqCst) {
-        return Err("Already recording".to_string());
+async fn start_recording(app_handle: AppHandle, max_duration: Option<u64>) -> Result<DeviceInfo, String> {
+    if max_duration == Some(0) {
+        return Err("Maximum duration must be at least one second".to_string());
+    }
+    let handle = app_handle.clone();
+    let start = move || audit::initiated_by(Initiator::Ui, || start_recording_internal(&handle));
+    let device = tauri::async_runtime::spawn_blocking(start)
+        .await
+        .map_err(|e| format!("Starting the recording failed: {}", e))??;
+    if let Some(seconds) = max_duration {
+        countdown::limit(app_handle, Duration::from_secs(seconds));
     }
+    Ok(device)
+}
+
+// Stop recording and write WAV file; long recordings report `recording-save-progress` meanwhile
+#[tauri::command]
+async fn stop_recording(app_handle: AppHandle) -> Result<AudioRecordingResponse, String> {
+    let stop = move || audit::initiated_by(Initiator::Ui, || stop_recording_internal(&app_handle));
+    let result = tauri::async_runtime::spawn_blocking(stop)
+        .await
+        .map_err(|e| format!("Saving the recording failed: {}", e))?;
+
+    match result {
+        Ok(filepath) => Ok(AudioRecordingResponse {
+            success: true,
+            path: Some(filepath.to_string_lossy().to_string()),
+            no_audio_captured: false,
+            error: None,
+        }),
+        Err(StopError::NoAudioCaptured) => Ok(AudioRecordingResponse {
+            success: false,
+            path: None,
+            no_audio_captured: true,
+            error: Some(StopError::NoAudioCaptured.to_string()),
+        }),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// Drop a marker at the current position of the recording in progress
+#[tauri::command]
+fn add_marker(state: State<'_, Arc<RecordingState>>, label: Option<String>) -> Result<Marker, String> {
+    state.add_marker(label)
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct RecordingStartFailedEvent {
+    error: String,
+    /// Set when another program holds the input device
+    device_busy: Option<DeviceBusy>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct RecorderStateEvent {
+    previous: RecorderState,
+    state: RecorderState,
+}
+
+// Move the recorder to `next` and tell the frontend
+fn set_recorder_state(app_handle: &AppHandle, next: RecorderState) -> Result<(), String> {
+    let previous = app_handle.state::<Arc<RecordingState>>().transition(next)?;
+    debug!("Recorder {:?} -> {:?}", previous, next);
+    let _ = app_handle.emit("recorder-state-changed", RecorderStateEvent { previous, state: next });
+    announce::state_changed(app_handle, previous, next);
+    overlay::state_changed(app_handle, next);
+    overdub::state_changed(app_handle, next);
+    monitor::state_changed(app_handle, next);
+    speak::confirm(app_handle, previous, next);
+    if let Some(cue) = cues::for_transition(previous, next) {
+        cues::play(app_handle, cue);
+    }
+    compliance::state_changed(app_handle, previous, next);
+    audit::state_changed(app_handle, previous, next);
+    Ok(())
+}
+
+// Stop keeping input until resumed; the recording stays open
+#[tauri::command]
+fn pause_recording(app_handle: AppHandle) -> Result<(), String> {
+    audit::initiated_by(Initiator::Ui, || pause_recording_internal(&app_handle))
+}
+
+#[tauri::command]
+fn resume_recording(app_handle: AppHandle) -> Result<(), String> {
+    audit::initiated_by(Initiator::Ui, || resume_recording_internal(&app_handle))
+}
+
+fn pause_recording_internal(app_handle: &AppHandle) -> Result<(), String> {
+    set_recorder_state(app_handle, RecorderState::Paused)?;
+    app_handle.state::<Arc<RecordingState>>().telemetry.lock().unwrap().pause();
+    info!("Recording paused");
+    Ok(())
+}
+
+fn resume_recording_internal(app_handle: &AppHandle) -> Result<(), String> {
+    set_recorder_state(app_handle, RecorderState::Recording)?;
+    info!("Recording resumed");
+    Ok(())
+}
+
+#[tauri::command]
+fn get_recorder_state(state: State<'_, Arc<RecordingState>>) -> RecorderState {
+    state.recorder_state()
+}
+
+// Shared by the recording commands and remote triggers (OSC, MIDI)
+fn start_recording_internal(app_handle: &AppHandle) -> Result<DeviceInfo, StartError> {
+    let state = app_handle.state::<Arc<RecordingState>>();
+    let recorder = app_handle.state::<Mutex<BackgroundRecorder>>();
+    let config = app_handle.state::<ConfigState>();
+    let encryption = app_handle.state::<EncryptionState>();
+
+    if !state.is_idle() {
+        return Err("Already recording".to_string().into());
+    }
+
+    // Refuse up front rather than end up with a plaintext file we can't encrypt
+    if config.get().encryption.is_some_and(|e| e.enabled) && !encryption.is_unlocked() {
+        return Err("Encryption is enabled but locked; unlock it before recording".to_string().into());
+    }
+
+    // Fail with something the user can act on instead of a stream error from the recorder thread
+    permissions::ensure_microphone_access()?;
+    #[cfg(mobile)]
+    mobile::prepare_capture()?;
+
+    set_recorder_state(app_handle, RecorderState::Starting)?;
 
     // Clear old data
     {
         let mut audio_data = state.audio_data.lock().unwrap();
         audio_data.clear();
     }
+    state.lead_in.lock().unwrap().clear();
+    state.markers.lock().unwrap().clear();
+
+    // The tuner listens through the idle monitor; recording takes the input over from it
+    tuner::stop(app_handle);
+    monitor::update(app_handle)?;
 
-    // Actually start the background recorder
+    // Actually start the background recorder, which returns once capture is live
+    let session = state.session.fetch_add(1, Ordering::SeqCst) + 1;
     let mut bg_recorder = recorder.lock().unwrap();
-    bg_recorder.start(Arc::clone(state.inner()))?;
+    let device = match bg_recorder.start(Arc::clone(state.inner()), app_handle.clone(), session) {
+        Ok(device) => device,
+        Err(e) => {
+            error!("Recording failed to start: {}", e);
+            set_recorder_state(app_handle, RecorderState::Idle)?;
+            indicator::set_recording_badge(app_handle, false);
+            let device_busy = match &e {
+                StartError::DeviceBusy(busy) => Some(busy.clone()),
+                StartError::Failed(_) => None,
+            };
+            let _ = app_handle.emit(
+                "recording-start-failed",
+                RecordingStartFailedEvent {
+                    error: e.to_string(),
+                    device_busy,
+                },
+            );
+            return Err(e);
+        }
+    };
+    drop(bg_recorder);
 
-    state.is_recording.store(true, Ordering::SeqCst);
-    println!("Recording started");
+    set_recorder_state(app_handle, RecorderState::Recording)?;
+    info!("Recording started");
+    indicator::set_recording_badge(app_handle, true);
+    system_audio::recording_started(app_handle);
+    metronome::start(app_handle);
 
-    Ok(())
+    if let Some(target) = config.get().stream_target.filter(|t| t.enabled) {
+        stream::spawn(app_handle.clone(), target, Arc::clone(state.inner()));
+    }
+    let voice_commands = config.get().voice_commands;
+    if voice_commands.enabled {
+        voice_commands::spawn(app_handle.clone(), voice_commands, Arc::clone(state.inner()));
+    }
+    storage::monitor(app_handle.clone(), Arc::clone(state.inner()));
+
+    Ok(device)
 }
 
-// Stop recording and write WAV file
-#[tauri::command]
-async fn stop_recording(
-    app_handle: AppHandle,
-    state: State<'_, Arc<RecordingState>>,
-    recorder: State<'_, Mutex<BackgroundRecorder>>,
-) -> Result<AudioRecordingResponse, String> {
-    if !state.is_recording.load(Ordering::SeqCst) {
-        return Err("Not recording".to_string());
+/// Why a recording couldn't start
+#[derive(Debug)]
+enum StartError {
+    /// Another program has the input device to itself
+    DeviceBusy(DeviceBusy),
+    Failed(String),
+}
+
+impl StartError {
+    // A failure to open or start `device`, told apart as busy where the error says so
+    fn opening(device: &str, error: String) -> Self {
+        match device_check::diagnose_busy(device, &error) {
+            Some(busy) => StartError::DeviceBusy(busy),
+            None => StartError::Failed(error),
+        }
+    }
+}
+
+impl fmt::Display for StartError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StartError::DeviceBusy(busy) => write!(f, "Device busy: {}", busy),
+            StartError::Failed(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<String> for StartError {
+    fn from(message: String) -> Self {
+        StartError::Failed(message)
+    }
+}
+
+impl From<StartError> for String {
+    fn from(error: StartError) -> Self {
+        error.to_string()
+    }
+}
+
+/// Why stopping didn't produce a recording
+#[derive(Debug)]
+enum StopError {
+    /// The input delivered nothing at all, usually because the device failed
+    NoAudioCaptured,
+    Failed(String),
+}
+
+impl fmt::Display for StopError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StopError::NoAudioCaptured => write!(f, "No audio was captured; check that the input device is working"),
+            StopError::Failed(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<String> for StopError {
+    fn from(message: String) -> Self {
+        StopError::Failed(message)
     }
+}
+
+impl From<StopError> for String {
+    fn from(error: StopError) -> Self {
+        error.to_string()
+    }
+}
+
+fn stop_recording_internal(app_handle: &AppHandle) -> Result<PathBuf, StopError> {
+    if !app_handle.state::<Arc<RecordingState>>().is_recording() {
+        return Err("Not recording".to_string().into());
+    }
+    set_recorder_state(app_handle, RecorderState::Stopping)?;
+
+    // Whatever happens while stopping and saving, the recorder ends up idle again
+    let result = finish_recording(app_handle);
+    audit::record(
+        app_handle,
+        AuditAction::Stop,
+        result.as_deref().ok(),
+        result.as_ref().err().map(StopError::to_string),
+    );
+    set_recorder_state(app_handle, RecorderState::Idle)?;
+    result
+}
+
+fn finish_recording(app_handle: &AppHandle) -> Result<PathBuf, StopError> {
+    let state = app_handle.state::<Arc<RecordingState>>();
+    let recorder = app_handle.state::<Mutex<BackgroundRecorder>>();
+    let library = app_handle.state::<Library>();
+    let config = app_handle.state::<ConfigState>();
+    let encryption = app_handle.state::<EncryptionState>();
 
     // Stop background recorder
     {
@@ -333,8 +726,19 @@ async fn stop_recording(
         bg_recorder.stop()?;
     }
 
-    state.is_recording.store(false, Ordering::SeqCst);
-    println!("Recording stopped");
+    info!("Recording stopped");
+    indicator::set_recording_badge(app_handle, false);
+    system_audio::recording_stopped(app_handle);
+    metronome::stop(app_handle);
+
+    // A file of silence would pass for a recording; better to say nothing was captured
+    if state.audio_data.lock().unwrap().is_empty() {
+        warn!("No audio was captured; nothing to save");
+        state.markers.lock().unwrap().clear();
+        return Err(StopError::NoAudioCaptured);
+    }
+
+    set_recorder_state(app_handle, RecorderState::Saving)?;
 
     // Determine where to save
     let app_dir = app_handle
@@ -350,60 +754,85 @@ async fn stop_recording(
     let filename = format!("recording_{}.wav", timestamp);
     let filepath = app_dir.join(filename);
 
-    // Retrieve the actual channels and sample rate we used
-    let channels = *state.channels.lock().unwrap();
-    let sample_rate = *state.sample_rate.lock().unwrap();
-    println!("Writing WAV with {} channel(s) at {} Hz", channels, sample_rate);
-
-    // Create WAV
-    let spec = hound::WavSpec {
-        channels,
-        sample_rate,
-        bits_per_sample: 16,
-        sample_format: hound::SampleFormat::Int,
-    };
-
-    let mut writer = hound::WavWriter::create(&filepath, spec)
-        .map_err(|e| format!("Failed to create WAV file: {}", e))?;
-
-    let audio_data = state.audio_data.lock().unwrap();
-
-    if audio_data.is_empty() {
-        println!("No audio data recorded, creating 1s silent file...");
-        for _ in 0..(sample_rate * channels as u32) {
-            writer.write_sample(0i16)
-                .map_err(|e| format!("Failed to write sample: {}", e))?;
+    // The format the stream negotiated for this session, not whatever is configured now
+    let format = state
+        .capture_format()
+        .ok_or_else(|| "Capture format of the recording is unknown".to_string())?;
+    let (channels, sample_rate) = (format.channels, format.sample_rate);
+    info!("Writing WAV with {} channel(s) at {} Hz", channels, sample_rate);
+
+    let path = filepath.to_string_lossy().to_string();
+    let bext = state.bext();
+    if let Some(bext) = &bext {
+        info!("Recording started at {} (+{} samples)", bext.origination, bext.time_reference);
+    }
+    let samples = state.recorded_samples();
+    recording::write_capture(&filepath, channels, sample_rate, &samples, bext.as_ref(), |progress| {
+        let _ = app_handle.emit(
+            "recording-save-progress",
+            RecordingSaveProgressEvent {
+                path: path.clone(),
+                progress,
+            },
+        );
+    })?;
+    drop(samples);
+
+    let mut entry = app_handle.state::<Library>().probe(&filepath)?;
+    entry.markers = state.take_markers();
+    entry.clock_drift_ppm = state.telemetry.lock().unwrap().snapshot().clock_drift_ppm;
+    entry.compliance_tone_secs = compliance::take_interval(app_handle);
+    if let Some(drift) = entry.clock_drift_ppm {
+        info!("Input clock drifted {:+.1} ppm from the system clock", drift);
+    }
+    crypto::seal_new_recording(&config, &encryption, &mut entry)?;
+    let encrypt = entry.encrypted;
+    let filepath = storage::relocate(app_handle, Path::new(&entry.path));
+    entry.path = filepath.to_string_lossy().to_string();
+    if config.get().timestamp_sidecar {
+        if let Err(e) = recording::write_timestamps(&filepath, sample_rate, &state.timestamps()) {
+            warn!("{}", e);
         }
-    } else {
-        println!("Writing {} samples...", audio_data.len());
-        for &sample in audio_data.iter() {
-            writer.write_sample(sample)
-                .map_err(|e| format!("Failed to write sample: {}", e))?;
+    }
+    calendar::tag_recording(app_handle, state.session.load(Ordering::SeqCst), &mut entry);
+    library.assign_take(&mut entry);
+    let duration_ms = entry.duration_ms;
+    library.add(entry)?;
+    announce::saved(app_handle, duration_ms);
+
+    if let Some(profile) = config.active_profile() {
+        if encrypt && !profile.pipeline.is_empty() {
+            // Processing stages would write plaintext copies next to the encrypted file
+            warn!("Skipping post-processing pipeline because encryption is enabled");
+        } else {
+            pipeline::spawn(app_handle.clone(), filepath.clone(), profile.pipeline);
         }
     }
+    retention::apply(app_handle);
+    hooks::spawn(app_handle.clone(), filepath.clone(), config.get().post_hooks);
+    if config.get().webdav.is_some_and(|w| w.auto_upload) {
+        sync::spawn_upload(app_handle.clone(), filepath.clone());
+    }
 
-    writer
-        .finalize()
-        .map_err(|e| format!("Failed to finalize WAV: {}", e))?;
-
-    Ok(AudioRecordingResponse {
-        success: true,
-        path: Some(filepath.to_string_lossy().to_string()),
-        error: None,
-    })
+    Ok(filepath)
 }
 
 // Return the recorded file as base64
 #[tauri::command]
-async fn get_audio_data(path: String) -> Result<AudioDataResponse, String> {
-    let mut file = File::open(&path)
-        .map_err(|e| format!("Failed to open file: {}", e))?;
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)
-        .map_err(|e| format!("Failed to read file: {}", e))?;
-
-    // Base64 encode
-    let base64_data = BASE64_STANDARD.encode(&buffer);
+async fn get_audio_data(
+    path: String,
+    app_handle: AppHandle,
+    app_lock: State<'_, AppLock>,
+) -> Result<AudioDataResponse, String> {
+    app_lock.ensure_unlocked()?;
+    let source = path.clone();
+    let base64_data = tauri::async_runtime::spawn_blocking(move || {
+        let buffer = crypto::read_recording(&app_handle.state::<EncryptionState>(), std::path::Path::new(&source))?;
+        Ok::<_, String>(BASE64_STANDARD.encode(&buffer))
+    })
+    .await
+    .map_err(|e| format!("Failed to read recording: {}", e))??;
+    let path = path.trim_end_matches(&format!(".{}", crypto::ENCRYPTED_EXTENSION));
 
     // Infer MIME
     let mime_type = if path.ends_with(".wav") {
@@ -425,7 +854,23 @@ async fn get_audio_data(path: String) -> Result<AudioDataResponse, String> {
 // Check if currently recording
 #[tauri::command]
 fn is_recording(state: State<'_, Arc<RecordingState>>) -> bool {
-    state.is_recording.load(Ordering::SeqCst)
+    state.is_recording()
+}
+
+// Callback timing for the current recording, or the last one if none is running
+#[tauri::command]
+fn get_session_telemetry(state: State<'_, Arc<RecordingState>>) -> SessionTelemetry {
+    state.telemetry.lock().unwrap().snapshot()
+}
+
+// Choose whether each new recording gets a sidecar of wall-clock timestamps, for syncing
+// with other devices; the start time is always in the file's bext chunk
+#[tauri::command]
+fn set_timestamp_sidecar(config: State<'_, ConfigState>, enabled: bool) -> Result<(), String> {
+    config.update(|c| {
+        c.timestamp_sidecar = enabled;
+        Ok(())
+    })
 }
 
 // Check if currently playing
@@ -436,73 +881,44 @@ fn is_playing(playback_state: State<'_, AudioPlaybackState>) -> bool {
 
 // List available audio input devices
 #[tauri::command]
-fn get_audio_devices() -> Result<AudioConfigResponse, String> {
-    let host = cpal::default_host();
-
-    let devices = host
-        .input_devices()
-        .map_err(|e| format!("Failed to get input devices: {}", e))?
-        .collect::<Vec<_>>();
-
-    let default_device = host
-        .default_input_device()
-        .ok_or_else(|| "No default input device available.".to_string())?;
-
-    let default_name = default_device
-        .name()
-        .unwrap_or_else(|_| "Unknown Device".to_string());
-    let default_config = default_device
-        .default_input_config()
-        .map_err(|e| format!("Failed to get default config: {}", e))?;
-    let supported_configs = default_device
-        .supported_input_configs()
-        .map_err(|e| format!("Failed to get supported configs: {}", e))?
-        .collect::<Vec<_>>();
-    let formats = supported_configs
-        .iter()
-        .map(|cfg| format!("{:?}", cfg.sample_format()))
-        .collect();
-
-    // Collect info
-    let available_devices = devices
-        .iter()
-        .filter_map(|dev| {
-            let name = dev.name().ok()?;
-            let cfg = dev.default_input_config().ok()?;
-            let sup = dev.supported_input_configs().ok()?.collect::<Vec<_>>();
-            let fmts = sup.iter()
-                .map(|c| format!("{:?}", c.sample_format()))
-                .collect::<Vec<_>>();
-
-            Some(AudioDeviceInfo {
-                name,
-                channels: cfg.channels(),
-                sample_rate: cfg.sample_rate().0,
-                formats: fmts,
-            })
-        })
-        .collect::<Vec<_>>();
-
+fn get_audio_devices(backend: State<'_, Arc<dyn AudioBackend>>) -> Result<AudioConfigResponse, String> {
+    let current_device = backend.default_input()?;
     Ok(AudioConfigResponse {
         success: true,
-        device_name: default_name.clone(),
-        current_device: AudioDeviceInfo {
-            name: default_name,
-            channels: default_config.channels(),
-            sample_rate: default_config.sample_rate().0,
-            formats,
-        },
-        available_devices,
+        device_name: current_device.name.clone(),
+        current_device,
+        available_devices: backend.input_devices()?,
         error: None,
     })
 }
 
-// Set user-chosen config (currently just stored; not used in build_input_stream)
+// Set the format new recordings open the input in, unless the active profile sets its own.
+// `device_pair` records two mono devices as the left and right channel of a stereo
+// recording instead of using the default input.
 #[tauri::command]
-fn set_audio_config(state: State<'_, Arc<RecordingState>>, channels: u16, sample_rate: u32) -> Result<(), String> {
-    if state.is_recording.load(Ordering::SeqCst) {
+fn set_audio_config(
+    app_handle: AppHandle,
+    state: State<'_, Arc<RecordingState>>,
+    backend: State<'_, Arc<dyn AudioBackend>>,
+    channels: u16,
+    sample_rate: u32,
+    device_pair: Option<DevicePair>,
+) -> Result<(), String> {
+    if !state.is_idle() {
         return Err("Cannot change config while recording.".to_string());
     }
+    if let Some(pair) = &device_pair {
+        if channels != 2 {
+            return Err("A device pair records in stereo; set 2 channels".to_string());
+        }
+        if pair.left == pair.right {
+            return Err("A device pair needs two different devices".to_string());
+        }
+        let inputs = backend.input_devices()?;
+        if let Some(missing) = [&pair.left, &pair.right].into_iter().find(|name| !inputs.iter().any(|d| &d.name == *name)) {
+            return Err(format!("Input device not found: {}", missing));
+        }
+    }
 
     // Simple validations
     if !(1..=2).contains(&channels) {
@@ -518,53 +934,76 @@ fn set_audio_config(state: State<'_, Arc<RecordingState>>, channels: u16, sample
 
     *state.channels.lock().unwrap() = channels;
     *state.sample_rate.lock().unwrap() = sample_rate;
+    *state.device_pair.lock().unwrap() = device_pair;
 
-    println!("Audio config set to {} ch, {} Hz", channels, sample_rate);
+    info!("Audio config set to {} ch, {} Hz", channels, sample_rate);
+    // Keep the pre-roll in the format recordings will now use
+    monitor::restart(&app_handle);
     Ok(())
 }
 
-// Get the currently stored config (not necessarily the device's default)
+// Play a chirp and time how long it takes to come back on the input, which needs a
+// loopback cable or the microphone near the speaker. Devices are the defaults when
+// not named. The result is kept and used to line up overdubs.
 #[tauri::command]
-fn get_current_audio_config(state: State<'_, Arc<RecordingState>>) -> Result<AudioDeviceInfo, String> {
-    let host = cpal::default_host();
-    let device = host
-        .default_input_device()
-        .ok_or_else(|| "No default input device available.".to_string())?;
-    let name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+async fn measure_roundtrip_latency(
+    app_handle: AppHandle,
+    input_device: Option<String>,
+    output_device: Option<String>,
+) -> Result<RoundTrip, String> {
+    if !app_handle.state::<Arc<RecordingState>>().is_idle() {
+        return Err("Cannot measure latency while recording".to_string());
+    }
+    tuner::stop(&app_handle);
+
+    let backend = Arc::clone(app_handle.state::<Arc<dyn AudioBackend>>().inner());
+    let round_trip = tauri::async_runtime::spawn_blocking(move || {
+        latency::measure_roundtrip(backend.as_ref(), input_device.as_deref(), output_device.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Measuring latency failed: {}", e))??;
+
+    info!(
+        "Round trip from {} to {} takes {:.1} ms",
+        round_trip.output_device, round_trip.input_device, round_trip.latency_ms
+    );
+    app_handle.state::<ConfigState>().update(|c| {
+        c.roundtrip_latency_ms = Some(round_trip.latency_ms);
+        Ok(())
+    })?;
+    Ok(round_trip)
+}
 
-    let device_config = device
-        .default_input_config()
-        .map_err(|e| format!("Failed to get device config: {}", e))?;
+// Run generated audio through capture, writing and decoding on a mock device and check
+// nothing was lost or altered; no real hardware is involved
+#[tauri::command]
+async fn self_test() -> Result<SelfTestReport, String> {
+    let report = tauri::async_runtime::spawn_blocking(rekt_core::self_test::run)
+        .await
+        .map_err(|e| format!("Self-test failed to run: {}", e))?;
+    if report.passed {
+        info!("Self-test passed");
+    } else {
+        warn!("Self-test failed: {:?}", report.steps.iter().filter(|step| !step.passed).collect::<Vec<_>>());
+    }
+    Ok(report)
+}
 
+// Get the currently stored config (not necessarily the device's default)
+#[tauri::command]
+fn get_current_audio_config(
+    state: State<'_, Arc<RecordingState>>,
+    backend: State<'_, Arc<dyn AudioBackend>>,
+) -> Result<DeviceInfo, String> {
+    let device = backend.default_input()?;
     let stored_channels = *state.channels.lock().unwrap();
     let stored_rate = *state.sample_rate.lock().unwrap();
 
     // If stored is zero (never set), fallback to device default
-    let channels = if stored_channels == 0 {
-        device_config.channels()
-    } else {
-        stored_channels
-    };
-    let sample_rate = if stored_rate == 0 {
-        device_config.sample_rate().0
-    } else {
-        stored_rate
-    };
-
-    let supported_configs = device
-        .supported_input_configs()
-        .map_err(|e| format!("Failed to get supported input configs: {}", e))?
-        .collect::<Vec<_>>();
-    let formats = supported_configs
-        .iter()
-        .map(|c| format!("{:?}", c.sample_format()))
-        .collect::<Vec<_>>();
-
-    Ok(AudioDeviceInfo {
-        name,
-        channels,
-        sample_rate,
-        formats,
+    Ok(DeviceInfo {
+        channels: if stored_channels == 0 { device.channels } else { stored_channels },
+        sample_rate: if stored_rate == 0 { device.sample_rate } else { stored_rate },
+        ..device
     })
 }
 
@@ -576,9 +1015,35 @@ fn get_current_audio_config(state: State<'_, Arc<RecordingState>>) -> Result<Aud
 #[tauri::command]
 async fn play_audio(
     path: String,
+    resume: Option<bool>,
     app_handle: AppHandle,
     playback_state: State<'_, AudioPlaybackState>,
+    app_lock: State<'_, AppLock>,
 ) -> Result<AudioPlaybackResponse, String> {
+    app_lock.ensure_unlocked()?;
+
+    // Encrypted recordings are decrypted into memory and never touch the disk in plaintext
+    let decrypted = if crypto::is_encrypted(std::path::Path::new(&path)) {
+        let (app_handle, path) = (app_handle.clone(), path.clone());
+        let bytes = tauri::async_runtime::spawn_blocking(move || {
+            app_handle.state::<EncryptionState>().decrypt_file(std::path::Path::new(&path))
+        })
+        .await
+        .map_err(|e| format!("Failed to decrypt recording: {}", e))??;
+        Some(bytes)
+    } else {
+        None
+    };
+
+    // `resume` picks up where the last playback of this recording was stopped
+    let library_entry = app_handle.state::<Library>().get(&path);
+    let start = library_entry
+        .as_ref()
+        .filter(|_| resume.unwrap_or(false))
+        .and_then(|entry| entry.playback_position_ms)
+        .map(Duration::from_millis);
+    let library_entry = library_entry.is_some();
+
     stop_audio_internal(&playback_state); // Stop any existing audio
 
     let playback_id = nanoid::nanoid!();
@@ -632,40 +1097,104 @@ async fn play_audio(
 
     // Playback in a separate thread
     thread::spawn(move || {
-        use rodio::{Decoder, Sink};
-
-        let file = match File::open(&path_clone) {
-            Ok(f) => f,
-            Err(e) => {
-                eprintln!("Error opening file for playback: {}", e);
-                let _ = app_handle.emit("audio-playback-stopped", AudioPlaybackEvent { playback_id: playback_id_clone });
-                return;
-            }
+        let media: Result<Box<dyn MediaSource>, PlaybackError> = match decrypted {
+            Some(bytes) => Ok(Box::new(Cursor::new(bytes))),
+            None => File::open(&path_clone)
+                .map(|f| Box::new(f) as Box<dyn MediaSource>)
+                .map_err(|e| PlaybackError::new(PlaybackErrorKind::Open, format!("Failed to open {}: {}", path_clone, e))),
         };
-
-        let buf_reader = BufReader::new(file);
-        let source = match Decoder::new(buf_reader) {
-            Ok(s) => s,
-            Err(e) => {
-                eprintln!("Error decoding file: {}", e);
-                let _ = app_handle.emit("audio-playback-stopped", AudioPlaybackEvent { playback_id: playback_id_clone });
-                return;
+        // Probe by the real extension, not the encryption suffix
+        let plain_path = path_clone
+            .strip_suffix(&format!(".{}", crypto::ENCRYPTED_EXTENSION))
+            .unwrap_or(&path_clone);
+        let extension = std::path::Path::new(plain_path).extension().and_then(|e| e.to_str());
+
+        let played = media.and_then(|media| {
+            decode::play(
+                &app_handle,
+                &stream_handle,
+                &playback_id_clone,
+                Some(&path_clone),
+                media,
+                extension.map(FormatHint::Extension),
+                start,
+            )
+        });
+        match played {
+            // Remember where a library recording was left; finishing it starts the next play over
+            Ok(end) if library_entry => {
+                let position_ms = match end {
+                    PlaybackEnd::Finished => None,
+                    PlaybackEnd::Stopped { position } => Some(position.as_millis() as u64),
+                };
+                if let Err(e) = app_handle
+                    .state::<Library>()
+                    .update(&path_clone, |entry| entry.playback_position_ms = position_ms)
+                {
+                    warn!("Failed to save playback position: {}", e);
+                }
             }
-        };
+            Ok(_) => {}
+            Err(e) => decode::report(&app_handle, &playback_id_clone, &e),
+        }
+        let _ = app_handle.emit("audio-playback-stopped", AudioPlaybackEvent { playback_id: playback_id_clone });
+    });
 
-        let sink = match Sink::try_new(&stream_handle) {
-            Ok(s) => s,
-            Err(e) => {
-                eprintln!("Error creating Sink: {}", e);
-                let _ = app_handle.emit("audio-playback-stopped", AudioPlaybackEvent { playback_id: playback_id_clone });
-                return;
-            }
-        };
+    Ok(AudioPlaybackResponse {
+        success: true,
+        is_playing: true,
+        error: None,
+    })
+}
 
-        sink.append(source);
-        sink.sleep_until_end();
+// Play the most recent `seconds` of the recording in progress without stopping it
+#[tauri::command]
+async fn play_last(
+    seconds: f32,
+    app_handle: AppHandle,
+    state: State<'_, Arc<RecordingState>>,
+    playback_state: State<'_, AudioPlaybackState>,
+    app_lock: State<'_, AppLock>,
+) -> Result<AudioPlaybackResponse, String> {
+    app_lock.ensure_unlocked()?;
 
-        let _ = app_handle.emit("audio-playback-stopped", AudioPlaybackEvent { playback_id: playback_id_clone });
+    if !(0.5..=MAX_REPLAY_SECS).contains(&seconds) {
+        return Err(format!("Replay length must be between 0.5 and {} seconds", MAX_REPLAY_SECS));
+    }
+
+    // While recording replay the take so far, otherwise whatever the pre-roll has heard
+    let (channels, sample_rate, samples) = if state.is_recording() {
+        let format = state
+            .capture_format()
+            .ok_or_else(|| "Capture format not known yet".to_string())?;
+        let (channels, sample_rate) = (format.channels, format.sample_rate);
+        let audio_data = state.audio_data.lock().unwrap();
+        let wanted = (seconds * sample_rate as f32) as usize * channels as usize;
+        // Start on a frame boundary so channels don't get swapped
+        let start = audio_data.len().saturating_sub(wanted) / channels as usize * channels as usize;
+        (channels, sample_rate, audio_data[start..].to_vec())
+    } else if state.is_idle() {
+        state.pre_roll.lock().unwrap().last(seconds)
+    } else {
+        return Err("Not recording".to_string());
+    };
+    if samples.is_empty() {
+        return Err("Nothing has been captured yet".to_string());
+    }
+
+    stop_audio_internal(&playback_state);
+    let stream_handle = ensure_output_stream(&playback_state)?;
+
+    let playback_id = nanoid::nanoid!();
+    *playback_state.current_playback_id.lock().unwrap() = Some(playback_id.clone());
+    playback_state.is_playing.store(true, Ordering::SeqCst);
+
+    thread::spawn(move || {
+        let played = decode::play_samples(&app_handle, &stream_handle, &playback_id, channels, sample_rate, samples);
+        if let Err(e) = played {
+            decode::report(&app_handle, &playback_id, &e);
+        }
+        let _ = app_handle.emit("audio-playback-stopped", AudioPlaybackEvent { playback_id });
     });
 
     Ok(AudioPlaybackResponse {
@@ -675,6 +1204,23 @@ async fn play_audio(
     })
 }
 
+// Reuse the open output stream or open the default device
+fn ensure_output_stream(playback_state: &AudioPlaybackState) -> Result<rodio::OutputStreamHandle, String> {
+    let mut output = playback_state.output_stream.lock().unwrap();
+    if let Some(ref existing) = *output {
+        return Ok(existing.handle.clone());
+    }
+
+    let (stream, handle) =
+        rodio::OutputStream::try_default().map_err(|e| format!("Failed to create output stream: {}", e))?;
+    *output = Some(AudioOutputStream {
+        stream,
+        handle: handle.clone(),
+    });
+    playback_state.device_initialized.store(true, Ordering::SeqCst);
+    Ok(handle)
+}
+
 // Stop any playback
 #[tauri::command]
 fn stop_audio(playback_state: State<'_, AudioPlaybackState>) -> Result<AudioPlaybackResponse, String> {
@@ -691,7 +1237,8 @@ fn stop_audio_internal(playback_state: &AudioPlaybackState) {
     if playback_state.is_playing.load(Ordering::SeqCst) {
         playback_state.is_playing.store(false, Ordering::SeqCst);
         *playback_state.current_playback_id.lock().unwrap() = None;
-        // Actual stopping is done because rodio Sinks run in another thread
+        *playback_state.control.lock().unwrap() = None;
+        // The playback thread sees its id is no longer current and stops its sink
     }
 }
 
@@ -702,7 +1249,10 @@ async fn play_audio_from_base64(
     mime_type: String,
     app_handle: AppHandle,
     playback_state: State<'_, AudioPlaybackState>,
+    app_lock: State<'_, AppLock>,
 ) -> Result<AudioPlaybackResponse, String> {
+    app_lock.ensure_unlocked()?;
+
     stop_audio_internal(&playback_state);
 
     let playback_id = nanoid::nanoid!();
@@ -772,40 +1322,25 @@ async fn play_audio_from_base64(
 
     // Spawn thread for playback
     thread::spawn(move || {
-        use rodio::{Decoder, Sink};
-
         // Keep the temp file alive
-        let file = match File::open(path_clone) {
-            Ok(f) => f,
-            Err(e) => {
-                eprintln!("Failed to open temp file: {}", e);
-                let _ = app_handle.emit("audio-playback-stopped", AudioPlaybackEvent { playback_id: playback_id_clone });
-                return;
-            }
-        };
-
-        let buf_reader = BufReader::new(file);
-        let source = match Decoder::new(buf_reader) {
-            Ok(s) => s,
-            Err(e) => {
-                eprintln!("Failed to decode base64 audio: {}", e);
-                let _ = app_handle.emit("audio-playback-stopped", AudioPlaybackEvent { playback_id: playback_id_clone });
-                return;
-            }
-        };
-
-        let sink = match Sink::try_new(&stream_handle) {
-            Ok(s) => s,
-            Err(e) => {
-                eprintln!("Failed creating Sink: {}", e);
-                let _ = app_handle.emit("audio-playback-stopped", AudioPlaybackEvent { playback_id: playback_id_clone });
-                return;
-            }
-        };
-
-        sink.append(source);
-        sink.sleep_until_end();
-
+        let media = File::open(path_clone)
+            .map(|f| Box::new(f) as Box<dyn MediaSource>)
+            .map_err(|e| PlaybackError::new(PlaybackErrorKind::Open, format!("Failed to open temp file: {}", e)));
+
+        let played = media.and_then(|media| {
+            decode::play(
+                &app_handle,
+                &stream_handle,
+                &playback_id_clone,
+                None,
+                media,
+                Some(FormatHint::MimeType(&mime_type)),
+                None,
+            )
+        });
+        if let Err(e) = played {
+            decode::report(&app_handle, &playback_id_clone, &e);
+        }
         let _ = app_handle.emit("audio-playback-stopped", AudioPlaybackEvent { playback_id: playback_id_clone });
         // temp_file drops here
     });
@@ -823,28 +1358,321 @@ async fn play_audio_from_base64(
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    println!("Initializing audio system with correct, per-session device config");
     tauri::Builder::default()
         .manage(Arc::new(RecordingState::default()))
+        .manage::<Arc<dyn AudioBackend>>(Arc::new(CpalBackend))
         .manage(Mutex::new(BackgroundRecorder::default()))
         .manage(AudioPlaybackState::default())
+        .manage(countdown::DelayedStartState::default())
+        .manage(overdub::OverdubState::default())
+        .manage(overdub::PunchState::default())
+        .manage(metronome::MetronomeState::default())
+        .manage(tuner::TunerState::default())
+        .manage(test_tone::TestToneState::default())
+        .manage(speak::SpeechState::default())
+        .manage(compliance::ComplianceToneState::default())
+        .manage(Arc::new(eq::PlaybackEq::default()))
+        .manage(EncryptionState::default())
+        .manage(AppLock::load())
+        .manage(share::ShareRegistry::default())
+        .manage(privacy::WipeConfirmation::default())
+        .manage(osc::OscListener::default())
+        .manage(midi::MidiState::default())
+        .manage(system_audio::SystemAudioState::default())
+        .manage(watch::FolderWatcher::default())
+        .manage(calendar::CalendarState::default())
+        .register_uri_scheme_protocol(overlay::SCHEME, overlay::serve)
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
+        .setup(|app| {
+            #[cfg(target_os = "android")]
+            mobile::init(app)?;
+            let app_dir = app.path().app_data_dir()?;
+            app.manage(logging::init(&app_dir.join("logs"))?);
+            app.manage(audit::AuditLog::open(&app_dir)?);
+            info!("Initializing audio system with correct, per-session device config");
+            let config = ConfigState::load(&app_dir)?;
+            if let Some(error) = config.load_error() {
+                notifications::notify(app.handle(), "Settings were reset", error);
+            }
+            if let Err(e) = secrets::migrate_plaintext(&config) {
+                warn!("Failed to move stored credentials to keychain: {}", e);
+            }
+            if let Err(e) = app.state::<osc::OscListener>().restart(app.handle().clone(), config.get().osc) {
+                warn!("{}", e);
+            }
+            if let Err(e) = app.state::<midi::MidiState>().connect(app.handle().clone(), config.get().midi.as_ref()) {
+                warn!("{}", e);
+            }
+            app.state::<Arc<eq::PlaybackEq>>().set(config.get().playback_eq);
+            app.manage(config);
+            app.manage(jobs::JobQueue::open(&app_dir)?);
+            app.manage(versions::VersionStore::open(&app_dir)?);
+            app.manage(Library::open(app_dir)?);
+            jobs::start_workers(app.handle());
+            let watch_folder = app.state::<ConfigState>().get().watch_folder.map(PathBuf::from);
+            if let Err(e) = app.state::<watch::FolderWatcher>().restart(app.handle().clone(), watch_folder.as_deref()) {
+                warn!("{}", e);
+            }
+            #[cfg(desktop)]
+            {
+                app.handle().plugin(tauri_plugin_global_shortcut::Builder::new().build())?;
+                let presets = app.state::<ConfigState>().get().hotkeys;
+                if let Err(e) = hotkeys::register_all(app.handle(), &presets) {
+                    warn!("{}", e);
+                }
+            }
+            power::watch(app.handle().clone());
+            #[cfg(desktop)]
+            screen_lock::watch(app.handle().clone());
+            calendar::watch(app.handle().clone());
+            meeting_detect::watch(app.handle().clone());
+            device_follow::watch(app.handle().clone());
+            if let Err(e) = monitor::update(app.handle()) {
+                warn!("Could not start the pre-roll: {}", e);
+            }
+            #[cfg(mobile)]
+            mobile::watch_interruptions(app.handle().clone());
+            retention::apply(app.handle());
+            Ok(())
+        })
+        .on_window_event(|window, event| match event {
+            tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) => {
+                import::import_dropped_files(window.app_handle().clone(), paths.clone());
+            }
+            #[cfg(desktop)]
+            tauri::WindowEvent::Destroyed if window.label() == mini_recorder::MINI_RECORDER_WINDOW => {
+                mini_recorder::notify(window.app_handle(), false);
+            }
+            _ => {}
+        })
         .invoke_handler(tauri::generate_handler![
             // Recording
             start_recording,
+            countdown::start_recording_with_delay,
+            countdown::cancel_delayed_start,
             stop_recording,
             is_recording,
+            pause_recording,
+            resume_recording,
+            get_recorder_state,
+            get_session_telemetry,
+            set_timestamp_sidecar,
             get_audio_data,
             set_audio_config,
             get_current_audio_config,
             get_audio_devices,
+            measure_roundtrip_latency,
+            self_test,
+            test_tone::play_test_tone,
+            test_tone::stop_test_tone,
+            speak::speak_text,
+            speak::stop_speaking,
+            speak::set_voice_confirmations,
+            monitor::set_pre_roll,
+            monitor::get_pre_roll,
+            audit::get_audit_log,
+            #[cfg(desktop)]
+            mini_recorder::open_mini_recorder,
+            #[cfg(desktop)]
+            mini_recorder::close_mini_recorder,
+            #[cfg(desktop)]
+            mini_recorder::is_mini_recorder_open,
+            overlay::set_overlay_indicator,
+            overlay::get_overlay_indicator,
+            add_marker,
+            mic_test::run_mic_test,
+            overdub::start_overdub,
+            overdub::stop_overdub,
+            overdub::punch_record,
+            overdub::finish_punch,
+            metronome::set_metronome,
+            metronome::get_metronome,
+            tuner::start_tuner,
+            tuner::stop_tuner,
             // Playback
             play_audio,
             stop_audio,
             is_playing,
             play_audio_from_base64,
+            play_last,
+            eq::set_playback_eq,
+            eq::get_playback_eq,
+            navigation::seek_to_marker,
+            navigation::seek_to_next_speech,
+            navigation::seek_to_previous_speech,
+            // Files
+            files::show_in_folder,
+            storage::set_save_dir,
+            storage::get_save_dir,
+            files::copy_recording_to_clipboard,
+            share::share_recording,
+            share::stop_sharing,
+            // Profiles
+            config::get_config_load_error,
+            config::list_profiles,
+            config::save_profile,
+            config::delete_profile,
+            config::set_active_profile,
+            // Post hooks
+            hooks::add_post_hook,
+            hooks::list_post_hooks,
+            hooks::remove_post_hook,
+            // Library
+            library::list_recordings,
+            library::search_recordings,
+            library::get_recording_stats,
+            library::set_recording_note,
+            library::get_recording_note,
+            library::add_tag,
+            library::remove_tag,
+            library::list_tags,
+            library::set_favorite,
+            library::list_favorites,
+            library::find_duplicates,
+            library::create_folder,
+            library::delete_folder,
+            library::list_folders,
+            library::move_recording,
+            watch::set_watch_folder,
+            watch::get_watch_folder,
+            export::export_recordings,
+            share_sheet::share_recording_native,
+            retention::set_retention_policy,
+            retention::get_retention_policy,
+            library::list_saved_filters,
+            library::save_filter,
+            library::delete_saved_filter,
+            spectrogram::generate_spectrogram,
+            quality::analyze_quality,
+            tempo::detect_tempo,
+            effects::process_recording,
+            effects::reverse_recording,
+            effects::split_channels,
+            edits::set_edit_list,
+            edits::get_edit_list,
+            edits::clear_edit_list,
+            edits::preview_edits,
+            transcript::set_transcript,
+            transcript::get_transcript,
+            transcript::export_transcript,
+            transcription::set_transcription,
+            transcription::get_transcription,
+            transcription::detect_language,
+            transcription::set_recording_language,
+            translation::translate_transcript,
+            translation::set_translation,
+            translation::get_translation,
+            import::import_recordings,
+            // App lock
+            lock::set_app_password,
+            lock::unlock_app,
+            lock::lock_app,
+            lock::is_app_locked,
+            // Encryption
+            crypto::set_encryption,
+            crypto::unlock_encryption,
+            crypto::get_encryption_status,
+            // Streaming
+            stream::set_stream_target,
+            stream::get_stream_target,
+            // Focus mode
+            focus::set_focus_mode,
+            focus::get_focus_mode,
+            system_audio::set_duck_system_audio,
+            system_audio::get_duck_system_audio,
+            // Sleep handling
+            power::set_resume_after_sleep,
+            #[cfg(desktop)]
+            screen_lock::set_screen_lock,
+            #[cfg(desktop)]
+            screen_lock::get_screen_lock,
+            // Silence warning
+            sessions::create_session,
+            sessions::end_session,
+            sessions::get_active_session,
+            sessions::list_sessions,
+            sessions::export_session,
+            silence::set_silence_warning,
+            silence::get_silence_warning,
+            versions::list_versions,
+            versions::restore_version,
+            voice_commands::set_voice_commands,
+            voice_commands::get_voice_commands,
+            // Remote control
+            osc::set_osc_config,
+            osc::get_osc_config,
+            midi::list_midi_inputs,
+            midi::set_midi_binding,
+            midi::remove_midi_binding,
+            midi::get_midi_config,
+            #[cfg(desktop)]
+            hotkeys::list_hotkey_presets,
+            #[cfg(desktop)]
+            hotkeys::set_hotkey_preset,
+            #[cfg(desktop)]
+            hotkeys::remove_hotkey_preset,
+            // Sync
+            sync::set_webdav_config,
+            sync::get_webdav_config,
+            sync::sync_recording,
+            // Calendar
+            calendar::set_calendar,
+            calendar::get_calendar,
+            calendar::list_meetings,
+            calendar::record_meeting,
+            meeting_detect::set_meeting_detection,
+            meeting_detect::get_meeting_detection,
+            device_follow::set_follow_default_input,
+            device_follow::get_follow_default_input,
+            permissions::get_microphone_permission,
+            permissions::request_microphone_permission,
+            setup::run_setup_checks,
+            // Jobs
+            jobs::enqueue_job,
+            jobs::list_jobs,
+            jobs::cancel_job,
+            // Backup
+            backup::backup_library,
+            backup::restore_library,
+            // Data export and wipe
+            privacy::export_all_data,
+            privacy::prepare_wipe_all_data,
+            privacy::wipe_all_data,
+            // Secrets
+            secrets::set_secret_command,
+            secrets::delete_secret_command,
+            secrets::has_secret,
+            // Diagnostics
+            logging::get_recent_logs,
+            logging::set_log_level,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            // Config changes are written in the background; make sure the last one lands
+            if let tauri::RunEvent::Exit = event {
+                app_handle.state::<ConfigState>().flush();
+                return;
+            }
+
+            // Quitting mid-recording would lose everything still in memory, so
+            // hold the exit until the recording has been saved
+            if let tauri::RunEvent::ExitRequested { api, .. } = event {
+                if app_handle.state::<Arc<RecordingState>>().is_recording() {
+                    api.prevent_exit();
+                    let app_handle = app_handle.clone();
+                    thread::spawn(move || {
+                        info!("Exit requested while recording; saving first");
+                        match stop_recording_internal(&app_handle) {
+                            Ok(path) => info!("Saved {} before exit", path.display()),
+                            // Stopping always leaves the recorder idle, so the next exit request goes through
+                            Err(e) => error!("Failed to save recording before exit: {}", e),
+                        }
+                        app_handle.exit(0);
+                    });
+                }
+            }
+        });
 }
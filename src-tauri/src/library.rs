@@ -0,0 +1,394 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::Datelike;
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+pub use rekt_core::library::*;
+
+use crate::config::ConfigState;
+use crate::crypto::{self, EncryptionState};
+use crate::lock::AppLock;
+
+//
+// ====== Library commands ======
+//
+
+const MAX_NOTE_LEN: usize = 10_000;
+const MAX_TAG_LEN: usize = 64;
+
+/// Index a file that an edit or effect wrote from `source`, encrypting it first when
+/// encryption is on; returns where it ended up
+pub fn add_derived(app_handle: &AppHandle, source: &Path, path: &Path) -> Result<String, String> {
+    let mut entry = app_handle.state::<Library>().probe(path)?;
+    entry.derived_from = Some(source.to_string_lossy().to_string());
+    let config = app_handle.state::<ConfigState>();
+    crypto::seal_new_recording(&config, &app_handle.state::<EncryptionState>(), &mut entry)?;
+    let path = entry.path.clone();
+    app_handle.state::<Library>().add(entry)?;
+    Ok(path)
+}
+
+#[derive(Debug, Serialize)]
+pub struct WeeklyCount {
+    week: String,
+    count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TagCount {
+    tag: String,
+    count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FolderInfo {
+    name: String,
+    count: usize,
+}
+
+/// Recordings with identical audio content
+#[derive(Debug, Serialize)]
+pub struct DuplicateGroup {
+    content_hash: String,
+    recordings: Vec<RecordingEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateReport {
+    groups: Vec<DuplicateGroup>,
+    /// Bytes freed by keeping only one recording per group
+    reclaimable_bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecordingStats {
+    total_recordings: usize,
+    total_duration_ms: u64,
+    storage_bytes: u64,
+    longest_recording: Option<RecordingEntry>,
+    per_week: Vec<WeeklyCount>,
+}
+
+fn normalize_tag(tag: &str) -> Result<String, String> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return Err("Tag cannot be empty".to_string());
+    }
+    if tag.chars().count() > MAX_TAG_LEN {
+        return Err(format!("Tag is longer than {} characters", MAX_TAG_LEN));
+    }
+    Ok(tag.to_string())
+}
+
+// List the library newest first, optionally narrowed by a text query and/or a filter
+#[tauri::command]
+pub fn list_recordings(
+    library: State<'_, Library>,
+    app_lock: State<'_, AppLock>,
+    query: Option<String>,
+    filter: Option<LibraryFilter>,
+) -> Result<Vec<RecordingEntry>, String> {
+    app_lock.ensure_unlocked()?;
+    let mut filter = filter.unwrap_or_default();
+    filter.validate()?;
+    if query.is_some() {
+        filter.text = query;
+    }
+
+    let mut entries = library.entries();
+    entries.retain(|e| filter.matches(e));
+    entries.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(entries)
+}
+
+// Find recordings by what was said in them. Each match carries a snippet per matching
+// transcript segment and the position to start playback at to hear it.
+#[tauri::command]
+pub fn search_recordings(
+    library: State<'_, Library>,
+    app_lock: State<'_, AppLock>,
+    query: String,
+) -> Result<Vec<TranscriptMatch>, String> {
+    app_lock.ensure_unlocked()?;
+    Ok(library.search_transcripts(&query))
+}
+
+// Attach a note to a recording; an empty note removes it
+#[tauri::command]
+pub fn set_recording_note(
+    library: State<'_, Library>,
+    app_lock: State<'_, AppLock>,
+    path: String,
+    text: String,
+) -> Result<(), String> {
+    app_lock.ensure_unlocked()?;
+    if text.chars().count() > MAX_NOTE_LEN {
+        return Err(format!("Note is longer than {} characters", MAX_NOTE_LEN));
+    }
+
+    let note = Some(text.trim().to_string()).filter(|t| !t.is_empty());
+    library.update(&path, |entry| entry.note = note)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_recording_note(
+    library: State<'_, Library>,
+    app_lock: State<'_, AppLock>,
+    path: String,
+) -> Result<Option<String>, String> {
+    app_lock.ensure_unlocked()?;
+    library
+        .get(&path)
+        .map(|entry| entry.note)
+        .ok_or_else(|| format!("Recording not found in library: {}", path))
+}
+
+// Aggregate numbers for the stats dashboard
+#[tauri::command]
+pub fn get_recording_stats(
+    library: State<'_, Library>,
+    app_lock: State<'_, AppLock>,
+) -> Result<RecordingStats, String> {
+    app_lock.ensure_unlocked()?;
+    let entries = library.entries();
+
+    // ISO week keys like "2025-W07" sort chronologically as plain strings
+    let mut per_week = std::collections::BTreeMap::<String, usize>::new();
+    for entry in &entries {
+        if let Ok(created) = chrono::DateTime::parse_from_rfc3339(&entry.created_at) {
+            let week = created.iso_week();
+            *per_week
+                .entry(format!("{}-W{:02}", week.year(), week.week()))
+                .or_default() += 1;
+        }
+    }
+
+    Ok(RecordingStats {
+        total_recordings: entries.len(),
+        total_duration_ms: entries.iter().map(|e| e.duration_ms).sum(),
+        storage_bytes: entries.iter().map(|e| e.size_bytes).sum(),
+        longest_recording: entries.iter().max_by_key(|e| e.duration_ms).cloned(),
+        per_week: per_week
+            .into_iter()
+            .map(|(week, count)| WeeklyCount { week, count })
+            .collect(),
+    })
+}
+
+// Group recordings with identical content. Entries without a hash, or whose files have
+// changed since they were hashed, are hashed first; encrypted ones without a hash can't
+// be compared and are skipped.
+#[tauri::command]
+pub async fn find_duplicates(app_handle: AppHandle, app_lock: State<'_, AppLock>) -> Result<DuplicateReport, String> {
+    app_lock.ensure_unlocked()?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let library = app_handle.state::<Library>();
+        for entry in library.entries() {
+            if entry.encrypted {
+                continue;
+            }
+            match library.probe(Path::new(&entry.path)) {
+                Ok(probed) if probed.hashed_mtime_ms != entry.hashed_mtime_ms || entry.content_hash.is_none() => {
+                    library.update(&entry.path, |e| {
+                        e.content_hash = probed.content_hash;
+                        e.hashed_mtime_ms = probed.hashed_mtime_ms;
+                    })?;
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Failed to hash {}: {}", entry.path, e),
+            }
+        }
+
+        let mut by_hash = HashMap::<String, Vec<RecordingEntry>>::new();
+        for entry in library.entries() {
+            if let Some(hash) = entry.content_hash.clone() {
+                by_hash.entry(hash).or_default().push(entry);
+            }
+        }
+
+        let mut groups = Vec::new();
+        let mut reclaimable_bytes = 0;
+        for (content_hash, mut recordings) in by_hash {
+            if recordings.len() < 2 {
+                continue;
+            }
+            // Oldest first: that's the copy most likely worth keeping
+            recordings.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+            reclaimable_bytes += recordings[1..].iter().map(|e| e.size_bytes).sum::<u64>();
+            groups.push(DuplicateGroup {
+                content_hash,
+                recordings,
+            });
+        }
+        groups.sort_by(|a, b| a.recordings[0].created_at.cmp(&b.recordings[0].created_at));
+
+        info!("Found {} duplicate groups, {} bytes reclaimable", groups.len(), reclaimable_bytes);
+        Ok(DuplicateReport {
+            groups,
+            reclaimable_bytes,
+        })
+    })
+    .await
+    .map_err(|e| format!("Duplicate search failed: {}", e))?
+}
+
+//
+// ====== Folders ======
+//
+
+#[tauri::command]
+pub fn create_folder(library: State<'_, Library>, app_lock: State<'_, AppLock>, name: String) -> Result<(), String> {
+    app_lock.ensure_unlocked()?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Folder name cannot be empty".to_string());
+    }
+    library.create_folder(name)
+}
+
+#[tauri::command]
+pub fn delete_folder(library: State<'_, Library>, app_lock: State<'_, AppLock>, name: String) -> Result<(), String> {
+    app_lock.ensure_unlocked()?;
+    library.delete_folder(&name)
+}
+
+// Every folder with how many recordings it holds, alphabetically
+#[tauri::command]
+pub fn list_folders(library: State<'_, Library>, app_lock: State<'_, AppLock>) -> Result<Vec<FolderInfo>, String> {
+    app_lock.ensure_unlocked()?;
+    let entries = library.entries();
+    let mut folders = library
+        .folders()
+        .into_iter()
+        .map(|name| FolderInfo {
+            count: entries.iter().filter(|e| e.folder.as_ref() == Some(&name)).count(),
+            name,
+        })
+        .collect::<Vec<_>>();
+    folders.sort_by_key(|f| f.name.to_lowercase());
+    Ok(folders)
+}
+
+// File a recording under `folder`, or back in the library root with `None`
+#[tauri::command]
+pub fn move_recording(
+    library: State<'_, Library>,
+    app_lock: State<'_, AppLock>,
+    path: String,
+    folder: Option<String>,
+) -> Result<(), String> {
+    app_lock.ensure_unlocked()?;
+    if let Some(ref folder) = folder {
+        if !library.folders().contains(folder) {
+            return Err(format!("No folder named '{}'", folder));
+        }
+    }
+    library.update(&path, |entry| entry.folder = folder)?;
+    Ok(())
+}
+
+//
+// ====== Favorites ======
+//
+
+#[tauri::command]
+pub fn set_favorite(
+    library: State<'_, Library>,
+    app_lock: State<'_, AppLock>,
+    path: String,
+    favorite: bool,
+) -> Result<(), String> {
+    app_lock.ensure_unlocked()?;
+    library.update(&path, |entry| entry.favorite = favorite)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_favorites(library: State<'_, Library>, app_lock: State<'_, AppLock>) -> Result<Vec<RecordingEntry>, String> {
+    app_lock.ensure_unlocked()?;
+    let mut entries = library.entries();
+    entries.retain(|e| e.favorite);
+    entries.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(entries)
+}
+
+//
+// ====== Tags and saved filters ======
+//
+
+#[tauri::command]
+pub fn add_tag(library: State<'_, Library>, app_lock: State<'_, AppLock>, path: String, tag: String) -> Result<(), String> {
+    app_lock.ensure_unlocked()?;
+    let tag = normalize_tag(&tag)?;
+    library.update(&path, |entry| {
+        if !entry.tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+            entry.tags.push(tag);
+        }
+    })?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn remove_tag(library: State<'_, Library>, app_lock: State<'_, AppLock>, path: String, tag: String) -> Result<(), String> {
+    app_lock.ensure_unlocked()?;
+    library.update(&path, |entry| entry.tags.retain(|t| !t.eq_ignore_ascii_case(tag.trim())))?;
+    Ok(())
+}
+
+// Every tag in use with how many recordings carry it, most used first
+#[tauri::command]
+pub fn list_tags(library: State<'_, Library>, app_lock: State<'_, AppLock>) -> Result<Vec<TagCount>, String> {
+    app_lock.ensure_unlocked()?;
+    let mut counts = std::collections::BTreeMap::<String, usize>::new();
+    for entry in library.entries() {
+        for tag in entry.tags {
+            *counts.entry(tag).or_default() += 1;
+        }
+    }
+    let mut tags = counts
+        .into_iter()
+        .map(|(tag, count)| TagCount { tag, count })
+        .collect::<Vec<_>>();
+    tags.sort_by_key(|t| std::cmp::Reverse(t.count));
+    Ok(tags)
+}
+
+#[tauri::command]
+pub fn list_saved_filters(config: State<'_, ConfigState>) -> Vec<SavedFilter> {
+    config.get().saved_filters
+}
+
+// Save a named filter, replacing one with the same name
+#[tauri::command]
+pub fn save_filter(config: State<'_, ConfigState>, name: String, filter: LibraryFilter) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Filter name cannot be empty".to_string());
+    }
+    filter.validate()?;
+
+    config.update(|c| {
+        c.saved_filters.retain(|f| f.name != name);
+        c.saved_filters.push(SavedFilter {
+            name: name.clone(),
+            filter: filter.clone(),
+        });
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn delete_saved_filter(config: State<'_, ConfigState>, name: String) -> Result<(), String> {
+    config.update(|c| {
+        let before = c.saved_filters.len();
+        c.saved_filters.retain(|f| f.name != name);
+        if c.saved_filters.len() == before {
+            return Err(format!("No saved filter named '{}'", name));
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,112 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use tauri::State;
+use tracing::{error, info};
+
+use crate::secrets;
+
+//
+// ====== App password lock ======
+//
+
+const PASSWORD_HASH_KEY: &str = "app-password-hash";
+
+/// Whether protected commands may run. Starts locked when a password is set.
+pub struct AppLock {
+    unlocked: AtomicBool,
+}
+
+impl AppLock {
+    pub fn load() -> Self {
+        let has_password = match secrets::get_secret(PASSWORD_HASH_KEY) {
+            Ok(hash) => hash.is_some(),
+            Err(e) => {
+                // Fail closed: if the keychain can't be read we can't prove there is no password
+                error!("{}", e);
+                true
+            }
+        };
+        Self {
+            unlocked: AtomicBool::new(!has_password),
+        }
+    }
+
+    /// Guard for commands that expose recordings
+    pub fn ensure_unlocked(&self) -> Result<(), String> {
+        if self.unlocked.load(Ordering::SeqCst) {
+            Ok(())
+        } else {
+            Err("App is locked".to_string())
+        }
+    }
+}
+
+fn verify_password(password: &str) -> Result<bool, String> {
+    let stored = match secrets::get_secret(PASSWORD_HASH_KEY)? {
+        Some(hash) => hash,
+        None => return Ok(true),
+    };
+    let parsed = PasswordHash::new(&stored).map_err(|e| format!("Stored password hash is invalid: {}", e))?;
+    Ok(Argon2::default()
+        .verify_password(password.as_bytes(), &parsed)
+        .is_ok())
+}
+
+// Set, change or remove (`new_password: None`) the app password.
+// When a password already exists the current one must be supplied.
+#[tauri::command]
+pub fn set_app_password(
+    app_lock: State<'_, AppLock>,
+    current_password: Option<String>,
+    new_password: Option<String>,
+) -> Result<(), String> {
+    if !verify_password(current_password.as_deref().unwrap_or_default())? {
+        return Err("Current password is incorrect".to_string());
+    }
+
+    match new_password {
+        Some(password) => {
+            if password.len() < 4 {
+                return Err("Password must be at least 4 characters".to_string());
+            }
+            let salt = SaltString::generate(&mut chacha20poly1305::aead::OsRng);
+            let hash = Argon2::default()
+                .hash_password(password.as_bytes(), &salt)
+                .map_err(|e| format!("Failed to hash password: {}", e))?;
+            secrets::set_secret(PASSWORD_HASH_KEY, &hash.to_string())?;
+            info!("App password set");
+        }
+        None => {
+            secrets::delete_secret(PASSWORD_HASH_KEY)?;
+            info!("App password removed");
+        }
+    }
+
+    app_lock.unlocked.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn unlock_app(app_lock: State<'_, AppLock>, password: String) -> Result<(), String> {
+    if !verify_password(&password)? {
+        return Err("Incorrect password".to_string());
+    }
+    app_lock.unlocked.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+// Lock again without quitting; a no-op when no password is set
+#[tauri::command]
+pub fn lock_app(app_lock: State<'_, AppLock>) -> Result<(), String> {
+    if secrets::get_secret(PASSWORD_HASH_KEY)?.is_some() {
+        app_lock.unlocked.store(false, Ordering::SeqCst);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn is_app_locked(app_lock: State<'_, AppLock>) -> bool {
+    app_lock.ensure_unlocked().is_err()
+}
@@ -0,0 +1,174 @@
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::Serialize;
+use tauri::State;
+use tracing::level_filters::LevelFilter;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{self, Rotation};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, Registry};
+
+use crate::lock::AppLock;
+
+//
+// ====== Structured logging ======
+//
+
+const LOG_FILE_PREFIX: &str = "rekt";
+const LOG_FILE_SUFFIX: &str = "log";
+const MAX_LOG_FILES: usize = 7;
+const DEFAULT_LOG_LIMIT: usize = 200;
+
+/// Keeps the file writer alive and lets commands change the level at runtime
+pub struct LogState {
+    dir: PathBuf,
+    level_handle: reload::Handle<LevelFilter, Registry>,
+    #[allow(dead_code)] // Dropping the guard stops the background writer
+    guard: WorkerGuard,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LogEntry {
+    timestamp: String,
+    level: String,
+    target: String,
+    message: String,
+}
+
+impl LogState {
+    /// Every log file, oldest first
+    pub fn files(&self) -> Result<Vec<PathBuf>, String> {
+        // Rotated files carry the date in their name, so name order is chronological
+        let mut files = fs::read_dir(&self.dir)
+            .map_err(|e| format!("Failed to read log directory: {}", e))?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().starts_with(LOG_FILE_PREFIX))
+                    .unwrap_or(false)
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+        Ok(files)
+    }
+
+    /// Empty every log file. They're truncated rather than deleted, because the writer
+    /// keeps today's file open.
+    pub fn clear(&self) -> Result<(), String> {
+        for file in self.files()? {
+            File::create(&file).map_err(|e| format!("Failed to clear {}: {}", file.display(), e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Install the global subscriber: human-readable on stdout, JSON lines in a daily-rotated file
+pub fn init(dir: &Path) -> Result<LogState, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create log directory: {}", e))?;
+
+    let appender = rolling::Builder::new()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(dir)
+        .map_err(|e| format!("Failed to create log file: {}", e))?;
+    let (file_writer, guard) = tracing_appender::non_blocking(appender);
+
+    let (level_layer, level_handle) = reload::Layer::new(LevelFilter::INFO);
+
+    tracing_subscriber::registry()
+        .with(level_layer)
+        .with(fmt::layer())
+        .with(fmt::layer().json().with_ansi(false).with_writer(file_writer))
+        .try_init()
+        .map_err(|e| format!("Failed to install logger: {}", e))?;
+
+    Ok(LogState {
+        dir: dir.to_path_buf(),
+        level_handle,
+        guard,
+    })
+}
+
+fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    LevelFilter::from_str(level).map_err(|_| {
+        format!(
+            "Invalid log level '{}', must be one of error, warn, info, debug, trace, off",
+            level
+        )
+    })
+}
+
+fn parse_line(line: &str) -> Option<LogEntry> {
+    let value: serde_json::Value = serde_json::from_str(line).ok()?;
+    let field = |name: &str| value.get(name).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+
+    Some(LogEntry {
+        timestamp: field("timestamp"),
+        level: field("level"),
+        target: field("target"),
+        message: value
+            .pointer("/fields/message")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string(),
+    })
+}
+
+// Most recent log entries at or above `level`, oldest first
+#[tauri::command]
+pub fn get_recent_logs(
+    app_lock: State<'_, AppLock>,
+    log_state: State<'_, LogState>,
+    level: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<LogEntry>, String> {
+    app_lock.ensure_unlocked()?;
+    let max_level = match level {
+        Some(level) => parse_level(&level)?,
+        None => LevelFilter::TRACE,
+    };
+    let limit = limit.unwrap_or(DEFAULT_LOG_LIMIT);
+
+    let files = log_state.files()?;
+    let mut entries = Vec::new();
+    for file in files.iter().rev() {
+        let contents = fs::read_to_string(file)
+            .map_err(|e| format!("Failed to read log file: {}", e))?;
+        for entry in contents.lines().rev().filter_map(parse_line) {
+            let matches = tracing::Level::from_str(&entry.level)
+                .map(|l| LevelFilter::from_level(l) <= max_level)
+                .unwrap_or(false);
+            if matches {
+                entries.push(entry);
+                if entries.len() >= limit {
+                    break;
+                }
+            }
+        }
+        if entries.len() >= limit {
+            break;
+        }
+    }
+
+    entries.reverse();
+    Ok(entries)
+}
+
+// Change the minimum level written to stdout and the log file
+#[tauri::command]
+pub fn set_log_level(log_state: State<'_, LogState>, level: String) -> Result<(), String> {
+    let filter = parse_level(&level)?;
+    log_state
+        .level_handle
+        .modify(|current| *current = filter)
+        .map_err(|e| format!("Failed to change log level: {}", e))?;
+
+    tracing::info!("Log level set to {}", filter);
+    Ok(())
+}
@@ -0,0 +1,254 @@
+use std::collections::HashSet;
+use std::process::Command;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tracing::{debug, info, warn};
+
+use crate::config::ConfigState;
+use crate::notifications;
+use crate::RecordingState;
+
+//
+// ====== Conferencing app detection ======
+//
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MeetingDetectConfig {
+    pub enabled: bool,
+    /// Matched case-insensitively against the names of processes using the microphone.
+    /// Add browser names such as `chrome` to catch Google Meet; where microphone use
+    /// can't be seen (macOS) a matching process only has to be running.
+    pub process_names: Vec<String>,
+    /// Switch to this profile and start recording when a meeting is detected, instead of
+    /// only emitting `meeting-detected`
+    pub auto_start_profile: Option<String>,
+}
+
+impl Default for MeetingDetectConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            process_names: ["zoom", "teams", "webex", "skype"].map(String::from).to_vec(),
+            auto_start_profile: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct MeetingDetectedEvent {
+    /// Configured name that matched
+    app: String,
+    /// Whether the app was seen using the microphone, rather than just running
+    using_microphone: bool,
+    /// Whether recording was started for it
+    recording: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct MeetingEndedEvent {
+    app: String,
+}
+
+fn run(command: &mut Command) -> Result<String, String> {
+    let output = command
+        .output()
+        .map_err(|e| format!("Failed to list processes: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Listing processes failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+// Programs recording from an input right now, per PulseAudio or PipeWire
+#[cfg(target_os = "linux")]
+pub fn microphone_users() -> Result<Vec<String>, String> {
+    let output = run(Command::new("pactl").args(["list", "source-outputs"]))?;
+    Ok(output
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("application.process.binary = "))
+        .map(|binary| binary.trim_matches('"').to_string())
+        .collect())
+}
+
+// Apps Windows' privacy settings show as using the microphone: their consent entry has
+// a start time but no stop time yet
+#[cfg(target_os = "windows")]
+pub fn microphone_users() -> Result<Vec<String>, String> {
+    const CONSENT_STORE: &str =
+        r"HKCU\Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore\microphone";
+    let output = run(Command::new("reg").args(["query", CONSENT_STORE, "/s", "/v", "LastUsedTimeStop"]))?;
+    let mut users = Vec::new();
+    let mut app = None;
+    for line in output.lines() {
+        if line.starts_with("HKEY_") {
+            // Desktop apps are keyed by their path with `#` for `\`, store apps by package
+            app = line.rsplit(['\\', '#']).next().map(|name| name.trim_end_matches(".exe").to_string());
+        } else if line.trim().starts_with("LastUsedTimeStop") && line.trim().ends_with("0x0") {
+            users.extend(app.take());
+        }
+    }
+    Ok(users)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub fn microphone_users() -> Result<Vec<String>, String> {
+    Err("Microphone use isn't visible on this platform".to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn running_processes() -> Result<Vec<String>, String> {
+    let entries = std::fs::read_dir("/proc").map_err(|e| format!("Failed to list processes: {}", e))?;
+    Ok(entries
+        .flatten()
+        .filter_map(|entry| std::fs::read_to_string(entry.path().join("comm")).ok())
+        .map(|name| name.trim().to_string())
+        .collect())
+}
+
+#[cfg(target_os = "windows")]
+fn running_processes() -> Result<Vec<String>, String> {
+    // "zoom.exe","1234",...
+    let output = run(Command::new("tasklist").args(["/fo", "csv", "/nh"]))?;
+    Ok(output
+        .lines()
+        .filter_map(|line| line.split(',').next())
+        .map(|name| name.trim_matches('"').trim_end_matches(".exe").to_string())
+        .collect())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn running_processes() -> Result<Vec<String>, String> {
+    let output = run(Command::new("ps").args(["-axco", "comm="]))?;
+    Ok(output.lines().map(|name| name.trim().to_string()).collect())
+}
+
+// Configured names found among the microphone's users, or among running processes if
+// those can't be seen; the flag says which
+fn detect(names: &[String]) -> Result<(HashSet<String>, bool), String> {
+    let (processes, using_microphone) = match microphone_users() {
+        Ok(users) => (users, true),
+        Err(e) => {
+            debug!("Falling back to running processes: {}", e);
+            (running_processes()?, false)
+        }
+    };
+    let processes = processes.iter().map(|p| p.to_lowercase()).collect::<Vec<_>>();
+    let found = names
+        .iter()
+        .filter(|name| {
+            let name = name.to_lowercase();
+            processes.iter().any(|process| process.contains(&name))
+        })
+        .cloned()
+        .collect();
+    Ok((found, using_microphone))
+}
+
+fn meeting_detected(app_handle: &AppHandle, config: &MeetingDetectConfig, app: &str, using_microphone: bool) {
+    info!("Detected a meeting in {}", app);
+    let idle = app_handle.state::<Arc<RecordingState>>().is_idle();
+    let mut recording = false;
+    if let Some(profile) = config.auto_start_profile.clone().filter(|_| idle) {
+        let started = app_handle
+            .state::<ConfigState>()
+            .update(|c| {
+                if !c.profiles.iter().any(|p| p.name == profile) {
+                    return Err(format!("No profile named '{}'", profile));
+                }
+                c.active_profile = Some(profile.clone());
+                Ok(())
+            })
+            .and_then(|_| crate::start_recording_internal(app_handle).map_err(String::from));
+        match started {
+            Ok(_) => recording = true,
+            Err(e) => warn!("Failed to start recording for the meeting in {}: {}", app, e),
+        }
+    }
+
+    if !recording {
+        notifications::notify(app_handle, "Meeting detected", &format!("{} is in a call. Record it?", app));
+    }
+    let _ = app_handle.emit(
+        "meeting-detected",
+        MeetingDetectedEvent {
+            app: app.to_string(),
+            using_microphone,
+            recording,
+        },
+    );
+}
+
+/// Watch for the configured conferencing apps starting and ending calls
+pub fn watch(app_handle: AppHandle) {
+    thread::spawn(move || {
+        let mut active = HashSet::new();
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            let config = app_handle.state::<ConfigState>().get().meeting_detection;
+            if !config.enabled {
+                active.clear();
+                continue;
+            }
+            let (found, using_microphone) = match detect(&config.process_names) {
+                Ok(found) => found,
+                Err(e) => {
+                    warn!("Meeting detection failed: {}", e);
+                    continue;
+                }
+            };
+
+            for app in found.difference(&active) {
+                meeting_detected(&app_handle, &config, app, using_microphone);
+            }
+            for app in active.difference(&found) {
+                info!("Meeting in {} ended", app);
+                let _ = app_handle.emit("meeting-ended", MeetingEndedEvent { app: app.clone() });
+            }
+            active = found;
+        }
+    });
+}
+
+//
+// ====== Meeting detection commands ======
+//
+
+#[tauri::command]
+pub fn set_meeting_detection(config: State<'_, ConfigState>, settings: MeetingDetectConfig) -> Result<(), String> {
+    let settings = MeetingDetectConfig {
+        process_names: settings
+            .process_names
+            .iter()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect(),
+        ..settings
+    };
+    if settings.enabled && settings.process_names.is_empty() {
+        return Err("At least one process name is needed".to_string());
+    }
+    config.update(|c| {
+        if let Some(ref profile) = settings.auto_start_profile {
+            if !c.profiles.iter().any(|p| &p.name == profile) {
+                return Err(format!("No profile named '{}'", profile));
+            }
+        }
+        c.meeting_detection = settings.clone();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn get_meeting_detection(config: State<'_, ConfigState>) -> MeetingDetectConfig {
+    config.get().meeting_detection
+}
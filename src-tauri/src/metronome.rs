@@ -0,0 +1,136 @@
+use std::f32::consts::PI;
+use std::sync::Mutex;
+use std::time::Duration;
+
+pub use rekt_core::settings::MetronomeConfig;
+use rodio::{Sink, Source};
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+use crate::config::ConfigState;
+use crate::AudioPlaybackState;
+
+//
+// ====== Metronome click during recording ======
+//
+
+const CLICK_SAMPLE_RATE: u32 = 48_000;
+const CLICK_MS: u32 = 30;
+const ACCENT_HZ: f32 = 1_600.0;
+const BEAT_HZ: f32 = 1_000.0;
+
+/// Endless click track. It only ever goes to the output device, so it is heard on the
+/// monitor (headphones) but never lands in the recorded file.
+struct Click {
+    config: MetronomeConfig,
+    // Position within the current beat, in samples
+    position: u64,
+    beat_samples: u64,
+    beat: u32,
+}
+
+impl Click {
+    fn new(config: MetronomeConfig) -> Self {
+        Self {
+            beat_samples: (CLICK_SAMPLE_RATE as f32 * 60.0 / config.bpm) as u64,
+            config,
+            position: 0,
+            beat: 0,
+        }
+    }
+}
+
+impl Iterator for Click {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let click_samples = (CLICK_SAMPLE_RATE * CLICK_MS / 1000) as u64;
+        let sample = if self.position < click_samples {
+            let t = self.position as f32 / CLICK_SAMPLE_RATE as f32;
+            let frequency = if self.beat == 0 { ACCENT_HZ } else { BEAT_HZ };
+            // Short sine burst with a linear decay so it doesn't pop
+            let envelope = 1.0 - self.position as f32 / click_samples as f32;
+            (2.0 * PI * frequency * t).sin() * envelope * self.config.volume
+        } else {
+            0.0
+        };
+
+        self.position += 1;
+        if self.position >= self.beat_samples {
+            self.position = 0;
+            self.beat = (self.beat + 1) % self.config.beats_per_bar;
+        }
+        Some(sample)
+    }
+}
+
+impl Source for Click {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        CLICK_SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// The click currently playing, if any
+#[derive(Default)]
+pub struct MetronomeState {
+    sink: Mutex<Option<Sink>>,
+}
+
+/// Start clicking if the metronome is enabled; called when a recording starts
+pub fn start(app_handle: &AppHandle) {
+    let config = app_handle.state::<ConfigState>().get().metronome;
+    if !config.enabled {
+        return;
+    }
+
+    let playback_state = app_handle.state::<AudioPlaybackState>();
+    let sink = crate::ensure_output_stream(&playback_state)
+        .and_then(|handle| Sink::try_new(&handle).map_err(|e| format!("Failed to create sink: {}", e)));
+    match sink {
+        Ok(sink) => {
+            info!("Metronome at {} BPM, {} beats per bar", config.bpm, config.beats_per_bar);
+            sink.append(Click::new(config));
+            if let Some(previous) = app_handle.state::<MetronomeState>().sink.lock().unwrap().replace(sink) {
+                previous.stop();
+            }
+        }
+        Err(e) => warn!("Metronome unavailable: {}", e),
+    }
+}
+
+/// Silence the click; called when a recording stops
+pub fn stop(app_handle: &AppHandle) {
+    if let Some(sink) = app_handle.state::<MetronomeState>().sink.lock().unwrap().take() {
+        sink.stop();
+    }
+}
+
+//
+// ====== Metronome commands ======
+//
+
+#[tauri::command]
+pub fn set_metronome(config: State<'_, ConfigState>, settings: MetronomeConfig) -> Result<(), String> {
+    settings.validate()?;
+    config.update(|c| {
+        c.metronome = settings.clone();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn get_metronome(config: State<'_, ConfigState>) -> MetronomeConfig {
+    config.get().metronome
+}
@@ -0,0 +1,176 @@
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample};
+use serde::Serialize;
+use tauri::State;
+use tracing::{error, info};
+
+use crate::processing::{self, AudioBuffer};
+use crate::RecordingState;
+
+//
+// ====== Microphone check ======
+//
+
+const MIN_TEST_MS: u64 = 500;
+const MAX_TEST_MS: u64 = 10_000;
+const WINDOW_MS: u32 = 50;
+
+const CLIPPING_PEAK_DB: f32 = -0.1;
+const NO_SIGNAL_PEAK_DB: f32 = -90.0;
+const TOO_QUIET_RMS_DB: f32 = -45.0;
+const HIGH_NOISE_FLOOR_DB: f32 = -50.0;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MicVerdict {
+    Good,
+    NoSignal,
+    TooQuiet,
+    Clipping,
+    HighNoise,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MicTestResult {
+    device_name: String,
+    duration_ms: u64,
+    peak_db: f32,
+    rms_db: f32,
+    /// Level of the quietest tenth of the test, i.e. the background when nobody speaks
+    noise_floor_db: f32,
+    clipped_samples: usize,
+    verdict: MicVerdict,
+}
+
+/// Open the default input device, appending everything it captures to `samples` as
+/// interleaved f32. Capture runs until the returned stream is dropped.
+pub fn open_input(samples: Arc<Mutex<Vec<f32>>>) -> Result<(String, cpal::SupportedStreamConfig, cpal::Stream), String> {
+    let host = cpal::default_host();
+    let device = host
+        .default_input_device()
+        .ok_or_else(|| "No input device available".to_string())?;
+    let device_name = device.name().unwrap_or_else(|_| "unknown".to_string());
+    let config = device
+        .default_input_config()
+        .map_err(|e| format!("Failed to get default input config: {}", e))?;
+
+    let stream = match config.sample_format() {
+        SampleFormat::I16 => build_capture::<i16>(&device, &config.config(), samples),
+        SampleFormat::U16 => build_capture::<u16>(&device, &config.config(), samples),
+        SampleFormat::F32 => build_capture::<f32>(&device, &config.config(), samples),
+        other => return Err(format!("Unsupported sample format: {:?}", other)),
+    }?;
+
+    stream
+        .play()
+        .map_err(|e| format!("Failed to start input stream: {}", e))?;
+    Ok((device_name, config, stream))
+}
+
+/// Record `duration` from the default input device into memory
+pub fn capture_input(duration: Duration) -> Result<(String, AudioBuffer), String> {
+    let samples = Arc::new(Mutex::new(Vec::new()));
+    let (device_name, config, stream) = open_input(Arc::clone(&samples))?;
+    thread::sleep(duration);
+    drop(stream);
+
+    let samples = std::mem::take(&mut *samples.lock().unwrap());
+    Ok((
+        device_name,
+        AudioBuffer {
+            channels: config.channels(),
+            sample_rate: config.sample_rate().0,
+            samples,
+        },
+    ))
+}
+
+fn build_capture<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    samples: Arc<Mutex<Vec<f32>>>,
+) -> Result<cpal::Stream, String>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                if let Ok(mut samples) = samples.lock() {
+                    samples.extend(data.iter().map(|&s| s.to_sample::<f32>()));
+                }
+            },
+            |err| error!("An error occurred on the input stream: {}", err),
+            None,
+        )
+        .map_err(|e| format!("Failed to build input stream: {}", e))
+}
+
+fn analyze(buffer: &AudioBuffer) -> (f32, f32, f32, usize, MicVerdict) {
+    let peak_db = processing::to_db(buffer.peak());
+    let rms_db = processing::to_db(buffer.rms());
+
+    let mut windows = buffer.window_rms(WINDOW_MS);
+    windows.sort_by(|a, b| a.total_cmp(b));
+    let noise_floor_db = processing::to_db(windows.get(windows.len() / 10).copied().unwrap_or(0.0));
+
+    let clipped_samples = buffer.samples.iter().filter(|s| s.abs() >= 0.999).count();
+
+    // Worst problem first: a clipping mic is also loud, a dead one is also quiet
+    let verdict = if peak_db <= NO_SIGNAL_PEAK_DB {
+        MicVerdict::NoSignal
+    } else if peak_db >= CLIPPING_PEAK_DB {
+        MicVerdict::Clipping
+    } else if rms_db < TOO_QUIET_RMS_DB {
+        MicVerdict::TooQuiet
+    } else if noise_floor_db > HIGH_NOISE_FLOOR_DB {
+        MicVerdict::HighNoise
+    } else {
+        MicVerdict::Good
+    };
+
+    (peak_db, rms_db, noise_floor_db, clipped_samples, verdict)
+}
+
+// Record a short burst and judge whether the input level is usable
+#[tauri::command]
+pub async fn run_mic_test(
+    state: State<'_, Arc<RecordingState>>,
+    duration_ms: Option<u64>,
+) -> Result<MicTestResult, String> {
+    if !state.is_idle() {
+        return Err("Cannot test the microphone while recording".to_string());
+    }
+
+    let duration_ms = duration_ms.unwrap_or(3000).clamp(MIN_TEST_MS, MAX_TEST_MS);
+    let (device_name, buffer) =
+        tauri::async_runtime::spawn_blocking(move || capture_input(Duration::from_millis(duration_ms)))
+            .await
+            .map_err(|e| format!("Mic test failed: {}", e))??;
+
+    if buffer.samples.is_empty() {
+        return Err("No audio was captured from the input device".to_string());
+    }
+
+    let (peak_db, rms_db, noise_floor_db, clipped_samples, verdict) = analyze(&buffer);
+    info!(
+        "Mic test on {}: peak {:.1} dBFS, RMS {:.1} dBFS, floor {:.1} dBFS -> {:?}",
+        device_name, peak_db, rms_db, noise_floor_db, verdict
+    );
+
+    Ok(MicTestResult {
+        device_name,
+        duration_ms,
+        peak_db,
+        rms_db,
+        noise_floor_db,
+        clipped_samples,
+        verdict,
+    })
+}
@@ -0,0 +1,217 @@
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use midir::{MidiInput, MidiInputConnection};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tracing::{info, warn};
+
+use crate::config::ConfigState;
+use crate::remote::{self, RemoteAction};
+
+//
+// ====== MIDI remote control ======
+//
+
+const CLIENT_NAME: &str = "rekt";
+
+const NOTE_ON: u8 = 0x90;
+const CONTROL_CHANGE: u8 = 0xB0;
+
+/// A note or controller on a specific channel (0-15)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MidiTrigger {
+    Note { channel: u8, note: u8 },
+    ControlChange { channel: u8, controller: u8 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MidiBinding {
+    pub trigger: MidiTrigger,
+    pub action: RemoteAction,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MidiConfig {
+    /// Input port name as reported by `list_midi_inputs`
+    pub port: String,
+    #[serde(default)]
+    pub bindings: Vec<MidiBinding>,
+}
+
+/// The open input connection, plus the action waiting for a learned trigger
+#[derive(Default)]
+pub struct MidiState {
+    connection: Mutex<Option<MidiInputConnection<()>>>,
+    learning: Arc<Mutex<Option<RemoteAction>>>,
+}
+
+impl MidiState {
+    /// Listen on the port named in `midi`, or close the connection with `None`
+    pub fn connect(&self, app_handle: AppHandle, midi: Option<&MidiConfig>) -> Result<(), String> {
+        let mut connection = self.connection.lock().unwrap();
+        if let Some(previous) = connection.take() {
+            previous.close();
+        }
+
+        let port_name = match midi {
+            Some(midi) => midi.port.clone(),
+            None => return Ok(()),
+        };
+
+        let input = MidiInput::new(CLIENT_NAME).map_err(|e| format!("Failed to open MIDI: {}", e))?;
+        let port = input
+            .ports()
+            .into_iter()
+            .find(|p| input.port_name(p).ok().as_deref() == Some(port_name.as_str()))
+            .ok_or_else(|| format!("MIDI input '{}' not found", port_name))?;
+
+        let learning = Arc::clone(&self.learning);
+        let opened = input
+            .connect(
+                &port,
+                "rekt-control",
+                move |_, message, _| {
+                    if let Some(trigger) = parse_trigger(message) {
+                        handle_trigger(&app_handle, &learning, trigger);
+                    }
+                },
+                (),
+            )
+            .map_err(|e| format!("Failed to connect to MIDI input '{}': {}", port_name, e))?;
+
+        info!("Listening for MIDI on '{}'", port_name);
+        *connection = Some(opened);
+        Ok(())
+    }
+}
+
+// Note-on and controller messages with a non-zero value; note-off and
+// release (velocity/value 0) are ignored so a button press acts once
+fn parse_trigger(message: &[u8]) -> Option<MidiTrigger> {
+    let (&status, data) = message.split_first()?;
+    let (&number, rest) = data.split_first()?;
+    let &value = rest.first()?;
+    if value == 0 {
+        return None;
+    }
+
+    let channel = status & 0x0F;
+    match status & 0xF0 {
+        NOTE_ON => Some(MidiTrigger::Note { channel, note: number }),
+        CONTROL_CHANGE => Some(MidiTrigger::ControlChange {
+            channel,
+            controller: number,
+        }),
+        _ => None,
+    }
+}
+
+fn handle_trigger(app_handle: &AppHandle, learning: &Mutex<Option<RemoteAction>>, trigger: MidiTrigger) {
+    if let Some(action) = learning.lock().unwrap().take() {
+        let binding = MidiBinding { trigger, action };
+        let config = app_handle.state::<ConfigState>();
+        let saved = config.update(|c| {
+            let midi = c.midi.get_or_insert_with(MidiConfig::default);
+            save_binding(midi, binding.clone());
+            Ok(())
+        });
+        match saved {
+            Ok(()) => {
+                info!("Learned MIDI binding {:?}", binding);
+                let _ = app_handle.emit("midi-binding-learned", binding);
+            }
+            Err(e) => warn!("Failed to save learned MIDI binding: {}", e),
+        }
+        return;
+    }
+
+    let bindings = app_handle.state::<ConfigState>().get().midi.map(|m| m.bindings).unwrap_or_default();
+    if let Some(binding) = bindings.into_iter().find(|b| b.trigger == trigger) {
+        // Stopping writes the file; keep that off the MIDI callback thread
+        let app_handle = app_handle.clone();
+        thread::spawn(move || remote::perform(&app_handle, "midi", binding.action));
+    }
+}
+
+// One trigger per action: learning a new trigger replaces the old one
+fn save_binding(midi: &mut MidiConfig, binding: MidiBinding) {
+    midi.bindings.retain(|b| b.action != binding.action && b.trigger != binding.trigger);
+    midi.bindings.push(binding);
+}
+
+//
+// ====== MIDI commands ======
+//
+
+#[tauri::command]
+pub fn list_midi_inputs() -> Result<Vec<String>, String> {
+    let input = MidiInput::new(CLIENT_NAME).map_err(|e| format!("Failed to open MIDI: {}", e))?;
+    Ok(input
+        .ports()
+        .iter()
+        .filter_map(|p| input.port_name(p).ok())
+        .collect())
+}
+
+// Bind `action` to a note or CC on `port`. Without a `trigger` the next note or
+// CC received is learned instead and reported with a `midi-binding-learned` event.
+#[tauri::command]
+pub fn set_midi_binding(
+    app_handle: AppHandle,
+    config: State<'_, ConfigState>,
+    midi: State<'_, MidiState>,
+    port: String,
+    action: RemoteAction,
+    trigger: Option<MidiTrigger>,
+) -> Result<(), String> {
+    let current = config.get().midi.filter(|m| m.port == port);
+    let mut updated = current.clone().unwrap_or(MidiConfig {
+        port,
+        bindings: Vec::new(),
+    });
+
+    if current.is_none() {
+        midi.connect(app_handle, Some(&updated))?;
+    }
+
+    match trigger {
+        Some(trigger) => save_binding(&mut updated, MidiBinding { trigger, action }),
+        None => *midi.learning.lock().unwrap() = Some(action),
+    }
+
+    config.update(|c| {
+        c.midi = Some(updated.clone());
+        Ok(())
+    })
+}
+
+// Drop the binding for `action`; removing the last one closes the MIDI input
+#[tauri::command]
+pub fn remove_midi_binding(
+    app_handle: AppHandle,
+    config: State<'_, ConfigState>,
+    midi: State<'_, MidiState>,
+    action: RemoteAction,
+) -> Result<(), String> {
+    let mut updated = match config.get().midi {
+        Some(m) => m,
+        None => return Ok(()),
+    };
+    updated.bindings.retain(|b| b.action != action);
+
+    let updated = (!updated.bindings.is_empty()).then_some(updated);
+    if updated.is_none() {
+        midi.connect(app_handle, None)?;
+    }
+    config.update(|c| {
+        c.midi = updated.clone();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn get_midi_config(config: State<'_, ConfigState>) -> Option<MidiConfig> {
+    config.get().midi
+}
@@ -0,0 +1,65 @@
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+use tracing::info;
+
+//
+// ====== Mini recorder window ======
+//
+
+pub const MINI_RECORDER_WINDOW: &str = "mini-recorder";
+const WIDTH: f64 = 300.0;
+const HEIGHT: f64 = 96.0;
+// The frontend shows only the recorder controls for this view
+const URL: &str = "index.html?view=mini";
+
+#[derive(Debug, Clone, Serialize)]
+struct MiniRecorderEvent {
+    open: bool,
+}
+
+/// Tell every window whether the mini recorder is open, e.g. to toggle a menu item
+pub fn notify(app_handle: &AppHandle, open: bool) {
+    let _ = app_handle.emit("mini-recorder-changed", MiniRecorderEvent { open });
+}
+
+// Open a small always-on-top window with just the recorder controls, or bring it forward
+// if it is already open. It drives the same recorder as the main window: events go to
+// every window and any of them can start, pause or stop. Async because building a window
+// from a synchronous command, which runs on the main thread, deadlocks on Windows.
+#[tauri::command]
+pub async fn open_mini_recorder(app_handle: AppHandle) -> Result<(), String> {
+    if let Some(window) = app_handle.get_webview_window(MINI_RECORDER_WINDOW) {
+        window.show().map_err(|e| format!("Failed to show mini recorder: {}", e))?;
+        return window.set_focus().map_err(|e| format!("Failed to focus mini recorder: {}", e));
+    }
+
+    WebviewWindowBuilder::new(&app_handle, MINI_RECORDER_WINDOW, WebviewUrl::App(URL.into()))
+        .title("rekt")
+        .inner_size(WIDTH, HEIGHT)
+        .resizable(false)
+        .maximizable(false)
+        .minimizable(false)
+        .always_on_top(true)
+        .visible_on_all_workspaces(true)
+        .skip_taskbar(true)
+        .build()
+        .map_err(|e| format!("Failed to open mini recorder: {}", e))?;
+    info!("Mini recorder opened");
+    notify(&app_handle, true);
+    Ok(())
+}
+
+// Close the mini recorder; recording carries on regardless
+#[tauri::command]
+pub fn close_mini_recorder(app_handle: AppHandle) -> Result<(), String> {
+    match app_handle.get_webview_window(MINI_RECORDER_WINDOW) {
+        // Closing sends the window's `Destroyed` event, which does the notifying
+        Some(window) => window.close().map_err(|e| format!("Failed to close mini recorder: {}", e)),
+        None => Ok(()),
+    }
+}
+
+#[tauri::command]
+pub fn is_mini_recorder_open(app_handle: AppHandle) -> bool {
+    app_handle.get_webview_window(MINI_RECORDER_WINDOW).is_some()
+}
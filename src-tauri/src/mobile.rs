@@ -0,0 +1,454 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+use crate::cues::Cue;
+use crate::notifications;
+use crate::{RecorderState, RecordingState};
+
+//
+// ====== Phone platform setup ======
+//
+
+/// Point temporary files somewhere the app may write. Android's default temp dir,
+/// `/data/local/tmp`, is off limits to apps; iOS already sets `TMPDIR` to the sandbox.
+#[cfg(target_os = "android")]
+pub fn init(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
+    let cache_dir = app.path().app_cache_dir()?;
+    std::fs::create_dir_all(&cache_dir)?;
+    std::env::set_var("TMPDIR", cache_dir);
+    Ok(())
+}
+
+/// Get the OS ready to hand the microphone over before capture starts. iOS only lets an
+/// app record while its audio session allows it; Android needs nothing beyond the
+/// `RECORD_AUDIO` permission.
+pub fn prepare_capture() -> Result<(), String> {
+    #[cfg(target_os = "ios")]
+    audio_session::activate()?;
+    Ok(())
+}
+
+/// Run `f` with the app's activity on the current thread
+#[cfg(target_os = "android")]
+pub fn with_activity<T>(
+    f: impl FnOnce(&mut jni::JNIEnv, &jni::objects::JObject) -> jni::errors::Result<T>,
+) -> Result<T, String> {
+    let context = ndk_context::android_context();
+    // SAFETY: both pointers are set up by the Android runtime before `run` is called
+    let vm = unsafe { jni::JavaVM::from_raw(context.vm().cast()) }
+        .map_err(|e| format!("Failed to reach the Java VM: {}", e))?;
+    let activity = unsafe { jni::objects::JObject::from_raw(context.context().cast()) };
+    let mut env = vm
+        .attach_current_thread()
+        .map_err(|e| format!("Failed to reach the Java VM: {}", e))?;
+    let result = f(&mut env, &activity);
+    // A Java exception left pending makes every later JNI call on this thread fail
+    if env.exception_check().unwrap_or(false) {
+        let _ = env.exception_clear();
+    }
+    result.map_err(|e| format!("Android call failed: {}", e))
+}
+
+//
+// ====== Haptics ======
+//
+
+/// Tap the vibration motor: lighter when recording starts, firmer when it stops
+pub fn haptic(app_handle: &AppHandle, cue: Cue) {
+    #[cfg(target_os = "ios")]
+    {
+        // UIKit feedback generators only work on the main thread
+        let result = app_handle.run_on_main_thread(move || {
+            if let Err(e) = audio_session::impact(cue) {
+                warn!("Haptic feedback failed: {}", e);
+            }
+        });
+        if let Err(e) = result {
+            warn!("Haptic feedback failed: {}", e);
+        }
+    }
+    #[cfg(target_os = "android")]
+    {
+        let _ = app_handle;
+        if let Err(e) = android::vibrate(cue) {
+            warn!("Haptic feedback failed: {}", e);
+        }
+    }
+}
+
+//
+// ====== Interruptions ======
+//
+
+// Set while a recording is paused because of an interruption, so only those are resumed
+static PAUSED_BY_INTERRUPTION: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum InterruptionCause {
+    PhoneCall,
+    /// iOS doesn't say why: a call, an alarm, or another app taking over audio
+    AudioSession,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct RecordingInterruptedEvent {
+    cause: InterruptionCause,
+    /// Whether a recording was paused for it
+    paused: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct RecordingInterruptionEndedEvent {
+    /// Whether the paused recording carried on by itself
+    resumed: bool,
+    error: Option<String>,
+}
+
+fn interruption_began(app_handle: &AppHandle, cause: InterruptionCause) {
+    info!("Audio interrupted ({:?})", cause);
+    let state = app_handle.state::<Arc<RecordingState>>();
+    let paused = state.recorder_state() == RecorderState::Recording
+        && match crate::pause_recording_internal(app_handle) {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("Failed to pause recording for the interruption: {}", e);
+                false
+            }
+        };
+    PAUSED_BY_INTERRUPTION.store(paused, Ordering::SeqCst);
+    let _ = app_handle.emit("recording-interrupted", RecordingInterruptedEvent { cause, paused });
+}
+
+// `may_resume` is false when the OS says the interrupting app means to keep the audio
+fn interruption_ended(app_handle: &AppHandle, may_resume: bool) {
+    info!("Audio interruption ended");
+    let mut event = RecordingInterruptionEndedEvent {
+        resumed: false,
+        error: None,
+    };
+    // Still paused, i.e. not stopped or resumed by hand in the meantime
+    let paused = PAUSED_BY_INTERRUPTION.swap(false, Ordering::SeqCst)
+        && app_handle.state::<Arc<RecordingState>>().recorder_state() == RecorderState::Paused;
+    if paused && may_resume {
+        match prepare_capture().and_then(|_| crate::resume_recording_internal(app_handle)) {
+            Ok(()) => event.resumed = true,
+            Err(e) => {
+                warn!("Failed to resume recording after the interruption: {}", e);
+                event.error = Some(e);
+            }
+        }
+    }
+    if paused && !event.resumed {
+        notifications::notify(
+            app_handle,
+            "Recording paused",
+            "The recording was paused for an interruption. Resume it when you're ready.",
+        );
+    }
+    let _ = app_handle.emit("recording-interruption-ended", event);
+}
+
+/// Pause recording through phone calls and other interruptions, resuming afterwards
+pub fn watch_interruptions(app_handle: AppHandle) {
+    #[cfg(target_os = "ios")]
+    audio_session::observe_interruptions(app_handle);
+    #[cfg(target_os = "android")]
+    android::watch(app_handle);
+}
+
+#[cfg(target_os = "ios")]
+mod audio_session {
+    use std::ptr::{self, NonNull};
+
+    use block2::RcBlock;
+    use objc2::msg_send;
+    use objc2::rc::{Allocated, Retained};
+    use objc2::runtime::{AnyClass, AnyObject, Bool};
+    use objc2_foundation::NSString;
+    use tauri::AppHandle;
+
+    use super::InterruptionCause;
+    use crate::cues::Cue;
+
+    #[link(name = "AVFAudio", kind = "framework")]
+    extern "C" {}
+
+    // `AVAudioSessionCategoryPlayAndRecord`, so playback keeps working while recording
+    const PLAY_AND_RECORD: &str = "AVAudioSessionCategoryPlayAndRecord";
+    // `DefaultToSpeaker | AllowBluetooth`: play through the speaker rather than the
+    // earpiece, and accept Bluetooth headset microphones
+    const CATEGORY_OPTIONS: usize = 0x8 | 0x4;
+    const INTERRUPTION_NOTIFICATION: &str = "AVAudioSessionInterruptionNotification";
+    const INTERRUPTION_TYPE_KEY: &str = "AVAudioSessionInterruptionTypeKey";
+    const INTERRUPTION_OPTION_KEY: &str = "AVAudioSessionInterruptionOptionKey";
+    // `AVAudioSessionInterruptionTypeBegan`; `Ended` is 0
+    const INTERRUPTION_BEGAN: usize = 1;
+    // `AVAudioSessionInterruptionOptionShouldResume`
+    const SHOULD_RESUME: usize = 1;
+
+    fn shared_session() -> Result<Retained<AnyObject>, String> {
+        let class = AnyClass::get(c"AVAudioSession").ok_or_else(|| "AVFAudio is not available".to_string())?;
+        // SAFETY: `sharedInstance` returns the app's session, which lives as long as the app
+        let session: Option<Retained<AnyObject>> = unsafe { msg_send![class, sharedInstance] };
+        session.ok_or_else(|| "No audio session".to_string())
+    }
+
+    pub fn activate() -> Result<(), String> {
+        let session = shared_session()?;
+        let category = NSString::from_str(PLAY_AND_RECORD);
+        let mut error: *mut AnyObject = ptr::null_mut();
+        // SAFETY: both take an `NSError **` that is only written on failure
+        let categorized: Bool = unsafe {
+            msg_send![&*session, setCategory: &*category, withOptions: CATEGORY_OPTIONS, error: &mut error]
+        };
+        if !categorized.as_bool() {
+            return Err("Failed to set the audio session up for recording".to_string());
+        }
+        let active: Bool = unsafe { msg_send![&*session, setActive: Bool::YES, error: &mut error] };
+        if !active.as_bool() {
+            return Err("Failed to activate the audio session; another app may be using audio".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn impact(cue: Cue) -> Result<(), String> {
+        // `UIImpactFeedbackStyleLight` and `UIImpactFeedbackStyleHeavy`
+        let style: isize = match cue {
+            Cue::Start => 0,
+            Cue::Stop => 2,
+        };
+        let class =
+            AnyClass::get(c"UIImpactFeedbackGenerator").ok_or_else(|| "UIKit is not available".to_string())?;
+        // SAFETY: called on the main thread, which UIKit requires
+        unsafe {
+            let allocated: Allocated<AnyObject> = msg_send![class, alloc];
+            let generator: Option<Retained<AnyObject>> = msg_send![allocated, initWithStyle: style];
+            let generator = generator.ok_or_else(|| "No feedback generator".to_string())?;
+            let _: () = msg_send![&*generator, impactOccurred];
+        }
+        Ok(())
+    }
+
+    // An unsigned integer out of a notification's `userInfo`, if it's there
+    fn user_info_value(notification: &AnyObject, key: &str) -> Option<usize> {
+        let key = NSString::from_str(key);
+        // SAFETY: `userInfo` is an NSDictionary and the values read here are NSNumbers
+        unsafe {
+            let info: Option<Retained<AnyObject>> = msg_send![notification, userInfo];
+            let value: Option<Retained<AnyObject>> = msg_send![&*info?, objectForKey: &*key];
+            Some(msg_send![&*value?, unsignedIntegerValue])
+        }
+    }
+
+    pub fn observe_interruptions(app_handle: AppHandle) {
+        let handler = RcBlock::new(move |notification: NonNull<AnyObject>| {
+            // SAFETY: the notification outlives the call to this block
+            let notification = unsafe { notification.as_ref() };
+            if user_info_value(notification, INTERRUPTION_TYPE_KEY) == Some(INTERRUPTION_BEGAN) {
+                super::interruption_began(&app_handle, InterruptionCause::AudioSession);
+            } else {
+                let options = user_info_value(notification, INTERRUPTION_OPTION_KEY).unwrap_or(0);
+                super::interruption_ended(&app_handle, options & SHOULD_RESUME != 0);
+            }
+        });
+
+        let Some(center) = AnyClass::get(c"NSNotificationCenter") else {
+            return;
+        };
+        let name = NSString::from_str(INTERRUPTION_NOTIFICATION);
+        // SAFETY: the block is copied by the notification center; the observer token is
+        // kept for the life of the app, since interruptions are watched until it exits
+        unsafe {
+            let center: Retained<AnyObject> = msg_send![center, defaultCenter];
+            let observer: Option<Retained<AnyObject>> = msg_send![
+                &*center,
+                addObserverForName: &*name,
+                object: ptr::null::<AnyObject>(),
+                queue: ptr::null::<AnyObject>(),
+                usingBlock: &*handler
+            ];
+            std::mem::forget(observer);
+        }
+    }
+}
+
+#[cfg(target_os = "android")]
+mod android {
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use jni::objects::{GlobalRef, JObject, JValue};
+    use jni::JNIEnv;
+    use tauri::{AppHandle, Manager};
+    use tracing::warn;
+
+    use super::InterruptionCause;
+    use crate::cues::Cue;
+    use crate::RecordingState;
+
+    const POLL_INTERVAL: Duration = Duration::from_secs(1);
+    // `AudioManager.MODE_IN_CALL`: a phone call has the microphone. `MODE_IN_COMMUNICATION`
+    // is left alone, since VoIP apps, and recording alongside them, use it too.
+    const MODE_IN_CALL: i32 = 2;
+    // `PowerManager.PARTIAL_WAKE_LOCK`
+    const PARTIAL_WAKE_LOCK: i32 = 1;
+    // Declared in the app's manifest with the `microphone` service type
+    const RECORDING_SERVICE: &str = "com.rekt.app.RecordingService";
+    // `Build.VERSION_CODES.O`, which added `startForegroundService`
+    const ANDROID_O: i32 = 26;
+
+    fn in_call() -> Result<bool, String> {
+        let mode = super::with_activity(|env, activity| {
+            let service = env.new_string("audio")?;
+            let audio = env
+                .call_method(activity, "getSystemService", "(Ljava/lang/String;)Ljava/lang/Object;", &[(&service).into()])?
+                .l()?;
+            env.call_method(&audio, "getMode", "()I", &[])?.i()
+        })?;
+        Ok(mode == MODE_IN_CALL)
+    }
+
+    pub fn vibrate(cue: Cue) -> Result<(), String> {
+        let millis: i64 = match cue {
+            Cue::Start => 30,
+            Cue::Stop => 80,
+        };
+        super::with_activity(|env, activity| {
+            let service = env.new_string("vibrator")?;
+            let vibrator = env
+                .call_method(activity, "getSystemService", "(Ljava/lang/String;)Ljava/lang/Object;", &[(&service).into()])?
+                .l()?;
+            // Deprecated in favour of `VibrationEffect`, which needs API 26; this works on all
+            env.call_method(&vibrator, "vibrate", "(J)V", &[JValue::Long(millis)])?;
+            Ok(())
+        })
+    }
+
+    // Keeps the CPU awake while recording with the screen off
+    fn acquire_wake_lock() -> Result<GlobalRef, String> {
+        super::with_activity(|env, activity| {
+            let service = env.new_string("power")?;
+            let power = env
+                .call_method(activity, "getSystemService", "(Ljava/lang/String;)Ljava/lang/Object;", &[(&service).into()])?
+                .l()?;
+            let tag = env.new_string("rekt:recording")?;
+            let lock = env
+                .call_method(
+                    &power,
+                    "newWakeLock",
+                    "(ILjava/lang/String;)Landroid/os/PowerManager$WakeLock;",
+                    &[JValue::Int(PARTIAL_WAKE_LOCK), (&tag).into()],
+                )?
+                .l()?;
+            env.call_method(&lock, "acquire", "()V", &[])?;
+            env.new_global_ref(lock)
+        })
+    }
+
+    fn release_wake_lock(lock: GlobalRef) -> Result<(), String> {
+        super::with_activity(|env, _| {
+            let lock: &JObject = lock.as_obj();
+            env.call_method(lock, "release", "()V", &[])?;
+            Ok(())
+        })
+    }
+
+    // An intent for the recording service. Its class is loaded through the activity, since
+    // `FindClass` on a native thread only sees the system's classes.
+    fn service_intent<'local>(env: &mut JNIEnv<'local>, activity: &JObject) -> jni::errors::Result<JObject<'local>> {
+        let loader = env
+            .call_method(activity, "getClassLoader", "()Ljava/lang/ClassLoader;", &[])?
+            .l()?;
+        let name = env.new_string(RECORDING_SERVICE)?;
+        let class = env
+            .call_method(&loader, "loadClass", "(Ljava/lang/String;)Ljava/lang/Class;", &[(&name).into()])?
+            .l()?;
+        env.new_object(
+            "android/content/Intent",
+            "(Landroid/content/Context;Ljava/lang/Class;)V",
+            &[activity.into(), (&class).into()],
+        )
+    }
+
+    // Keeps capture going while the app is in the background, with a notification saying so
+    fn start_service() -> Result<(), String> {
+        super::with_activity(|env, activity| {
+            let intent = service_intent(env, activity)?;
+            let sdk = env.get_static_field("android/os/Build$VERSION", "SDK_INT", "I")?.i()?;
+            let method = if sdk >= ANDROID_O { "startForegroundService" } else { "startService" };
+            env.call_method(
+                activity,
+                method,
+                "(Landroid/content/Intent;)Landroid/content/ComponentName;",
+                &[(&intent).into()],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn stop_service() -> Result<(), String> {
+        super::with_activity(|env, activity| {
+            let intent = service_intent(env, activity)?;
+            env.call_method(activity, "stopService", "(Landroid/content/Intent;)Z", &[(&intent).into()])?;
+            Ok(())
+        })
+    }
+
+    // Android tells apps about calls through listeners, which need Java code; the audio
+    // mode switching to a call is visible from here
+    pub fn watch(app_handle: AppHandle) {
+        thread::spawn(move || {
+            let mut calling = false;
+            let mut in_foreground = false;
+            let mut wake_lock = None;
+            loop {
+                thread::sleep(POLL_INTERVAL);
+
+                let recording = app_handle.state::<Arc<RecordingState>>().is_recording();
+                if recording && !in_foreground {
+                    match start_service() {
+                        Ok(()) => in_foreground = true,
+                        Err(e) => warn!("Failed to start the recording service: {}", e),
+                    }
+                } else if !recording && in_foreground {
+                    if let Err(e) = stop_service() {
+                        warn!("Failed to stop the recording service: {}", e);
+                    }
+                    in_foreground = false;
+                }
+                match (recording, wake_lock.take()) {
+                    (true, None) => match acquire_wake_lock() {
+                        Ok(lock) => wake_lock = Some(lock),
+                        Err(e) => warn!("Failed to keep the device awake for recording: {}", e),
+                    },
+                    (false, Some(lock)) => {
+                        if let Err(e) = release_wake_lock(lock) {
+                            warn!("Failed to release the wake lock: {}", e);
+                        }
+                    }
+                    (_, lock) => wake_lock = lock,
+                }
+
+                let now_calling = match in_call() {
+                    Ok(now_calling) => now_calling,
+                    Err(e) => {
+                        warn!("Failed to check for phone calls: {}", e);
+                        continue;
+                    }
+                };
+                if now_calling && !calling {
+                    super::interruption_began(&app_handle, InterruptionCause::PhoneCall);
+                } else if calling && !now_calling {
+                    super::interruption_ended(&app_handle, true);
+                }
+                calling = now_calling;
+            }
+        });
+    }
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use rekt_core::backend::AudioBackend;
+use rekt_core::recording::{RecorderState, RecordingState};
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+use crate::config::ConfigState;
+use crate::tuner;
+
+// Longest pre-roll that can be asked for, to bound what is held in memory
+const MAX_PRE_ROLL_SECS: u32 = 30;
+
+//
+// ====== Input monitoring while idle ======
+//
+
+/// Open or close the idle input to match whether anything is listening to it: the tuner,
+/// or the pre-roll. Recording opens its own stream, so the monitor is only ever open while
+/// the recorder is idle.
+pub fn update(app_handle: &AppHandle) -> Result<(), String> {
+    let state = app_handle.state::<Arc<RecordingState>>();
+    let pre_roll_secs = app_handle.state::<ConfigState>().get().pre_roll_secs;
+    let wanted = state.is_idle() && (tuner::is_running(app_handle) || pre_roll_secs > 0);
+
+    let mut monitor = state.monitor_stream.lock().unwrap();
+    if !wanted {
+        if monitor.take().is_some() {
+            info!("Stopped monitoring the input");
+        }
+        return Ok(());
+    }
+    if monitor.is_some() {
+        return Ok(());
+    }
+
+    let backend = Arc::clone(app_handle.state::<Arc<dyn AudioBackend>>().inner());
+    // The recording's format, so the pre-roll can open it
+    let format = crate::requested_format(app_handle);
+    let stream = backend.open_input(None, format, RecordingState::monitor(state.inner()))?;
+    state
+        .pre_roll
+        .lock()
+        .unwrap()
+        .configure(pre_roll_secs, stream.device.channels.max(1), stream.device.sample_rate);
+    stream.play()?;
+    info!("Monitoring input on {}", stream.device.name);
+    *monitor = Some(stream);
+    Ok(())
+}
+
+/// Reopen the monitor once the recorder is idle again, so the pre-roll starts filling
+pub fn state_changed(app_handle: &AppHandle, next: RecorderState) {
+    if next != RecorderState::Idle {
+        return;
+    }
+    if let Err(e) = update(app_handle) {
+        warn!("Could not reopen the input monitor: {}", e);
+    }
+}
+
+/// Reopen the monitor on the current default input, e.g. after it changed
+pub fn restart(app_handle: &AppHandle) {
+    app_handle.state::<Arc<RecordingState>>().monitor_stream.lock().unwrap().take();
+    if let Err(e) = update(app_handle) {
+        warn!("Could not reopen the input monitor: {}", e);
+    }
+}
+
+/// Channels and sample rate of the monitored input, while it is open
+pub fn format(state: &RecordingState) -> Option<(u16, u32)> {
+    let monitor = state.monitor_stream.lock().unwrap();
+    monitor.as_ref().map(|stream| (stream.device.channels.max(1), stream.device.sample_rate))
+}
+
+pub fn validate_pre_roll(seconds: u32) -> Result<(), String> {
+    if seconds > MAX_PRE_ROLL_SECS {
+        return Err(format!("Pre-roll can be at most {} seconds", MAX_PRE_ROLL_SECS));
+    }
+    Ok(())
+}
+
+// Keep this many seconds from before each recording starts; 0 turns the pre-roll off
+#[tauri::command]
+pub fn set_pre_roll(app_handle: AppHandle, config: State<'_, ConfigState>, seconds: u32) -> Result<(), String> {
+    validate_pre_roll(seconds)?;
+    config.update(|c| {
+        c.pre_roll_secs = seconds;
+        Ok(())
+    })?;
+    // Reopen so the buffer takes the new length
+    restart(&app_handle);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_pre_roll(config: State<'_, ConfigState>) -> u32 {
+    config.get().pre_roll_secs
+}
@@ -0,0 +1,118 @@
+use std::path::Path;
+use std::time::Duration;
+
+use rekt_core::speech::{self, SpeechSegment};
+use tauri::{AppHandle, Manager, State};
+use tracing::info;
+
+use crate::crypto::{self, EncryptionState};
+use crate::decode::PlaybackControl;
+use crate::library::Library;
+use crate::lock::AppLock;
+use crate::processing;
+use crate::AudioPlaybackState;
+
+//
+// ====== Playback navigation ======
+//
+
+// Start a little early so the first syllable isn't clipped
+const LEAD_IN_MS: u64 = 150;
+// "Previous" within this long of an utterance's start goes to the one before it
+const PREVIOUS_GRACE_MS: u64 = 1_500;
+
+// Speech segments for a recording, detected and cached on first use
+fn speech_segments(app_handle: &AppHandle, path: &str) -> Result<Vec<SpeechSegment>, String> {
+    let library = app_handle.state::<Library>();
+    let entry = library
+        .get(path)
+        .ok_or_else(|| format!("Recording not found in library: {}", path))?;
+    if let Some(segments) = entry.speech_segments {
+        return Ok(segments);
+    }
+
+    let bytes = crypto::read_recording(&app_handle.state::<EncryptionState>(), Path::new(path))?;
+    let segments = speech::detect_speech(&processing::read_wav_bytes(&bytes)?);
+    info!("Found {} speech segments in {}", segments.len(), path);
+    library.update(path, |entry| entry.speech_segments = Some(segments.clone()))?;
+    Ok(segments)
+}
+
+// The library recording that is playing now
+fn current_playback(app_handle: &AppHandle) -> Result<(PlaybackControl, String), String> {
+    let control = app_handle
+        .state::<AudioPlaybackState>()
+        .control
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| "Nothing is playing".to_string())?;
+    let path = control
+        .path
+        .clone()
+        .ok_or_else(|| "Only library recordings can be navigated".to_string())?;
+    Ok((control, path))
+}
+
+//
+// ====== Navigation commands ======
+//
+
+// Jump playback to the recording's `index`th marker (in time order); returns the new position in ms
+#[tauri::command]
+pub fn seek_to_marker(app_handle: AppHandle, app_lock: State<'_, AppLock>, index: usize) -> Result<u64, String> {
+    app_lock.ensure_unlocked()?;
+    let (control, path) = current_playback(&app_handle)?;
+    let mut markers = app_handle
+        .state::<Library>()
+        .get(&path)
+        .map(|entry| entry.markers)
+        .unwrap_or_default();
+    markers.sort_by_key(|m| m.position_ms);
+    let marker = markers
+        .get(index)
+        .ok_or_else(|| format!("Marker {} does not exist; the recording has {}", index, markers.len()))?;
+
+    control.seek(Duration::from_millis(marker.position_ms));
+    Ok(marker.position_ms)
+}
+
+// Jump to the start of the next utterance after the playhead; returns the new position in ms
+#[tauri::command]
+pub async fn seek_to_next_speech(app_handle: AppHandle, app_lock: State<'_, AppLock>) -> Result<u64, String> {
+    app_lock.ensure_unlocked()?;
+    let (control, path) = current_playback(&app_handle)?;
+    let segments = tauri::async_runtime::spawn_blocking(move || speech_segments(&app_handle, &path))
+        .await
+        .map_err(|e| format!("Speech detection failed: {}", e))??;
+
+    let position = control.position().as_millis() as u64;
+    let next = segments
+        .iter()
+        .map(|s| s.start_ms.saturating_sub(LEAD_IN_MS))
+        .find(|&start| start > position)
+        .ok_or_else(|| "No more speech after this point".to_string())?;
+    control.seek(Duration::from_millis(next));
+    Ok(next)
+}
+
+// Jump back to the start of the current utterance, or the one before it if that start
+// was only just played; returns the new position in ms
+#[tauri::command]
+pub async fn seek_to_previous_speech(app_handle: AppHandle, app_lock: State<'_, AppLock>) -> Result<u64, String> {
+    app_lock.ensure_unlocked()?;
+    let (control, path) = current_playback(&app_handle)?;
+    let segments = tauri::async_runtime::spawn_blocking(move || speech_segments(&app_handle, &path))
+        .await
+        .map_err(|e| format!("Speech detection failed: {}", e))??;
+
+    let position = control.position().as_millis() as u64;
+    let previous = segments
+        .iter()
+        .map(|s| s.start_ms.saturating_sub(LEAD_IN_MS))
+        .rev()
+        .find(|&start| start + PREVIOUS_GRACE_MS < position)
+        .unwrap_or(0);
+    control.seek(Duration::from_millis(previous));
+    Ok(previous)
+}
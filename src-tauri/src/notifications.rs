@@ -0,0 +1,27 @@
+use std::sync::Arc;
+
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tracing::{debug, warn};
+
+use crate::config::ConfigState;
+use crate::RecordingState;
+
+//
+// ====== System notifications ======
+//
+
+/// Show a desktop notification; failures are logged, never fatal
+pub fn notify(app_handle: &AppHandle, title: &str, body: &str) {
+    let recording = app_handle
+        .state::<Arc<RecordingState>>()
+        .is_recording();
+    if recording && app_handle.state::<ConfigState>().get().focus_mode.suppress_notifications {
+        debug!("Notification suppressed by focus mode: {}", title);
+        return;
+    }
+
+    if let Err(e) = app_handle.notification().builder().title(title).body(body).show() {
+        warn!("Failed to show notification: {}", e);
+    }
+}
@@ -0,0 +1,212 @@
+use std::net::{Ipv4Addr, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+use tracing::{debug, info, warn};
+
+use crate::config::ConfigState;
+use crate::remote::{self, RemoteAction};
+
+//
+// ====== OSC remote control ======
+//
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+const MAX_PACKET: usize = 8 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OscConfig {
+    pub port: u16,
+    /// Accept messages from other machines, not just localhost
+    #[serde(default)]
+    pub allow_lan: bool,
+}
+
+/// Stop flag and handle of the listener thread that is currently running, if any
+#[derive(Default)]
+pub struct OscListener {
+    running: Mutex<Option<(Arc<AtomicBool>, JoinHandle<()>)>>,
+}
+
+#[derive(Debug, PartialEq)]
+enum OscArg {
+    Int(i32),
+    Float(f32),
+    Str(String),
+    Bool(bool),
+}
+
+impl OscListener {
+    /// Replace any running listener with one for `osc`, or just stop it with `None`
+    pub fn restart(&self, app_handle: AppHandle, osc: Option<OscConfig>) -> Result<(), String> {
+        let mut running = self.running.lock().unwrap();
+        if let Some((stop, thread)) = running.take() {
+            // The old socket is only closed once its thread returns, within one poll, and
+            // binding the same port before then fails
+            stop.store(true, Ordering::SeqCst);
+            let _ = thread.join();
+        }
+
+        let osc = match osc {
+            Some(osc) => osc,
+            None => return Ok(()),
+        };
+
+        let bind_ip = if osc.allow_lan { Ipv4Addr::UNSPECIFIED } else { Ipv4Addr::LOCALHOST };
+        let socket = UdpSocket::bind((bind_ip, osc.port))
+            .map_err(|e| format!("Failed to listen for OSC on port {}: {}", osc.port, e))?;
+        socket
+            .set_read_timeout(Some(POLL_INTERVAL))
+            .map_err(|e| format!("Failed to configure OSC socket: {}", e))?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+        info!("Listening for OSC on {}:{}", bind_ip, osc.port);
+
+        let thread = thread::spawn(move || {
+            let stop = thread_stop;
+            let mut buf = [0u8; MAX_PACKET];
+            while !stop.load(Ordering::SeqCst) {
+                let len = match socket.recv_from(&mut buf) {
+                    Ok((len, _)) => len,
+                    Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => {
+                        continue
+                    }
+                    Err(e) => {
+                        warn!("OSC listener stopped: {}", e);
+                        break;
+                    }
+                };
+
+                let mut messages = Vec::new();
+                parse_packet(&buf[..len], &mut messages);
+                for (address, args) in messages {
+                    match action_for(&address, &args) {
+                        Some(action) => remote::perform(&app_handle, "osc", action),
+                        None => debug!("Ignoring OSC message {}", address),
+                    }
+                }
+            }
+        });
+        *running = Some((stop, thread));
+
+        Ok(())
+    }
+}
+
+fn action_for(address: &str, args: &[OscArg]) -> Option<RemoteAction> {
+    // Push buttons on control surfaces send 1 on press and 0 on release; only act on press
+    let released = match args.first() {
+        Some(OscArg::Int(v)) => *v == 0,
+        Some(OscArg::Float(v)) => *v == 0.0,
+        Some(OscArg::Bool(v)) => !v,
+        _ => false,
+    };
+
+    match address {
+        _ if released => None,
+        "/rekt/record/start" => Some(RemoteAction::StartRecording),
+        "/rekt/record/stop" => Some(RemoteAction::StopRecording),
+        "/rekt/record/toggle" => Some(RemoteAction::ToggleRecording),
+        "/rekt/marker" => Some(RemoteAction::Marker {
+            label: args.iter().find_map(|arg| match arg {
+                OscArg::Str(s) => Some(s.clone()),
+                _ => None,
+            }),
+        }),
+        _ => None,
+    }
+}
+
+// Flatten a packet (a message or a possibly nested bundle) into its messages
+fn parse_packet(packet: &[u8], messages: &mut Vec<(String, Vec<OscArg>)>) {
+    if let Some(mut rest) = packet.strip_prefix(b"#bundle\0") {
+        // Skip the time tag; everything is handled as soon as it arrives
+        rest = rest.get(8..).unwrap_or_default();
+        while rest.len() >= 4 {
+            let size = i32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]).max(0) as usize;
+            let Some(element) = rest.get(4..4 + size) else { break };
+            parse_packet(element, messages);
+            rest = &rest[4 + size..];
+        }
+    } else if let Some(message) = parse_message(packet) {
+        messages.push(message);
+    }
+}
+
+fn parse_message(packet: &[u8]) -> Option<(String, Vec<OscArg>)> {
+    let mut pos = 0;
+    let address = read_string(packet, &mut pos)?;
+    if !address.starts_with('/') {
+        return None;
+    }
+
+    let mut args = Vec::new();
+    // Very old senders omit the type tag string entirely
+    let tags = match read_string(packet, &mut pos) {
+        Some(tags) if tags.starts_with(',') => tags,
+        _ => return Some((address, args)),
+    };
+
+    for tag in tags.chars().skip(1) {
+        let arg = match tag {
+            'i' => OscArg::Int(i32::from_be_bytes(read_word(packet, &mut pos)?)),
+            'f' => OscArg::Float(f32::from_be_bytes(read_word(packet, &mut pos)?)),
+            's' | 'S' => OscArg::Str(read_string(packet, &mut pos)?),
+            'T' => OscArg::Bool(true),
+            'F' => OscArg::Bool(false),
+            // Types we don't use; stop rather than misread what follows
+            _ => break,
+        };
+        args.push(arg);
+    }
+
+    Some((address, args))
+}
+
+fn read_word(packet: &[u8], pos: &mut usize) -> Option<[u8; 4]> {
+    let word = packet.get(*pos..*pos + 4)?.try_into().ok()?;
+    *pos += 4;
+    Some(word)
+}
+
+// OSC strings are NUL-terminated and padded to a multiple of 4 bytes
+fn read_string(packet: &[u8], pos: &mut usize) -> Option<String> {
+    let rest = packet.get(*pos..)?;
+    let len = rest.iter().position(|&b| b == 0)?;
+    let s = String::from_utf8_lossy(&rest[..len]).to_string();
+    *pos += (len + 4) & !3;
+    Some(s)
+}
+
+//
+// ====== OSC commands ======
+//
+
+// Enable the OSC listener on a port, or turn it off with `None`
+#[tauri::command]
+pub fn set_osc_config(
+    app_handle: AppHandle,
+    config: State<'_, ConfigState>,
+    listener: State<'_, OscListener>,
+    osc: Option<OscConfig>,
+) -> Result<(), String> {
+    if osc.as_ref().is_some_and(|o| o.port == 0) {
+        return Err("OSC port cannot be 0".to_string());
+    }
+
+    listener.restart(app_handle, osc.clone())?;
+    config.update(|c| {
+        c.osc = osc.clone();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn get_osc_config(config: State<'_, ConfigState>) -> Option<OscConfig> {
+    config.get().osc
+}
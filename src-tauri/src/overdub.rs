@@ -0,0 +1,370 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rekt_core::speech;
+use rodio::buffer::SamplesBuffer;
+use rodio::{Sink, Source};
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+use tracing::info;
+
+use crate::audit::{self, Initiator};
+use crate::config::ConfigState;
+use crate::crypto::{self, EncryptionState};
+use crate::library::Library;
+use crate::lock::AppLock;
+use crate::processing::{self, AudioBuffer};
+use crate::{AudioPlaybackState, RecorderState, RecordingState};
+
+//
+// ====== Loop / overdub recording ======
+//
+
+// Headroom left when the layers are summed
+const LAYER_CEILING_DB: f32 = -1.0;
+
+/// Source adapter that counts the samples handed to the output device. Together with
+/// the number of captured samples this is the clock that lines a take up with the loop.
+struct Clocked<S> {
+    inner: S,
+    samples: Arc<AtomicU64>,
+}
+
+impl<S: Source<Item = f32>> Iterator for Clocked<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.inner.next()?;
+        self.samples.fetch_add(1, Ordering::Relaxed);
+        Some(sample)
+    }
+}
+
+impl<S: Source<Item = f32>> Source for Clocked<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+struct OverdubSession {
+    base: AudioBuffer,
+    played_samples: Arc<AtomicU64>,
+    sink: Sink,
+}
+
+/// The loop currently playing under a take, if any
+#[derive(Default)]
+pub struct OverdubState {
+    session: Mutex<Option<OverdubSession>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OverdubResult {
+    /// The new take on its own
+    take_path: String,
+    /// The loop with the take mixed in, ready to be overdubbed again
+    layered_path: String,
+}
+
+// Sum the take onto the loop, repeating the loop for as long as the take runs.
+// `start_frame` is where in the loop (counted from its first pass) the take began.
+fn layer(base: &AudioBuffer, take: &AudioBuffer, start_frame: u64) -> AudioBuffer {
+    let channels = base.channels.max(1) as usize;
+    let loop_frames = base.samples.len() / channels;
+    let take = processing::resample(&processing::remix(take, base.channels), base.sample_rate);
+    let take_frames = take.samples.len() / channels;
+
+    let offset = (start_frame % loop_frames as u64) as usize;
+    let passes = (offset + take_frames).div_ceil(loop_frames).max(1);
+    let mut samples = Vec::with_capacity(passes * base.samples.len());
+    for _ in 0..passes {
+        samples.extend_from_slice(&base.samples);
+    }
+    for (i, sample) in take.samples.iter().enumerate() {
+        samples[offset * channels + i] += sample;
+    }
+
+    let mut layered = AudioBuffer {
+        channels: base.channels,
+        sample_rate: base.sample_rate,
+        samples,
+    };
+    processing::limit(&mut layered, LAYER_CEILING_DB);
+    layered
+}
+
+// Write an edit next to the recording it came from and index it, encrypting it like
+// that recording if needed
+fn save_derived(app_handle: &AppHandle, source: &Path, suffix: &str, buffer: &AudioBuffer) -> Result<PathBuf, String> {
+    let plain_source = source
+        .to_string_lossy()
+        .strip_suffix(&format!(".{}", crypto::ENCRYPTED_EXTENSION))
+        .map(PathBuf::from)
+        .unwrap_or_else(|| source.to_path_buf());
+    let output = processing::derived_path(&plain_source, suffix, "wav");
+    processing::write_wav(&output, buffer)?;
+
+    let mut entry = app_handle.state::<Library>().probe(&output)?;
+    entry.derived_from = Some(source.to_string_lossy().to_string());
+    let output = if crypto::is_encrypted(source) {
+        let encrypted = app_handle.state::<EncryptionState>().encrypt_file(&output)?;
+        entry.path = encrypted.to_string_lossy().to_string();
+        entry.size_bytes = std::fs::metadata(&encrypted).map(|m| m.len()).unwrap_or(0);
+        entry.encrypted = true;
+        encrypted
+    } else {
+        output
+    };
+    app_handle.state::<Library>().add(entry)?;
+    Ok(output)
+}
+
+/// Silence the loop when a take ends some other way than `stop_overdub`, e.g. a hotkey or
+/// the time limit. The take is kept as an ordinary recording, without the layered mix.
+pub fn state_changed(app_handle: &AppHandle, next: RecorderState) {
+    if !matches!(next, RecorderState::Stopping | RecorderState::Idle) {
+        return;
+    }
+    if let Some(session) = app_handle.state::<OverdubState>().session.lock().unwrap().take() {
+        session.sink.stop();
+        info!("Recording stopped outside the overdub; loop stopped");
+    }
+}
+
+//
+// ====== Overdub commands ======
+//
+
+// Loop a library recording and start recording over it; finish with `stop_overdub`
+#[tauri::command]
+pub fn start_overdub(
+    app_handle: AppHandle,
+    app_lock: State<'_, AppLock>,
+    state: State<'_, Arc<RecordingState>>,
+    overdub: State<'_, OverdubState>,
+    path: String,
+) -> Result<(), String> {
+    app_lock.ensure_unlocked()?;
+    if !state.is_idle() {
+        return Err("Already recording".to_string());
+    }
+    if app_handle.state::<Library>().get(&path).is_none() {
+        return Err(format!("Recording not found in library: {}", path));
+    }
+
+    let bytes = crypto::read_recording(&app_handle.state::<EncryptionState>(), Path::new(&path))?;
+    let base = processing::read_wav_bytes(&bytes)?;
+    if base.samples.len() < base.channels.max(1) as usize {
+        return Err("Loop contains no audio".to_string());
+    }
+
+    let playback_state = app_handle.state::<AudioPlaybackState>();
+    crate::stop_audio_internal(&playback_state);
+    let stream_handle = crate::ensure_output_stream(&playback_state)?;
+    let sink = Sink::try_new(&stream_handle).map_err(|e| format!("Failed to create sink: {}", e))?;
+    let played_samples = Arc::new(AtomicU64::new(0));
+    sink.append(Clocked {
+        inner: SamplesBuffer::new(base.channels, base.sample_rate, base.samples.clone()).repeat_infinite(),
+        samples: Arc::clone(&played_samples),
+    });
+
+    if let Err(e) = audit::initiated_by(Initiator::Ui, || crate::start_recording_internal(&app_handle)) {
+        sink.stop();
+        return Err(e.into());
+    }
+    info!("Overdubbing over {}", path);
+    *overdub.session.lock().unwrap() = Some(OverdubSession {
+        base,
+        played_samples,
+        sink,
+    });
+    Ok(())
+}
+
+// Stop the take and the loop, saving the take and a layered mix of both
+#[tauri::command]
+pub async fn stop_overdub(app_handle: AppHandle) -> Result<OverdubResult, String> {
+    let session = app_handle
+        .state::<OverdubState>()
+        .session
+        .lock()
+        .unwrap()
+        .take()
+        .ok_or_else(|| "No overdub in progress".to_string())?;
+
+    // Read both clocks at the same instant: the take started as many frames before
+    // now as it holds, pre-roll included, so that many frames before the loop's current position
+    let state = app_handle.state::<Arc<RecordingState>>();
+    let start_frame = state.capture_format().map_or(0, |format| {
+        let lead_in_frames = state.lead_in_frames();
+        let audio_data = state.audio_data.lock().unwrap();
+        let played_frames = session.played_samples.load(Ordering::Relaxed) / session.base.channels.max(1) as u64;
+        let held_frames = audio_data.len() as u64 / format.channels as u64 + lead_in_frames;
+        let captured_frames = held_frames * session.base.sample_rate as u64 / format.sample_rate as u64;
+        played_frames.saturating_sub(captured_frames)
+    });
+    // What's captured lags what's handed to the output by the round trip through both
+    // devices, so the take lines up that much earlier in the loop
+    let latency_frames = app_handle
+        .state::<ConfigState>()
+        .get()
+        .roundtrip_latency_ms
+        .map_or(0, |ms| (ms / 1000.0 * session.base.sample_rate as f64) as u64);
+    let start_frame = start_frame.saturating_sub(latency_frames);
+
+    let take_path = audit::initiated_by(Initiator::Ui, || crate::stop_recording_internal(&app_handle));
+    session.sink.stop();
+    let take_path = take_path?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let bytes = crypto::read_recording(&app_handle.state::<EncryptionState>(), &take_path)?;
+        let take = processing::read_wav_bytes(&bytes)?;
+        let layered = layer(&session.base, &take, start_frame);
+        let layered_path = save_derived(&app_handle, &take_path, "layered", &layered)?;
+        info!("Saved overdub layer {}", layered_path.display());
+        Ok::<_, String>(OverdubResult {
+            take_path: take_path.to_string_lossy().to_string(),
+            layered_path: layered_path.to_string_lossy().to_string(),
+        })
+    })
+    .await
+    .map_err(|e| format!("Overdub failed: {}", e))?
+}
+
+//
+// ====== Punch-in ======
+//
+
+// Length of the crossfade at each edge of a punched-in region
+const PUNCH_CROSSFADE_MS: u32 = 30;
+// Room tone kept around the speech in a punch-in take
+const PUNCH_PADDING_MS: u64 = 100;
+
+struct PunchSession {
+    source: PathBuf,
+    base: AudioBuffer,
+    start_frame: usize,
+    end_frame: usize,
+}
+
+/// The region being re-recorded, if any
+#[derive(Default)]
+pub struct PunchState {
+    session: Mutex<Option<PunchSession>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PunchResult {
+    /// The new take on its own
+    take_path: String,
+    /// The recording with the region replaced by the take
+    punched_path: String,
+}
+
+// Cut the take down to its speech, so the pauses before and after speaking don't end
+// up in the punched-in region
+fn trim_to_speech(take: AudioBuffer) -> AudioBuffer {
+    let segments = speech::detect_speech(&take);
+    let (Some(first), Some(last)) = (segments.first(), segments.last()) else {
+        return take;
+    };
+    let channels = take.channels.max(1) as usize;
+    let frames = take.samples.len() / channels;
+    let to_frame = |ms: u64| ((ms * take.sample_rate as u64 / 1000) as usize).min(frames);
+    let start = to_frame(first.start_ms.saturating_sub(PUNCH_PADDING_MS));
+    let end = to_frame(last.end_ms + PUNCH_PADDING_MS);
+    AudioBuffer {
+        channels: take.channels,
+        sample_rate: take.sample_rate,
+        samples: take.samples[start * channels..end * channels].to_vec(),
+    }
+}
+
+// Re-record `start_ms..end_ms` of a library recording; finish with `finish_punch`
+#[tauri::command]
+pub fn punch_record(
+    app_handle: AppHandle,
+    app_lock: State<'_, AppLock>,
+    state: State<'_, Arc<RecordingState>>,
+    punch: State<'_, PunchState>,
+    path: String,
+    start_ms: u64,
+    end_ms: u64,
+) -> Result<(), String> {
+    app_lock.ensure_unlocked()?;
+    if !state.is_idle() {
+        return Err("Already recording".to_string());
+    }
+    if end_ms <= start_ms {
+        return Err("Punch region must end after it starts".to_string());
+    }
+    if app_handle.state::<Library>().get(&path).is_none() {
+        return Err(format!("Recording not found in library: {}", path));
+    }
+
+    let bytes = crypto::read_recording(&app_handle.state::<EncryptionState>(), Path::new(&path))?;
+    let base = processing::read_wav_bytes(&bytes)?;
+    let frames = base.samples.len() / base.channels.max(1) as usize;
+    let to_frame = |ms: u64| (ms * base.sample_rate as u64 / 1000) as usize;
+    let (start_frame, end_frame) = (to_frame(start_ms), to_frame(end_ms));
+    if end_frame > frames {
+        return Err("Punch region runs past the end of the recording".to_string());
+    }
+
+    audit::initiated_by(Initiator::Ui, || crate::start_recording_internal(&app_handle))?;
+    info!("Punching in over {} from {} ms to {} ms", path, start_ms, end_ms);
+    *punch.session.lock().unwrap() = Some(PunchSession {
+        source: PathBuf::from(path),
+        base,
+        start_frame,
+        end_frame,
+    });
+    Ok(())
+}
+
+// Stop the take and save a copy of the recording with the region replaced by it
+#[tauri::command]
+pub async fn finish_punch(app_handle: AppHandle) -> Result<PunchResult, String> {
+    let session = app_handle
+        .state::<PunchState>()
+        .session
+        .lock()
+        .unwrap()
+        .take()
+        .ok_or_else(|| "No punch-in in progress".to_string())?;
+    let take_path = audit::initiated_by(Initiator::Ui, || crate::stop_recording_internal(&app_handle))?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let bytes = crypto::read_recording(&app_handle.state::<EncryptionState>(), &take_path)?;
+        let take = trim_to_speech(processing::read_wav_bytes(&bytes)?);
+        let punched = processing::splice(
+            &session.base,
+            &take,
+            session.start_frame,
+            session.end_frame,
+            PUNCH_CROSSFADE_MS,
+        );
+        let punched_path = save_derived(&app_handle, &session.source, "punched", &punched)?;
+        info!("Saved punch-in {}", punched_path.display());
+        Ok::<_, String>(PunchResult {
+            take_path: take_path.to_string_lossy().to_string(),
+            punched_path: punched_path.to_string_lossy().to_string(),
+        })
+    })
+    .await
+    .map_err(|e| format!("Punch-in failed: {}", e))?
+}
@@ -0,0 +1,244 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::http::{header, Request, Response};
+use tauri::{
+    AppHandle, Manager, PhysicalPosition, Runtime, State, UriSchemeContext, Url, WebviewUrl, WebviewWindowBuilder,
+};
+use tracing::{info, warn};
+
+use crate::config::ConfigState;
+use crate::remote::{self, RemoteAction};
+use crate::{RecorderState, RecordingState};
+
+//
+// ====== Overlay recording indicator ======
+//
+
+pub const OVERLAY_WINDOW: &str = "recording-overlay";
+/// Serves the indicator's page, so it doesn't depend on the frontend
+pub const SCHEME: &str = "rekt-overlay";
+// Logical pixels
+const WIDTH: f64 = 96.0;
+const HEIGHT: f64 = 32.0;
+const MARGIN: f64 = 16.0;
+const TICK: Duration = Duration::from_millis(250);
+
+// A click navigates to `stop`, which `on_navigation` turns into stopping the recording
+const PAGE: &str = r#"<!doctype html>
+<html>
+<head>
+<meta charset="utf-8">
+<style>
+html, body { margin: 0; height: 100%; overflow: hidden; cursor: pointer; user-select: none; }
+body { display: flex; align-items: center; justify-content: center; gap: 8px;
+       background: #1c1c1e; color: #fff; font: 600 14px system-ui, sans-serif; }
+#dot { width: 10px; height: 10px; border-radius: 50%; background: #e5484d; }
+body.paused #dot { background: #8e8e93; }
+#time { font-variant-numeric: tabular-nums; }
+</style>
+</head>
+<body title="Click to stop recording">
+<span id="dot"></span><span id="time">0:00</span>
+<script>
+function update(ms, paused) {
+  var s = Math.floor(ms / 1000), h = Math.floor(s / 3600), m = Math.floor(s / 60) % 60;
+  s %= 60;
+  var time = (h ? h + ":" + String(m).padStart(2, "0") : m) + ":" + String(s).padStart(2, "0");
+  document.getElementById("time").textContent = time;
+  document.body.classList.toggle("paused", paused);
+}
+document.body.addEventListener("click", function () { location.href = "stop"; });
+</script>
+</body>
+</html>
+"#;
+
+// Set to stop the timer of the overlay on screen
+static SHOWN: Mutex<Option<Arc<AtomicBool>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreenCorner {
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OverlayConfig {
+    /// Show a red dot and the recording time on top of other windows while recording
+    pub enabled: bool,
+    pub corner: ScreenCorner,
+}
+
+/// The indicator's page, for `SCHEME`
+pub fn serve<R: Runtime>(_context: UriSchemeContext<'_, R>, _request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+    Response::builder()
+        .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+        .body(PAGE.as_bytes().to_vec())
+        .unwrap_or_default()
+}
+
+// Custom schemes are served from a `localhost` subdomain on Windows
+fn page_url() -> Url {
+    let url = if cfg!(windows) {
+        format!("http://{}.localhost/", SCHEME)
+    } else {
+        format!("{}://localhost/", SCHEME)
+    };
+    Url::parse(&url).expect("overlay URL is valid")
+}
+
+// Where the overlay goes: `corner` of the screen the main window is on, inside the taskbar
+// and menu bar
+fn place(app_handle: &AppHandle, corner: ScreenCorner) -> Option<PhysicalPosition<i32>> {
+    let monitor = app_handle
+        .get_webview_window("main")
+        .and_then(|window| window.current_monitor().ok().flatten())
+        .or_else(|| app_handle.primary_monitor().ok().flatten())?;
+    let scale = monitor.scale_factor();
+    let area = monitor.work_area();
+    let (width, height, margin) = ((WIDTH * scale) as i32, (HEIGHT * scale) as i32, (MARGIN * scale) as i32);
+    let left = area.position.x + margin;
+    let top = area.position.y + margin;
+    let right = area.position.x + area.size.width as i32 - width - margin;
+    let bottom = area.position.y + area.size.height as i32 - height - margin;
+    Some(match corner {
+        ScreenCorner::TopLeft => PhysicalPosition::new(left, top),
+        ScreenCorner::TopRight => PhysicalPosition::new(right, top),
+        ScreenCorner::BottomLeft => PhysicalPosition::new(left, bottom),
+        ScreenCorner::BottomRight => PhysicalPosition::new(right, bottom),
+    })
+}
+
+fn open(app_handle: &AppHandle, corner: ScreenCorner) -> Result<(), String> {
+    if let Some(window) = app_handle.get_webview_window(OVERLAY_WINDOW) {
+        // Already up; just follow a change of corner
+        if let Some(position) = place(app_handle, corner) {
+            window.set_position(position).map_err(|e| format!("Failed to move recording indicator: {}", e))?;
+        }
+        return Ok(());
+    }
+
+    let stopper = app_handle.clone();
+    let window = WebviewWindowBuilder::new(app_handle, OVERLAY_WINDOW, WebviewUrl::CustomProtocol(page_url()))
+        .title("Recording")
+        .inner_size(WIDTH, HEIGHT)
+        .decorations(false)
+        .resizable(false)
+        .shadow(false)
+        .always_on_top(true)
+        .visible_on_all_workspaces(true)
+        .skip_taskbar(true)
+        .focused(false)
+        .visible(false)
+        .on_navigation(move |url| {
+            if url.path() != "/stop" {
+                return true;
+            }
+            // Stopping waits for the file to be written, so not on the webview's thread
+            let app_handle = stopper.clone();
+            thread::spawn(move || remote::perform(&app_handle, "overlay", RemoteAction::StopRecording));
+            false
+        })
+        .build()
+        .map_err(|e| format!("Failed to open recording indicator: {}", e))?;
+    if let Some(position) = place(app_handle, corner) {
+        window.set_position(position).map_err(|e| format!("Failed to move recording indicator: {}", e))?;
+    }
+    window.show().map_err(|e| format!("Failed to show recording indicator: {}", e))?;
+
+    let closed = Arc::new(AtomicBool::new(false));
+    if let Some(previous) = SHOWN.lock().unwrap().replace(Arc::clone(&closed)) {
+        previous.store(true, Ordering::SeqCst);
+    }
+    let app_handle = app_handle.clone();
+    thread::spawn(move || {
+        let state = app_handle.state::<Arc<RecordingState>>();
+        while !closed.load(Ordering::SeqCst) {
+            let Some(window) = app_handle.get_webview_window(OVERLAY_WINDOW) else {
+                break;
+            };
+            let paused = state.recorder_state() == RecorderState::Paused;
+            // Fails until the page has loaded, which is fine: the next tick catches up
+            let _ = window.eval(format!("update({}, {})", state.position_ms().unwrap_or(0), paused));
+            thread::sleep(TICK);
+        }
+    });
+    info!("Recording indicator shown");
+    Ok(())
+}
+
+fn close(app_handle: &AppHandle) {
+    if let Some(closed) = SHOWN.lock().unwrap().take() {
+        closed.store(true, Ordering::SeqCst);
+    }
+    if let Some(window) = app_handle.get_webview_window(OVERLAY_WINDOW) {
+        if let Err(e) = window.close() {
+            warn!("Failed to close recording indicator: {}", e);
+        }
+    }
+}
+
+fn show(app_handle: &AppHandle) {
+    let config = app_handle.state::<ConfigState>().get().overlay_indicator;
+    if !config.enabled {
+        return;
+    }
+    // Building a window from the main thread deadlocks on Windows, and state changes can
+    // come from synchronous commands, which run there
+    let app_handle = app_handle.clone();
+    thread::spawn(move || {
+        if let Err(e) = open(&app_handle, config.corner) {
+            warn!("{}", e);
+        }
+    });
+}
+
+/// Show the indicator while recording, if it is enabled, and take it away after
+pub fn state_changed(app_handle: &AppHandle, next: RecorderState) {
+    if cfg!(mobile) {
+        return;
+    }
+    match next {
+        RecorderState::Recording => show(app_handle),
+        RecorderState::Stopping | RecorderState::Idle => close(app_handle),
+        _ => {}
+    }
+}
+
+//
+// ====== Overlay indicator commands ======
+//
+
+#[tauri::command]
+pub fn set_overlay_indicator(
+    app_handle: AppHandle,
+    config: State<'_, ConfigState>,
+    state: State<'_, Arc<RecordingState>>,
+    settings: OverlayConfig,
+) -> Result<(), String> {
+    config.update(|c| {
+        c.overlay_indicator = settings.clone();
+        Ok(())
+    })?;
+    // Apply it to a recording in progress too
+    if settings.enabled && !state.is_idle() {
+        show(&app_handle);
+    } else if !settings.enabled {
+        close(&app_handle);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_overlay_indicator(config: State<'_, ConfigState>) -> OverlayConfig {
+    config.get().overlay_indicator
+}
@@ -0,0 +1,256 @@
+use serde::Serialize;
+use tauri::AppHandle;
+
+//
+// ====== Microphone privacy permission ======
+//
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+// Other platforms have no per-app permission and always report `Granted`
+#[cfg_attr(
+    not(any(target_os = "macos", target_os = "ios", target_os = "windows", target_os = "android")),
+    allow(dead_code)
+)]
+pub enum PermissionState {
+    Granted,
+    Denied,
+    /// Blocked by a policy the user can't change, e.g. parental controls or MDM
+    Restricted,
+    /// Never asked; the OS prompts on the first request
+    NotDetermined,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MicrophonePermission {
+    pub state: PermissionState,
+    /// Whether `request_microphone_permission` can show the OS prompt
+    pub can_prompt: bool,
+    /// What the user has to do to allow access, when it isn't granted
+    pub action: Option<String>,
+}
+
+impl MicrophonePermission {
+    fn new(state: PermissionState) -> Self {
+        let action = match state {
+            PermissionState::Granted => None,
+            PermissionState::NotDetermined => Some("Allow microphone access when asked".to_string()),
+            PermissionState::Denied => Some(SETTINGS_HINT.to_string()),
+            PermissionState::Restricted => {
+                Some("Microphone access is blocked by a system policy; ask your administrator".to_string())
+            }
+        };
+        Self {
+            state,
+            can_prompt: state == PermissionState::NotDetermined,
+            action,
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+const SETTINGS_HINT: &str = "Allow rekt under System Settings > Privacy & Security > Microphone";
+#[cfg(target_os = "ios")]
+const SETTINGS_HINT: &str = "Turn on Microphone under Settings > rekt";
+#[cfg(target_os = "windows")]
+const SETTINGS_HINT: &str = "Turn on microphone access for desktop apps under Settings > Privacy & security > Microphone";
+#[cfg(target_os = "android")]
+const SETTINGS_HINT: &str = "Allow the microphone under Settings > Apps > rekt > Permissions";
+#[cfg(not(any(target_os = "macos", target_os = "ios", target_os = "windows", target_os = "android")))]
+const SETTINGS_HINT: &str = "Allow microphone access in your system's privacy settings";
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+mod platform {
+    use std::sync::mpsc;
+
+    use block2::RcBlock;
+    use objc2::msg_send;
+    use objc2::runtime::{AnyClass, Bool};
+    use objc2_foundation::NSString;
+
+    use super::PermissionState;
+
+    #[link(name = "AVFoundation", kind = "framework")]
+    extern "C" {}
+
+    // `AVMediaTypeAudio`
+    const MEDIA_TYPE_AUDIO: &str = "soun";
+    #[cfg(target_os = "macos")]
+    pub const SETTINGS_URL: &str = "x-apple.systempreferences:com.apple.preference.security?Privacy_Microphone";
+    // `UIApplicationOpenSettingsURLString`: this app's page in Settings
+    #[cfg(target_os = "ios")]
+    pub const SETTINGS_URL: &str = "app-settings:";
+
+    fn capture_device() -> Result<&'static AnyClass, String> {
+        AnyClass::get(c"AVCaptureDevice").ok_or_else(|| "AVFoundation is not available".to_string())
+    }
+
+    pub fn status() -> Result<PermissionState, String> {
+        let media = NSString::from_str(MEDIA_TYPE_AUDIO);
+        // SAFETY: a class method taking an AVMediaType and returning AVAuthorizationStatus
+        let status: isize = unsafe { msg_send![capture_device()?, authorizationStatusForMediaType: &*media] };
+        Ok(match status {
+            0 => PermissionState::NotDetermined,
+            1 => PermissionState::Restricted,
+            2 => PermissionState::Denied,
+            _ => PermissionState::Granted,
+        })
+    }
+
+    // Show the system prompt and wait for the user's answer
+    pub fn prompt() -> Result<bool, String> {
+        let (sender, answer) = mpsc::channel();
+        let handler = RcBlock::new(move |granted: Bool| {
+            let _ = sender.send(granted.as_bool());
+        });
+        let media = NSString::from_str(MEDIA_TYPE_AUDIO);
+        // SAFETY: the completion handler is a `void (^)(BOOL)` block, retained by AVFoundation
+        unsafe {
+            let _: () = msg_send![capture_device()?, requestAccessForMediaType: &*media, completionHandler: &*handler];
+        }
+        answer.recv().map_err(|_| "The permission prompt went away without an answer".to_string())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::process::Command;
+
+    use super::PermissionState;
+
+    // The switch for all apps, then the one for desktop (non-Store) apps like this one
+    const CONSENT_KEYS: [&str; 3] = [
+        r"HKLM\SOFTWARE\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore\microphone",
+        r"HKCU\Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore\microphone",
+        r"HKCU\Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore\microphone\NonPackaged",
+    ];
+    pub const SETTINGS_URL: &str = "ms-settings:privacy-microphone";
+
+    // `Allow` or `Deny`; missing means the default, which allows
+    fn consent(key: &str) -> Option<String> {
+        let output = Command::new("reg").args(["query", key, "/v", "Value"]).output().ok()?;
+        let output = String::from_utf8_lossy(&output.stdout);
+        output
+            .lines()
+            .find(|line| line.trim().starts_with("Value"))
+            .and_then(|line| line.split_whitespace().last())
+            .map(str::to_string)
+    }
+
+    pub fn status() -> Result<PermissionState, String> {
+        let denied = CONSENT_KEYS
+            .iter()
+            .any(|key| consent(key).is_some_and(|value| value.eq_ignore_ascii_case("Deny")));
+        Ok(if denied { PermissionState::Denied } else { PermissionState::Granted })
+    }
+}
+
+#[cfg(target_os = "android")]
+mod platform {
+    use jni::objects::JValue;
+
+    use super::PermissionState;
+    use crate::mobile::with_activity;
+
+    const RECORD_AUDIO: &str = "android.permission.RECORD_AUDIO";
+    // `PackageManager.PERMISSION_GRANTED`
+    const PERMISSION_GRANTED: i32 = 0;
+
+    // Android doesn't say whether a missing permission was refused or never asked for;
+    // asking again just returns straight away if the user said "don't ask again"
+    pub fn status() -> Result<PermissionState, String> {
+        let result = with_activity(|env, activity| {
+            let permission = env.new_string(RECORD_AUDIO)?;
+            env.call_method(activity, "checkSelfPermission", "(Ljava/lang/String;)I", &[(&permission).into()])?
+                .i()
+        })?;
+        Ok(if result == PERMISSION_GRANTED {
+            PermissionState::Granted
+        } else {
+            PermissionState::NotDetermined
+        })
+    }
+
+    // Show the system prompt; the answer goes to the activity, so it isn't waited for
+    pub fn prompt() -> Result<(), String> {
+        with_activity(|env, activity| {
+            let permission = env.new_string(RECORD_AUDIO)?;
+            let permissions = env.new_object_array(1, "java/lang/String", &permission)?;
+            env.call_method(
+                activity,
+                "requestPermissions",
+                "([Ljava/lang/String;I)V",
+                &[(&permissions).into(), JValue::Int(0)],
+            )?;
+            Ok(())
+        })
+    }
+}
+
+// No per-app microphone permission to check
+#[cfg(not(any(target_os = "macos", target_os = "ios", target_os = "windows", target_os = "android")))]
+mod platform {
+    use super::PermissionState;
+
+    pub fn status() -> Result<PermissionState, String> {
+        Ok(PermissionState::Granted)
+    }
+}
+
+/// Whether this app may record from the microphone, as far as the OS is concerned
+pub fn microphone_permission() -> Result<MicrophonePermission, String> {
+    Ok(MicrophonePermission::new(platform::status()?))
+}
+
+/// Refuse to start recording when the OS will deny access anyway, with what to do about it
+pub fn ensure_microphone_access() -> Result<(), String> {
+    let permission = microphone_permission()?;
+    match (permission.state, permission.action) {
+        (PermissionState::Denied | PermissionState::Restricted, Some(action)) => {
+            Err(format!("Microphone access is denied. {}", action))
+        }
+        _ => Ok(()),
+    }
+}
+
+//
+// ====== Permission commands ======
+//
+
+#[tauri::command]
+pub fn get_microphone_permission() -> Result<MicrophonePermission, String> {
+    microphone_permission()
+}
+
+// Ask for microphone access: shows the OS prompt if it hasn't been answered yet, or
+// opens the privacy settings if it was denied. Resolves with the state afterwards;
+// after opening the settings or prompting on Android, poll `get_microphone_permission` to see the change.
+#[tauri::command]
+#[cfg_attr(
+    not(any(target_os = "macos", target_os = "ios", target_os = "windows")),
+    allow(unused_variables)
+)]
+pub async fn request_microphone_permission(app_handle: AppHandle) -> Result<MicrophonePermission, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let permission = microphone_permission()?;
+        match permission.state {
+            #[cfg(any(target_os = "macos", target_os = "ios", target_os = "android"))]
+            PermissionState::NotDetermined => {
+                platform::prompt()?;
+                microphone_permission()
+            }
+            #[cfg(any(target_os = "macos", target_os = "ios", target_os = "windows"))]
+            PermissionState::Denied => {
+                use tauri_plugin_opener::OpenerExt;
+                app_handle
+                    .opener()
+                    .open_url(platform::SETTINGS_URL, None::<&str>)
+                    .map_err(|e| format!("Failed to open privacy settings: {}", e))?;
+                Ok(permission)
+            }
+            _ => Ok(permission),
+        }
+    })
+    .await
+    .map_err(|e| format!("Requesting microphone permission failed: {}", e))?
+}
@@ -0,0 +1,197 @@
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tracing::{info, warn};
+
+use crate::jobs::{self, JobKind};
+use crate::library;
+use crate::processing;
+use crate::storage;
+
+//
+// ====== Post-recording processing pipeline ======
+//
+
+pub fn default_target_peak_db() -> f32 {
+    -1.0
+}
+
+fn default_bitrate_kbps() -> u32 {
+    128
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum PipelineStage {
+    Normalize {
+        #[serde(default = "default_target_peak_db")]
+        target_peak_db: f32,
+    },
+    Transcribe,
+    /// Summarizes the transcript with the translation service, transcribing first if needed
+    Summarize,
+    ExportMp3 {
+        #[serde(default = "default_bitrate_kbps")]
+        bitrate_kbps: u32,
+    },
+}
+
+impl PipelineStage {
+    fn name(&self) -> &'static str {
+        match self {
+            PipelineStage::Normalize { .. } => "normalize",
+            PipelineStage::Transcribe => "transcribe",
+            PipelineStage::Summarize => "summarize",
+            PipelineStage::ExportMp3 { .. } => "export_mp3",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum StageStatus {
+    Started,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PipelineProgressEvent {
+    recording_path: String,
+    stage: String,
+    stage_index: usize,
+    stage_count: usize,
+    status: StageStatus,
+    output: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PipelineCompleteEvent {
+    recording_path: String,
+    outputs: Vec<String>,
+    failed_stages: Vec<String>,
+}
+
+/// Run the stages in the background; the original WAV is never modified
+pub fn spawn(app_handle: AppHandle, recording: PathBuf, stages: Vec<PipelineStage>) {
+    if stages.is_empty() {
+        return;
+    }
+
+    thread::spawn(move || {
+        let recording_path = recording.to_string_lossy().to_string();
+        let stage_count = stages.len();
+        let mut outputs = Vec::new();
+        let mut failed_stages = Vec::new();
+
+        // Each stage works on the newest successful WAV so a failure doesn't derail the rest
+        let mut current = recording.clone();
+
+        for (stage_index, stage) in stages.iter().enumerate() {
+            let progress = |status: StageStatus, output: Option<String>, error: Option<String>| {
+                let _ = app_handle.emit(
+                    "pipeline-progress",
+                    PipelineProgressEvent {
+                        recording_path: recording_path.clone(),
+                        stage: stage.name().to_string(),
+                        stage_index,
+                        stage_count,
+                        status,
+                        output,
+                        error,
+                    },
+                );
+            };
+
+            progress(StageStatus::Started, None, None);
+
+            match run_stage(&app_handle, stage, &recording, &current) {
+                Ok(Some(output)) => {
+                    info!("Pipeline stage {} wrote {}", stage.name(), output.display());
+                    let output = if is_wav(&output) {
+                        // Registered as derived from its input, which may seal it under a new name
+                        let added = match library::add_derived(&app_handle, &current, &output) {
+                            Ok(added) => PathBuf::from(added),
+                            Err(e) => {
+                                warn!("Failed to add {} to the library: {}", output.display(), e);
+                                output
+                            }
+                        };
+                        current = added.clone();
+                        added
+                    } else {
+                        output
+                    };
+                    let output = output.to_string_lossy().to_string();
+                    outputs.push(output.clone());
+                    progress(StageStatus::Completed, Some(output), None);
+                }
+                Ok(None) => progress(StageStatus::Completed, None, None),
+                Err(e) => {
+                    warn!("Pipeline stage {} failed: {}", stage.name(), e);
+                    failed_stages.push(stage.name().to_string());
+                    progress(StageStatus::Failed, None, Some(e));
+                }
+            }
+        }
+
+        let _ = app_handle.emit(
+            "pipeline-complete",
+            PipelineCompleteEvent {
+                recording_path,
+                outputs,
+                failed_stages,
+            },
+        );
+    });
+}
+
+fn is_wav(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.eq_ignore_ascii_case("wav"))
+        .unwrap_or(false)
+}
+
+// Returns the file the stage produced, if any
+fn run_stage(
+    app_handle: &AppHandle,
+    stage: &PipelineStage,
+    recording: &Path,
+    input: &Path,
+) -> Result<Option<PathBuf>, String> {
+    match stage {
+        PipelineStage::Normalize { target_peak_db } => {
+            let mut buffer = processing::read_wav(input)?;
+            processing::normalize(&mut buffer, *target_peak_db);
+            let output = processing::derived_path(input, "normalized", "wav");
+            processing::write_wav(&output, &buffer)?;
+            Ok(Some(output))
+        }
+        // Queued with the default backend; the transcript belongs to the recording itself,
+        // whose timings earlier stages don't change
+        PipelineStage::Transcribe => {
+            let path = recording.to_string_lossy().to_string();
+            jobs::enqueue(app_handle, JobKind::Transcribe { path, backend: None })?;
+            Ok(None)
+        }
+        PipelineStage::Summarize => {
+            let path = recording.to_string_lossy().to_string();
+            jobs::enqueue(app_handle, JobKind::Summarize { path })?;
+            Ok(None)
+        }
+        PipelineStage::ExportMp3 { bitrate_kbps } => {
+            // Next to the input, numbered rather than replacing an MP3 already there
+            let stem = input.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+            let output = storage::unique_destination(
+                input.parent().unwrap_or_else(|| Path::new(".")),
+                &format!("{}.mp3", stem),
+            );
+            let bitrate = format!("{}k", bitrate_kbps);
+            processing::encode_with_ffmpeg(input, &output, &["-codec:a", "libmp3lame", "-b:a", &bitrate])?;
+            Ok(Some(output))
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tracing::{error, info, warn};
+
+use crate::config::ConfigState;
+use crate::notifications;
+use crate::RecordingState;
+
+//
+// ====== Sleep / wake handling ======
+//
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+// Time asleep shorter than this is ignored
+const SLEEP_THRESHOLD: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Serialize, Clone)]
+struct SystemResumedEvent {
+    slept_secs: u64,
+    /// Recording that was finalized because the machine went to sleep
+    saved_path: Option<String>,
+    /// Whether a new recording segment was started after waking
+    resumed: bool,
+    error: Option<String>,
+}
+
+// Two clocks in nanoseconds that clock changes and NTP corrections don't move: one that
+// keeps counting while the machine sleeps and one that doesn't
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn clocks() -> (u64, u64) {
+    (clock(libc::CLOCK_BOOTTIME), clock(libc::CLOCK_MONOTONIC))
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+fn clocks() -> (u64, u64) {
+    (clock(libc::CLOCK_MONOTONIC), clock(libc::CLOCK_UPTIME_RAW))
+}
+
+#[cfg(unix)]
+fn clock(id: libc::clockid_t) -> u64 {
+    let mut time = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    // SAFETY: only writes to `time`
+    unsafe { libc::clock_gettime(id, &mut time) };
+    time.tv_sec as u64 * 1_000_000_000 + time.tv_nsec as u64
+}
+
+#[cfg(target_os = "windows")]
+fn clocks() -> (u64, u64) {
+    use windows::Win32::System::WindowsProgramming::{QueryInterruptTime, QueryUnbiasedInterruptTime};
+
+    let mut unbiased = 0;
+    // SAFETY: both only read the interrupt time; the second writes it to `unbiased`
+    let total = unsafe {
+        let _ = QueryUnbiasedInterruptTime(&mut unbiased);
+        QueryInterruptTime()
+    };
+    // Both count in 100 ns units
+    (total * 100, unbiased * 100)
+}
+
+/// Watch for suspend/resume. Between two ticks the clock that counts sleep gets ahead
+/// of the one that doesn't by however long the machine was asleep.
+pub fn watch(app_handle: AppHandle) {
+    thread::spawn(move || {
+        let (mut last_total, mut last_awake) = clocks();
+        loop {
+            thread::sleep(WATCH_INTERVAL);
+            let (total, awake) = clocks();
+            let slept = total.saturating_sub(last_total).saturating_sub(awake.saturating_sub(last_awake));
+            (last_total, last_awake) = (total, awake);
+
+            if slept > SLEEP_THRESHOLD.as_nanos() as u64 {
+                handle_wake(&app_handle, Duration::from_nanos(slept));
+            }
+        }
+    });
+}
+
+// The input stream rarely survives a suspend, so close out the recording at the
+// point the machine went to sleep and optionally continue in a new segment
+fn handle_wake(app_handle: &AppHandle, slept: Duration) {
+    info!("System resumed after about {} s", slept.as_secs());
+
+    let recording = app_handle
+        .state::<Arc<RecordingState>>()
+        .is_recording();
+    if !recording {
+        return;
+    }
+
+    let mut event = SystemResumedEvent {
+        slept_secs: slept.as_secs(),
+        saved_path: None,
+        resumed: false,
+        error: None,
+    };
+
+    match crate::stop_recording_internal(app_handle) {
+        Ok(path) => {
+            info!("Finalized {} after sleep", path.display());
+            event.saved_path = Some(path.to_string_lossy().to_string());
+        }
+        Err(e) => {
+            error!("Failed to finalize recording after sleep: {}", e);
+            event.error = Some(e.to_string());
+        }
+    }
+
+    if app_handle.state::<ConfigState>().get().resume_after_sleep {
+        match crate::start_recording_internal(app_handle) {
+            Ok(_) => event.resumed = true,
+            Err(e) => {
+                warn!("Failed to resume recording after sleep: {}", e);
+                event.error = Some(e.to_string());
+            }
+        }
+    }
+
+    if !event.resumed {
+        notifications::notify(
+            app_handle,
+            "Recording stopped",
+            "The computer went to sleep, so the recording was saved and stopped.",
+        );
+    }
+    let _ = app_handle.emit("system-resumed", event);
+}
+
+// Choose whether recording continues in a new file after the computer wakes up
+#[tauri::command]
+pub fn set_resume_after_sleep(config: State<'_, ConfigState>, enabled: bool) -> Result<(), String> {
+    config.update(|c| {
+        c.resume_after_sleep = enabled;
+        Ok(())
+    })
+}
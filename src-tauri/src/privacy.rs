@@ -0,0 +1,340 @@
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rekt_core::recording::{self, RecorderState, RecordingState};
+use rekt_core::transcript::TranscriptFormat;
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::audit::{self, AuditAction, AuditLog, Initiator};
+use crate::calendar::CalendarState;
+use crate::config::{AppConfig, ConfigState};
+use crate::crypto::{self, EncryptionState};
+use crate::jobs::JobQueue;
+use crate::library::Library;
+use crate::lock::AppLock;
+use crate::logging::LogState;
+use crate::osc::OscListener;
+use crate::secrets;
+use crate::share::ShareRegistry;
+use crate::spectrogram;
+use crate::versions::VersionStore;
+use crate::watch::FolderWatcher;
+
+//
+// ====== Exporting and erasing all user data ======
+//
+
+// How long a wipe confirmation stays valid after it is asked for
+const WIPE_TOKEN_TTL: Duration = Duration::from_secs(120);
+
+/// The confirmation token handed out by `prepare_wipe_all_data`, with when it was issued.
+/// It is good for one attempt.
+#[derive(Default)]
+pub struct WipeConfirmation {
+    pending: Mutex<Option<(String, Instant)>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DataExportSummary {
+    path: String,
+    recordings: usize,
+    transcripts: usize,
+    size_bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WipeRequest {
+    /// Pass back to `wipe_all_data` to go ahead
+    token: String,
+    /// Recordings that would be deleted
+    recordings: usize,
+    expires_in_secs: u64,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct WipeSummary {
+    recordings_deleted: usize,
+    versions_deleted: usize,
+    /// Recordings whose files couldn't be deleted; they stay in the library
+    failed: Vec<String>,
+}
+
+fn write_entry(zip: &mut ZipWriter<File>, name: &str, options: SimpleFileOptions, bytes: &[u8]) -> Result<(), String> {
+    zip.start_file(name, options)
+        .and_then(|_| zip.write_all(bytes).map_err(Into::into))
+        .map_err(|e| format!("Failed to write {} to export: {}", name, e))
+}
+
+fn to_json(value: &impl Serialize) -> Result<Vec<u8>, String> {
+    serde_json::to_vec_pretty(value).map_err(|e| format!("Failed to serialize export: {}", e))
+}
+
+// The name a recording had before it was encrypted, so exported copies open anywhere
+fn plain_name(path: &Path) -> String {
+    let name = if crypto::is_encrypted(path) {
+        path.file_stem()
+    } else {
+        path.file_name()
+    };
+    name.map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+// `name`, numbered like `take_1.wav` if an earlier recording already used it
+fn unique_name(used: &mut HashSet<String>, name: String) -> String {
+    let (stem, ext) = match name.split_once('.') {
+        Some((stem, ext)) => (stem.to_string(), format!(".{}", ext)),
+        None => (name.clone(), String::new()),
+    };
+    let mut candidate = name;
+    let mut counter = 1;
+    while used.contains(&candidate) {
+        candidate = format!("{}_{}{}", stem, counter, ext);
+        counter += 1;
+    }
+    used.insert(candidate.clone());
+    candidate
+}
+
+fn export(app_handle: &AppHandle, dest: &Path) -> Result<DataExportSummary, String> {
+    let library = app_handle.state::<Library>();
+    let encryption = app_handle.state::<EncryptionState>();
+    let versions = app_handle.state::<VersionStore>();
+    let entries = library.entries();
+    let kept = versions.all();
+    let any_encrypted = entries.iter().map(|e| e.path.as_str()).chain(kept.iter().map(|v| v.path.as_str()));
+    if !encryption.is_unlocked() && any_encrypted.map(Path::new).any(crypto::is_encrypted) {
+        return Err("Some recordings are encrypted; enter the passphrase before exporting".to_string());
+    }
+
+    let file = File::create(dest).map_err(|e| format!("Failed to create export file: {}", e))?;
+    let mut zip = ZipWriter::new(file);
+    // Audio barely compresses, so it's stored as-is like in backups
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut transcripts = 0;
+    // Recordings from different folders can share a file name, but zip entries can't
+    let mut used = HashSet::new();
+    for entry in &entries {
+        let path = Path::new(&entry.path);
+        let name = unique_name(&mut used, plain_name(path));
+        if crypto::is_encrypted(path) {
+            let bytes = crypto::read_recording(&encryption, path)?;
+            write_entry(&mut zip, &format!("recordings/{}", name), stored, &bytes)?;
+        } else {
+            let mut source = File::open(path).map_err(|e| format!("Failed to open {}: {}", entry.path, e))?;
+            zip.start_file(format!("recordings/{}", name), stored)
+                .map_err(|e| format!("Failed to write export: {}", e))?;
+            io::copy(&mut source, &mut zip).map_err(|e| format!("Failed to export {}: {}", entry.path, e))?;
+        }
+
+        let timestamps = recording::timestamps_path(path);
+        if let Ok(bytes) = fs::read(&timestamps) {
+            write_entry(&mut zip, &format!("recordings/{}.timestamps.json", name), deflated, &bytes)?;
+        }
+
+        let stem = Path::new(&name).file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or(name.clone());
+        if let Some(transcript) = &entry.transcript {
+            let srt = transcript.render(TranscriptFormat::Srt)?;
+            write_entry(&mut zip, &format!("transcripts/{}.srt", stem), deflated, srt.as_bytes())?;
+            transcripts += 1;
+        }
+        for translation in &entry.translations {
+            let language = translation.language.as_deref().unwrap_or("unknown");
+            let srt = translation.render(TranscriptFormat::Srt)?;
+            write_entry(&mut zip, &format!("transcripts/{}.{}.srt", stem, language), deflated, srt.as_bytes())?;
+            transcripts += 1;
+        }
+    }
+
+    for version in &kept {
+        let file = versions.file(&version.id);
+        let bytes = if crypto::is_encrypted(Path::new(&version.path)) {
+            encryption.decrypt_file(&file)?
+        } else {
+            fs::read(&file).map_err(|e| format!("Failed to read version {}: {}", version.id, e))?
+        };
+        let name = format!("versions/{}_{}", version.id, plain_name(Path::new(&version.path)));
+        write_entry(&mut zip, &name, stored, &bytes)?;
+    }
+
+    let log_state = app_handle.state::<LogState>();
+    for log in log_state.files()? {
+        match fs::read(&log) {
+            Ok(bytes) => write_entry(&mut zip, &format!("logs/{}", plain_name(&log)), deflated, &bytes)?,
+            Err(e) => warn!("Leaving {} out of the export: {}", log.display(), e),
+        }
+    }
+
+    let audit_log = fs::read(app_handle.state::<AuditLog>().path())
+        .map_err(|e| format!("Failed to read audit log: {}", e))?;
+    let index = to_json(&serde_json::json!({ "recordings": entries, "folders": library.folders() }))?;
+    let files = [
+        ("library.json", index),
+        ("settings.json", to_json(&app_handle.state::<ConfigState>().get())?),
+        ("versions.json", to_json(&kept)?),
+        ("jobs.json", to_json(&app_handle.state::<JobQueue>().list())?),
+        ("audit.jsonl", audit_log),
+    ];
+    for (name, bytes) in files {
+        write_entry(&mut zip, name, deflated, &bytes)?;
+    }
+    zip.finish().map_err(|e| format!("Failed to finalize export: {}", e))?;
+
+    Ok(DataExportSummary {
+        path: dest.to_string_lossy().to_string(),
+        recordings: entries.len(),
+        transcripts,
+        size_bytes: fs::metadata(dest).map(|m| m.len()).unwrap_or(0),
+    })
+}
+
+// Bring what runs in the background in line with the reset settings. Meeting detection
+// reads its settings on every poll, so it is already off.
+fn stop_background_work(app_handle: &AppHandle) {
+    let _ = app_handle.state::<FolderWatcher>().restart(app_handle.clone(), None);
+    let _ = app_handle.state::<OscListener>().restart(app_handle.clone(), None);
+    app_handle.state::<CalendarState>().forget();
+    app_handle.state::<ShareRegistry>().stop_all();
+}
+
+fn wipe(app_handle: &AppHandle) -> Result<WipeSummary, String> {
+    // Everything that can refuse does so before anything is deleted
+    let recorder = app_handle.state::<Arc<RecordingState>>();
+    if recorder.recorder_state() != RecorderState::Idle {
+        return Err("Stop recording before wiping all data".to_string());
+    }
+    app_handle.state::<JobQueue>().clear()?;
+
+    let library = app_handle.state::<Library>();
+    let mut summary = WipeSummary::default();
+    for entry in library.entries() {
+        if let Err(e) = fs::remove_file(&entry.path) {
+            if e.kind() != io::ErrorKind::NotFound {
+                warn!("Could not delete {}: {}", entry.path, e);
+                summary.failed.push(entry.path);
+                continue;
+            }
+        }
+        spectrogram::remove_cached(&library, Path::new(&entry.path));
+        let _ = fs::remove_file(recording::timestamps_path(Path::new(&entry.path)));
+        library.remove(&entry.path)?;
+        summary.recordings_deleted += 1;
+    }
+    for folder in library.folders() {
+        library.delete_folder(&folder)?;
+    }
+    summary.versions_deleted = app_handle.state::<VersionStore>().clear()?;
+
+    secrets::delete_all()?;
+    app_handle.state::<ConfigState>().update(|c| {
+        *c = AppConfig::default();
+        Ok(())
+    })?;
+    stop_background_work(app_handle);
+    app_handle.state::<LogState>().clear()?;
+    app_handle.state::<AuditLog>().clear()?;
+    Ok(summary)
+}
+
+//
+// ====== Data export and wipe commands ======
+//
+
+// Write everything the app holds about the user to one zip: recordings (decrypted),
+// transcripts and translations as SRT, earlier versions, library metadata, settings,
+// jobs, logs and the audit log. Credentials stay in the keychain and aren't included.
+#[tauri::command]
+pub async fn export_all_data(
+    app_handle: AppHandle,
+    app_lock: State<'_, AppLock>,
+    dest: String,
+) -> Result<DataExportSummary, String> {
+    app_lock.ensure_unlocked()?;
+
+    let dest = PathBuf::from(dest);
+    let audit_handle = app_handle.clone();
+    let summary = tauri::async_runtime::spawn_blocking(move || {
+        let result = export(&app_handle, &dest);
+        if result.is_err() {
+            let _ = fs::remove_file(&dest);
+        }
+        result
+    })
+    .await
+    .map_err(|e| format!("Export failed: {}", e))??;
+
+    info!("Exported all data ({} recordings) to {}", summary.recordings, summary.path);
+    audit::record_as(
+        &audit_handle,
+        Initiator::Ui,
+        AuditAction::Export,
+        None,
+        Some(format!("All data to {}", summary.path)),
+    );
+    Ok(summary)
+}
+
+// First step of wiping all data: returns a token `wipe_all_data` needs within a couple
+// of minutes, so a purge is never one call away
+#[tauri::command]
+pub fn prepare_wipe_all_data(
+    app_handle: AppHandle,
+    app_lock: State<'_, AppLock>,
+    confirmation: State<'_, WipeConfirmation>,
+) -> Result<WipeRequest, String> {
+    app_lock.ensure_unlocked()?;
+    let token = nanoid::nanoid!(32);
+    *confirmation.pending.lock().unwrap() = Some((token.clone(), Instant::now()));
+    Ok(WipeRequest {
+        token,
+        recordings: app_handle.state::<Library>().entries().len(),
+        expires_in_secs: WIPE_TOKEN_TTL.as_secs(),
+    })
+}
+
+// Delete every recording with its sidecars, earlier versions, jobs, logs, the audit log,
+// stored credentials and settings. The watch folder, OSC listener, calendar and share links
+// stop right away. Copies elsewhere, like sync targets and backups, aren't touched, and
+// other settings already in effect, like hotkeys, last until a restart.
+#[tauri::command]
+pub async fn wipe_all_data(
+    app_handle: AppHandle,
+    app_lock: State<'_, AppLock>,
+    confirmation: State<'_, WipeConfirmation>,
+    token: String,
+) -> Result<WipeSummary, String> {
+    app_lock.ensure_unlocked()?;
+    match confirmation.pending.lock().unwrap().take() {
+        Some((expected, issued)) if expected == token && issued.elapsed() < WIPE_TOKEN_TTL => {}
+        _ => return Err("Confirmation is invalid or has expired; ask for a new one".to_string()),
+    }
+
+    let audit_handle = app_handle.clone();
+    let summary = tauri::async_runtime::spawn_blocking(move || wipe(&app_handle))
+        .await
+        .map_err(|e| format!("Wipe failed: {}", e))??;
+
+    info!(
+        "Wiped all data: {} recordings deleted, {} could not be",
+        summary.recordings_deleted,
+        summary.failed.len()
+    );
+    // The only entry left, so the log still shows that it was emptied on purpose
+    audit::record_as(
+        &audit_handle,
+        Initiator::Ui,
+        AuditAction::Delete,
+        None,
+        Some(format!("All data wiped ({} recordings)", summary.recordings_deleted)),
+    );
+    Ok(summary)
+}
@@ -0,0 +1,182 @@
+use std::path::Path;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+use tracing::info;
+
+use crate::crypto::{self, EncryptionState};
+use crate::lock::AppLock;
+use crate::processing::{self, AudioBuffer};
+
+//
+// ====== Recording quality analysis ======
+//
+
+const WINDOW_MS: u32 = 50;
+const CLIP_LEVEL: f32 = 0.999;
+// A dropout is a stretch of exact digital silence in the middle of the recording
+const MIN_DROPOUT_MS: u64 = 10;
+const MAX_REPORTED_DROPOUTS: usize = 100;
+
+const HIGH_NOISE_FLOOR_DB: f32 = -50.0;
+const LOW_SNR_DB: f32 = 20.0;
+const DC_OFFSET_LIMIT: f32 = 0.01;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum QualityIssue {
+    Clipping,
+    HighNoise,
+    LowSnr,
+    DcOffset,
+    Dropouts,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Dropout {
+    position_ms: u64,
+    duration_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QualityReport {
+    duration_ms: u64,
+    peak_db: f32,
+    clipped_samples: usize,
+    /// Runs of consecutive clipped samples, closer to what the ear notices
+    clipping_events: usize,
+    /// Level of the quietest tenth of the recording
+    noise_floor_db: f32,
+    /// Level of the loudest tenth of the recording
+    signal_db: f32,
+    snr_db: f32,
+    /// Mean sample value per channel
+    dc_offset: Vec<f32>,
+    dropouts: Vec<Dropout>,
+    issues: Vec<QualityIssue>,
+}
+
+fn analyze(buffer: &AudioBuffer) -> QualityReport {
+    let channels = buffer.channels.max(1) as usize;
+    let frames = buffer.samples.len() / channels;
+    let frame_ms = |frame: usize| frame as u64 * 1000 / buffer.sample_rate.max(1) as u64;
+
+    let mut clipped_samples = 0;
+    let mut clipping_events = 0;
+    let mut in_clip = vec![false; channels];
+    let mut sums = vec![0f64; channels];
+    for (i, &sample) in buffer.samples.iter().enumerate() {
+        let channel = i % channels;
+        sums[channel] += sample as f64;
+        let clipped = sample.abs() >= CLIP_LEVEL;
+        if clipped {
+            clipped_samples += 1;
+            if !in_clip[channel] {
+                clipping_events += 1;
+            }
+        }
+        in_clip[channel] = clipped;
+    }
+    let dc_offset = sums
+        .iter()
+        .map(|sum| (sum / frames.max(1) as f64) as f32)
+        .collect::<Vec<_>>();
+
+    let mut windows = buffer.window_rms(WINDOW_MS);
+    windows.sort_by(|a, b| a.total_cmp(b));
+    let percentile = |p: usize| windows.get(windows.len() * p / 100).copied().unwrap_or(0.0);
+    let noise_floor_db = processing::to_db(percentile(10));
+    let signal_db = processing::to_db(percentile(90));
+    let snr_db = signal_db - noise_floor_db;
+
+    let dropouts = find_dropouts(buffer, frame_ms);
+
+    let mut issues = Vec::new();
+    if clipping_events > 0 {
+        issues.push(QualityIssue::Clipping);
+    }
+    if noise_floor_db > HIGH_NOISE_FLOOR_DB {
+        issues.push(QualityIssue::HighNoise);
+    }
+    if snr_db < LOW_SNR_DB {
+        issues.push(QualityIssue::LowSnr);
+    }
+    if dc_offset.iter().any(|offset| offset.abs() > DC_OFFSET_LIMIT) {
+        issues.push(QualityIssue::DcOffset);
+    }
+    if !dropouts.is_empty() {
+        issues.push(QualityIssue::Dropouts);
+    }
+
+    QualityReport {
+        duration_ms: frame_ms(frames),
+        peak_db: processing::to_db(buffer.peak()),
+        clipped_samples,
+        clipping_events,
+        noise_floor_db,
+        signal_db,
+        snr_db,
+        dc_offset,
+        dropouts,
+        issues,
+    }
+}
+
+// Runs of all-zero frames long enough to be a lost buffer rather than a zero crossing.
+// Silence at the very start or end is just a quiet lead-in, not a dropout.
+fn find_dropouts(buffer: &AudioBuffer, frame_ms: impl Fn(usize) -> u64) -> Vec<Dropout> {
+    let channels = buffer.channels.max(1) as usize;
+    let mut dropouts = Vec::new();
+    let mut run_start = None;
+
+    for (frame, samples) in buffer.samples.chunks_exact(channels).enumerate() {
+        let silent = samples.iter().all(|&s| s == 0.0);
+        match (silent, run_start) {
+            (true, None) => run_start = Some(frame),
+            (false, Some(start)) => {
+                let duration_ms = frame_ms(frame - start);
+                if start > 0 && duration_ms >= MIN_DROPOUT_MS && dropouts.len() < MAX_REPORTED_DROPOUTS {
+                    dropouts.push(Dropout {
+                        position_ms: frame_ms(start),
+                        duration_ms,
+                    });
+                }
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+
+    dropouts
+}
+
+// Check a recording for clipping, noise, DC offset and dropouts
+#[tauri::command]
+pub async fn analyze_quality(
+    app_handle: AppHandle,
+    app_lock: State<'_, AppLock>,
+    path: String,
+) -> Result<QualityReport, String> {
+    app_lock.ensure_unlocked()?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let bytes = crypto::read_recording(&app_handle.state::<EncryptionState>(), Path::new(&path))?;
+        let buffer = processing::read_wav_bytes(&bytes)?;
+        if buffer.samples.is_empty() {
+            return Err("Recording contains no audio".to_string());
+        }
+
+        let report = analyze(&buffer);
+        info!(
+            "Quality of {}: floor {:.1} dBFS, SNR {:.1} dB, {} clipping events, {} dropouts",
+            path,
+            report.noise_floor_db,
+            report.snr_db,
+            report.clipping_events,
+            report.dropouts.len()
+        );
+        Ok(report)
+    })
+    .await
+    .map_err(|e| format!("Quality analysis failed: {}", e))?
+}
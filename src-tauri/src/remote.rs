@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+use crate::audit::{self, Initiator};
+use crate::RecordingState;
+
+//
+// ====== Remote recorder triggers ======
+//
+
+/// Recorder actions that external controllers (OSC, MIDI) can trigger
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum RemoteAction {
+    StartRecording,
+    StopRecording,
+    ToggleRecording,
+    Marker {
+        #[serde(default)]
+        label: Option<String>,
+    },
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct RemoteTriggerEvent {
+    source: String,
+    action: RemoteAction,
+    /// Saved file for stop actions
+    path: Option<String>,
+    error: Option<String>,
+}
+
+/// Run `action` and tell the frontend about it so the UI can follow along
+pub fn perform(app_handle: &AppHandle, source: &str, action: RemoteAction) {
+    let recording = app_handle
+        .state::<Arc<RecordingState>>()
+        .is_recording();
+
+    let initiator = match source {
+        "hotkey" => Initiator::Hotkey,
+        "overlay" => Initiator::Ui,
+        _ => Initiator::Api,
+    };
+    let result = audit::initiated_by(initiator, || match &action {
+        RemoteAction::StartRecording => crate::start_recording_internal(app_handle).map(|_| None).map_err(String::from),
+        RemoteAction::StopRecording => crate::stop_recording_internal(app_handle).map(Some).map_err(String::from),
+        RemoteAction::ToggleRecording if recording => crate::stop_recording_internal(app_handle).map(Some).map_err(String::from),
+        RemoteAction::ToggleRecording => crate::start_recording_internal(app_handle).map(|_| None).map_err(String::from),
+        RemoteAction::Marker { label } => {
+            app_handle.state::<Arc<RecordingState>>().add_marker(label.clone()).map(|_| None)
+        }
+    });
+
+    let event = match result {
+        Ok(path) => {
+            info!("Remote {} trigger: {:?}", source, action);
+            RemoteTriggerEvent {
+                source: source.to_string(),
+                action,
+                path: path.map(|p| p.to_string_lossy().to_string()),
+                error: None,
+            }
+        }
+        Err(e) => {
+            warn!("Remote {} trigger {:?} failed: {}", source, action, e);
+            RemoteTriggerEvent {
+                source: source.to_string(),
+                action,
+                path: None,
+                error: Some(e),
+            }
+        }
+    };
+    let _ = app_handle.emit("remote-trigger", event);
+}
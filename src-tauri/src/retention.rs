@@ -0,0 +1,112 @@
+use std::fs;
+use std::path::Path;
+
+use rekt_core::recording;
+pub use rekt_core::settings::RetentionPolicy;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+use crate::audit::{self, AuditAction, Initiator};
+use crate::config::ConfigState;
+use crate::library::{Library, RecordingEntry};
+use crate::spectrogram;
+use crate::versions::VersionStore;
+
+//
+// ====== Retention / auto-cleanup ======
+//
+
+/// Delete recordings that have outlived the configured policy, along with everything
+/// rendered from them; returns the removed paths
+pub fn apply(app_handle: &AppHandle) -> Vec<String> {
+    let policy = match app_handle.state::<ConfigState>().get().retention {
+        Some(policy) => policy,
+        None => return Vec::new(),
+    };
+    let library = app_handle.state::<Library>();
+    let cutoff = chrono::Local::now() - chrono::Duration::days(policy.max_age_days as i64);
+    let entries = library.entries();
+
+    // A restored recording starts its time over, or an old backup would be deleted as
+    // soon as it was brought back
+    let mut pending: Vec<(RecordingEntry, String)> = entries
+        .iter()
+        .filter(|entry| !entry.favorite)
+        .filter(|entry| {
+            let added = entry.restored_at.as_deref().unwrap_or(&entry.created_at);
+            chrono::DateTime::parse_from_rfc3339(added).is_ok_and(|added| added < cutoff)
+        })
+        .map(|entry| (entry.clone(), format!("Older than {} days", policy.max_age_days)))
+        .collect();
+
+    let mut removed = Vec::new();
+    while let Some((entry, reason)) = pending.pop() {
+        if removed.contains(&entry.path) {
+            continue;
+        }
+        if let Err(e) = delete(app_handle, &entry) {
+            warn!("{}", e);
+            continue;
+        }
+        audit::record_as(
+            app_handle,
+            Initiator::Automatic,
+            AuditAction::Delete,
+            Some(Path::new(&entry.path)),
+            Some(reason),
+        );
+        // Edits and effects of a deleted recording go with it, unless pinned themselves
+        pending.extend(
+            entries
+                .iter()
+                .filter(|derived| !derived.favorite && derived.derived_from.as_deref() == Some(entry.path.as_str()))
+                .map(|derived| (derived.clone(), format!("Derived from {}", entry.path))),
+        );
+        removed.push(entry.path);
+    }
+
+    if !removed.is_empty() {
+        info!("Retention policy removed {} recording(s)", removed.len());
+    }
+    removed
+}
+
+// Delete a recording with its sidecars and kept versions, and drop it from the library
+fn delete(app_handle: &AppHandle, entry: &RecordingEntry) -> Result<(), String> {
+    let library = app_handle.state::<Library>();
+    if let Err(e) = fs::remove_file(&entry.path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            return Err(format!("Retention could not delete {}: {}", entry.path, e));
+        }
+    }
+    spectrogram::remove_cached(&library, Path::new(&entry.path));
+    let _ = fs::remove_file(recording::timestamps_path(Path::new(&entry.path)));
+    if let Err(e) = app_handle.state::<VersionStore>().forget(&entry.path) {
+        warn!("{}", e);
+    }
+    library.remove(&entry.path)?;
+    Ok(())
+}
+
+// Set how long recordings are kept, or keep everything with `None`
+#[tauri::command]
+pub fn set_retention_policy(
+    app_handle: AppHandle,
+    config: State<'_, ConfigState>,
+    policy: Option<RetentionPolicy>,
+) -> Result<Vec<String>, String> {
+    if let Some(ref policy) = policy {
+        policy.validate()?;
+    }
+
+    config.update(|c| {
+        c.retention = policy.clone();
+        Ok(())
+    })?;
+    Ok(apply(&app_handle))
+}
+
+#[tauri::command]
+pub fn get_retention_policy(config: State<'_, ConfigState>) -> Option<RetentionPolicy> {
+    config.get().retention
+}
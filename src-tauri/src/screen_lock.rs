@@ -0,0 +1,218 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tracing::{info, warn};
+
+use crate::config::ConfigState;
+use crate::{RecorderState, RecordingState};
+
+//
+// ====== Pause on screen lock ======
+//
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LockAction {
+    /// Keep recording
+    #[default]
+    Nothing,
+    Pause,
+    /// Save the recording so far and stop
+    Stop,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScreenLockConfig {
+    /// What happens to a recording in progress when the session locks, so nothing is
+    /// captured after walking away
+    pub action: LockAction,
+    /// Carry on when the session is unlocked: a paused recording resumes, a stopped one
+    /// starts again in a new file
+    pub resume_on_unlock: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct ScreenLockEvent {
+    locked: bool,
+    /// What was done to the recording, if anything
+    action: Option<LockAction>,
+    /// Whether recording carried on after unlocking
+    resumed: bool,
+    error: Option<String>,
+}
+
+// Whether the session's lock screen is up, per logind
+#[cfg(target_os = "linux")]
+fn is_locked() -> Result<bool, String> {
+    let output = std::process::Command::new("loginctl")
+        .args(["show-session", "auto", "--property=LockedHint"])
+        .output()
+        .map_err(|e| format!("Failed to run loginctl: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Reading the session lock state failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim() == "LockedHint=yes")
+}
+
+#[cfg(target_os = "macos")]
+fn is_locked() -> Result<bool, String> {
+    use objc2::msg_send;
+    use objc2::rc::Retained;
+    use objc2::runtime::{AnyObject, Bool};
+    use objc2_foundation::NSString;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGSessionCopyCurrentDictionary() -> *mut AnyObject;
+    }
+
+    // SAFETY: a "Copy" function, so the dictionary is ours to release; CFDictionary is
+    // toll-free bridged to NSDictionary and the value read is a CFBoolean
+    unsafe {
+        let Some(session) = Retained::from_raw(CGSessionCopyCurrentDictionary()) else {
+            // No session: running outside a login session, e.g. over SSH
+            return Ok(false);
+        };
+        let key = NSString::from_str("CGSSessionScreenIsLocked");
+        let value: Option<Retained<AnyObject>> = msg_send![&*session, objectForKey: &*key];
+        Ok(value.is_some_and(|value| {
+            let locked: Bool = msg_send![&*value, boolValue];
+            locked.as_bool()
+        }))
+    }
+}
+
+// The lock screen runs on a secure desktop that other programs can't switch to
+#[cfg(target_os = "windows")]
+fn is_locked() -> Result<bool, String> {
+    use windows::Win32::System::StationsAndDesktops::{
+        CloseDesktop, OpenInputDesktop, SwitchDesktop, DESKTOP_CONTROL_FLAGS, DESKTOP_SWITCHDESKTOP,
+    };
+
+    // SAFETY: the handle is closed before returning
+    unsafe {
+        let Ok(desktop) = OpenInputDesktop(DESKTOP_CONTROL_FLAGS(0), false, DESKTOP_SWITCHDESKTOP) else {
+            return Ok(true);
+        };
+        let switchable = SwitchDesktop(desktop).is_ok();
+        let _ = CloseDesktop(desktop);
+        Ok(!switchable)
+    }
+}
+
+// Apply `action` to a recording in progress as the session locks. Returns what was done.
+fn locked(app_handle: &AppHandle, action: LockAction) -> Result<Option<LockAction>, String> {
+    let state = app_handle.state::<Arc<RecordingState>>();
+    match action {
+        LockAction::Pause if state.recorder_state() == RecorderState::Recording => {
+            crate::pause_recording_internal(app_handle)?;
+            info!("Recording paused because the screen locked");
+            Ok(Some(LockAction::Pause))
+        }
+        LockAction::Stop if state.is_recording() => {
+            let path = crate::stop_recording_internal(app_handle)?;
+            info!("Recording {} saved because the screen locked", path.display());
+            Ok(Some(LockAction::Stop))
+        }
+        _ => Ok(None),
+    }
+}
+
+// Undo what locking did. Returns whether recording carried on.
+fn unlocked(app_handle: &AppHandle, done: LockAction) -> Result<bool, String> {
+    let state = app_handle.state::<Arc<RecordingState>>();
+    match done {
+        // Unless someone stopped it meanwhile
+        LockAction::Pause if state.recorder_state() == RecorderState::Paused => {
+            crate::resume_recording_internal(app_handle)?;
+            Ok(true)
+        }
+        LockAction::Stop if state.is_idle() => {
+            crate::start_recording_internal(app_handle)?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Watch for the session locking and unlocking, and pause or stop recording as configured
+pub fn watch(app_handle: AppHandle) {
+    thread::spawn(move || {
+        let mut was_locked = false;
+        // What locking did to the recording, to undo on unlock
+        let mut done = None;
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            let config = app_handle.state::<ConfigState>().get().screen_lock;
+            if config.action == LockAction::Nothing && done.is_none() {
+                was_locked = false;
+                continue;
+            }
+            let is_locked = match is_locked() {
+                Ok(is_locked) => is_locked,
+                Err(e) => {
+                    // Usually a missing tool that won't appear later
+                    warn!("Screen lock can't be detected; giving up: {}", e);
+                    return;
+                }
+            };
+            if is_locked == was_locked {
+                continue;
+            }
+            was_locked = is_locked;
+
+            let mut event = ScreenLockEvent {
+                locked: is_locked,
+                action: None,
+                resumed: false,
+                error: None,
+            };
+            if is_locked {
+                match locked(&app_handle, config.action) {
+                    Ok(action) => {
+                        done = action;
+                        event.action = action;
+                    }
+                    Err(e) => {
+                        warn!("Failed to {:?} recording on screen lock: {}", config.action, e);
+                        event.error = Some(e);
+                    }
+                }
+            } else if let Some(action) = done.take() {
+                event.action = Some(action);
+                if config.resume_on_unlock {
+                    match unlocked(&app_handle, action) {
+                        Ok(resumed) => event.resumed = resumed,
+                        Err(e) => {
+                            warn!("Failed to resume recording on unlock: {}", e);
+                            event.error = Some(e);
+                        }
+                    }
+                }
+            }
+            let _ = app_handle.emit("screen-lock-changed", event);
+        }
+    });
+}
+
+#[tauri::command]
+pub fn set_screen_lock(config: State<'_, ConfigState>, settings: ScreenLockConfig) -> Result<(), String> {
+    config.update(|c| {
+        c.screen_lock = settings.clone();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn get_screen_lock(config: State<'_, ConfigState>) -> ScreenLockConfig {
+    config.get().screen_lock
+}
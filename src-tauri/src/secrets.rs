@@ -0,0 +1,104 @@
+use tracing::info;
+
+use crate::config::ConfigState;
+
+//
+// ====== OS keychain storage ======
+//
+
+const SERVICE: &str = "com.rekt.app";
+
+pub const WEBDAV_PASSWORD: &str = "webdav-password";
+pub const ICECAST_PASSWORD: &str = "icecast-password";
+pub const CALDAV_PASSWORD: &str = "caldav-password";
+pub const TRANSCRIPTION_API_KEY: &str = "transcription-api-key";
+pub const TRANSLATION_API_KEY: &str = "translation-api-key";
+
+/// Credentials the frontend may manage; internal entries like the app password hash are excluded
+const USER_SECRETS: [&str; 5] = [
+    WEBDAV_PASSWORD,
+    ICECAST_PASSWORD,
+    CALDAV_PASSWORD,
+    TRANSCRIPTION_API_KEY,
+    TRANSLATION_API_KEY,
+];
+
+fn entry(key: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(SERVICE, key).map_err(|e| format!("Failed to access keychain: {}", e))
+}
+
+pub fn get_secret(key: &str) -> Result<Option<String>, String> {
+    match entry(key)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read from keychain: {}", e)),
+    }
+}
+
+pub fn set_secret(key: &str, value: &str) -> Result<(), String> {
+    entry(key)?
+        .set_password(value)
+        .map_err(|e| format!("Failed to write to keychain: {}", e))
+}
+
+pub fn delete_secret(key: &str) -> Result<(), String> {
+    match entry(key)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("Failed to delete from keychain: {}", e)),
+    }
+}
+
+/// Remove every credential the user stored, e.g. when all of their data is wiped
+pub fn delete_all() -> Result<(), String> {
+    USER_SECRETS.iter().try_for_each(|key| delete_secret(key))
+}
+
+/// Move credentials that older versions kept in `audio_config.json` into the keychain
+pub fn migrate_plaintext(config: &ConfigState) -> Result<(), String> {
+    let plaintext = match config.get().webdav {
+        Some(webdav) if !webdav.password.is_empty() => webdav.password,
+        _ => return Ok(()),
+    };
+
+    set_secret(WEBDAV_PASSWORD, &plaintext)?;
+    // The password field is never serialized, so rewriting the config drops it from disk
+    config.update(|c| {
+        if let Some(webdav) = c.webdav.as_mut() {
+            webdav.password.clear();
+        }
+        Ok(())
+    })?;
+    info!("Moved WebDAV password from config file to keychain");
+    Ok(())
+}
+
+fn check_user_secret(name: &str) -> Result<(), String> {
+    if USER_SECRETS.contains(&name) {
+        Ok(())
+    } else {
+        Err(format!("Unknown secret '{}', expected one of {:?}", name, USER_SECRETS))
+    }
+}
+
+//
+// ====== Secret commands ======
+//
+
+#[tauri::command(rename = "set_secret")]
+pub fn set_secret_command(name: String, value: String) -> Result<(), String> {
+    check_user_secret(&name)?;
+    set_secret(&name, &value)
+}
+
+#[tauri::command(rename = "delete_secret")]
+pub fn delete_secret_command(name: String) -> Result<(), String> {
+    check_user_secret(&name)?;
+    delete_secret(&name)
+}
+
+// Whether a credential is stored; values are never sent back to the frontend
+#[tauri::command]
+pub fn has_secret(name: String) -> Result<bool, String> {
+    check_user_secret(&name)?;
+    Ok(get_secret(&name)?.is_some())
+}
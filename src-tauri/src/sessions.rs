@@ -0,0 +1,163 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use tracing::info;
+
+use crate::audit;
+use crate::crypto::EncryptionState;
+use crate::export::{self, ExportFormat, ExportProcessing};
+use crate::jobs::{self, JobContext, JobKind};
+use crate::library::{Library, Session};
+use crate::lock::AppLock;
+use crate::processing::{self, AudioBuffer};
+
+//
+// ====== Recording sessions ======
+//
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionExport {
+    /// Each take as its own file in a zip, like `export_recordings`
+    #[default]
+    Bundle,
+    /// All takes joined into one file, in take order
+    Concatenate,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionInfo {
+    name: String,
+    created_at: String,
+    takes: usize,
+    duration_ms: u64,
+    active: bool,
+}
+
+/// Join recordings end to end for a job, at the first one's format, and return the file
+pub fn run_concatenate_job(job: &JobContext, paths: &[String], dest: &str, format: ExportFormat) -> Result<String, String> {
+    let app_handle = job.app_handle();
+    let library = app_handle.state::<Library>();
+    let encryption = app_handle.state::<EncryptionState>();
+
+    let mut joined: Option<AudioBuffer> = None;
+    for (index, path) in paths.iter().enumerate() {
+        job.check_cancelled()?;
+        job.progress(index as f32 / paths.len() as f32);
+        let entry = library
+            .get(path)
+            .ok_or_else(|| format!("Recording not found in library: {}", path))?;
+        // Rendered like an export, so each take's edit list is applied
+        let wav = export::render(&encryption, &entry, ExportFormat::Wav, ExportProcessing::default())?;
+        let take = processing::read_wav_bytes(&wav)?;
+        match joined.as_mut() {
+            Some(joined) => {
+                let take = processing::resample(&processing::remix(&take, joined.channels), joined.sample_rate);
+                joined.samples.extend(take.samples);
+            }
+            None => joined = Some(take),
+        }
+    }
+    let joined = joined.ok_or_else(|| "No takes to export".to_string())?;
+
+    let dest = PathBuf::from(dest);
+    if format == ExportFormat::Wav {
+        processing::write_wav(&dest, &joined)?;
+    } else {
+        let dir = tempfile::tempdir().map_err(|e| format!("Failed to create temp directory: {}", e))?;
+        let input = dir.path().join("joined.wav");
+        processing::write_wav(&input, &joined)?;
+        processing::encode_with_ffmpeg_until(&input, &dest, format.codec_args(), || job.check_cancelled())?;
+    }
+    info!("Joined {} takes into {}", paths.len(), dest.display());
+    Ok(dest.to_string_lossy().to_string())
+}
+
+//
+// ====== Session commands ======
+//
+
+// Start a session; recordings made until `end_session` become its numbered takes
+#[tauri::command]
+pub fn create_session(library: State<'_, Library>, app_lock: State<'_, AppLock>, name: String) -> Result<Session, String> {
+    app_lock.ensure_unlocked()?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Session name cannot be empty".to_string());
+    }
+    let session = library.create_session(name)?;
+    info!("Started session '{}'", session.name);
+    Ok(session)
+}
+
+#[tauri::command]
+pub fn end_session(library: State<'_, Library>, app_lock: State<'_, AppLock>) -> Result<(), String> {
+    app_lock.ensure_unlocked()?;
+    library.end_session()
+}
+
+#[tauri::command]
+pub fn get_active_session(library: State<'_, Library>, app_lock: State<'_, AppLock>) -> Result<Option<String>, String> {
+    app_lock.ensure_unlocked()?;
+    Ok(library.active_session())
+}
+
+// Every session with its takes counted, newest first
+#[tauri::command]
+pub fn list_sessions(library: State<'_, Library>, app_lock: State<'_, AppLock>) -> Result<Vec<SessionInfo>, String> {
+    app_lock.ensure_unlocked()?;
+    let active = library.active_session();
+    let mut sessions = library
+        .sessions()
+        .into_iter()
+        .map(|session| {
+            let takes = library.takes(&session.name);
+            SessionInfo {
+                active: active.as_ref() == Some(&session.name),
+                takes: takes.len(),
+                duration_ms: takes.iter().map(|t| t.duration_ms).sum(),
+                name: session.name,
+                created_at: session.created_at,
+            }
+        })
+        .collect::<Vec<_>>();
+    sessions.reverse();
+    Ok(sessions)
+}
+
+// Queue exporting a session's takes to `dest`, either bundled in a zip or joined into
+// one file; returns the job id
+#[tauri::command]
+pub fn export_session(
+    app_handle: AppHandle,
+    app_lock: State<'_, AppLock>,
+    name: String,
+    dest: String,
+    mode: Option<SessionExport>,
+    format: Option<ExportFormat>,
+) -> Result<u64, String> {
+    app_lock.ensure_unlocked()?;
+    let library = app_handle.state::<Library>();
+    if !library.sessions().iter().any(|s| s.name == name) {
+        return Err(format!("No session named '{}'", name));
+    }
+    let paths = library.takes(&name).into_iter().map(|take| take.path).collect::<Vec<_>>();
+    if paths.is_empty() {
+        return Err(format!("Session '{}' has no takes", name));
+    }
+
+    audit::exported(&app_handle, &paths, &dest);
+    let format = format.unwrap_or_default();
+    let kind = match mode.unwrap_or_default() {
+        SessionExport::Bundle => JobKind::Export {
+            paths,
+            dest_zip: dest,
+            format,
+            dynamics: None,
+            stereo: None,
+        },
+        SessionExport::Concatenate => JobKind::Concatenate { paths, dest, format },
+    };
+    jobs::enqueue(&app_handle, kind)
+}
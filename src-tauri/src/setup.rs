@@ -0,0 +1,274 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use rekt_core::backend::AudioBackend;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::config::ConfigState;
+use crate::device_check;
+use crate::permissions::{self, PermissionState};
+use crate::storage;
+use crate::RecordingState;
+
+//
+// ====== First-run setup checks ======
+//
+
+// Under this much free space the disk check warns; about an hour of 48 kHz stereo
+const LOW_SPACE_BYTES: u64 = 1 << 30;
+// Under this much it fails; a few minutes' worth
+const MIN_SPACE_BYTES: u64 = 64 << 20;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SetupCheckKind {
+    MicrophonePermission,
+    InputDevice,
+    SaveDirectory,
+    DiskSpace,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SetupCheckStatus {
+    Ok,
+    /// Recording works, but something is worth fixing
+    Warning,
+    /// Recording won't work until this is fixed
+    Failed,
+    /// Couldn't be checked because an earlier check failed
+    Skipped,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SetupCheck {
+    kind: SetupCheckKind,
+    status: SetupCheckStatus,
+    message: String,
+    /// What the user can do about it, when it isn't `ok`
+    fix: Option<String>,
+}
+
+impl SetupCheck {
+    fn ok(kind: SetupCheckKind, message: String) -> Self {
+        Self {
+            kind,
+            status: SetupCheckStatus::Ok,
+            message,
+            fix: None,
+        }
+    }
+
+    fn problem(kind: SetupCheckKind, status: SetupCheckStatus, message: String, fix: &str) -> Self {
+        Self {
+            kind,
+            status,
+            message,
+            fix: Some(fix.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SetupChecklist {
+    /// No check failed or was skipped
+    ready: bool,
+    checks: Vec<SetupCheck>,
+}
+
+fn check_permission() -> SetupCheck {
+    let kind = SetupCheckKind::MicrophonePermission;
+    match permissions::microphone_permission() {
+        Ok(permission) => match (permission.state, permission.action) {
+            (PermissionState::Granted, _) => SetupCheck::ok(kind, "Microphone access is allowed".to_string()),
+            (PermissionState::NotDetermined, action) => SetupCheck {
+                kind,
+                status: SetupCheckStatus::Warning,
+                message: "Microphone access hasn't been asked for yet".to_string(),
+                fix: action,
+            },
+            (_, action) => SetupCheck {
+                kind,
+                status: SetupCheckStatus::Failed,
+                message: "Microphone access is denied".to_string(),
+                fix: action,
+            },
+        },
+        Err(e) => SetupCheck::problem(
+            kind,
+            SetupCheckStatus::Warning,
+            format!("Couldn't read the microphone permission: {}", e),
+            "Start a test recording to see whether the microphone can be used",
+        ),
+    }
+}
+
+// Open the default input briefly, unless the permission check failed, since on macOS
+// that would bring up the prompt out of turn
+fn check_device(app_handle: &AppHandle, permission: SetupCheckStatus) -> SetupCheck {
+    let kind = SetupCheckKind::InputDevice;
+    if permission != SetupCheckStatus::Ok {
+        return SetupCheck::problem(
+            kind,
+            SetupCheckStatus::Skipped,
+            "The input device can't be tested without microphone access".to_string(),
+            "Allow microphone access first",
+        );
+    }
+
+    let backend = app_handle.state::<Arc<dyn AudioBackend>>();
+    let device = match backend.default_input() {
+        Ok(device) => device,
+        Err(e) => {
+            return SetupCheck::problem(
+                kind,
+                SetupCheckStatus::Failed,
+                e,
+                "Connect a microphone or choose an input device in your system's sound settings",
+            )
+        }
+    };
+    // Already open for a recording, so evidently working
+    if !app_handle.state::<Arc<RecordingState>>().is_idle() {
+        return SetupCheck::ok(kind, format!("{} is in use for recording", device.name));
+    }
+
+    let opened = backend
+        .open_input(None, crate::requested_format(app_handle), Box::new(|_| {}))
+        .and_then(|stream| stream.play().map(|_| stream.device.clone()));
+    match opened {
+        Ok(opened) => SetupCheck::ok(
+            kind,
+            format!("{} works ({} Hz, {} channel(s))", device.name, opened.sample_rate, opened.channels),
+        ),
+        Err(e) => match device_check::diagnose_busy(&device.name, &e) {
+            Some(busy) => SetupCheck::problem(
+                kind,
+                SetupCheckStatus::Failed,
+                busy.to_string(),
+                "Close the other app using the microphone, or pick another input device",
+            ),
+            None => SetupCheck::problem(
+                kind,
+                SetupCheckStatus::Failed,
+                format!("{} couldn't be opened: {}", device.name, e),
+                "Check the device is connected and enabled, or pick another input device",
+            ),
+        },
+    }
+}
+
+// Recordings land in the app data dir first and are moved to the save dir afterwards,
+// so both have to be writable
+fn save_dirs(app_handle: &AppHandle) -> Result<Vec<PathBuf>, String> {
+    let app_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+    std::fs::create_dir_all(&app_dir).map_err(|e| format!("Failed to create app data directory: {}", e))?;
+    let save_dir = app_handle.state::<ConfigState>().get().save_dir.map(PathBuf::from);
+    Ok(std::iter::once(app_dir).chain(save_dir).collect())
+}
+
+fn check_save_dirs(dirs: &Result<Vec<PathBuf>, String>) -> SetupCheck {
+    let kind = SetupCheckKind::SaveDirectory;
+    let fix = "Choose a save directory you can write to, or reconnect the drive it's on";
+    let dirs = match dirs {
+        Ok(dirs) => dirs,
+        Err(e) => return SetupCheck::problem(kind, SetupCheckStatus::Failed, e.clone(), fix),
+    };
+    match dirs.iter().try_for_each(|dir| storage::check_writable(dir)) {
+        Ok(()) => SetupCheck::ok(kind, format!("Recordings are saved to {}", dirs[dirs.len() - 1].display())),
+        Err(e) => SetupCheck::problem(kind, SetupCheckStatus::Failed, e, fix),
+    }
+}
+
+#[cfg(unix)]
+fn free_space(dir: &Path) -> Result<u64, String> {
+    // `-P` keeps each filesystem on one line; sizes are in KiB
+    let output = std::process::Command::new("df")
+        .arg("-Pk")
+        .arg(dir)
+        .output()
+        .map_err(|e| format!("Failed to check free space: {}", e))?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .nth(1)
+        .and_then(|line| line.split_whitespace().nth(3))
+        .and_then(|available| available.parse::<u64>().ok())
+        .map(|kib| kib * 1024)
+        .ok_or_else(|| "Failed to check free space: unexpected output from df".to_string())
+}
+
+#[cfg(windows)]
+fn free_space(dir: &Path) -> Result<u64, String> {
+    use windows::core::HSTRING;
+    use windows::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let mut available = 0u64;
+    // SAFETY: `available` outlives the call
+    unsafe { GetDiskFreeSpaceExW(&HSTRING::from(dir.as_os_str()), Some(&mut available as *mut u64), None, None) }
+        .map_err(|e| format!("Failed to check free space: {}", e))?;
+    Ok(available)
+}
+
+fn format_space(bytes: u64) -> String {
+    format!("{:.1} GB", bytes as f64 / 1e9)
+}
+
+fn check_disk_space(dirs: &Result<Vec<PathBuf>, String>) -> SetupCheck {
+    let kind = SetupCheckKind::DiskSpace;
+    let Ok(dirs) = dirs else {
+        return SetupCheck::problem(
+            kind,
+            SetupCheckStatus::Skipped,
+            "Free space can't be checked without a save directory".to_string(),
+            "Fix the save directory first",
+        );
+    };
+    let mut least = None;
+    for dir in dirs {
+        match free_space(dir) {
+            Ok(free) if least.is_none_or(|(_, least)| free < least) => least = Some((dir, free)),
+            Ok(_) => {}
+            Err(e) => {
+                return SetupCheck::problem(kind, SetupCheckStatus::Warning, e, "Make sure the disk isn't nearly full")
+            }
+        }
+    }
+    let Some((dir, free)) = least else {
+        return SetupCheck::ok(kind, "No save directory to check".to_string());
+    };
+
+    let fix = "Free up space, or choose a save directory on a bigger disk";
+    let message = format!("{} free at {}", format_space(free), dir.display());
+    if free < MIN_SPACE_BYTES {
+        SetupCheck::problem(kind, SetupCheckStatus::Failed, message, fix)
+    } else if free < LOW_SPACE_BYTES {
+        SetupCheck::problem(kind, SetupCheckStatus::Warning, message, fix)
+    } else {
+        SetupCheck::ok(kind, message)
+    }
+}
+
+//
+// ====== Setup check commands ======
+//
+
+// Everything a new user needs in place before the first recording, in the order to fix it
+#[tauri::command]
+pub async fn run_setup_checks(app_handle: AppHandle) -> Result<SetupChecklist, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let permission = check_permission();
+        let device = check_device(&app_handle, permission.status);
+        let dirs = save_dirs(&app_handle);
+        let checks = vec![permission, device, check_save_dirs(&dirs), check_disk_space(&dirs)];
+        let ready = checks
+            .iter()
+            .all(|check| matches!(check.status, SetupCheckStatus::Ok | SetupCheckStatus::Warning));
+        SetupChecklist { ready, checks }
+    })
+    .await
+    .map_err(|e| format!("Setup checks failed: {}", e))
+}
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::net::{IpAddr, Ipv4Addr, UdpSocket};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rekt_core::share::{content_disposition, content_type, is_share_request, url_path};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tracing::{info, warn};
+
+use crate::crypto::{self, EncryptionState};
+use crate::library::Library;
+use crate::lock::AppLock;
+
+//
+// ====== One-time local HTTP share links ======
+//
+
+const DEFAULT_SHARE_TTL_SECS: u64 = 300;
+const MAX_SHARE_TTL_SECS: u64 = 24 * 60 * 60;
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Cancel flags for shares that are still being served, keyed by token
+#[derive(Default)]
+pub struct ShareRegistry {
+    active: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl ShareRegistry {
+    /// Close every open share link
+    pub fn stop_all(&self) {
+        for cancelled in self.active.lock().unwrap().values() {
+            cancelled.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShareLink {
+    token: String,
+    url: String,
+    expires_at: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct ShareFinishedEvent {
+    token: String,
+    downloaded: bool,
+}
+
+// Best-effort LAN address: connecting a UDP socket picks the outbound interface without sending anything
+fn lan_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket.connect((Ipv4Addr::new(192, 0, 2, 1), 9)).ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip())
+}
+
+// The file as it should be downloaded: encrypted recordings are decrypted first
+fn shared_file(app_handle: &AppHandle, path: &Path) -> Result<tiny_http::ResponseBox, String> {
+    if crypto::is_encrypted(path) {
+        let bytes = crypto::read_recording(&app_handle.state::<EncryptionState>(), path)?;
+        return Ok(tiny_http::Response::from_data(bytes).boxed());
+    }
+    let file = File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    Ok(tiny_http::Response::from_file(file).boxed())
+}
+
+fn header(name: &str, value: &str) -> tiny_http::Header {
+    tiny_http::Header::from_bytes(name.as_bytes(), value.as_bytes())
+        .expect("static header names and values are valid")
+}
+
+// Serve the recording at a random, single-use URL until it is downloaded or expires
+#[tauri::command]
+pub fn share_recording(
+    app_handle: AppHandle,
+    registry: State<'_, ShareRegistry>,
+    app_lock: State<'_, AppLock>,
+    path: String,
+    ttl_secs: Option<u64>,
+    lan: Option<bool>,
+) -> Result<ShareLink, String> {
+    app_lock.ensure_unlocked()?;
+
+    if app_handle.state::<Library>().get(&path).is_none() {
+        return Err(format!("Recording not found in library: {}", path));
+    }
+    let file_path = PathBuf::from(&path);
+    if !file_path.is_file() {
+        return Err(format!("File not found: {}", path));
+    }
+    // Downloads get the name the recording had before it was encrypted
+    let name = if crypto::is_encrypted(&file_path) {
+        file_path.file_stem()
+    } else {
+        file_path.file_name()
+    };
+    let file_name = name
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "recording.wav".to_string());
+
+    let ttl = Duration::from_secs(ttl_secs.unwrap_or(DEFAULT_SHARE_TTL_SECS).clamp(1, MAX_SHARE_TTL_SECS));
+
+    let (bind_ip, public_ip) = if lan.unwrap_or(false) {
+        let ip = lan_address().ok_or_else(|| "Could not determine a LAN address".to_string())?;
+        (IpAddr::V4(Ipv4Addr::UNSPECIFIED), ip)
+    } else {
+        (IpAddr::V4(Ipv4Addr::LOCALHOST), IpAddr::V4(Ipv4Addr::LOCALHOST))
+    };
+
+    let server = tiny_http::Server::http((bind_ip, 0))
+        .map_err(|e| format!("Failed to start share server: {}", e))?;
+    let port = server
+        .server_addr()
+        .to_ip()
+        .map(|addr| addr.port())
+        .ok_or_else(|| "Share server has no TCP address".to_string())?;
+
+    let token = nanoid::nanoid!(32);
+    let link = ShareLink {
+        token: token.clone(),
+        url: format!("http://{}:{}{}", public_ip, port, url_path(&token, &file_name)),
+        expires_at: (chrono::Local::now() + chrono::Duration::from_std(ttl).unwrap_or_default()).to_rfc3339(),
+    };
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    registry
+        .active
+        .lock()
+        .unwrap()
+        .insert(token.clone(), Arc::clone(&cancelled));
+
+    info!("Sharing {} on port {} for {:?}", path, port, ttl);
+
+    thread::spawn(move || {
+        let deadline = Instant::now() + ttl;
+        let mut downloaded = false;
+
+        while !downloaded && !cancelled.load(Ordering::SeqCst) && Instant::now() < deadline {
+            let request = match server.recv_timeout(POLL_INTERVAL) {
+                Ok(Some(request)) => request,
+                Ok(None) => continue,
+                Err(e) => {
+                    warn!("Share server error: {}", e);
+                    break;
+                }
+            };
+
+            // Anything but a GET for the exact tokenized path is a 404 and doesn't burn the link
+            let wanted = is_share_request(request.url(), &token, &file_name);
+            if request.method() != &tiny_http::Method::Get || !wanted {
+                let _ = request.respond(tiny_http::Response::empty(404));
+                continue;
+            }
+
+            let response = match shared_file(&app_handle, &file_path) {
+                Ok(response) => response
+                    .with_header(header("Content-Type", content_type(&file_name)))
+                    .with_header(header("Content-Disposition", &content_disposition(&file_name))),
+                Err(e) => {
+                    warn!("Shared file is no longer readable: {}", e);
+                    let _ = request.respond(tiny_http::Response::empty(410));
+                    break;
+                }
+            };
+
+            match request.respond(response) {
+                Ok(()) => downloaded = true,
+                Err(e) => warn!("Share download was interrupted: {}", e),
+            }
+        }
+
+        app_handle.state::<ShareRegistry>().active.lock().unwrap().remove(&token);
+        info!("Share {} closed (downloaded: {})", token, downloaded);
+        let _ = app_handle.emit("share-finished", ShareFinishedEvent { token, downloaded });
+    });
+
+    Ok(link)
+}
+
+// Revoke a share link before it is used or expires
+#[tauri::command]
+pub fn stop_sharing(registry: State<'_, ShareRegistry>, token: String) -> Result<(), String> {
+    let active = registry.active.lock().unwrap();
+    let cancelled = active
+        .get(&token)
+        .ok_or_else(|| "No active share with that token".to_string())?;
+    cancelled.store(true, Ordering::SeqCst);
+    Ok(())
+}
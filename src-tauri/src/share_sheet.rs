@@ -0,0 +1,122 @@
+use std::path::Path;
+
+use tauri::{State, WebviewWindow};
+
+use crate::crypto;
+use crate::lock::AppLock;
+
+//
+// ====== Native OS share sheet ======
+//
+
+// Hand a recording to the platform share UI (Mail, Messages, AirDrop, ...).
+// Not async: the share UIs must be driven from the main thread, which is where
+// synchronous commands run.
+#[tauri::command]
+pub fn share_recording_native(window: WebviewWindow, app_lock: State<'_, AppLock>, path: String) -> Result<(), String> {
+    app_lock.ensure_unlocked()?;
+    let path = Path::new(&path);
+    if !path.exists() {
+        return Err(format!("File not found: {}", path.display()));
+    }
+    if crypto::is_encrypted(path) {
+        // The receiver couldn't open it; export a decrypted copy first
+        return Err("Encrypted recordings cannot be shared directly; export them first".to_string());
+    }
+
+    show_share_sheet(&window, path)
+}
+
+#[cfg(target_os = "macos")]
+fn show_share_sheet(window: &WebviewWindow, path: &Path) -> Result<(), String> {
+    use std::cell::RefCell;
+
+    use objc2::rc::Retained;
+    use objc2::runtime::AnyObject;
+    use objc2::{AnyThread, MainThreadMarker};
+    use objc2_app_kit::{NSSharingServicePicker, NSView};
+    use objc2_foundation::{NSArray, NSRectEdge, NSString, NSURL};
+
+    thread_local! {
+        // AppKit doesn't retain the picker while it is on screen
+        static PICKER: RefCell<Option<Retained<NSSharingServicePicker>>> = const { RefCell::new(None) };
+    }
+
+    MainThreadMarker::new().ok_or_else(|| "The share sheet must be opened from the main thread".to_string())?;
+    let ns_view = window
+        .ns_view()
+        .map_err(|e| format!("Failed to get window view: {}", e))?;
+    // SAFETY: Tauri hands out the content view of a live window, which outlives this call
+    let view = unsafe { &*(ns_view as *const NSView) };
+
+    let url = NSURL::fileURLWithPath(&NSString::from_str(&path.to_string_lossy()));
+    let item: &AnyObject = &url;
+    let items = NSArray::from_slice(&[item]);
+    // SAFETY: NSURL conforms to NSPasteboardWriting, as the picker requires
+    let picker = unsafe { NSSharingServicePicker::initWithItems(NSSharingServicePicker::alloc(), &items) };
+    picker.showRelativeToRect_ofView_preferredEdge(view.bounds(), view, NSRectEdge::MinY);
+
+    PICKER.with(|current| current.replace(Some(picker)));
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn show_share_sheet(window: &WebviewWindow, path: &Path) -> Result<(), String> {
+    use std::sync::Mutex;
+
+    use windows::core::{factory, Interface, HSTRING};
+    use windows::ApplicationModel::DataTransfer::{DataRequestedEventArgs, DataTransferManager};
+    use windows::Foundation::TypedEventHandler;
+    use windows::Storage::{IStorageItem, StorageFile};
+    use windows::Win32::UI::Shell::IDataTransferManagerInterop;
+    use windows_collections::IIterable;
+
+    // Registration for the last share; each call replaces it so files don't pile up
+    static DATA_REQUESTED: Mutex<Option<i64>> = Mutex::new(None);
+
+    let hwnd = window
+        .hwnd()
+        .map_err(|e| format!("Failed to get window handle: {}", e))?;
+    let interop = factory::<DataTransferManager, IDataTransferManagerInterop>()
+        .map_err(|e| format!("Share UI is not available: {}", e))?;
+    // SAFETY: `hwnd` is a live top-level window owned by this thread
+    let manager: DataTransferManager =
+        unsafe { interop.GetForWindow(hwnd) }.map_err(|e| format!("Share UI is not available: {}", e))?;
+
+    let mut registration = DATA_REQUESTED.lock().unwrap();
+    if let Some(token) = registration.take() {
+        let _ = manager.RemoveDataRequested(token);
+    }
+
+    let file_path = HSTRING::from(path);
+    let title = HSTRING::from(
+        path.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    );
+    let handler = TypedEventHandler::<DataTransferManager, DataRequestedEventArgs>::new(move |_, args| {
+        let data = args.ok()?.Request()?.Data()?;
+        data.Properties()?.SetTitle(&title)?;
+        let file: IStorageItem = StorageFile::GetFileFromPathAsync(&file_path)?.join()?.cast()?;
+        data.SetStorageItemsReadOnly(&IIterable::<IStorageItem>::from(vec![Some(file)]))
+    });
+    *registration = Some(
+        manager
+            .DataRequested(&handler)
+            .map_err(|e| format!("Failed to prepare share: {}", e))?,
+    );
+
+    // SAFETY: same window as above
+    unsafe { interop.ShowShareUIForWindow(hwnd) }.map_err(|e| format!("Failed to open share UI: {}", e))
+}
+
+// The desktop portals have no share UI, so compose an email with the file attached instead
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn show_share_sheet(_window: &WebviewWindow, path: &Path) -> Result<(), String> {
+    std::process::Command::new("xdg-email")
+        .arg("--attach")
+        .arg(path)
+        .spawn()
+        .map_err(|e| format!("Failed to launch xdg-email (is xdg-utils installed?): {}", e))?;
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use std::time::{Duration, Instant};
+
+pub use rekt_core::settings::SilenceWarningConfig;
+use serde::Serialize;
+use tauri::State;
+
+use crate::config::ConfigState;
+
+//
+// ====== Silence detection while recording ======
+//
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SilenceWarningEvent {
+    pub silent_secs: u64,
+    pub level_db: f32,
+}
+
+/// Tracks how long the input has been below the threshold; warns once per silent stretch
+pub struct SilenceDetector {
+    settings: SilenceWarningConfig,
+    silent_since: Option<Instant>,
+    warned: bool,
+}
+
+impl SilenceDetector {
+    pub fn new(settings: SilenceWarningConfig) -> Self {
+        Self {
+            settings,
+            silent_since: None,
+            warned: false,
+        }
+    }
+
+    /// Feed the latest input level; returns a warning the first time silence lasts long enough
+    pub fn update(&mut self, level_db: f32) -> Option<SilenceWarningEvent> {
+        if !self.settings.enabled {
+            return None;
+        }
+        if level_db >= self.settings.threshold_db {
+            self.silent_since = None;
+            self.warned = false;
+            return None;
+        }
+
+        let silent_for = self.silent_since.get_or_insert_with(Instant::now).elapsed();
+        if self.warned || silent_for < Duration::from_secs(self.settings.after_secs) {
+            return None;
+        }
+
+        self.warned = true;
+        Some(SilenceWarningEvent {
+            silent_secs: silent_for.as_secs(),
+            level_db,
+        })
+    }
+
+    pub fn notify(&self) -> bool {
+        self.settings.notify
+    }
+}
+
+//
+// ====== Silence warning commands ======
+//
+
+#[tauri::command]
+pub fn set_silence_warning(config: State<'_, ConfigState>, settings: SilenceWarningConfig) -> Result<(), String> {
+    settings.validate()?;
+
+    config.update(|c| {
+        c.silence_warning = settings.clone();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn get_silence_warning(config: State<'_, ConfigState>) -> SilenceWarningConfig {
+    config.get().silence_warning
+}
@@ -0,0 +1,214 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use rekt_core::processing::{self, AudioBuffer};
+use rodio::buffer::SamplesBuffer;
+use rodio::Sink;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+use crate::announce;
+use crate::config::ConfigState;
+use crate::hooks;
+use crate::{AudioPlaybackState, RecorderState, RecordingState};
+
+//
+// ====== Offline text-to-speech ======
+//
+
+const MAX_TEXT_LEN: usize = 10_000;
+const SYNTHESIS_TIMEOUT: Duration = Duration::from_secs(60);
+const CHECK_INTERVAL: Duration = Duration::from_millis(20);
+
+/// The speech playing now, if any
+#[derive(Default)]
+pub struct SpeechState {
+    stop_flag: Mutex<Option<Arc<AtomicBool>>>,
+}
+
+// The system's own offline voice, writing a WAV file and reading the text from stdin
+#[cfg(target_os = "macos")]
+fn synthesizer(output: &std::path::Path) -> Command {
+    let mut command = Command::new("say");
+    command.arg("-o").arg(output).arg("--data-format=LEI16@22050").arg("-f").arg("-");
+    command
+}
+
+#[cfg(target_os = "windows")]
+fn synthesizer(output: &std::path::Path) -> Command {
+    let mut command = Command::new("powershell");
+    command
+        .args(["-NoProfile", "-NonInteractive", "-Command"])
+        .arg(
+            "Add-Type -AssemblyName System.Speech; \
+             $voice = New-Object System.Speech.Synthesis.SpeechSynthesizer; \
+             $voice.SetOutputToWaveFile($env:REKT_TTS_OUTPUT); \
+             $voice.Speak([Console]::In.ReadToEnd()); \
+             $voice.Dispose()",
+        )
+        .env("REKT_TTS_OUTPUT", output);
+    command
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn synthesizer(output: &std::path::Path) -> Command {
+    let mut command = Command::new("espeak-ng");
+    command.arg("-w").arg(output).arg("--stdin");
+    command
+}
+
+/// Render `text` to audio with the system's offline voice
+pub fn synthesize(text: &str) -> Result<AudioBuffer, String> {
+    let file = tempfile::Builder::new()
+        .suffix(".wav")
+        .tempfile()
+        .map_err(|e| format!("Failed to create temp file: {}", e))?;
+    let mut child = synthesizer(file.path())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to start speech synthesizer: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .map_err(|e| format!("Failed to send text to speech synthesizer: {}", e))?;
+    }
+    match hooks::wait_with_timeout(&mut child, SYNTHESIS_TIMEOUT)? {
+        Some(Some(0)) => processing::read_wav(file.path()),
+        Some(code) => Err(format!("Speech synthesizer exited with {:?}", code)),
+        None => Err("Speech synthesizer timed out".to_string()),
+    }
+}
+
+// Start `buffer` on the app's output stream, where rodio converts it to the device's
+// rate and channels
+fn start_on_output(app_handle: &AppHandle, buffer: AudioBuffer) -> Result<Sink, String> {
+    let playback_state = app_handle.state::<AudioPlaybackState>();
+    let stream_handle = crate::ensure_output_stream(&playback_state)?;
+    let sink = Sink::try_new(&stream_handle).map_err(|e| format!("Failed to create sink: {}", e))?;
+    sink.append(SamplesBuffer::new(buffer.channels.max(1), buffer.sample_rate, buffer.samples));
+    Ok(sink)
+}
+
+// Block until `sink` has played out or `stop_flag` is set
+fn wait(sink: Sink, stop_flag: &AtomicBool) {
+    while !stop_flag.load(Ordering::SeqCst) && !sink.empty() {
+        thread::sleep(CHECK_INTERVAL);
+    }
+    sink.stop();
+}
+
+/// Play `buffer` on the output in the background until it ends or `stop_flag` is set
+pub fn play_on_output(
+    app_handle: &AppHandle,
+    buffer: AudioBuffer,
+    stop_flag: Arc<AtomicBool>,
+) -> Result<(), String> {
+    let sink = start_on_output(app_handle, buffer)?;
+    thread::spawn(move || wait(sink, &stop_flag));
+    Ok(())
+}
+
+// Make this the speech playing now, cutting off any other
+fn take_over(app_handle: &AppHandle) -> Arc<AtomicBool> {
+    stop(app_handle);
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    *app_handle.state::<SpeechState>().stop_flag.lock().unwrap() = Some(Arc::clone(&stop_flag));
+    stop_flag
+}
+
+/// Play speech in the background; a later call, or `stop`, cuts it off
+pub fn play(app_handle: &AppHandle, buffer: AudioBuffer) -> Result<(), String> {
+    play_on_output(app_handle, buffer, take_over(app_handle))
+}
+
+/// Stop speaking if anything is being spoken
+pub fn stop(app_handle: &AppHandle) {
+    if let Some(flag) = app_handle.state::<SpeechState>().stop_flag.lock().unwrap().take() {
+        flag.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Speak `text` in the background, logging rather than returning failures
+pub fn say(app_handle: &AppHandle, text: &'static str) {
+    let app_handle = app_handle.clone();
+    thread::spawn(move || {
+        if let Err(e) = synthesize(text).and_then(|buffer| play(&app_handle, buffer)) {
+            warn!("Failed to speak '{}': {}", text, e);
+        }
+    });
+}
+
+/// Say what the recorder just did, when voice confirmations are on. A microphone near
+/// the speaker would pick up "Recording started", so the input is held back until the
+/// words are over.
+pub fn confirm(app_handle: &AppHandle, previous: RecorderState, next: RecorderState) {
+    if !app_handle.state::<ConfigState>().get().voice_confirmations {
+        return;
+    }
+    let Some(text) = announce::describe(previous, next) else {
+        return;
+    };
+    if next != RecorderState::Recording {
+        say(app_handle, text);
+        return;
+    }
+
+    let recorder = Arc::clone(app_handle.state::<Arc<RecordingState>>().inner());
+    recorder.hold_input(true);
+    let app_handle = app_handle.clone();
+    thread::spawn(move || {
+        let spoken = synthesize(text).and_then(|buffer| {
+            let stop_flag = take_over(&app_handle);
+            wait(start_on_output(&app_handle, buffer)?, &stop_flag);
+            Ok(())
+        });
+        recorder.hold_input(false);
+        if let Err(e) = spoken {
+            warn!("Failed to speak '{}': {}", text, e);
+        }
+    });
+}
+
+//
+// ====== Text-to-speech commands ======
+//
+
+// Read text aloud on the default output, e.g. a recording's note, with the system's
+// offline voice
+#[tauri::command]
+pub async fn speak_text(app_handle: AppHandle, text: String) -> Result<(), String> {
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err("Nothing to speak".to_string());
+    }
+    if text.chars().count() > MAX_TEXT_LEN {
+        return Err(format!("Text is longer than {} characters", MAX_TEXT_LEN));
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let buffer = synthesize(&text)?;
+        info!("Speaking {} characters", text.chars().count());
+        play(&app_handle, buffer)
+    })
+    .await
+    .map_err(|e| format!("Speaking failed: {}", e))?
+}
+
+#[tauri::command]
+pub fn stop_speaking(app_handle: AppHandle) {
+    stop(&app_handle);
+}
+
+// Speak "recording started", "paused" and so on as the recorder changes state
+#[tauri::command]
+pub fn set_voice_confirmations(config: State<'_, ConfigState>, enabled: bool) -> Result<(), String> {
+    config.update(|c| {
+        c.voice_confirmations = enabled;
+        Ok(())
+    })
+}
@@ -0,0 +1,201 @@
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use rustfft::num_complex::Complex;
+use rustfft::FftPlanner;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager, State};
+use tracing::info;
+
+use crate::crypto;
+use crate::dsp;
+use crate::jobs::{self, JobContext, JobKind};
+use crate::library::Library;
+use crate::lock::AppLock;
+use crate::processing::WavSource;
+
+//
+// ====== Spectrogram images ======
+//
+
+const SPECTROGRAM_DIR: &str = "spectrograms";
+const FFT_SIZE: usize = 1024;
+const MIN_FREQUENCY: f32 = 20.0;
+const FLOOR_DB: f32 = -100.0;
+const MAX_DIMENSION: u32 = 4096;
+const PROGRESS_COLUMNS: usize = 64;
+
+// Dark purple through red to pale yellow, similar to the "inferno" palette
+const PALETTE: [[f32; 3]; 5] = [
+    [0.0, 0.0, 4.0],
+    [87.0, 16.0, 110.0],
+    [188.0, 55.0, 84.0],
+    [249.0, 142.0, 9.0],
+    [252.0, 255.0, 164.0],
+];
+
+fn color(level: f32) -> [u8; 3] {
+    let scaled = level.clamp(0.0, 1.0) * (PALETTE.len() - 1) as f32;
+    let index = (scaled as usize).min(PALETTE.len() - 2);
+    let t = scaled - index as f32;
+    let (a, b) = (PALETTE[index], PALETTE[index + 1]);
+    [0, 1, 2].map(|c| (a[c] + (b[c] - a[c]) * t) as u8)
+}
+
+/// Render a log-frequency spectrogram of a WAV file to `output` as PNG
+pub fn render(source: &Path, output: &Path, width: u32, height: u32, job: &JobContext) -> Result<(), String> {
+    // Only one window is read per column, so long recordings aren't loaded whole
+    let mut wav = WavSource::open(source)?;
+    let channels = wav.channels().max(1) as usize;
+    let sample_rate = wav.sample_rate();
+    let frames = wav.frames() as usize;
+
+    let fft = FftPlanner::new().plan_fft_forward(FFT_SIZE);
+    let window = dsp::hann_window(FFT_SIZE);
+    let window_gain: f32 = window.iter().sum();
+
+    // Row 0 is the top of the image, so map it to the highest frequency
+    let nyquist = sample_rate as f32 / 2.0;
+    let bin_width = sample_rate as f32 / FFT_SIZE as f32;
+    let ratio = nyquist / MIN_FREQUENCY;
+    let row_bins = (0..height)
+        .map(|row| {
+            let position = 1.0 - (row as f32 + 0.5) / height as f32;
+            let frequency = MIN_FREQUENCY * ratio.powf(position);
+            ((frequency / bin_width) as usize).min(FFT_SIZE / 2 - 1)
+        })
+        .collect::<Vec<_>>();
+
+    let mut pixels = vec![0u8; width as usize * height as usize * 3];
+    let mut spectrum = vec![Complex::default(); FFT_SIZE];
+    for column in 0..width as usize {
+        if column % PROGRESS_COLUMNS == 0 {
+            job.check_cancelled()?;
+            job.progress(column as f32 / width as f32);
+        }
+        let center = column * frames / width as usize;
+        let start = center.saturating_sub(FFT_SIZE / 2);
+        wav.seek(start as u32)?;
+        let samples = wav.read(FFT_SIZE)?;
+        let mut mono = samples
+            .chunks_exact(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32);
+        for (i, slot) in spectrum.iter_mut().enumerate() {
+            let sample = mono.next().unwrap_or(0.0);
+            *slot = Complex::new(sample * window[i], 0.0);
+        }
+        fft.process(&mut spectrum);
+
+        for (row, &bin) in row_bins.iter().enumerate() {
+            let magnitude = spectrum[bin].norm() * 2.0 / window_gain;
+            let db = (20.0 * magnitude.max(1e-9).log10()).max(FLOOR_DB);
+            let offset = (row * width as usize + column) * 3;
+            pixels[offset..offset + 3].copy_from_slice(&color(1.0 - db / FLOOR_DB));
+        }
+    }
+
+    let file = File::create(output).map_err(|e| format!("Failed to create image: {}", e))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&pixels))
+        .map_err(|e| format!("Failed to write PNG: {}", e))
+}
+
+// Cached images of a recording start with this: its stem, for people browsing the
+// folder, and a hash of its full path, since recordings in different folders can share
+// a stem
+fn cache_prefix(recording: &Path) -> String {
+    let stem = recording
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "recording".to_string());
+    let hash = Sha256::digest(recording.to_string_lossy().as_bytes());
+    let key: String = hash.iter().take(6).map(|b| format!("{:02x}", b)).collect();
+    format!("{}_{}_", stem, key)
+}
+
+// Prefix of the images of the recording as it is now; a rewritten recording gets a new
+// mtime and so new names
+fn current_prefix(recording: &Path) -> Result<String, String> {
+    let mtime_ms = fs::metadata(recording)
+        .and_then(|m| m.modified())
+        .map_err(|e| format!("Failed to read recording metadata: {}", e))?
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_millis())
+        .unwrap_or(0);
+    Ok(format!("{}{}_", cache_prefix(recording), mtime_ms))
+}
+
+// Delete cached images of `recording`, except those starting with `keep`
+fn prune(dir: &Path, recording: &Path, keep: Option<&str>) {
+    let prefix = cache_prefix(recording);
+    let Ok(images) = fs::read_dir(dir) else {
+        return;
+    };
+    for image in images.flatten() {
+        let name = image.file_name().to_string_lossy().to_string();
+        if name.starts_with(&prefix) && !keep.is_some_and(|keep| name.starts_with(keep)) {
+            let _ = fs::remove_file(image.path());
+        }
+    }
+}
+
+/// Delete every cached image of `recording`, at any size
+pub fn remove_cached(library: &Library, recording: &Path) {
+    prune(&library.dir().join(SPECTROGRAM_DIR), recording, None);
+}
+
+/// Render (or reuse) the spectrogram for a job and return the image path
+pub fn run_job(job: &JobContext, path: &str, width: u32, height: u32) -> Result<String, String> {
+    let library = job.app_handle().state::<Library>();
+    let source = PathBuf::from(path);
+    let dir = library.dir().join(SPECTROGRAM_DIR);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create spectrogram directory: {}", e))?;
+    let prefix = current_prefix(&source)?;
+    let image = dir.join(format!("{}{}x{}.png", prefix, width, height));
+
+    if !image.exists() {
+        // Images of an earlier state of the recording would never be used again
+        prune(&dir, &source, Some(&prefix));
+        if let Err(e) = render(&source, &image, width, height, job) {
+            let _ = fs::remove_file(&image);
+            return Err(e);
+        }
+        info!("Rendered spectrogram {}", image.display());
+    }
+
+    let image = image.to_string_lossy().to_string();
+    library.update(path, |entry| entry.spectrogram = Some(image.clone()))?;
+    Ok(image)
+}
+
+// Queue rendering a PNG spectrogram and return the job id; the image path arrives
+// as the job's output
+#[tauri::command]
+pub fn generate_spectrogram(
+    app_handle: AppHandle,
+    library: State<'_, Library>,
+    app_lock: State<'_, AppLock>,
+    path: String,
+    width: Option<u32>,
+    height: Option<u32>,
+) -> Result<u64, String> {
+    app_lock.ensure_unlocked()?;
+
+    let width = width.unwrap_or(1024).clamp(16, MAX_DIMENSION);
+    let height = height.unwrap_or(256).clamp(16, MAX_DIMENSION);
+    if library.get(&path).is_none() {
+        return Err(format!("Recording not found in library: {}", path));
+    }
+    if crypto::is_encrypted(Path::new(&path)) {
+        // The image would reveal the content that encryption is meant to protect
+        return Err("Spectrograms are not available for encrypted recordings".to_string());
+    }
+
+    jobs::enqueue(&app_handle, JobKind::Spectrogram { path, width, height })
+}
@@ -0,0 +1,222 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use rekt_core::recording;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tracing::{info, warn};
+
+use crate::config::ConfigState;
+use crate::jobs::{self, JobContext, JobKind};
+use crate::library::Library;
+use crate::RecordingState;
+
+//
+// ====== Save directory on network shares and removable drives ======
+//
+
+// Recordings are always written to the app data dir first and moved afterwards,
+// so a flaky target never costs a take. Retries run as a job, about two minutes in all.
+const MOVE_ATTEMPTS: u32 = 6;
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+const PARTIAL_EXTENSION: &str = "partial";
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SaveDirFallbackEvent {
+    save_dir: String,
+    /// Where the recording was kept instead; `None` while still recording
+    path: Option<String>,
+    error: String,
+}
+
+fn fallback(app_handle: &AppHandle, save_dir: &Path, path: Option<&Path>, error: String) {
+    warn!("Save directory {} unavailable: {}", save_dir.display(), error);
+    let _ = app_handle.emit(
+        "save-dir-fallback",
+        SaveDirFallbackEvent {
+            save_dir: save_dir.to_string_lossy().to_string(),
+            path: path.map(|p| p.to_string_lossy().to_string()),
+            error,
+        },
+    );
+}
+
+// Write-probe rather than trusting `exists`, which stays true for a read-only or stale mount
+pub fn check_writable(dir: &Path) -> Result<(), String> {
+    if !dir.is_dir() {
+        return Err(format!("{} is not available", dir.display()));
+    }
+    let probe = dir.join(".rekt_write_test");
+    fs::write(&probe, b"").map_err(|e| format!("{} is not writable: {}", dir.display(), e))?;
+    let _ = fs::remove_file(&probe);
+    Ok(())
+}
+
+// `file_name` in `dir`, or with a counter added before its extensions if that's taken,
+// e.g. `recording_20250101_120000_1.wav.enc`
+pub fn unique_destination(dir: &Path, file_name: &str) -> PathBuf {
+    let (stem, extensions) = file_name.split_once('.').map_or((file_name, String::new()), |(stem, rest)| {
+        (stem, format!(".{}", rest))
+    });
+    let mut candidate = dir.join(file_name);
+    let mut counter = 1;
+    while candidate.exists() {
+        candidate = dir.join(format!("{}_{}{}", stem, counter, extensions));
+        counter += 1;
+    }
+    candidate
+}
+
+// Copy under a temporary name and rename, so the target never holds a half-written file
+// and a recording of the same name already there is never replaced
+fn copy_into(source: &Path, dir: &Path) -> Result<PathBuf, String> {
+    check_writable(dir)?;
+    let file_name = source
+        .file_name()
+        .ok_or_else(|| "Recording has no file name".to_string())?
+        .to_string_lossy();
+    let dest = unique_destination(dir, &file_name);
+    let partial = dir.join(format!("{}.{}", file_name, PARTIAL_EXTENSION));
+
+    let copied = fs::copy(source, &partial).map_err(|e| format!("Failed to copy recording: {}", e))?;
+    let expected = fs::metadata(source)
+        .map_err(|e| format!("Failed to read recording: {}", e))?
+        .len();
+    if copied != expected {
+        let _ = fs::remove_file(&partial);
+        return Err(format!("Copied {} of {} bytes", copied, expected));
+    }
+    fs::rename(&partial, &dest).map_err(|e| {
+        let _ = fs::remove_file(&partial);
+        format!("Failed to finish copy: {}", e)
+    })?;
+    Ok(dest)
+}
+
+// Move `local` into `save_dir`, its timestamp sidecar along with it
+fn move_into(local: &Path, save_dir: &Path) -> Result<PathBuf, String> {
+    let dest = copy_into(local, save_dir)?;
+    if let Err(e) = fs::remove_file(local) {
+        warn!("Failed to remove local copy {}: {}", local.display(), e);
+    }
+    let timestamps = recording::timestamps_path(local);
+    if timestamps.exists() {
+        match fs::copy(&timestamps, recording::timestamps_path(&dest)) {
+            Ok(_) => {
+                let _ = fs::remove_file(&timestamps);
+            }
+            Err(e) => warn!("Failed to move {}: {}", timestamps.display(), e),
+        }
+    }
+    info!("Moved recording to {}", dest.display());
+    Ok(dest)
+}
+
+/// Move a saved recording into the configured save directory. Returns the final
+/// location, which is still `local` if there is no save directory or it couldn't be
+/// reached right away; then a background job keeps retrying, so stopping never waits
+/// on a flaky target.
+pub fn relocate(app_handle: &AppHandle, local: &Path) -> PathBuf {
+    let Some(save_dir) = app_handle.state::<ConfigState>().get().save_dir.map(PathBuf::from) else {
+        return local.to_path_buf();
+    };
+
+    match move_into(local, &save_dir) {
+        Ok(dest) => dest,
+        Err(e) => {
+            warn!("Moving recording to {} failed, retrying in the background: {}", save_dir.display(), e);
+            let path = local.to_string_lossy().to_string();
+            if let Err(e) = jobs::enqueue(app_handle, JobKind::Relocate { path }) {
+                fallback(app_handle, &save_dir, Some(local), e);
+            }
+            local.to_path_buf()
+        }
+    }
+}
+
+/// Retry moving a library recording into the save directory with backoff, updating its
+/// library entry once it's there
+pub fn run_job(job: &JobContext, path: &str) -> Result<Option<String>, String> {
+    let app_handle = job.app_handle();
+    let Some(save_dir) = app_handle.state::<ConfigState>().get().save_dir.map(PathBuf::from) else {
+        return Ok(None);
+    };
+    let library = app_handle.state::<Library>();
+    let local = Path::new(path);
+
+    let mut backoff = INITIAL_BACKOFF;
+    let mut last_error = String::new();
+    for attempt in 1..=MOVE_ATTEMPTS {
+        job.sleep(backoff)?;
+        backoff *= 2;
+        match move_into(local, &save_dir) {
+            Ok(dest) => {
+                let dest_path = dest.to_string_lossy().to_string();
+                if let Some(mut entry) = library.remove(path)? {
+                    entry.path = dest_path.clone();
+                    entry.file_name = dest
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    library.add(entry)?;
+                }
+                return Ok(Some(dest_path));
+            }
+            Err(e) => {
+                warn!("Moving recording to {} failed (attempt {}): {}", save_dir.display(), attempt, e);
+                last_error = e;
+            }
+        }
+    }
+
+    fallback(app_handle, &save_dir, Some(local), last_error.clone());
+    Err(last_error)
+}
+
+/// Warn as soon as the save directory disappears during a recording, rather than at the end
+pub fn monitor(app_handle: AppHandle, state: Arc<RecordingState>) {
+    let Some(save_dir) = app_handle.state::<ConfigState>().get().save_dir.map(PathBuf::from) else {
+        return;
+    };
+
+    thread::spawn(move || {
+        let mut reported = false;
+        while state.is_recording() {
+            thread::sleep(CHECK_INTERVAL);
+            match check_writable(&save_dir) {
+                Ok(()) => reported = false,
+                Err(e) if !reported => {
+                    fallback(&app_handle, &save_dir, None, e);
+                    reported = true;
+                }
+                Err(_) => {}
+            }
+        }
+    });
+}
+
+//
+// ====== Save directory commands ======
+//
+
+// Save new recordings to `path` (e.g. a network share or USB drive), or back to the
+// app data dir with `None`
+#[tauri::command]
+pub fn set_save_dir(config: State<'_, ConfigState>, path: Option<String>) -> Result<(), String> {
+    if let Some(ref dir) = path {
+        check_writable(Path::new(dir))?;
+    }
+    config.update(|c| {
+        c.save_dir = path.clone();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn get_save_dir(config: State<'_, ConfigState>) -> Option<String> {
+    config.get().save_dir
+}
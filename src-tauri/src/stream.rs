@@ -0,0 +1,422 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use base64::prelude::*;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+use tracing::{info, warn};
+
+use crate::config::ConfigState;
+use crate::secrets;
+use crate::RecordingState;
+
+//
+// ====== Live streaming while recording ======
+//
+
+const MAX_RECONNECT_ATTEMPTS: u32 = 10;
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+// A connection that stayed up this long counts as healthy and resets the backoff
+const STABLE_CONNECTION: Duration = Duration::from_secs(30);
+// A server that takes longer than this to answer or accept data counts as gone
+const ICECAST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamCodec {
+    Opus,
+    Mp3,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "protocol", rename_all = "snake_case")]
+pub enum StreamEndpoint {
+    Icecast {
+        host: String,
+        port: u16,
+        /// Mount point, e.g. `/live`
+        mount: String,
+        #[serde(default = "default_icecast_user")]
+        username: String,
+        /// Only accepted as input; it is moved to the OS keychain and never written to config
+        #[serde(default, skip_serializing)]
+        password: String,
+    },
+    Rtp {
+        host: String,
+        port: u16,
+    },
+}
+
+fn default_icecast_user() -> String {
+    "source".to_string()
+}
+
+fn default_bitrate() -> u32 {
+    128
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamTarget {
+    pub endpoint: StreamEndpoint,
+    pub codec: StreamCodec,
+    #[serde(default = "default_bitrate")]
+    pub bitrate_kbps: u32,
+    /// Stream every recording automatically
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+impl StreamTarget {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.bitrate_kbps < 8 || self.bitrate_kbps > 320 {
+            return Err("Bitrate must be between 8 and 320 kbps".to_string());
+        }
+        let host = match &self.endpoint {
+            StreamEndpoint::Icecast { host, .. } | StreamEndpoint::Rtp { host, .. } => host,
+        };
+        if host.trim().is_empty() {
+            return Err("Stream host cannot be empty".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamState {
+    Connecting,
+    Live,
+    Reconnecting,
+    Stopped,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct StreamStatusEvent {
+    state: StreamState,
+    attempt: u32,
+    error: Option<String>,
+}
+
+enum PumpOutcome {
+    RecordingEnded,
+    Disconnected(String),
+}
+
+/// Start streaming the current recording to `target`; ends when the recording stops
+pub fn spawn(app_handle: AppHandle, target: StreamTarget, state: Arc<RecordingState>) {
+    let samples = state.add_tap();
+
+    thread::spawn(move || {
+        let report = |stream_state: StreamState, attempt: u32, error: Option<String>| {
+            let _ = app_handle.emit(
+                "stream-status",
+                StreamStatusEvent {
+                    state: stream_state,
+                    attempt,
+                    error,
+                },
+            );
+        };
+
+        // The device format is only known once the first samples arrive
+        let first = match samples.recv() {
+            Ok(chunk) => chunk,
+            Err(_) => return,
+        };
+        let Some(format) = state.capture_format() else {
+            report(StreamState::Failed, 0, Some("Capture format not known".to_string()));
+            return;
+        };
+
+        let args = ffmpeg_args(&target, format.channels, format.sample_rate);
+        let password = match icecast_password(&target.endpoint) {
+            Ok(password) => password,
+            Err(e) => {
+                warn!("Cannot start stream: {}", e);
+                report(StreamState::Failed, 0, Some(e));
+                return;
+            }
+        };
+
+        let mut pending = Some(first);
+        let mut attempt = 0;
+        let mut backoff = INITIAL_BACKOFF;
+
+        report(StreamState::Connecting, attempt, None);
+        loop {
+            let connected_at = Instant::now();
+            let outcome = match connect_icecast(&target.endpoint, target.codec, &password) {
+                Err(e) => PumpOutcome::Disconnected(e),
+                Ok(server) => {
+                    let mut child = match start_ffmpeg(&args, server.is_some()) {
+                        Ok(child) => child,
+                        Err(e) => {
+                            warn!("{}", e);
+                            report(StreamState::Failed, attempt, Some(e));
+                            return;
+                        }
+                    };
+                    let stdin = child.stdin.take().expect("stdin is piped");
+                    let sender = server.map(|server| forward(child.stdout.take().expect("stdout is piped"), server));
+
+                    info!("Streaming to {}", describe(&target.endpoint));
+                    report(StreamState::Live, attempt, None);
+                    match pump(stdin, &samples, pending.take()) {
+                        PumpOutcome::RecordingEnded => {
+                            // Closing stdin lets ffmpeg flush and disconnect cleanly
+                            let _ = child.wait();
+                            if let Some(sender) = sender {
+                                let _ = sender.join();
+                            }
+                            PumpOutcome::RecordingEnded
+                        }
+                        PumpOutcome::Disconnected(e) => {
+                            let encoder_error = finish(child);
+                            let send_error = sender.and_then(|sender| sender.join().ok().flatten());
+                            PumpOutcome::Disconnected(send_error.or(encoder_error).unwrap_or(e))
+                        }
+                    }
+                }
+            };
+
+            match outcome {
+                PumpOutcome::RecordingEnded => {
+                    info!("Stream ended with the recording");
+                    report(StreamState::Stopped, attempt, None);
+                    return;
+                }
+                PumpOutcome::Disconnected(reason) => {
+                    warn!("Stream disconnected: {}", reason);
+
+                    if connected_at.elapsed() >= STABLE_CONNECTION {
+                        attempt = 0;
+                        backoff = INITIAL_BACKOFF;
+                    }
+                    attempt += 1;
+                    if attempt > MAX_RECONNECT_ATTEMPTS {
+                        report(StreamState::Failed, attempt, Some(reason));
+                        return;
+                    }
+                    report(StreamState::Reconnecting, attempt, Some(reason));
+
+                    // Audio captured while offline is dropped; the recording itself is unaffected
+                    if !wait_while_recording(&samples, backoff) {
+                        report(StreamState::Stopped, attempt, None);
+                        return;
+                    }
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+            }
+        }
+    });
+}
+
+fn pump(mut stdin: ChildStdin, samples: &Receiver<Vec<i16>>, first: Option<Vec<i16>>) -> PumpOutcome {
+    let mut bytes = Vec::new();
+    let mut next = first;
+
+    loop {
+        let chunk = match next.take() {
+            Some(chunk) => chunk,
+            None => match samples.recv() {
+                Ok(chunk) => chunk,
+                Err(_) => return PumpOutcome::RecordingEnded,
+            },
+        };
+
+        bytes.clear();
+        for sample in chunk {
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+        if let Err(e) = stdin.write_all(&bytes) {
+            return PumpOutcome::Disconnected(format!("Failed to write to encoder: {}", e));
+        }
+    }
+}
+
+// Sleep for `duration`, discarding audio; returns false if the recording ended meanwhile
+fn wait_while_recording(samples: &Receiver<Vec<i16>>, duration: Duration) -> bool {
+    let deadline = Instant::now() + duration;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return true;
+        }
+        match samples.recv_timeout(remaining) {
+            Ok(_) => continue,
+            Err(RecvTimeoutError::Timeout) => return true,
+            Err(RecvTimeoutError::Disconnected) => return false,
+        }
+    }
+}
+
+// Reap ffmpeg and return its error output, if it printed any
+fn finish(mut child: Child) -> Option<String> {
+    let _ = child.kill();
+    let _ = child.wait();
+    let mut stderr = String::new();
+    child.stderr.take()?.read_to_string(&mut stderr).ok()?;
+    let stderr = stderr.trim();
+    (!stderr.is_empty()).then(|| format!("ffmpeg: {}", stderr))
+}
+
+fn start_ffmpeg(args: &[String], piped_output: bool) -> Result<Child, String> {
+    Command::new("ffmpeg")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(if piped_output { Stdio::piped() } else { Stdio::null() })
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run ffmpeg (is it installed and on PATH?): {}", e))
+}
+
+fn ffmpeg_args(target: &StreamTarget, channels: u16, sample_rate: u32) -> Vec<String> {
+    let mut args: Vec<String> = [
+        "-hide_banner", "-loglevel", "error", "-f", "s16le", "-ar", &sample_rate.to_string(), "-ac",
+        &channels.to_string(), "-i", "pipe:0",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+
+    match target.codec {
+        // Opus only accepts a handful of rates; 48 kHz always works
+        StreamCodec::Opus => args.extend(["-c:a", "libopus", "-ar", "48000"].map(String::from)),
+        StreamCodec::Mp3 => args.extend(["-c:a", "libmp3lame"].map(String::from)),
+    }
+    args.extend(["-b:a".to_string(), format!("{}k", target.bitrate_kbps)]);
+
+    match &target.endpoint {
+        // The server connection is made here rather than by ffmpeg; see `connect_icecast`
+        StreamEndpoint::Icecast { .. } => {
+            let format = match target.codec {
+                StreamCodec::Opus => "ogg",
+                StreamCodec::Mp3 => "mp3",
+            };
+            args.extend(["-f", format, "pipe:1"].map(String::from));
+        }
+        StreamEndpoint::Rtp { host, port } => {
+            args.extend(["-f".to_string(), "rtp".to_string(), format!("rtp://{}:{}", host, port)]);
+        }
+    }
+
+    args
+}
+
+// The stored source password, for Icecast targets
+fn icecast_password(endpoint: &StreamEndpoint) -> Result<String, String> {
+    match endpoint {
+        StreamEndpoint::Icecast { .. } => {
+            secrets::get_secret(secrets::ICECAST_PASSWORD)?.ok_or_else(|| "No Icecast password is stored".to_string())
+        }
+        StreamEndpoint::Rtp { .. } => Ok(String::new()),
+    }
+}
+
+// Characters left as they are in a mount point, besides letters and digits
+const MOUNT_SAFE: &AsciiSet = &NON_ALPHANUMERIC.remove(b'/').remove(b'-').remove(b'_').remove(b'.').remove(b'~');
+
+/// Connect to an Icecast server as a source the way ffmpeg's icecast protocol would: an
+/// HTTP PUT whose body is the encoded stream. Done here rather than by ffmpeg so the
+/// password never appears on its command line, where any local user could list it.
+fn connect_icecast(endpoint: &StreamEndpoint, codec: StreamCodec, password: &str) -> Result<Option<TcpStream>, String> {
+    let StreamEndpoint::Icecast {
+        host,
+        port,
+        mount,
+        username,
+        ..
+    } = endpoint
+    else {
+        return Ok(None);
+    };
+    let content_type = match codec {
+        StreamCodec::Opus => "audio/ogg",
+        StreamCodec::Mp3 => "audio/mpeg",
+    };
+
+    let mut server = TcpStream::connect((host.as_str(), *port))
+        .map_err(|e| format!("Failed to connect to {}:{}: {}", host, port, e))?;
+    server
+        .set_read_timeout(Some(ICECAST_TIMEOUT))
+        .and_then(|_| server.set_write_timeout(Some(ICECAST_TIMEOUT)))
+        .map_err(|e| format!("Failed to configure connection: {}", e))?;
+    let request = format!(
+        "PUT /{} HTTP/1.1\r\nHost: {}:{}\r\nAuthorization: Basic {}\r\nContent-Type: {}\r\n\
+         Ice-Public: 0\r\nUser-Agent: rekt\r\nExpect: 100-continue\r\n\r\n",
+        utf8_percent_encode(mount.trim_start_matches('/'), MOUNT_SAFE),
+        host,
+        port,
+        BASE64_STANDARD.encode(format!("{}:{}", username, password)),
+        content_type
+    );
+    server
+        .write_all(request.as_bytes())
+        .map_err(|e| format!("Failed to send to Icecast server: {}", e))?;
+
+    let mut status = String::new();
+    BufReader::new(&server)
+        .read_line(&mut status)
+        .map_err(|e| format!("No answer from Icecast server: {}", e))?;
+    match status.split_whitespace().nth(1) {
+        Some("100" | "200") => Ok(Some(server)),
+        Some("401") => Err("Icecast server rejected the username or password".to_string()),
+        _ => Err(format!("Icecast server refused the stream: {}", status.trim())),
+    }
+}
+
+// Copy ffmpeg's encoded output to the server; returns why sending stopped, if it failed
+fn forward(mut encoded: ChildStdout, mut server: TcpStream) -> thread::JoinHandle<Option<String>> {
+    thread::spawn(move || {
+        io::copy(&mut encoded, &mut server)
+            .err()
+            .map(|e| format!("Failed to send to Icecast server: {}", e))
+    })
+}
+
+// Endpoint for log lines, without credentials
+fn describe(endpoint: &StreamEndpoint) -> String {
+    match endpoint {
+        StreamEndpoint::Icecast { host, port, mount, .. } => format!("icecast://{}:{}{}", host, port, mount),
+        StreamEndpoint::Rtp { host, port } => format!("rtp://{}:{}", host, port),
+    }
+}
+
+//
+// ====== Streaming commands ======
+//
+
+// Configure where recordings are streamed, or remove the target with `None`
+#[tauri::command]
+pub fn set_stream_target(config: State<'_, ConfigState>, target: Option<StreamTarget>) -> Result<(), String> {
+    let mut target = target;
+
+    if let Some(ref mut target) = target {
+        target.validate()?;
+        if let StreamEndpoint::Icecast { ref mut password, .. } = target.endpoint {
+            if !password.is_empty() {
+                secrets::set_secret(secrets::ICECAST_PASSWORD, password)?;
+                password.clear();
+            }
+        }
+    }
+
+    config.update(|c| {
+        c.stream_target = target.clone();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn get_stream_target(config: State<'_, ConfigState>) -> Option<StreamTarget> {
+    config.get().stream_target
+}
@@ -0,0 +1,303 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use base64::prelude::*;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tracing::{info, warn};
+
+use crate::config::ConfigState;
+use crate::jobs::{self, JobContext, JobKind};
+use crate::library::{Library, SyncState, SyncStatus};
+use crate::lock::AppLock;
+use crate::secrets;
+
+//
+// ====== WebDAV (Nextcloud / ownCloud) sync ======
+//
+
+const MAX_ATTEMPTS: u32 = 5;
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebDavConfig {
+    /// Base DAV URL, e.g. `https://cloud.example.com/remote.php/dav/files/alice`
+    pub url: String,
+    pub username: String,
+    /// Only accepted as input; it is moved to the OS keychain and never written to config
+    #[serde(default, skip_serializing)]
+    pub password: String,
+    /// Folder under `url` that recordings are uploaded into
+    #[serde(default)]
+    pub remote_dir: String,
+    /// Upload each recording as soon as it is saved
+    #[serde(default)]
+    pub auto_upload: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct SyncStatusEvent {
+    path: String,
+    status: SyncStatus,
+}
+
+struct UploadError {
+    retryable: bool,
+    message: String,
+}
+
+impl UploadError {
+    fn fatal(message: String) -> Self {
+        Self {
+            retryable: false,
+            message,
+        }
+    }
+
+    fn from_ureq(context: &str, error: ureq::Error) -> Self {
+        match error {
+            // Server errors, timeouts and rate limiting are worth another try
+            ureq::Error::Status(code, _) => Self {
+                retryable: code >= 500 || code == 408 || code == 429,
+                message: format!("{}: server returned HTTP {}", context, code),
+            },
+            ureq::Error::Transport(e) => Self {
+                retryable: true,
+                message: format!("{}: {}", context, e),
+            },
+        }
+    }
+}
+
+/// Queue a background upload; progress is tracked in the library entry and the job list
+pub fn spawn_upload(app_handle: AppHandle, path: PathBuf) {
+    if app_handle.state::<ConfigState>().get().webdav.is_none() {
+        return;
+    }
+    let path = path.to_string_lossy().to_string();
+    if let Err(e) = jobs::enqueue(&app_handle, JobKind::Upload { path }) {
+        warn!("Failed to queue upload: {}", e);
+    }
+}
+
+/// Upload with retries on behalf of the job queue, returning the remote URL
+pub fn run_upload(job: &JobContext, path: &Path) -> Result<String, String> {
+    let app_handle = job.app_handle();
+    let webdav = app_handle
+        .state::<ConfigState>()
+        .get()
+        .webdav
+        .ok_or_else(|| "WebDAV sync is not configured".to_string())?;
+
+    let path_str = path.to_string_lossy().to_string();
+    let report = |state: SyncState, attempts: u32, remote_url: Option<String>, error: Option<String>| {
+        let status = SyncStatus {
+            state,
+            remote_url,
+            error,
+            attempts,
+            updated_at: chrono::Local::now().to_rfc3339(),
+        };
+        let library = app_handle.state::<Library>();
+        let _ = library.update(&path_str, |entry| entry.sync_status = Some(status.clone()));
+        let _ = app_handle.emit(
+            "sync-status",
+            SyncStatusEvent {
+                path: path_str.clone(),
+                status,
+            },
+        );
+    };
+
+    report(SyncState::Pending, 0, None, None);
+
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(15))
+        .build();
+
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        report(SyncState::Uploading, attempt, None, None);
+        job.progress((attempt - 1) as f32 / MAX_ATTEMPTS as f32);
+
+        let e = match upload(&agent, &webdav, path) {
+            Ok(remote_url) => {
+                info!("Uploaded {} to {}", path.display(), remote_url);
+                report(SyncState::Synced, attempt, Some(remote_url.clone()), None);
+                return Ok(remote_url);
+            }
+            Err(e) => e,
+        };
+        if !e.retryable || attempt >= MAX_ATTEMPTS {
+            warn!("Upload of {} failed: {}", path.display(), e.message);
+            report(SyncState::Failed, attempt, None, Some(e.message.clone()));
+            return Err(e.message);
+        }
+        warn!(
+            "Upload attempt {} of {} failed, retrying in {:?}: {}",
+            attempt, MAX_ATTEMPTS, backoff, e.message
+        );
+        if let Err(cancelled) = job.sleep(backoff) {
+            report(SyncState::Failed, attempt, None, Some(cancelled.clone()));
+            return Err(cancelled);
+        }
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+fn upload(agent: &ureq::Agent, webdav: &WebDavConfig, path: &Path) -> Result<String, UploadError> {
+    let password = secrets::get_secret(secrets::WEBDAV_PASSWORD)
+        .map_err(UploadError::fatal)?
+        .unwrap_or_default();
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| UploadError::fatal("Recording has no file name".to_string()))?;
+    let auth = format!(
+        "Basic {}",
+        BASE64_STANDARD.encode(format!("{}:{}", webdav.username, password))
+    );
+
+    let collection = ensure_collection(agent, webdav, &auth)?;
+    let remote_url = free_remote_url(agent, &collection, &file_name, &auth)?;
+
+    let file = File::open(path).map_err(|e| UploadError::fatal(format!("Failed to open file: {}", e)))?;
+    let length = file
+        .metadata()
+        .map_err(|e| UploadError::fatal(format!("Failed to read file metadata: {}", e)))?
+        .len();
+
+    agent
+        .put(&remote_url)
+        .set("Authorization", &auth)
+        .set("Content-Type", "audio/wav")
+        .set("Content-Length", &length.to_string())
+        // Refuse to overwrite if something appeared at this name since we checked
+        .set("If-None-Match", "*")
+        .send(file)
+        .map_err(|e| UploadError::from_ureq("Upload failed", e))?;
+
+    Ok(remote_url)
+}
+
+// Create each level of the remote folder, returning the collection URL with a trailing slash
+fn ensure_collection(agent: &ureq::Agent, webdav: &WebDavConfig, auth: &str) -> Result<String, UploadError> {
+    let mut url = format!("{}/", webdav.url.trim_end_matches('/'));
+
+    for segment in webdav.remote_dir.split('/').filter(|s| !s.is_empty()) {
+        url.push_str(&encode_segment(segment));
+        url.push('/');
+
+        match agent.request("MKCOL", &url).set("Authorization", auth).call() {
+            Ok(_) => {}
+            // 405 means the collection already exists
+            Err(ureq::Error::Status(405, _)) => {}
+            Err(e) => return Err(UploadError::from_ureq("Failed to create remote folder", e)),
+        }
+    }
+
+    Ok(url)
+}
+
+// Never clobber a remote file: probe `name.wav`, `name (1).wav`, ... until one is free
+fn free_remote_url(
+    agent: &ureq::Agent,
+    collection: &str,
+    file_name: &str,
+    auth: &str,
+) -> Result<String, UploadError> {
+    let (stem, extension) = match file_name.rsplit_once('.') {
+        Some((stem, ext)) => (stem.to_string(), format!(".{}", ext)),
+        None => (file_name.to_string(), String::new()),
+    };
+
+    for counter in 0..100 {
+        let candidate = if counter == 0 {
+            file_name.to_string()
+        } else {
+            format!("{} ({}){}", stem, counter, extension)
+        };
+        let url = format!("{}{}", collection, encode_segment(&candidate));
+
+        match agent.head(&url).set("Authorization", auth).call() {
+            Ok(_) => continue,
+            Err(ureq::Error::Status(404, _)) => return Ok(url),
+            Err(e) => return Err(UploadError::from_ureq("Failed to check remote file", e)),
+        }
+    }
+
+    Err(UploadError::fatal("Could not find a free remote file name".to_string()))
+}
+
+fn encode_segment(segment: &str) -> String {
+    let mut encoded = String::new();
+    for byte in segment.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+//
+// ====== Sync commands ======
+//
+
+// Store WebDAV settings, or remove them with `None`
+#[tauri::command]
+pub fn set_webdav_config(config: State<'_, ConfigState>, webdav: Option<WebDavConfig>) -> Result<(), String> {
+    if let Some(ref webdav) = webdav {
+        if !webdav.url.starts_with("http://") && !webdav.url.starts_with("https://") {
+            return Err("WebDAV URL must start with http:// or https://".to_string());
+        }
+    }
+
+    match webdav {
+        Some(ref webdav) if !webdav.password.is_empty() => {
+            secrets::set_secret(secrets::WEBDAV_PASSWORD, &webdav.password)?
+        }
+        Some(_) => {}
+        None => secrets::delete_secret(secrets::WEBDAV_PASSWORD)?,
+    }
+
+    config.update(|c| {
+        c.webdav = webdav.clone().map(|webdav| WebDavConfig {
+            password: String::new(),
+            ..webdav
+        });
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn get_webdav_config(config: State<'_, ConfigState>) -> Option<WebDavConfig> {
+    config.get().webdav
+}
+
+// Queue a manual upload; progress arrives as `sync-status` events
+#[tauri::command]
+pub fn sync_recording(
+    app_handle: AppHandle,
+    app_lock: State<'_, AppLock>,
+    config: State<'_, ConfigState>,
+    library: State<'_, Library>,
+    path: String,
+) -> Result<(), String> {
+    app_lock.ensure_unlocked()?;
+    if config.get().webdav.is_none() {
+        return Err("WebDAV sync is not configured".to_string());
+    }
+    if library.get(&path).is_none() {
+        return Err(format!("Recording not found in library: {}", path));
+    }
+
+    spawn_upload(app_handle, PathBuf::from(path));
+    Ok(())
+}
@@ -0,0 +1,246 @@
+use std::process::Command;
+use std::sync::Mutex;
+
+pub use rekt_core::settings::DuckConfig;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+use crate::config::ConfigState;
+
+//
+// ====== System output volume ======
+//
+
+/// Master output level of the default playback device
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutputVolume {
+    /// 0.0..=1.0
+    pub level: f32,
+    pub muted: bool,
+}
+
+/// Remembers the user's output volume while recording overrides it
+#[derive(Default)]
+pub struct SystemAudioState {
+    saved: Mutex<Option<OutputVolume>>,
+}
+
+impl SystemAudioState {
+    /// Change the output for the duration of a recording; `adjust` gets the
+    /// level the user had before any override
+    pub fn override_output<F>(&self, adjust: F)
+    where
+        F: FnOnce(OutputVolume) -> OutputVolume,
+    {
+        let mut saved = self.saved.lock().unwrap();
+        let original = match *saved {
+            Some(original) => original,
+            None => match output_volume() {
+                Ok(current) => *saved.insert(current),
+                Err(e) => {
+                    // Without the original level we couldn't put it back, so leave it alone
+                    warn!("Not changing system volume: {}", e);
+                    return;
+                }
+            },
+        };
+
+        let target = adjust(original);
+        match set_output_volume(target) {
+            Ok(()) => info!("System output set to {:?} while recording", target),
+            Err(e) => warn!("Failed to change system volume: {}", e),
+        }
+    }
+
+    /// Put back whatever `override_output` replaced
+    pub fn restore_output(&self) {
+        if let Some(previous) = self.saved.lock().unwrap().take() {
+            match set_output_volume(previous) {
+                Ok(()) => info!("System output restored to {:?}", previous),
+                Err(e) => warn!("Failed to restore system volume: {}", e),
+            }
+        }
+    }
+}
+
+/// Duck or mute system output for a recording that just started, per config
+pub fn recording_started(app_handle: &AppHandle) {
+    let config = app_handle.state::<ConfigState>().get();
+    let mute = config.focus_mode.mute_output;
+    let duck = config.duck_system_audio;
+    if !mute && !duck.enabled {
+        return;
+    }
+
+    app_handle.state::<SystemAudioState>().override_output(|current| OutputVolume {
+        level: if duck.enabled { current.level.min(duck.level) } else { current.level },
+        muted: current.muted || mute,
+    });
+}
+
+/// Undo whatever `recording_started` changed
+pub fn recording_stopped(app_handle: &AppHandle) {
+    app_handle.state::<SystemAudioState>().restore_output();
+}
+
+fn run(command: &mut Command) -> Result<String, String> {
+    let output = command
+        .output()
+        .map_err(|e| format!("Failed to launch volume control: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Volume control failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(target_os = "windows")]
+const WINDOWS_AUDIO_API: &str = r#"
+Add-Type -TypeDefinition @'
+using System;
+using System.Runtime.InteropServices;
+[Guid("5CDF2C82-841E-4546-9722-0CF74078229A"), InterfaceType(ComInterfaceType.InterfaceIsIUnknown), ComImport]
+interface IAudioEndpointVolume {
+  int _0(); int _1(); int _2(); int _3();
+  int SetMasterVolumeLevelScalar(float level, Guid context);
+  int _4();
+  int GetMasterVolumeLevelScalar(out float level);
+  int _5(); int _6(); int _7(); int _8();
+  int SetMute([MarshalAs(UnmanagedType.Bool)] bool mute, Guid context);
+  int GetMute(out bool mute);
+}
+[Guid("D666063F-1587-4E43-81F1-B948E807363F"), InterfaceType(ComInterfaceType.InterfaceIsIUnknown), ComImport]
+interface IMMDevice { int Activate(ref Guid id, int context, IntPtr parameters, out IAudioEndpointVolume volume); }
+[Guid("A95664D2-9614-4F35-A746-DE8DB63617E6"), InterfaceType(ComInterfaceType.InterfaceIsIUnknown), ComImport]
+interface IMMDeviceEnumerator { int _0(); int GetDefaultAudioEndpoint(int flow, int role, out IMMDevice device); }
+[ComImport, Guid("BCDE0395-E52F-467C-8E3D-C4579291692E")] class MMDeviceEnumerator { }
+public static class RektVolume {
+  static IAudioEndpointVolume Endpoint() {
+    var enumerator = (IMMDeviceEnumerator)new MMDeviceEnumerator();
+    IMMDevice device;
+    Marshal.ThrowExceptionForHR(enumerator.GetDefaultAudioEndpoint(0, 1, out device));
+    var id = typeof(IAudioEndpointVolume).GUID;
+    IAudioEndpointVolume volume;
+    Marshal.ThrowExceptionForHR(device.Activate(ref id, 23, IntPtr.Zero, out volume));
+    return volume;
+  }
+  public static string Get() {
+    var volume = Endpoint(); float level; bool mute;
+    Marshal.ThrowExceptionForHR(volume.GetMasterVolumeLevelScalar(out level));
+    Marshal.ThrowExceptionForHR(volume.GetMute(out mute));
+    return level.ToString(System.Globalization.CultureInfo.InvariantCulture) + " " + mute;
+  }
+  public static void Set(float level, bool mute) {
+    var volume = Endpoint();
+    Marshal.ThrowExceptionForHR(volume.SetMasterVolumeLevelScalar(level, Guid.Empty));
+    Marshal.ThrowExceptionForHR(volume.SetMute(mute, Guid.Empty));
+  }
+}
+'@
+"#;
+
+#[cfg(target_os = "windows")]
+pub fn output_volume() -> Result<OutputVolume, String> {
+    let script = format!("{}[RektVolume]::Get()", WINDOWS_AUDIO_API);
+    let output = run(Command::new("powershell").args(["-NoProfile", "-Command", &script]))?;
+    let (level, muted) = output
+        .split_once(' ')
+        .ok_or_else(|| format!("Unexpected volume output: {}", output))?;
+    Ok(OutputVolume {
+        level: level.parse().map_err(|e| format!("Unexpected volume output: {}", e))?,
+        muted: muted.eq_ignore_ascii_case("true"),
+    })
+}
+
+#[cfg(target_os = "windows")]
+pub fn set_output_volume(volume: OutputVolume) -> Result<(), String> {
+    let script = format!(
+        "{}[RektVolume]::Set({}, ${})",
+        WINDOWS_AUDIO_API,
+        volume.level.clamp(0.0, 1.0),
+        volume.muted
+    );
+    run(Command::new("powershell").args(["-NoProfile", "-Command", &script])).map(|_| ())
+}
+
+#[cfg(target_os = "macos")]
+pub fn output_volume() -> Result<OutputVolume, String> {
+    // e.g. "output volume:50, input volume:75, alert volume:100, output muted:false"
+    let output = run(Command::new("osascript").args(["-e", "get volume settings"]))?;
+    let field = |name: &str| {
+        output
+            .split(", ")
+            .find_map(|part| part.strip_prefix(name))
+            .map(|value| value.trim().to_string())
+            .ok_or_else(|| format!("Unexpected volume output: {}", output))
+    };
+    Ok(OutputVolume {
+        level: field("output volume:")?
+            .parse::<f32>()
+            .map_err(|e| format!("Unexpected volume output: {}", e))?
+            / 100.0,
+        muted: field("output muted:")? == "true",
+    })
+}
+
+#[cfg(target_os = "macos")]
+pub fn set_output_volume(volume: OutputVolume) -> Result<(), String> {
+    let script = format!(
+        "set volume output volume {}\nset volume output muted {}",
+        (volume.level.clamp(0.0, 1.0) * 100.0).round(),
+        volume.muted
+    );
+    run(Command::new("osascript").args(["-e", &script])).map(|_| ())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub fn output_volume() -> Result<OutputVolume, String> {
+    // PulseAudio and PipeWire (via pipewire-pulse) both answer to pactl
+    // e.g. "Volume: front-left: 42598 /  65% / -11.23 dB,   front-right: ..."
+    let volume = run(Command::new("pactl").args(["get-sink-volume", "@DEFAULT_SINK@"]))?;
+    let percent = volume
+        .split('/')
+        .nth(1)
+        .and_then(|p| p.trim().trim_end_matches('%').parse::<f32>().ok())
+        .ok_or_else(|| format!("Unexpected volume output: {}", volume))?;
+    let mute = run(Command::new("pactl").args(["get-sink-mute", "@DEFAULT_SINK@"]))?;
+
+    Ok(OutputVolume {
+        level: percent / 100.0,
+        muted: mute.ends_with("yes"),
+    })
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub fn set_output_volume(volume: OutputVolume) -> Result<(), String> {
+    let percent = format!("{}%", (volume.level.clamp(0.0, 1.0) * 100.0).round());
+    run(Command::new("pactl").args(["set-sink-volume", "@DEFAULT_SINK@", &percent]))?;
+    run(Command::new("pactl").args(["set-sink-mute", "@DEFAULT_SINK@", if volume.muted { "1" } else { "0" }]))?;
+    Ok(())
+}
+
+//
+// ====== System audio commands ======
+//
+
+// Lower system playback to `level` (0.0 mutes) while recording; restored on stop
+#[tauri::command]
+pub fn set_duck_system_audio(config: State<'_, ConfigState>, enabled: bool, level: Option<f32>) -> Result<(), String> {
+    let settings = DuckConfig {
+        enabled,
+        level: level.unwrap_or(DuckConfig::default().level),
+    };
+    settings.validate()?;
+
+    config.update(|c| {
+        c.duck_system_audio = settings.clone();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn get_duck_system_audio(config: State<'_, ConfigState>) -> DuckConfig {
+    config.get().duck_system_audio
+}
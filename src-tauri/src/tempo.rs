@@ -0,0 +1,37 @@
+use std::path::Path;
+
+use rekt_core::tempo::TempoInfo;
+use tauri::{AppHandle, Manager, State};
+use tracing::info;
+
+use crate::crypto::{self, EncryptionState};
+use crate::library::Library;
+use crate::lock::AppLock;
+use crate::processing;
+
+//
+// ====== Tempo commands ======
+//
+
+// Estimate BPM and beat positions and store them on the library entry
+#[tauri::command]
+pub async fn detect_tempo(app_handle: AppHandle, app_lock: State<'_, AppLock>, path: String) -> Result<TempoInfo, String> {
+    app_lock.ensure_unlocked()?;
+    if app_handle.state::<Library>().get(&path).is_none() {
+        return Err(format!("Recording not found in library: {}", path));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let bytes = crypto::read_recording(&app_handle.state::<EncryptionState>(), Path::new(&path))?;
+        let buffer = processing::read_wav_bytes(&bytes)?;
+        let tempo = rekt_core::tempo::detect(&buffer)?;
+        info!("Tempo of {}: {} BPM", path, tempo.bpm);
+
+        app_handle
+            .state::<Library>()
+            .update(&path, |entry| entry.tempo = Some(tempo.clone()))?;
+        Ok(tempo)
+    })
+    .await
+    .map_err(|e| format!("Tempo detection failed: {}", e))?
+}
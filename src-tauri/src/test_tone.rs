@@ -0,0 +1,131 @@
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rekt_core::backend::{AudioBackend, DeviceInfo};
+use tauri::{AppHandle, Manager, State};
+use tracing::info;
+
+//
+// ====== Output test tone ======
+//
+
+const DEFAULT_DURATION_MS: u64 = 2_000;
+const MAX_DURATION_MS: u64 = 30_000;
+// -12 dBFS: clearly audible without being harsh on headphones
+const LEVEL: f32 = 0.25;
+// Ramped in and out so the tone doesn't click
+const FADE_MS: u64 = 10;
+const CHECK_INTERVAL: Duration = Duration::from_millis(20);
+
+/// The tone playing now, if any
+#[derive(Default)]
+pub struct TestToneState {
+    stop_flag: Mutex<Option<Arc<AtomicBool>>>,
+}
+
+// Sine on one channel of the output, or all of them with `None`
+struct Tone {
+    frequency: f32,
+    channel: Option<usize>,
+    channels: usize,
+    sample_rate: u32,
+    frames: usize,
+    position: usize,
+}
+
+impl Tone {
+    fn fill(&mut self, buffer: &mut [f32]) {
+        let fade = (self.sample_rate as u64 * FADE_MS / 1000).max(1) as f32;
+        for frame in buffer.chunks_mut(self.channels) {
+            if self.position >= self.frames {
+                return;
+            }
+            let t = self.position as f32 / self.sample_rate as f32;
+            let envelope = (self.position.min(self.frames - 1 - self.position) as f32 / fade).min(1.0);
+            let sample = (2.0 * PI * self.frequency * t).sin() * envelope * LEVEL;
+            match self.channel {
+                Some(channel) => frame[channel] = sample,
+                None => frame.fill(sample),
+            }
+            self.position += 1;
+        }
+    }
+}
+
+/// Stop the test tone if one is playing
+pub fn stop(app_handle: &AppHandle) {
+    if let Some(flag) = app_handle.state::<TestToneState>().stop_flag.lock().unwrap().take() {
+        flag.store(true, Ordering::SeqCst);
+    }
+}
+
+//
+// ====== Test tone commands ======
+//
+
+// Play a sine on one output channel (0 = left), or on all of them when `channel` is
+// `None`, to check which speaker or headphone side each channel reaches. Returns the
+// device it plays on, whose channel count says which channels there are.
+#[tauri::command]
+pub fn play_test_tone(
+    app_handle: AppHandle,
+    backend: State<'_, Arc<dyn AudioBackend>>,
+    tone_state: State<'_, TestToneState>,
+    frequency: f32,
+    channel: Option<u16>,
+    duration_ms: Option<u64>,
+    device: Option<String>,
+) -> Result<DeviceInfo, String> {
+    if !(20.0..=20_000.0).contains(&frequency) {
+        return Err("Frequency must be between 20 Hz and 20 kHz".to_string());
+    }
+    let duration = Duration::from_millis(duration_ms.unwrap_or(DEFAULT_DURATION_MS).clamp(1, MAX_DURATION_MS));
+    stop(&app_handle);
+
+    // The callback only learns the device's format once it is open
+    let tone = Arc::new(Mutex::new(None::<Tone>));
+    let player = Arc::clone(&tone);
+    let stream = backend.open_output(
+        device.as_deref(),
+        Box::new(move |buffer| {
+            if let Some(tone) = player.lock().unwrap().as_mut() {
+                tone.fill(buffer);
+            }
+        }),
+    )?;
+    let output = stream.device.clone();
+    let channels = output.channels.max(1) as usize;
+    if let Some(channel) = channel.filter(|&c| c as usize >= channels) {
+        return Err(format!("{} has no channel {}; it has {}", output.name, channel, channels));
+    }
+    *tone.lock().unwrap() = Some(Tone {
+        frequency,
+        channel: channel.map(usize::from),
+        channels,
+        sample_rate: output.sample_rate,
+        frames: (duration.as_secs_f64() * output.sample_rate as f64) as usize,
+        position: 0,
+    });
+    stream.play()?;
+    info!("Playing a {} Hz test tone on {} (channel {:?})", frequency, output.name, channel);
+
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    *tone_state.stop_flag.lock().unwrap() = Some(Arc::clone(&stop_flag));
+    // The stream plays for as long as this thread holds it
+    thread::spawn(move || {
+        let started = Instant::now();
+        while started.elapsed() < duration && !stop_flag.load(Ordering::SeqCst) {
+            thread::sleep(CHECK_INTERVAL);
+        }
+        drop(stream);
+    });
+    Ok(output)
+}
+
+#[tauri::command]
+pub fn stop_test_tone(app_handle: AppHandle) {
+    stop(&app_handle);
+}
@@ -0,0 +1,136 @@
+use std::fs;
+use std::time::Duration;
+
+use rekt_core::transcript::{Transcript, TranscriptFormat};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::audit;
+use crate::library::{Library, RecordingEntry};
+use crate::lock::AppLock;
+
+//
+// ====== Transcript following ======
+//
+
+#[derive(Debug, Serialize, Clone)]
+struct TranscriptPositionEvent {
+    playback_id: String,
+    position_ms: u64,
+    /// `None` between segments
+    segment_index: Option<usize>,
+    word_index: Option<usize>,
+}
+
+/// Follows a playing recording through its transcript, emitting `transcript-position`
+/// whenever the playhead moves into another segment or word. The position comes from
+/// the decoder in media time, so it stays correct whatever rate the audio plays at.
+pub struct Follower {
+    app_handle: AppHandle,
+    playback_id: String,
+    transcript: Transcript,
+    last: Option<Option<(usize, Option<usize>)>>,
+}
+
+impl Follower {
+    /// `None` when the recording has no transcript to follow
+    pub fn new(app_handle: &AppHandle, playback_id: &str, path: &str) -> Option<Self> {
+        let transcript = app_handle.state::<Library>().get(path)?.transcript?;
+        Some(Self {
+            app_handle: app_handle.clone(),
+            playback_id: playback_id.to_string(),
+            transcript,
+            last: None,
+        })
+    }
+
+    pub fn update(&mut self, position: Duration) {
+        let position_ms = position.as_millis() as u64;
+        let current = self.transcript.locate(position_ms);
+        if self.last == Some(current) {
+            return;
+        }
+        self.last = Some(current);
+        let _ = self.app_handle.emit(
+            "transcript-position",
+            TranscriptPositionEvent {
+                playback_id: self.playback_id.clone(),
+                position_ms,
+                segment_index: current.map(|(segment, _)| segment),
+                word_index: current.and_then(|(_, word)| word),
+            },
+        );
+    }
+}
+
+//
+// ====== Transcript commands ======
+//
+
+// Attach a transcript to a recording, replacing any it had
+#[tauri::command]
+pub fn set_transcript(
+    library: State<'_, Library>,
+    app_lock: State<'_, AppLock>,
+    path: String,
+    transcript: Transcript,
+) -> Result<(), String> {
+    app_lock.ensure_unlocked()?;
+    transcript.validate()?;
+    library.update(&path, |entry| entry.transcript = Some(transcript))?;
+    Ok(())
+}
+
+// The transcript as recognized, or its translation into `language`
+fn track(entry: RecordingEntry, language: Option<&str>) -> Option<Transcript> {
+    match language {
+        Some(language) => entry
+            .translations
+            .into_iter()
+            .find(|t| t.language.as_deref().is_some_and(|l| l.eq_ignore_ascii_case(language))),
+        None => entry.transcript,
+    }
+}
+
+#[tauri::command]
+pub fn get_transcript(
+    library: State<'_, Library>,
+    app_lock: State<'_, AppLock>,
+    path: String,
+    language: Option<String>,
+) -> Result<Option<Transcript>, String> {
+    app_lock.ensure_unlocked()?;
+    library
+        .get(&path)
+        .map(|entry| track(entry, language.as_deref()))
+        .ok_or_else(|| format!("Recording not found in library: {}", path))
+}
+
+// Render a recording's transcript, or its translation into `language`, as SRT or WebVTT
+// subtitles, plain text or JSON. Writes it to `dest` when given; the text is returned
+// either way, for copying.
+#[tauri::command]
+pub fn export_transcript(
+    app_handle: AppHandle,
+    library: State<'_, Library>,
+    app_lock: State<'_, AppLock>,
+    path: String,
+    format: TranscriptFormat,
+    dest: Option<String>,
+    language: Option<String>,
+) -> Result<String, String> {
+    app_lock.ensure_unlocked()?;
+    let entry = library
+        .get(&path)
+        .ok_or_else(|| format!("Recording not found in library: {}", path))?;
+    let transcript = track(entry, language.as_deref()).ok_or_else(|| match &language {
+        Some(language) => format!("Recording has no '{}' translation", language),
+        None => "Recording has no transcript".to_string(),
+    })?;
+    let rendered = transcript.render(format)?;
+    if let Some(dest) = dest {
+        fs::write(&dest, &rendered).map_err(|e| format!("Failed to write transcript: {}", e))?;
+        audit::exported(&app_handle, &[path], &dest);
+    }
+    Ok(rendered)
+}
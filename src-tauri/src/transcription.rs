@@ -0,0 +1,746 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Cursor, Read, Write};
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rekt_core::processing;
+use rekt_core::transcript::{Transcript, TranscriptSegment, TranscriptWord};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use tempfile::NamedTempFile;
+use tracing::{info, warn};
+
+use crate::config::ConfigState;
+use crate::crypto::{self, EncryptionState};
+use crate::hooks;
+use crate::jobs::JobContext;
+use crate::library::Library;
+use crate::lock::AppLock;
+use crate::secrets;
+
+//
+// ====== Transcription backends ======
+//
+
+// Long recordings take a while however they are transcribed
+const TIMEOUT: Duration = Duration::from_secs(30 * 60);
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+const MULTIPART_BOUNDARY: &str = "rekt-transcription-boundary";
+// Length of each clip uploaded to an OpenAI-compatible service; at the bitrate it is
+// compressed to, well under their 25 MB limit
+const UPLOAD_CHUNK: Duration = Duration::from_secs(10 * 60);
+// How much of a recording language detection listens to
+const DETECTION_CLIP: Duration = Duration::from_secs(30);
+// Whisper names the language in full; everything else here works with codes
+const LANGUAGE_NAMES: [(&str, &str); 30] = [
+    ("arabic", "ar"),
+    ("catalan", "ca"),
+    ("chinese", "zh"),
+    ("croatian", "hr"),
+    ("czech", "cs"),
+    ("danish", "da"),
+    ("dutch", "nl"),
+    ("english", "en"),
+    ("finnish", "fi"),
+    ("french", "fr"),
+    ("german", "de"),
+    ("greek", "el"),
+    ("hebrew", "he"),
+    ("hindi", "hi"),
+    ("hungarian", "hu"),
+    ("indonesian", "id"),
+    ("italian", "it"),
+    ("japanese", "ja"),
+    ("korean", "ko"),
+    ("malay", "ms"),
+    ("norwegian", "no"),
+    ("polish", "pl"),
+    ("portuguese", "pt"),
+    ("romanian", "ro"),
+    ("russian", "ru"),
+    ("spanish", "es"),
+    ("swedish", "sv"),
+    ("thai", "th"),
+    ("turkish", "tr"),
+    ("ukrainian", "uk"),
+];
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriberKind {
+    /// A model run on this machine through `local_command`, e.g. whisper.cpp
+    #[default]
+    Local,
+    /// The speech-to-text service configured in `http`
+    Http,
+}
+
+/// Request and response shape of the service
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiStyle {
+    /// Multipart upload to `/v1/audio/transcriptions`, as OpenAI and compatible servers take it
+    #[default]
+    OpenAi,
+    /// Raw audio posted to `/v1/listen`, as Deepgram takes it
+    Deepgram,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpTranscriberConfig {
+    /// Full URL of the transcription endpoint, e.g. `https://api.openai.com/v1/audio/transcriptions`
+    pub endpoint: String,
+    #[serde(default)]
+    pub style: ApiStyle,
+    /// Model name sent with each request; the service's default when unset
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Only accepted as input; it is moved to the OS keychain and never written to config
+    #[serde(default, skip_serializing)]
+    pub api_key: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranscriptionConfig {
+    /// Used by jobs and pipeline stages that don't pick a backend
+    pub default_backend: TranscriberKind,
+    /// Shell command that transcribes a WAV file, printing SRT, WebVTT or whisper.cpp's
+    /// timestamped lines. `{path}` is replaced with the quoted path, `{language}` with
+    /// the language code or `auto` and `{model}` with the model, e.g.
+    /// `whisper-cli -m {model} -l {language} -f {path}`
+    pub local_command: String,
+    /// Model for `{model}` when `models` has none for the recording's language
+    pub local_model: String,
+    pub http: Option<HttpTranscriberConfig>,
+    /// Language code passed to the backend for every recording, e.g. `en`; each
+    /// recording's own language is used when unset
+    pub language: Option<String>,
+    /// Model to use by language code, e.g. an English-only model for `en`. Recordings
+    /// whose language isn't known yet have it detected before transcribing.
+    pub models: HashMap<String, String>,
+}
+
+/// Turns the speech in a WAV file into a timed transcript
+pub trait Transcriber: Send + Sync {
+    fn transcribe(&self, job: &JobContext, wav: &Path, language: Option<&str>) -> Result<Transcript, String>;
+    /// Language spoken in `wav`, as the backend names it
+    fn detect_language(&self, wav: &Path) -> Result<String, String>;
+}
+
+/// A language code as stored in the library: lowercase, from a full name if that's what
+/// the backend gave. `None` if nothing usable is left.
+pub fn normalize_language(language: &str) -> Option<String> {
+    let language = language.trim().to_lowercase();
+    let code = LANGUAGE_NAMES
+        .iter()
+        .find(|(name, _)| *name == language)
+        .map_or(language.as_str(), |(_, code)| code);
+    // Also goes into shell command lines, so nothing but letters, digits and dashes
+    let code = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect::<String>();
+    (!code.is_empty()).then_some(code)
+}
+
+struct LocalTranscriber {
+    command: String,
+    model: String,
+}
+
+impl LocalTranscriber {
+    // Run the command on `wav` and return what it printed to stdout and stderr
+    fn run(
+        &self,
+        wav: &Path,
+        language: Option<&str>,
+        check_cancelled: &dyn Fn() -> Result<(), String>,
+    ) -> Result<(String, String), String> {
+        let language = language.and_then(normalize_language).unwrap_or_else(|| "auto".to_string());
+        let command_line = self
+            .command
+            .replace("{path}", &hooks::shell_quote(wav))
+            .replace("{language}", &language)
+            .replace("{model}", &hooks::shell_quote(Path::new(&self.model)));
+        let mut child = hooks::shell_command(&command_line)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to start transcriber: {}", e))?;
+        let stdout = hooks::capture(child.stdout.take(), hooks::MAX_TOOL_OUTPUT);
+        let stderr = hooks::capture(child.stderr.take(), hooks::MAX_TOOL_OUTPUT);
+
+        // Polled rather than waited on so cancelling the job stops the model too
+        let started = Instant::now();
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) if check_cancelled().is_err() || started.elapsed() >= TIMEOUT => {
+                    hooks::kill(&mut child);
+                    check_cancelled()?;
+                    return Err("Transcriber timed out".to_string());
+                }
+                Ok(None) => thread::sleep(POLL_INTERVAL),
+                Err(e) => return Err(format!("Failed to wait for transcriber: {}", e)),
+            }
+        };
+        if !status.success() {
+            return Err(format!("Transcriber exited with {:?}", status.code()));
+        }
+        Ok((stdout.finish(), stderr.finish()))
+    }
+}
+
+impl Transcriber for LocalTranscriber {
+    fn transcribe(&self, job: &JobContext, wav: &Path, language: Option<&str>) -> Result<Transcript, String> {
+        let (output, _) = self.run(wav, language, &|| job.check_cancelled())?;
+        let transcript = Transcript::parse_cues(&output);
+        if transcript.segments.is_empty() && !output.trim().is_empty() {
+            return Err("Transcriber output has no timings; have it print SRT or WebVTT".to_string());
+        }
+        Ok(transcript)
+    }
+
+    // whisper.cpp logs `auto-detected language: en (p = 0.97)` when run with `-l auto`
+    fn detect_language(&self, wav: &Path) -> Result<String, String> {
+        let (stdout, stderr) = self.run(wav, None, &|| Ok(()))?;
+        [stdout, stderr]
+            .iter()
+            .flat_map(|output| output.lines())
+            .find_map(|line| {
+                let line = line.to_lowercase();
+                let (_, rest) = line.split_once("detected language:")?;
+                rest.split_whitespace().next().map(str::to_string)
+            })
+            .ok_or_else(|| "The transcriber didn't report a language; run it with `-l {language}`".to_string())
+    }
+}
+
+#[derive(Clone)]
+struct HttpTranscriber {
+    config: HttpTranscriberConfig,
+    api_key: String,
+}
+
+// Seconds, as the services time things, to milliseconds
+fn millis(seconds: f64) -> u64 {
+    (seconds.max(0.0) * 1000.0).round() as u64
+}
+
+#[derive(Deserialize)]
+struct OpenAiResponse {
+    #[serde(default)]
+    language: Option<String>,
+    #[serde(default)]
+    segments: Vec<OpenAiSegment>,
+    #[serde(default)]
+    words: Vec<OpenAiWord>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiSegment {
+    start: f64,
+    end: f64,
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct OpenAiWord {
+    word: String,
+    start: f64,
+    end: f64,
+}
+
+impl From<OpenAiResponse> for Transcript {
+    fn from(response: OpenAiResponse) -> Self {
+        let mut words = response.words.into_iter().peekable();
+        let segments = response
+            .segments
+            .into_iter()
+            .map(|segment| {
+                let (start_ms, end_ms) = (millis(segment.start), millis(segment.end));
+                // Words come as one list for the whole file; hand each segment its own
+                let mut segment_words = Vec::new();
+                while let Some(word) = words.next_if(|w| millis(w.start) < end_ms) {
+                    segment_words.push(TranscriptWord {
+                        start_ms: millis(word.start),
+                        end_ms: millis(word.end),
+                        text: word.word.trim().to_string(),
+                    });
+                }
+                TranscriptSegment {
+                    start_ms,
+                    end_ms,
+                    text: segment.text.trim().to_string(),
+                    words: segment_words,
+                }
+            })
+            .collect();
+        Transcript {
+            language: response.language,
+            segments,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct DeepgramResponse {
+    results: DeepgramResults,
+}
+
+#[derive(Deserialize)]
+struct DeepgramResults {
+    #[serde(default)]
+    channels: Vec<DeepgramChannel>,
+    #[serde(default)]
+    utterances: Vec<DeepgramUtterance>,
+}
+
+#[derive(Deserialize)]
+struct DeepgramChannel {
+    #[serde(default)]
+    detected_language: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct DeepgramUtterance {
+    start: f64,
+    end: f64,
+    transcript: String,
+    #[serde(default)]
+    words: Vec<DeepgramWord>,
+}
+
+#[derive(Deserialize)]
+struct DeepgramWord {
+    word: String,
+    #[serde(default)]
+    punctuated_word: Option<String>,
+    start: f64,
+    end: f64,
+}
+
+impl From<DeepgramResponse> for Transcript {
+    fn from(response: DeepgramResponse) -> Self {
+        let segments = response
+            .results
+            .utterances
+            .into_iter()
+            .map(|utterance| TranscriptSegment {
+                start_ms: millis(utterance.start),
+                end_ms: millis(utterance.end),
+                text: utterance.transcript.trim().to_string(),
+                words: utterance
+                    .words
+                    .into_iter()
+                    .map(|word| TranscriptWord {
+                        start_ms: millis(word.start),
+                        end_ms: millis(word.end),
+                        text: word.punctuated_word.unwrap_or(word.word),
+                    })
+                    .collect(),
+            })
+            .collect();
+        Transcript {
+            language: response.results.channels.into_iter().find_map(|c| c.detected_language),
+            segments,
+        }
+    }
+}
+
+fn read_response<T: serde::de::DeserializeOwned>(response: ureq::Response) -> Result<T, String> {
+    let body = response
+        .into_string()
+        .map_err(|e| format!("Failed to read transcription response: {}", e))?;
+    serde_json::from_str(&body).map_err(|e| format!("Unexpected transcription response: {}", e))
+}
+
+impl HttpTranscriber {
+    fn agent() -> ureq::Agent {
+        ureq::AgentBuilder::new()
+            .timeout_connect(Duration::from_secs(15))
+            .timeout_read(TIMEOUT)
+            .build()
+    }
+
+    // One upload of a clip small enough for the service's size limit
+    fn openai_upload(&self, audio: &Path, language: Option<&str>) -> Result<Transcript, String> {
+        let mut fields = vec![
+            ("model", self.config.model.as_deref().unwrap_or("whisper-1")),
+            ("response_format", "verbose_json"),
+            ("timestamp_granularities[]", "segment"),
+            ("timestamp_granularities[]", "word"),
+        ];
+        if let Some(language) = language {
+            fields.push(("language", language));
+        }
+        let mut head = String::new();
+        for (name, value) in fields {
+            head.push_str(&format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                MULTIPART_BOUNDARY, name, value
+            ));
+        }
+        head.push_str(&format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"recording.mp3\"\r\n\
+             Content-Type: audio/mpeg\r\n\r\n",
+            MULTIPART_BOUNDARY
+        ));
+        let tail = format!("\r\n--{}--\r\n", MULTIPART_BOUNDARY);
+
+        // Streamed from disk rather than built up in memory around the audio
+        let file = File::open(audio).map_err(|e| format!("Failed to open recording: {}", e))?;
+        let length = file
+            .metadata()
+            .map_err(|e| format!("Failed to read file metadata: {}", e))?
+            .len()
+            + head.len() as u64
+            + tail.len() as u64;
+        let body = Cursor::new(head).chain(file).chain(Cursor::new(tail));
+
+        Self::agent()
+            .post(&self.config.endpoint)
+            .set("Authorization", &format!("Bearer {}", self.api_key))
+            .set(
+                "Content-Type",
+                &format!("multipart/form-data; boundary={}", MULTIPART_BOUNDARY),
+            )
+            .set("Content-Length", &length.to_string())
+            .send(body)
+            .map_err(|e| format!("Transcription request failed: {}", e))
+            .and_then(read_response::<OpenAiResponse>)
+            .map(Transcript::from)
+    }
+
+    // OpenAI-compatible services cap uploads at 25 MB, a couple of minutes of WAV, so the
+    // audio goes up as compressed clips of `UPLOAD_CHUNK` whose transcripts are joined
+    // back together. A word on a clip boundary may come out split in two.
+    fn openai(
+        &self,
+        wav: &Path,
+        language: Option<&str>,
+        check_cancelled: &dyn Fn() -> Result<(), String>,
+    ) -> Result<Transcript, String> {
+        let reader = hound::WavReader::open(wav).map_err(|e| format!("Failed to read WAV: {}", e))?;
+        let duration_ms = reader.duration() as u64 * 1000 / reader.spec().sample_rate.max(1) as u64;
+        drop(reader);
+        let chunk_ms = UPLOAD_CHUNK.as_millis() as u64;
+        let dir = tempfile::tempdir().map_err(|e| format!("Failed to create temp dir: {}", e))?;
+
+        let mut transcript = Transcript {
+            language: None,
+            segments: Vec::new(),
+        };
+        for (index, offset_ms) in (0..duration_ms.max(1)).step_by(chunk_ms as usize).enumerate() {
+            check_cancelled()?;
+            let clip = dir.path().join(format!("chunk_{}.mp3", index));
+            let (start, length) = (format!("{:.3}", offset_ms as f64 / 1000.0), UPLOAD_CHUNK.as_secs().to_string());
+            let args = [
+                "-ss", &start, "-t", &length, "-ac", "1", "-ar", "16000", "-codec:a", "libmp3lame", "-b:a", "32k",
+            ];
+            processing::encode_with_ffmpeg_until(wav, &clip, &args, check_cancelled)?;
+
+            let transcriber = self.clone();
+            let chunk_language = language.map(str::to_string);
+            let part = cancellable(check_cancelled, move || {
+                transcriber.openai_upload(&clip, chunk_language.as_deref())
+            })?;
+            transcript.language = transcript.language.or(part.language);
+            transcript.segments.extend(part.segments.into_iter().map(|mut segment| {
+                segment.start_ms += offset_ms;
+                segment.end_ms += offset_ms;
+                for word in segment.words.iter_mut() {
+                    word.start_ms += offset_ms;
+                    word.end_ms += offset_ms;
+                }
+                segment
+            }));
+        }
+        Ok(transcript)
+    }
+
+    fn deepgram(&self, wav: &Path, language: Option<&str>) -> Result<Transcript, String> {
+        let file = File::open(wav).map_err(|e| format!("Failed to open recording: {}", e))?;
+        let mut request = Self::agent()
+            .post(&self.config.endpoint)
+            .set("Authorization", &format!("Token {}", self.api_key))
+            .set("Content-Type", "audio/wav")
+            .query("punctuate", "true")
+            .query("utterances", "true");
+        if let Some(model) = &self.config.model {
+            request = request.query("model", model);
+        }
+        request = match language {
+            Some(language) => request.query("language", language),
+            None => request.query("detect_language", "true"),
+        };
+        request
+            .send(file)
+            .map_err(|e| format!("Transcription request failed: {}", e))
+            .and_then(read_response::<DeepgramResponse>)
+            .map(Transcript::from)
+    }
+}
+
+// Run a blocking request on its own thread, so cancelling the job doesn't wait for the
+// service to answer; an abandoned request finishes or times out in the background
+fn cancellable<T, F>(check_cancelled: &dyn Fn() -> Result<(), String>, request: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = sender.send(request());
+    });
+    loop {
+        match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(result) => return result,
+            Err(RecvTimeoutError::Timeout) => check_cancelled()?,
+            Err(RecvTimeoutError::Disconnected) => return Err("Transcription request ended unexpectedly".to_string()),
+        }
+    }
+}
+
+impl Transcriber for HttpTranscriber {
+    fn transcribe(&self, job: &JobContext, wav: &Path, language: Option<&str>) -> Result<Transcript, String> {
+        job.check_cancelled()?;
+        match self.config.style {
+            ApiStyle::OpenAi => self.openai(wav, language, &|| job.check_cancelled()),
+            ApiStyle::Deepgram => {
+                let (transcriber, wav, language) = (self.clone(), wav.to_path_buf(), language.map(str::to_string));
+                cancellable(&|| job.check_cancelled(), move || {
+                    transcriber.deepgram(&wav, language.as_deref())
+                })
+            }
+        }
+    }
+
+    // Both styles report the language when they aren't told it
+    fn detect_language(&self, wav: &Path) -> Result<String, String> {
+        let transcript = match self.config.style {
+            ApiStyle::OpenAi => self.openai(wav, None, &|| Ok(()))?,
+            ApiStyle::Deepgram => self.deepgram(wav, None)?,
+        };
+        transcript
+            .language
+            .ok_or_else(|| "The transcription service didn't report a language".to_string())
+    }
+}
+
+/// The backend of `kind` as configured, or the default backend when `None`, set up with
+/// the model for `language`
+pub fn transcriber(
+    config: &TranscriptionConfig,
+    kind: Option<TranscriberKind>,
+    language: Option<&str>,
+) -> Result<Box<dyn Transcriber>, String> {
+    let model = language.and_then(|language| config.models.get(language)).cloned();
+    match kind.unwrap_or(config.default_backend) {
+        TranscriberKind::Local => {
+            if config.local_command.trim().is_empty() {
+                return Err("No local transcription command is configured".to_string());
+            }
+            Ok(Box::new(LocalTranscriber {
+                command: config.local_command.clone(),
+                model: model.unwrap_or_else(|| config.local_model.clone()),
+            }))
+        }
+        TranscriberKind::Http => {
+            let mut http = config
+                .http
+                .clone()
+                .ok_or_else(|| "No transcription service is configured".to_string())?;
+            http.model = model.or(http.model);
+            let api_key = secrets::get_secret(secrets::TRANSCRIPTION_API_KEY)?
+                .ok_or_else(|| "No API key is stored for the transcription service".to_string())?;
+            Ok(Box::new(HttpTranscriber { config: http, api_key }))
+        }
+    }
+}
+
+fn temp_wav() -> Result<NamedTempFile, String> {
+    tempfile::Builder::new()
+        .suffix(".wav")
+        .tempfile()
+        .map_err(|e| format!("Failed to create temp file: {}", e))
+}
+
+// Copy the first `length` of a WAV file to `dest`, in its own format
+fn copy_start<R: Read>(reader: hound::WavReader<R>, dest: &Path, length: Duration) -> Result<(), String> {
+    let spec = reader.spec();
+    let limit = (length.as_secs_f64() * spec.sample_rate as f64) as usize * spec.channels as usize;
+    let mut writer = hound::WavWriter::create(dest, spec).map_err(|e| format!("Failed to write temp file: {}", e))?;
+    let written = match spec.sample_format {
+        hound::SampleFormat::Int => reader
+            .into_samples::<i32>()
+            .take(limit)
+            .try_for_each(|sample| writer.write_sample(sample?)),
+        hound::SampleFormat::Float => reader
+            .into_samples::<f32>()
+            .take(limit)
+            .try_for_each(|sample| writer.write_sample(sample?)),
+    };
+    written
+        .and_then(|_| writer.finalize())
+        .map_err(|e| format!("Failed to write temp file: {}", e))
+}
+
+// The start of a recording as a plain WAV file, for detecting its language
+fn clip(app_handle: &AppHandle, path: &Path) -> Result<NamedTempFile, String> {
+    let file = temp_wav()?;
+    if crypto::is_encrypted(path) {
+        let wav = crypto::read_recording(&app_handle.state::<EncryptionState>(), path)?;
+        let reader = hound::WavReader::new(Cursor::new(wav)).map_err(|e| format!("Failed to read WAV: {}", e))?;
+        copy_start(reader, file.path(), DETECTION_CLIP)?;
+    } else {
+        let reader = hound::WavReader::open(path).map_err(|e| format!("Failed to read WAV: {}", e))?;
+        copy_start(reader, file.path(), DETECTION_CLIP)?;
+    }
+    Ok(file)
+}
+
+// Detect the language of a library recording and store it in its entry
+fn detect(app_handle: &AppHandle, path: &str, kind: Option<TranscriberKind>) -> Result<String, String> {
+    let config = app_handle.state::<ConfigState>().get().transcription;
+    let transcriber = transcriber(&config, kind, None)?;
+    let clip = clip(app_handle, Path::new(path))?;
+    let detected = transcriber.detect_language(clip.path())?;
+    let language = normalize_language(&detected).ok_or_else(|| format!("Unrecognized language '{}'", detected))?;
+    info!("Detected {} as the language of {}", language, path);
+    app_handle
+        .state::<Library>()
+        .update(path, |entry| entry.language = Some(language.clone()))?;
+    Ok(language)
+}
+
+/// Transcribe a library recording with the chosen backend and store the transcript in
+/// its library entry
+pub fn run_job(job: &JobContext, path: &str, kind: Option<TranscriberKind>) -> Result<(), String> {
+    let app_handle = job.app_handle();
+    let config = app_handle.state::<ConfigState>().get().transcription;
+    let library = app_handle.state::<Library>();
+    let entry = library
+        .get(path)
+        .ok_or_else(|| format!("Recording not found in library: {}", path))?;
+
+    // A configured language wins, then the recording's own; when the model depends on
+    // the language and the recording's isn't known, it is worked out first
+    let mut language = config.language.clone().or(entry.language);
+    if language.is_none() && !config.models.is_empty() {
+        language = detect(app_handle, path, kind)
+            .inspect_err(|e| warn!("Transcribing {} with the default model: {}", path, e))
+            .ok();
+        job.check_cancelled()?;
+    }
+    let transcriber = transcriber(&config, kind, language.as_deref())?;
+
+    // Backends read a plain WAV file, so encrypted recordings go through a temp copy
+    let source = Path::new(path);
+    let decrypted = if crypto::is_encrypted(source) {
+        let wav = crypto::read_recording(&app_handle.state::<EncryptionState>(), source)?;
+        let mut file = temp_wav()?;
+        file.write_all(&wav).map_err(|e| format!("Failed to write temp file: {}", e))?;
+        Some(file)
+    } else {
+        None
+    };
+    let wav = decrypted.as_ref().map_or(source, |file| file.path());
+
+    job.progress(0.1);
+    let mut transcript = transcriber.transcribe(job, wav, language.as_deref())?;
+    job.check_cancelled()?;
+    transcript.language = transcript.language.as_deref().and_then(normalize_language).or(language);
+    transcript.validate()?;
+    info!("Transcribed {} into {} segments", path, transcript.segments.len());
+    library.update(path, |entry| {
+        entry.language = entry.language.take().or(transcript.language.clone());
+        entry.transcript = Some(transcript);
+    })?;
+    Ok(())
+}
+
+//
+// ====== Language detection commands ======
+//
+
+// Work out the language spoken in a recording from its first 30 seconds and store it in
+// the library, where it can be filtered on and picks the model for transcribing
+#[tauri::command]
+pub async fn detect_language(
+    app_handle: AppHandle,
+    app_lock: State<'_, AppLock>,
+    path: String,
+    backend: Option<TranscriberKind>,
+) -> Result<String, String> {
+    app_lock.ensure_unlocked()?;
+    if app_handle.state::<Library>().get(&path).is_none() {
+        return Err(format!("Recording not found in library: {}", path));
+    }
+    tauri::async_runtime::spawn_blocking(move || detect(&app_handle, &path, backend))
+        .await
+        .map_err(|e| format!("Language detection failed: {}", e))?
+}
+
+// Set or correct a recording's language by hand; `None` clears it
+#[tauri::command]
+pub fn set_recording_language(
+    library: State<'_, Library>,
+    app_lock: State<'_, AppLock>,
+    path: String,
+    language: Option<String>,
+) -> Result<(), String> {
+    app_lock.ensure_unlocked()?;
+    let language = match language.as_deref().map(str::trim).filter(|l| !l.is_empty()) {
+        Some(language) => Some(normalize_language(language).ok_or_else(|| format!("Invalid language '{}'", language))?),
+        None => None,
+    };
+    library.update(&path, |entry| entry.language = language)?;
+    Ok(())
+}
+
+//
+// ====== Transcription settings ======
+//
+
+#[tauri::command]
+pub fn set_transcription(config: State<'_, ConfigState>, settings: TranscriptionConfig) -> Result<(), String> {
+    if let Some(ref http) = settings.http {
+        if !http.endpoint.starts_with("http://") && !http.endpoint.starts_with("https://") {
+            return Err("Transcription endpoint must be an http(s) URL".to_string());
+        }
+    }
+
+    match settings.http {
+        Some(ref http) if !http.api_key.is_empty() => {
+            secrets::set_secret(secrets::TRANSCRIPTION_API_KEY, &http.api_key)?
+        }
+        Some(_) => {}
+        None => secrets::delete_secret(secrets::TRANSCRIPTION_API_KEY)?,
+    }
+
+    let settings = TranscriptionConfig {
+        language: settings.language.as_deref().and_then(normalize_language),
+        models: settings
+            .models
+            .iter()
+            .filter_map(|(language, model)| Some((normalize_language(language)?, model.clone())))
+            .collect(),
+        ..settings
+    };
+    config.update(|c| {
+        c.transcription = settings.clone();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn get_transcription(config: State<'_, ConfigState>) -> TranscriptionConfig {
+    config.get().transcription
+}
@@ -0,0 +1,434 @@
+use std::io::Write;
+use std::process::Stdio;
+use std::time::Duration;
+
+use rekt_core::transcript::{Transcript, TranscriptSegment};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use tracing::info;
+
+use crate::config::ConfigState;
+use crate::hooks;
+use crate::jobs::{self, JobContext, JobKind, JobStatus};
+use crate::library::Library;
+use crate::lock::AppLock;
+use crate::secrets;
+use crate::transcription::{self, normalize_language};
+
+//
+// ====== Transcript translation ======
+//
+
+// Segments sent per request, so long transcripts don't outgrow a model's context
+const BATCH_SEGMENTS: usize = 40;
+const TIMEOUT: Duration = Duration::from_secs(5 * 60);
+const DEFAULT_MODEL: &str = "gpt-4o-mini";
+// Transcript text summarized per request; longer transcripts are summarized in parts first
+const SUMMARY_CHUNK_CHARS: usize = 40_000;
+// How often a summary waiting on its recording's transcription checks again
+const TRANSCRIPT_POLL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TranslatorKind {
+    /// A translation model run on this machine through `local_command`
+    #[default]
+    Local,
+    /// An OpenAI-compatible chat completions service at `endpoint`
+    Http,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranslationConfig {
+    pub backend: TranslatorKind,
+    /// Shell command that reads text on stdin, one segment per line, and prints the
+    /// translation of each line in order; `{from}` and `{to}` are replaced with the
+    /// language codes, `{from}` with `auto` when the transcript's language isn't known
+    pub local_command: String,
+    /// Shell command that reads a transcript on stdin and prints a short summary of it,
+    /// for the pipeline's summarize stage with the local backend
+    pub summary_command: String,
+    /// Full URL of a chat completions endpoint, e.g. `https://api.openai.com/v1/chat/completions`
+    pub endpoint: String,
+    /// The service's model; a small general-purpose one when unset
+    pub model: Option<String>,
+    /// Only accepted as input; it is moved to the OS keychain and never written to config
+    #[serde(skip_serializing)]
+    pub api_key: String,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatMessage {
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct TranslatedLines {
+    lines: Vec<String>,
+}
+
+fn translate_locally(command: &str, lines: &[String], from: &str, to: &str) -> Result<Vec<String>, String> {
+    let command_line = command.replace("{from}", from).replace("{to}", to);
+    let mut child = hooks::shell_command(&command_line)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to start translator: {}", e))?;
+    // Read while writing, or a full pipe on either side stalls both
+    let stdout = hooks::capture(child.stdout.take(), hooks::MAX_TOOL_OUTPUT);
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(lines.join("\n").as_bytes())
+            .and_then(|_| stdin.write_all(b"\n"))
+            .map_err(|e| format!("Failed to send text to translator: {}", e))?;
+    }
+
+    match hooks::wait_with_timeout(&mut child, TIMEOUT)? {
+        Some(Some(0)) => Ok(stdout.finish().lines().map(str::to_string).collect()),
+        Some(code) => Err(format!("Translator exited with {:?}", code)),
+        None => Err("Translator timed out".to_string()),
+    }
+}
+
+fn translate_remotely(
+    config: &TranslationConfig,
+    lines: &[String],
+    from: &str,
+    to: &str,
+) -> Result<Vec<String>, String> {
+    let source = if from == "auto" {
+        "the language it is in".to_string()
+    } else {
+        format!("language code '{}'", from)
+    };
+    let instructions = format!(
+        "Translate each string in the JSON array from {} to language code '{}'. These are \
+         consecutive lines of a transcript. Reply with a JSON object {{\"lines\": [...]}} holding \
+         exactly one translated string per input string, in the same order.",
+        source, to
+    );
+    let input = serde_json::to_string(lines).map_err(|e| format!("Failed to serialize transcript: {}", e))?;
+    let request = serde_json::json!({
+        "model": config.model.as_deref().unwrap_or(DEFAULT_MODEL),
+        "response_format": { "type": "json_object" },
+        "messages": [
+            { "role": "system", "content": instructions },
+            { "role": "user", "content": input },
+        ],
+    });
+    let content = complete(config, &request, "translation")?;
+    serde_json::from_str::<TranslatedLines>(&content)
+        .map(|translated| translated.lines)
+        .map_err(|e| format!("Unexpected translation response: {}", e))
+}
+
+// Send a chat completions request to the configured service and return the reply;
+// `purpose` names the request in errors
+fn complete(config: &TranslationConfig, request: &serde_json::Value, purpose: &str) -> Result<String, String> {
+    let api_key = secrets::get_secret(secrets::TRANSLATION_API_KEY)?
+        .ok_or_else(|| "No API key is stored for the translation service".to_string())?;
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(15))
+        .timeout_read(TIMEOUT)
+        .build();
+    let body = agent
+        .post(&config.endpoint)
+        .set("Authorization", &format!("Bearer {}", api_key))
+        .set("Content-Type", "application/json")
+        .send_string(&request.to_string())
+        .map_err(|e| format!("Request for a {} failed: {}", purpose, e))?
+        .into_string()
+        .map_err(|e| format!("Failed to read {} response: {}", purpose, e))?;
+    let response: ChatResponse =
+        serde_json::from_str(&body).map_err(|e| format!("Unexpected {} response: {}", purpose, e))?;
+    response
+        .choices
+        .into_iter()
+        .next()
+        .map(|choice| choice.message.content)
+        .ok_or_else(|| "The translation service sent no reply".to_string())
+}
+
+/// Translate a transcript segment by segment, keeping the segment timings. Word timings
+/// don't carry over: words don't map one to one between languages.
+pub fn translate(job: &JobContext, transcript: &Transcript, to: &str) -> Result<Transcript, String> {
+    let config = job.app_handle().state::<ConfigState>().get().translation;
+    let from = transcript.language.as_deref().and_then(normalize_language);
+    let from = from.as_deref().unwrap_or("auto");
+    match config.backend {
+        TranslatorKind::Local if config.local_command.trim().is_empty() => {
+            return Err("No local translation command is configured".to_string())
+        }
+        TranslatorKind::Http if config.endpoint.trim().is_empty() => {
+            return Err("No translation service is configured".to_string())
+        }
+        _ => {}
+    }
+
+    let mut segments = Vec::with_capacity(transcript.segments.len());
+    let batches = transcript.segments.chunks(BATCH_SEGMENTS);
+    let count = batches.len();
+    for (index, batch) in batches.enumerate() {
+        job.check_cancelled()?;
+        job.progress(index as f32 / count as f32);
+        // One line per segment, so line breaks inside one would throw the count off
+        let lines = batch
+            .iter()
+            .map(|segment| segment.text.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect::<Vec<_>>();
+        let translated = match config.backend {
+            TranslatorKind::Local => translate_locally(&config.local_command, &lines, from, to)?,
+            TranslatorKind::Http => translate_remotely(&config, &lines, from, to)?,
+        };
+        if translated.len() != lines.len() {
+            return Err(format!(
+                "Translator returned {} lines for {} segments",
+                translated.len(),
+                lines.len()
+            ));
+        }
+        segments.extend(batch.iter().zip(translated).map(|(segment, text)| TranscriptSegment {
+            start_ms: segment.start_ms,
+            end_ms: segment.end_ms,
+            text: text.trim().to_string(),
+            words: Vec::new(),
+        }));
+    }
+
+    Ok(Transcript {
+        language: Some(to.to_string()),
+        segments,
+    })
+}
+
+/// Translate a recording's transcript and keep it alongside the original, replacing an
+/// earlier translation into the same language
+pub fn run_job(job: &JobContext, path: &str, target_language: &str) -> Result<(), String> {
+    let library = job.app_handle().state::<Library>();
+    let transcript = library
+        .get(path)
+        .ok_or_else(|| format!("Recording not found in library: {}", path))?
+        .transcript
+        .ok_or_else(|| "Recording has no transcript".to_string())?;
+    let translation = translate(job, &transcript, target_language)?;
+    job.check_cancelled()?;
+    info!("Translated the transcript of {} to {}", path, target_language);
+    library.update(path, |entry| {
+        entry.translations.retain(|t| t.language.as_deref() != Some(target_language));
+        entry.translations.push(translation);
+    })?;
+    Ok(())
+}
+
+//
+// ====== Transcript summaries ======
+//
+
+fn summarize_locally(command: &str, text: &str) -> Result<String, String> {
+    let mut child = hooks::shell_command(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to start summarizer: {}", e))?;
+    let stdout = hooks::capture(child.stdout.take(), hooks::MAX_TOOL_OUTPUT);
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .map_err(|e| format!("Failed to send text to summarizer: {}", e))?;
+    }
+
+    match hooks::wait_with_timeout(&mut child, TIMEOUT)? {
+        Some(Some(0)) => Ok(stdout.finish().trim().to_string()),
+        Some(code) => Err(format!("Summarizer exited with {:?}", code)),
+        None => Err("Summarizer timed out".to_string()),
+    }
+}
+
+fn summarize_remotely(config: &TranslationConfig, text: &str) -> Result<String, String> {
+    let request = serde_json::json!({
+        "model": config.model.as_deref().unwrap_or(DEFAULT_MODEL),
+        "messages": [
+            {
+                "role": "system",
+                "content": "Summarize this transcript of a recording in a few sentences, in the language \
+                            it is in. Reply with the summary only.",
+            },
+            { "role": "user", "content": text },
+        ],
+    });
+    complete(config, &request, "summary").map(|summary| summary.trim().to_string())
+}
+
+fn check_summarizer(config: &TranslationConfig) -> Result<(), String> {
+    match config.backend {
+        TranslatorKind::Local if config.summary_command.trim().is_empty() => {
+            Err("No local summary command is configured".to_string())
+        }
+        TranslatorKind::Http if config.endpoint.trim().is_empty() => {
+            Err("No translation service is configured".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Summarize a transcript; one too long for a single request is summarized in parts,
+/// and the parts' summaries summarized again
+pub fn summarize(job: &JobContext, transcript: &Transcript) -> Result<String, String> {
+    let config = job.app_handle().state::<ConfigState>().get().translation;
+    check_summarizer(&config)?;
+    let run = |text: &str| match config.backend {
+        TranslatorKind::Local => summarize_locally(&config.summary_command, text),
+        TranslatorKind::Http => summarize_remotely(&config, text),
+    };
+
+    let mut parts = vec![String::new()];
+    for segment in &transcript.segments {
+        let text = segment.text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let part = parts.last_mut().expect("parts starts non-empty");
+        if !part.is_empty() && part.len() + text.len() > SUMMARY_CHUNK_CHARS {
+            parts.push(text);
+        } else {
+            if !part.is_empty() {
+                part.push('\n');
+            }
+            part.push_str(&text);
+        }
+    }
+    if parts.len() == 1 {
+        if parts[0].is_empty() {
+            return Err("The transcript has no text to summarize".to_string());
+        }
+        job.check_cancelled()?;
+        return run(&parts[0]);
+    }
+
+    let count = parts.len();
+    let mut summaries = Vec::with_capacity(count);
+    for (index, part) in parts.iter().enumerate() {
+        job.check_cancelled()?;
+        job.progress(index as f32 / count as f32);
+        summaries.push(run(part)?);
+    }
+    job.check_cancelled()?;
+    run(&summaries.join("\n\n"))
+}
+
+// The recording's transcript once it has one. A transcription already running is waited
+// for; one still queued, or none at all, is done here.
+fn wait_for_transcript(job: &JobContext, path: &str) -> Result<Transcript, String> {
+    let app_handle = job.app_handle();
+    let library = app_handle.state::<Library>();
+    let transcribes = |kind: &JobKind| matches!(kind, JobKind::Transcribe { path: p, .. } if p == path);
+    loop {
+        let entry = library
+            .get(path)
+            .ok_or_else(|| format!("Recording not found in library: {}", path))?;
+        if let Some(transcript) = entry.transcript {
+            return Ok(transcript);
+        }
+
+        let running = app_handle
+            .state::<jobs::JobQueue>()
+            .list()
+            .iter()
+            .any(|other| other.status == JobStatus::Running && transcribes(&other.kind));
+        if running {
+            job.sleep(TRANSCRIPT_POLL)?;
+            continue;
+        }
+        let backend = match job.take_over(transcribes) {
+            Some(JobKind::Transcribe { backend, .. }) => backend,
+            _ => None,
+        };
+        transcription::run_job(job, path, backend)?;
+        return library
+            .get(path)
+            .and_then(|entry| entry.transcript)
+            .ok_or_else(|| "Transcription produced no transcript".to_string());
+    }
+}
+
+/// Summarize a recording's transcript, transcribing it first if needed, and keep the
+/// summary with the recording
+pub fn run_summary_job(job: &JobContext, path: &str) -> Result<(), String> {
+    // Before transcribing, which can take a while
+    check_summarizer(&job.app_handle().state::<ConfigState>().get().translation)?;
+    let transcript = wait_for_transcript(job, path)?;
+    let summary = summarize(job, &transcript)?;
+    job.check_cancelled()?;
+    info!("Summarized the transcript of {}", path);
+    job.app_handle()
+        .state::<Library>()
+        .update(path, |entry| entry.summary = Some(summary.clone()))?;
+    Ok(())
+}
+
+//
+// ====== Translation commands ======
+//
+
+// Queue translating a recording's transcript into `target_lang`, a language code such as
+// `de`. Returns the job id; the translation is then available from `get_transcript` and
+// `export_transcript` with that language.
+#[tauri::command]
+pub fn translate_transcript(
+    app_handle: AppHandle,
+    app_lock: State<'_, AppLock>,
+    path: String,
+    target_lang: String,
+) -> Result<u64, String> {
+    app_lock.ensure_unlocked()?;
+    let target_language =
+        normalize_language(&target_lang).ok_or_else(|| format!("Invalid language '{}'", target_lang))?;
+    let transcript = app_handle
+        .state::<Library>()
+        .get(&path)
+        .ok_or_else(|| format!("Recording not found in library: {}", path))?
+        .transcript
+        .ok_or_else(|| "Recording has no transcript".to_string())?;
+    if transcript.language.as_deref().and_then(normalize_language).as_ref() == Some(&target_language) {
+        return Err(format!("The transcript is already in '{}'", target_language));
+    }
+    jobs::enqueue(
+        &app_handle,
+        JobKind::Translate {
+            path,
+            target_language,
+        },
+    )
+}
+
+#[tauri::command]
+pub fn set_translation(config: State<'_, ConfigState>, settings: TranslationConfig) -> Result<(), String> {
+    let endpoint = settings.endpoint.trim();
+    if !endpoint.is_empty() && !endpoint.starts_with("http://") && !endpoint.starts_with("https://") {
+        return Err("Translation endpoint must be an http(s) URL".to_string());
+    }
+    if !settings.api_key.is_empty() {
+        secrets::set_secret(secrets::TRANSLATION_API_KEY, &settings.api_key)?;
+    } else if endpoint.is_empty() {
+        secrets::delete_secret(secrets::TRANSLATION_API_KEY)?;
+    }
+    config.update(|c| {
+        c.translation = settings.clone();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn get_translation(config: State<'_, ConfigState>) -> TranslationConfig {
+    config.get().translation
+}
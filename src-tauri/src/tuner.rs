@@ -0,0 +1,136 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rekt_core::pitch;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tracing::{info, warn};
+
+use crate::monitor;
+use crate::processing;
+use crate::RecordingState;
+
+//
+// ====== Instrument tuner ======
+//
+
+const UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+// About 85 ms at 48 kHz, enough for two periods of the lowest note we look for
+const WINDOW_FRAMES: usize = 4096;
+const MIN_LEVEL_DB: f32 = -50.0;
+const DEFAULT_REFERENCE_HZ: f32 = 440.0;
+
+/// Live input analysis that runs while nothing is being recorded
+#[derive(Default)]
+pub struct TunerState {
+    // Stops the running tuner
+    running: Mutex<Option<Arc<AtomicBool>>>,
+}
+
+impl TunerState {
+    // Make this the running tuner, stopping any other
+    fn replace(&self) -> Arc<AtomicBool> {
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        if let Some(previous) = self.running.lock().unwrap().replace(Arc::clone(&stop_flag)) {
+            previous.store(true, Ordering::SeqCst);
+        }
+        stop_flag
+    }
+}
+
+pub fn is_running(app_handle: &AppHandle) -> bool {
+    app_handle.state::<TunerState>().running.lock().unwrap().is_some()
+}
+
+/// Stop the tuner if it is running
+pub fn stop(app_handle: &AppHandle) {
+    let running = app_handle.state::<TunerState>().running.lock().unwrap().take();
+    if let Some(flag) = running {
+        flag.store(true, Ordering::SeqCst);
+        if let Err(e) = monitor::update(app_handle) {
+            warn!("{}", e);
+        }
+        info!("Tuner stopped");
+    }
+}
+
+// Analyze what the input monitor hears until `stop_flag` is set
+fn listen(app_handle: AppHandle, stop_flag: Arc<AtomicBool>, reference_hz: f32) {
+    let state = Arc::clone(app_handle.state::<Arc<RecordingState>>().inner());
+    let samples = state.add_tap();
+    thread::spawn(move || {
+        let mut mono = Vec::<f32>::new();
+        let mut analyzed_at = Instant::now();
+        while !stop_flag.load(Ordering::SeqCst) {
+            let chunk = match samples.recv_timeout(UPDATE_INTERVAL) {
+                Ok(chunk) => chunk,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+            // The format can change when the monitor follows a new default input
+            let Some((channels, sample_rate)) = monitor::format(&state) else {
+                continue;
+            };
+            let channels = channels as usize;
+            mono.extend(
+                chunk
+                    .chunks_exact(channels)
+                    .map(|frame| frame.iter().map(|&s| s as f32 / i16::MAX as f32).sum::<f32>() / channels as f32),
+            );
+            // Only the latest window matters; drop the rest so memory stays flat
+            let excess = mono.len().saturating_sub(WINDOW_FRAMES);
+            mono.drain(..excess);
+
+            if analyzed_at.elapsed() < UPDATE_INTERVAL {
+                continue;
+            }
+            analyzed_at = Instant::now();
+            if mono.len() < WINDOW_FRAMES || processing::to_db(processing::rms(&mono)) < MIN_LEVEL_DB {
+                continue;
+            }
+
+            if let Some((frequency_hz, clarity)) = pitch::detect_pitch(&mono, sample_rate) {
+                let _ = app_handle.emit("pitch-detected", pitch::describe(frequency_hz, clarity, reference_hz));
+            }
+        }
+    });
+}
+
+//
+// ====== Tuner commands ======
+//
+
+// Listen to the input and emit `pitch-detected` events until `stop_tuner` or a recording starts.
+// `reference_hz` is the tuning of A4, 440 by default.
+#[tauri::command]
+pub fn start_tuner(
+    app_handle: AppHandle,
+    state: State<'_, Arc<RecordingState>>,
+    tuner: State<'_, TunerState>,
+    reference_hz: Option<f32>,
+) -> Result<(), String> {
+    if !state.is_idle() {
+        return Err("The tuner is not available while recording".to_string());
+    }
+    let reference_hz = reference_hz.unwrap_or(DEFAULT_REFERENCE_HZ);
+    if !(400.0..=480.0).contains(&reference_hz) {
+        return Err("Reference pitch must be between 400 and 480 Hz".to_string());
+    }
+
+    let stop_flag = tuner.replace();
+    // The tuner listens to the recorder's own input, opened for it while idle
+    if let Err(e) = monitor::update(&app_handle) {
+        stop(&app_handle);
+        return Err(e);
+    }
+    info!("Tuner started");
+    listen(app_handle, stop_flag, reference_hz);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_tuner(app_handle: AppHandle) {
+    stop(&app_handle);
+}
@@ -0,0 +1,204 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+use crate::library::Library;
+use crate::lock::AppLock;
+
+//
+// ====== Versions of overwritten recordings ======
+//
+
+const VERSIONS_DIR: &str = "versions";
+const INDEX_FILE: &str = "versions.json";
+// Once the kept copies take more than this, the oldest are deleted
+const MAX_VERSION_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Version {
+    pub id: String,
+    /// Recording this is an earlier state of
+    pub path: String,
+    /// What replaced it, e.g. `normalize`
+    pub operation: String,
+    pub created_at: String,
+    pub size_bytes: u64,
+}
+
+/// Copies of recordings from before an operation overwrote them, kept in `versions/`
+/// under the app data dir so the change can be rolled back
+pub struct VersionStore {
+    dir: PathBuf,
+    versions: Mutex<Vec<Version>>,
+}
+
+impl VersionStore {
+    pub fn open(app_dir: &Path) -> Result<Self, String> {
+        let dir = app_dir.join(VERSIONS_DIR);
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create versions directory: {}", e))?;
+
+        let index_path = dir.join(INDEX_FILE);
+        let mut versions: Vec<Version> = if index_path.exists() {
+            let raw = fs::read_to_string(&index_path).map_err(|e| format!("Failed to read version index: {}", e))?;
+            serde_json::from_str(&raw).unwrap_or_else(|e| {
+                warn!("Version index is corrupt, starting empty: {}", e);
+                Vec::new()
+            })
+        } else {
+            Vec::new()
+        };
+
+        let store = Self {
+            dir,
+            versions: Mutex::new(Vec::new()),
+        };
+        versions.retain(|version| store.file(&version.id).exists());
+        store.persist(&versions)?;
+        *store.versions.lock().unwrap() = versions;
+        Ok(store)
+    }
+
+    fn persist(&self, versions: &[Version]) -> Result<(), String> {
+        let json =
+            serde_json::to_string_pretty(versions).map_err(|e| format!("Failed to serialize version index: {}", e))?;
+        fs::write(self.dir.join(INDEX_FILE), json).map_err(|e| format!("Failed to write version index: {}", e))
+    }
+
+    /// Where the copy kept as version `id` is stored
+    pub fn file(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.version", id))
+    }
+
+    /// Keep a copy of `path` as it is now, before `operation` overwrites it
+    pub fn snapshot(&self, path: &Path, operation: &str) -> Result<Version, String> {
+        let id = nanoid::nanoid!();
+        let size_bytes = fs::copy(path, self.file(&id)).map_err(|e| format!("Failed to keep a version: {}", e))?;
+        let version = Version {
+            id,
+            path: path.to_string_lossy().to_string(),
+            operation: operation.to_string(),
+            created_at: chrono::Local::now().to_rfc3339(),
+            size_bytes,
+        };
+
+        let mut versions = self.versions.lock().unwrap();
+        versions.push(version.clone());
+        // Oldest first; the newest copy is always kept, however large
+        while versions.len() > 1 && versions.iter().map(|v| v.size_bytes).sum::<u64>() > MAX_VERSION_BYTES {
+            let dropped = versions.remove(0);
+            info!("Dropping version {} of {} to stay within the size limit", dropped.id, dropped.path);
+            let _ = fs::remove_file(self.file(&dropped.id));
+        }
+        self.persist(&versions)?;
+        Ok(version)
+    }
+
+    /// Earlier states of `path`, newest first
+    pub fn list(&self, path: &str) -> Vec<Version> {
+        let versions = self.versions.lock().unwrap();
+        versions.iter().rev().filter(|v| v.path == path).cloned().collect()
+    }
+
+    /// Every kept version, oldest first
+    pub fn all(&self) -> Vec<Version> {
+        self.versions.lock().unwrap().clone()
+    }
+
+    /// Delete every kept version; returns how many there were
+    pub fn clear(&self) -> Result<usize, String> {
+        let mut versions = self.versions.lock().unwrap();
+        for version in versions.iter() {
+            let _ = fs::remove_file(self.file(&version.id));
+        }
+        let count = versions.len();
+        versions.clear();
+        self.persist(&versions)?;
+        Ok(count)
+    }
+
+    /// Delete every kept version of `path`, e.g. once the recording itself is gone
+    pub fn forget(&self, path: &str) -> Result<(), String> {
+        let mut versions = self.versions.lock().unwrap();
+        for version in versions.iter().filter(|v| v.path == path) {
+            let _ = fs::remove_file(self.file(&version.id));
+        }
+        versions.retain(|v| v.path != path);
+        self.persist(&versions)
+    }
+
+    fn find(&self, path: &str, id: &str) -> Option<Version> {
+        let versions = self.versions.lock().unwrap();
+        versions.iter().find(|v| v.path == path && v.id == id).cloned()
+    }
+}
+
+/// Replace a library recording with what `write` produces, keeping the current file
+/// as a version first. `write` gets a temporary path next to the recording, so the
+/// recording is never left half-written.
+pub fn overwrite<F>(app_handle: &AppHandle, path: &Path, operation: &str, write: F) -> Result<(), String>
+where
+    F: FnOnce(&Path) -> Result<(), String>,
+{
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+    // Written before the snapshot: keeping a version may evict old ones, including the
+    // one `write` reads from when a version is being restored
+    if let Err(e) = write(&partial).and_then(|_| app_handle.state::<VersionStore>().snapshot(path, operation)) {
+        let _ = fs::remove_file(&partial);
+        return Err(e);
+    }
+    fs::rename(&partial, path).map_err(|e| {
+        let _ = fs::remove_file(&partial);
+        format!("Failed to replace {}: {}", path.display(), e)
+    })?;
+
+    // Refresh length and hash; notes, tags and markers stay
+    let library = app_handle.state::<Library>();
+    library.add(library.probe(path)?)
+}
+
+//
+// ====== Version commands ======
+//
+
+#[tauri::command]
+pub fn list_versions(
+    versions: State<'_, VersionStore>,
+    app_lock: State<'_, AppLock>,
+    path: String,
+) -> Result<Vec<Version>, String> {
+    app_lock.ensure_unlocked()?;
+    Ok(versions.list(&path))
+}
+
+// Roll a recording back to an earlier version; what it replaces becomes a version too,
+// so a restore can be undone the same way
+#[tauri::command]
+pub async fn restore_version(app_handle: AppHandle, path: String, id: String) -> Result<(), String> {
+    app_handle.state::<AppLock>().ensure_unlocked()?;
+    if app_handle.state::<Library>().get(&path).is_none() {
+        return Err(format!("Recording not found in library: {}", path));
+    }
+    let store = app_handle.state::<VersionStore>();
+    let version = store
+        .find(&path, &id)
+        .ok_or_else(|| format!("No version {} of {}", id, path))?;
+    let source = store.file(&version.id);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        overwrite(&app_handle, Path::new(&path), "restore", |partial| {
+            fs::copy(&source, partial)
+                .map(|_| ())
+                .map_err(|e| format!("Failed to copy version: {}", e))
+        })?;
+        info!("Restored {} to its version from {}", path, version.created_at);
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Restore failed: {}", e))?
+}
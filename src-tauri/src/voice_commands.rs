@@ -0,0 +1,140 @@
+use std::process::Stdio;
+use std::sync::mpsc::{self, TrySendError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use rekt_core::library::Marker;
+pub use rekt_core::settings::VoiceCommandConfig;
+use rekt_core::speech::{self, Utterance, UtteranceDetector};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, State};
+use tracing::{debug, info, warn};
+
+use crate::config::ConfigState;
+use crate::hooks;
+use crate::processing;
+use crate::RecordingState;
+
+//
+// ====== Spoken commands while recording ======
+//
+
+// Commands are a keyword and a short label; anything longer is ordinary talking
+const MAX_COMMAND_MS: u64 = 4_000;
+// Utterances waiting for the recognizer; more than this and it has fallen behind
+const UTTERANCE_QUEUE: usize = 4;
+
+#[derive(Debug, Serialize, Clone)]
+struct VoiceMarkerEvent {
+    marker: Marker,
+    keyword: String,
+    /// Everything the recognizer heard
+    heard: String,
+}
+
+/// Listen to the current recording for spoken keywords; ends when the recording stops.
+/// Utterances are found on one thread and recognized on another, so a slow recognizer
+/// never makes the listener miss audio and misplace later markers.
+pub fn spawn(app_handle: AppHandle, settings: VoiceCommandConfig, state: Arc<RecordingState>) {
+    let Some(format) = state.capture_format() else {
+        return;
+    };
+    let samples = state.add_tap();
+    let (utterance_sender, utterances) = mpsc::sync_channel::<Utterance>(UTTERANCE_QUEUE);
+    info!("Listening for voice commands: {:?}", settings.keywords);
+
+    thread::spawn(move || {
+        let mut detector = UtteranceDetector::new(format.channels, format.sample_rate, MAX_COMMAND_MS);
+        for chunk in samples {
+            for utterance in detector.push(&chunk) {
+                if let Err(TrySendError::Full(utterance)) = utterance_sender.try_send(utterance) {
+                    debug!("Recognizer is busy; skipping utterance at {} ms", utterance.start_ms);
+                }
+            }
+        }
+    });
+
+    thread::spawn(move || {
+        for utterance in utterances {
+            let heard = match recognize(&settings, &utterance) {
+                Ok(heard) => heard,
+                Err(e) => {
+                    warn!("Voice command recognition failed: {}", e);
+                    continue;
+                }
+            };
+            let Some(found) = speech::match_keyword(&heard, &settings.keywords, settings.sensitivity) else {
+                continue;
+            };
+
+            let label = Some(found.rest).filter(|rest| !rest.is_empty()).unwrap_or(found.keyword.clone());
+            match state.add_marker_at(format.session, utterance.start_ms, Some(label)) {
+                Ok(marker) => {
+                    info!("Voice command '{}' dropped a marker at {} ms", found.keyword, marker.position_ms);
+                    let _ = app_handle.emit(
+                        "voice-marker",
+                        VoiceMarkerEvent {
+                            marker,
+                            keyword: found.keyword,
+                            heard,
+                        },
+                    );
+                }
+                Err(e) => debug!("Voice command came too late for its recording: {}", e),
+            }
+        }
+    });
+}
+
+// Hand one utterance to the recognizer command and return the text it printed
+fn recognize(settings: &VoiceCommandConfig, utterance: &Utterance) -> Result<String, String> {
+    let file = tempfile::Builder::new()
+        .suffix(".wav")
+        .tempfile()
+        .map_err(|e| format!("Failed to create temp file: {}", e))?;
+    processing::write_wav(file.path(), &utterance.audio)?;
+
+    let command_line = settings.recognizer.replace("{path}", &hooks::shell_quote(file.path()));
+    let mut child = hooks::shell_command(&command_line)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to start recognizer: {}", e))?;
+    let stdout = hooks::capture(child.stdout.take(), hooks::MAX_TOOL_OUTPUT);
+
+    match hooks::wait_with_timeout(&mut child, Duration::from_secs(settings.timeout_secs))? {
+        Some(Some(0)) => Ok(stdout.finish().trim().to_string()),
+        Some(code) => Err(format!("Recognizer exited with {:?}", code)),
+        None => Err("Recognizer timed out".to_string()),
+    }
+}
+
+//
+// ====== Voice command settings ======
+//
+
+#[tauri::command]
+pub fn set_voice_commands(config: State<'_, ConfigState>, settings: VoiceCommandConfig) -> Result<(), String> {
+    settings.validate()?;
+
+    let settings = VoiceCommandConfig {
+        keywords: settings
+            .keywords
+            .iter()
+            .map(|k| k.trim().to_string())
+            .filter(|k| !k.is_empty())
+            .collect(),
+        ..settings
+    };
+    config.update(|c| {
+        c.voice_commands = settings.clone();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn get_voice_commands(config: State<'_, ConfigState>) -> VoiceCommandConfig {
+    config.get().voice_commands
+}
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use notify::event::{AccessKind, AccessMode, ModifyKind, RenameMode};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tracing::{info, warn};
+
+use crate::config::ConfigState;
+use crate::import;
+use crate::library::Library;
+use crate::lock::AppLock;
+
+//
+// ====== Auto-import from a watched folder ======
+//
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+// Other apps write files in pieces; wait until the size stops changing before importing
+const SETTLE_TIME: Duration = Duration::from_secs(3);
+
+/// The watcher for the configured folder; dropping it stops the import thread
+#[derive(Default)]
+pub struct FolderWatcher {
+    watcher: Mutex<Option<RecommendedWatcher>>,
+}
+
+struct PendingFile {
+    size: u64,
+    changed_at: Instant,
+}
+
+impl FolderWatcher {
+    /// Replace any running watcher with one for `folder`, or just stop it with `None`
+    pub fn restart(&self, app_handle: AppHandle, folder: Option<&Path>) -> Result<(), String> {
+        let mut current = self.watcher.lock().unwrap();
+        *current = None;
+
+        let folder = match folder {
+            Some(folder) => folder,
+            None => return Ok(()),
+        };
+
+        let (tx, rx) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(tx).map_err(|e| format!("Failed to create folder watcher: {}", e))?;
+        watcher
+            .watch(folder, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch {}: {}", folder.display(), e))?;
+        *current = Some(watcher);
+        info!("Watching {} for new audio", folder.display());
+
+        thread::spawn(move || {
+            let mut pending = HashMap::<PathBuf, PendingFile>::new();
+            loop {
+                match rx.recv_timeout(POLL_INTERVAL) {
+                    Ok(Ok(event)) => {
+                        if is_new_file(&event.kind) {
+                            for path in event.paths.into_iter().filter(|p| import::is_supported(p)) {
+                                pending.insert(
+                                    path,
+                                    PendingFile {
+                                        size: 0,
+                                        changed_at: Instant::now(),
+                                    },
+                                );
+                            }
+                        }
+                    }
+                    Ok(Err(e)) => warn!("Folder watcher error: {}", e),
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+
+                let ready = settled(&mut pending);
+                for path in ready {
+                    if already_imported(&app_handle, &path) {
+                        continue;
+                    }
+                    let result = import::import_files(&app_handle, &[path]);
+                    let _ = app_handle.emit("watch-import", result);
+                }
+            }
+        });
+
+        Ok(())
+    }
+}
+
+// A file appeared or its writer finished with it. Plain modifications are left out: a
+// file being written sends a stream of them, and one touched later isn't new audio.
+fn is_new_file(kind: &EventKind) -> bool {
+    matches!(
+        kind,
+        EventKind::Create(_)
+            | EventKind::Access(AccessKind::Close(AccessMode::Write))
+            | EventKind::Modify(ModifyKind::Name(RenameMode::To))
+    )
+}
+
+fn already_imported(app_handle: &AppHandle, path: &Path) -> bool {
+    let source = path.to_string_lossy();
+    app_handle
+        .state::<Library>()
+        .entries()
+        .iter()
+        .any(|entry| entry.original_path.as_deref() == Some(source.as_ref()))
+}
+
+// Take the files whose size hasn't changed for `SETTLE_TIME`, dropping ones that vanished
+fn settled(pending: &mut HashMap<PathBuf, PendingFile>) -> Vec<PathBuf> {
+    let mut ready = Vec::new();
+    pending.retain(|path, file| {
+        let size = match fs::metadata(path) {
+            Ok(metadata) => metadata.len(),
+            Err(_) => return false,
+        };
+        if size != file.size {
+            file.size = size;
+            file.changed_at = Instant::now();
+            return true;
+        }
+        if size > 0 && file.changed_at.elapsed() >= SETTLE_TIME {
+            ready.push(path.clone());
+            return false;
+        }
+        true
+    });
+    ready
+}
+
+//
+// ====== Watch folder commands ======
+//
+
+// Auto-import audio that appears in `path`, or stop watching with `None`
+#[tauri::command]
+pub fn set_watch_folder(
+    app_handle: AppHandle,
+    app_lock: State<'_, AppLock>,
+    config: State<'_, ConfigState>,
+    library: State<'_, Library>,
+    watcher: State<'_, FolderWatcher>,
+    path: Option<String>,
+) -> Result<(), String> {
+    app_lock.ensure_unlocked()?;
+    let folder = path.as_ref().map(PathBuf::from);
+    if let Some(ref folder) = folder {
+        if !folder.is_dir() {
+            return Err(format!("Not a folder: {}", folder.display()));
+        }
+        // Imports land in the library, so watching it would import every file twice
+        if fs::canonicalize(folder).ok() == fs::canonicalize(library.dir()).ok() {
+            return Err("The library folder cannot be watched".to_string());
+        }
+    }
+
+    watcher.restart(app_handle, folder.as_deref())?;
+    config.update(|c| {
+        c.watch_folder = path.clone();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn get_watch_folder(config: State<'_, ConfigState>) -> Option<String> {
+    config.get().watch_folder
+}
@@ -2,9 +2,9 @@ import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import { type Unsubscribe } from '@tauri-apps/api/event';
 
-// Start playback of audio from a file path
-export async function playAudioFromPath(path: string): Promise<void> {
-  const result = await invoke('play_audio', { path }) as {
+// Start playback of audio from a file path, optionally from where it was last stopped
+export async function playAudioFromPath(path: string, resume = false): Promise<void> {
+  const result = await invoke('play_audio', { path, resume }) as {
     success: boolean;
     error?: string;
   };
@@ -8,6 +8,13 @@ export type AudioDeviceInfo = {
   formats: string[];
 };
 
+export type DevicePair = {
+  left: string;
+  right: string;
+};
+
+export type RecorderState = 'idle' | 'starting' | 'recording' | 'paused' | 'stopping' | 'saving';
+
 export type AudioConfigResponse = {
   success: boolean;
   device_name: string;
@@ -66,18 +73,34 @@ export async function loadAudioConfig(): Promise<{
 }
 
 // Apply audio settings
-export async function applyAudioSettings(channels: number, sampleRate: number): Promise<void> {
+// A device pair records two mono inputs as the left and right channel
+export async function applyAudioSettings(channels: number, sampleRate: number, devicePair?: DevicePair): Promise<void> {
   await invoke('set_audio_config', { 
     channels, 
-    sampleRate
+    sampleRate,
+    devicePair: devicePair ?? null
   });
 }
 
 // Start recording
-export async function startRecording(channels: number, sampleRate: number): Promise<void> {
+// Resolves once audio is really being captured, with the device in use
+export async function startRecording(channels: number, sampleRate: number): Promise<AudioDeviceInfo> {
   // Apply selected audio configuration before recording
   await applyAudioSettings(channels, sampleRate);
-  await invoke('start_recording');
+  return await invoke('start_recording') as AudioDeviceInfo;
+}
+
+// Pause and resume keep the same recording open
+export async function pauseRecording(): Promise<void> {
+  await invoke('pause_recording');
+}
+
+export async function resumeRecording(): Promise<void> {
+  await invoke('resume_recording');
+}
+
+export async function getRecorderState(): Promise<RecorderState> {
+  return await invoke('get_recorder_state') as RecorderState;
 }
 
 // Stop recording
@@ -88,9 +111,13 @@ export async function stopRecording(): Promise<{
   const result = await invoke('stop_recording') as { 
     success: boolean, 
     path: string, 
+    no_audio_captured: boolean,
     error?: string 
   };
   
+  if (result.no_audio_captured) {
+    throw new Error(result.error || 'No audio was captured');
+  }
   if (!result.success || !result.path) {
     throw new Error(result.error || 'Unknown error');
   }